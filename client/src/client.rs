@@ -0,0 +1,213 @@
+/**
+ * TYPED HTTP CLIENT
+ * One method per server route, returning the unwrapped `ApiResponse` payload
+ */
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::routes;
+use crate::types::*;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Server returned {status}: {message}")]
+    Status { status: u16, message: String },
+    #[error("API error: {0}")]
+    Api(String),
+    #[error("Response from {0} contained no data")]
+    MissingData(&'static str),
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+#[derive(Debug, Clone)]
+enum Credentials {
+    None,
+    ApiKey(String),
+    Bearer(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct BlackDiamondClient {
+    base_url: String,
+    http: reqwest::Client,
+    credentials: Credentials,
+}
+
+impl BlackDiamondClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+            credentials: Credentials::None,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.credentials = Credentials::ApiKey(api_key.into());
+        self
+    }
+
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.credentials = Credentials::Bearer(token.into());
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.credentials {
+            Credentials::None => request,
+            Credentials::ApiKey(key) => request.header("x-api-key", key),
+            Credentials::Bearer(token) => request.bearer_auth(token),
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, path: &'static str, request: reqwest::RequestBuilder) -> ClientResult<T> {
+        let response = self.authorize(request).send().await?;
+        let status = response.status();
+
+        let envelope: ApiResponse<T> = match response.json().await {
+            Ok(envelope) => envelope,
+            Err(e) if status.is_success() => return Err(e.into()),
+            Err(_) => {
+                return Err(ClientError::Status {
+                    status: status.as_u16(),
+                    message: status.canonical_reason().unwrap_or("unknown").to_string(),
+                })
+            }
+        };
+
+        if !status.is_success() {
+            return Err(ClientError::Status {
+                status: status.as_u16(),
+                message: envelope.error.unwrap_or_default(),
+            });
+        }
+        if !envelope.success {
+            return Err(ClientError::Api(envelope.error.unwrap_or_default()));
+        }
+
+        envelope.data.ok_or(ClientError::MissingData(path))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &'static str) -> ClientResult<T> {
+        let request = self.http.get(format!("{}{}", self.base_url, path));
+        self.send(path, request).await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &'static str) -> ClientResult<T> {
+        let request = self.http.post(format!("{}{}", self.base_url, path));
+        self.send(path, request).await
+    }
+
+    async fn post_json<B: Serialize, T: DeserializeOwned>(&self, path: &'static str, body: &B) -> ClientResult<T> {
+        let request = self.http.post(format!("{}{}", self.base_url, path)).json(body);
+        self.send(path, request).await
+    }
+
+    pub async fn openapi(&self) -> ClientResult<serde_json::Value> {
+        let request = self.http.get(format!("{}{}", self.base_url, routes::OPENAPI));
+        Ok(request.send().await?.error_for_status()?.json().await?)
+    }
+
+    pub async fn system_overview(&self) -> ClientResult<SystemOverview> {
+        self.get(routes::SYSTEM_OVERVIEW).await
+    }
+
+    pub async fn quantum_status(&self) -> ClientResult<QuantumStatus> {
+        self.get(routes::QUANTUM_STATUS).await
+    }
+
+    pub async fn execute_quantum_trading(&self, request: &QuantumTradingRequest) -> ClientResult<QuantumTradingResult> {
+        self.post_json(routes::QUANTUM_EXECUTE_TRADING, request).await
+    }
+
+    pub async fn quantum_superposition(&self) -> ClientResult<QuantumSuperposition> {
+        self.post(routes::QUANTUM_SUPERPOSITION).await
+    }
+
+    pub async fn quantum_tunnel_barriers(&self) -> ClientResult<QuantumTunnelResult> {
+        self.post(routes::QUANTUM_TUNNEL_BARRIERS).await
+    }
+
+    pub async fn quantum_entanglement(&self) -> ClientResult<QuantumEntanglement> {
+        self.post(routes::QUANTUM_ENTANGLEMENT).await
+    }
+
+    pub async fn quantum_consciousness(&self) -> ClientResult<QuantumConsciousness> {
+        self.post(routes::QUANTUM_CONSCIOUSNESS).await
+    }
+
+    pub async fn black_diamond_status(&self) -> ClientResult<PipelineStatus> {
+        self.get(routes::BLACK_DIAMOND_STATUS).await
+    }
+
+    pub async fn execute_arbitrage(&self, request: &ArbitrageRequest) -> ClientResult<ArbitrageExecution> {
+        self.post_json(routes::EXECUTE_ARBITRAGE, request).await
+    }
+
+    pub async fn zero_capital_strategies(&self) -> ClientResult<Vec<ZeroCapitalStrategy>> {
+        self.get(routes::ZERO_CAPITAL_STRATEGIES).await
+    }
+
+    pub async fn zero_capital_stats(&self) -> ClientResult<ZeroCapitalStats> {
+        self.get(routes::ZERO_CAPITAL_STATS).await
+    }
+
+    pub async fn memecoin_targets(&self) -> ClientResult<Vec<MemecoinTarget>> {
+        self.get(routes::MEMECOIN_TARGETS).await
+    }
+
+    pub async fn memecoin_stats(&self) -> ClientResult<SnipingStats> {
+        self.get(routes::MEMECOIN_STATS).await
+    }
+
+    pub async fn bot_performance(&self) -> ClientResult<Vec<BotPerformance>> {
+        self.get(routes::BOT_PERFORMANCE).await
+    }
+
+    pub async fn quantum_boost(&self) -> ClientResult<QuantumBoostResult> {
+        self.post(routes::QUANTUM_BOOST).await
+    }
+
+    pub async fn wallet_metrics(&self) -> ClientResult<Vec<WalletMetrics>> {
+        self.get(routes::WALLET_METRICS).await
+    }
+
+    pub async fn metrics_overview(&self) -> ClientResult<MetricsOverview> {
+        self.get(routes::METRICS_OVERVIEW).await
+    }
+
+    pub async fn strategies(&self) -> ClientResult<Vec<StrategyEntry>> {
+        self.get(routes::STRATEGIES).await
+    }
+
+    pub async fn set_strategy_enabled(&self, request: &StrategyToggleRequest) -> ClientResult<StrategyEntry> {
+        self.post_json(routes::STRATEGY_ENABLE, request).await
+    }
+
+    pub async fn configure_strategy(&self, request: &StrategyConfigRequest) -> ClientResult<StrategyEntry> {
+        self.post_json(routes::STRATEGY_CONFIGURE, request).await
+    }
+
+    pub async fn backtest_strategy(&self, request: &BacktestRequest) -> ClientResult<StrategyReport> {
+        self.post_json(routes::STRATEGY_BACKTEST, request).await
+    }
+
+    pub async fn paper_trading(&self) -> ClientResult<Vec<StrategyReport>> {
+        self.get(routes::PAPER_TRADING).await
+    }
+
+    pub async fn activate_all_systems(&self) -> ClientResult<SystemActivation> {
+        self.post(routes::ACTIVATE_ALL).await
+    }
+
+    pub async fn audit_log(&self) -> ClientResult<Vec<AuditEntry>> {
+        self.get(routes::AUDIT_LOG).await
+    }
+}
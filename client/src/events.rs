@@ -0,0 +1,118 @@
+/**
+ * EVENT STREAM MESSAGES
+ * Typed JSON frames exchanged over the `/api/stream` WebSocket
+ */
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamTopic {
+    Trades,
+    Alerts,
+    Wallets,
+    Opportunities,
+    System,
+}
+
+impl StreamTopic {
+    pub const ALL: [StreamTopic; 5] = [
+        StreamTopic::Trades,
+        StreamTopic::Alerts,
+        StreamTopic::Wallets,
+        StreamTopic::Opportunities,
+        StreamTopic::System,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    TradeExecuted {
+        strategy: String,
+        token_a: String,
+        token_b: String,
+        amount: f64,
+        success: bool,
+        signature: Option<String>,
+        error: Option<String>,
+    },
+    Alert {
+        id: String,
+        alert_type: String,
+        severity: String,
+        message: String,
+        wallet_address: String,
+        transaction_hash: Option<String>,
+        amount: Option<f64>,
+    },
+    WalletBalanceChanged {
+        address: String,
+        name: String,
+        previous_sol: f64,
+        current_sol: f64,
+    },
+    OpportunityDetected {
+        source: String,
+        identifier: String,
+        description: String,
+        score: f64,
+    },
+    SystemStatus {
+        /// Only the fields that changed since the previous status event
+        #[schema(value_type = Object)]
+        changes: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl StreamEvent {
+    pub fn topic(&self) -> StreamTopic {
+        match self {
+            StreamEvent::TradeExecuted { .. } => StreamTopic::Trades,
+            StreamEvent::Alert { .. } => StreamTopic::Alerts,
+            StreamEvent::WalletBalanceChanged { .. } => StreamTopic::Wallets,
+            StreamEvent::OpportunityDetected { .. } => StreamTopic::Opportunities,
+            StreamEvent::SystemStatus { .. } => StreamTopic::System,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub topic: StreamTopic,
+    pub event: StreamEvent,
+}
+
+/// Frames sent by the client.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum StreamRequest {
+    /// Replaces the current subscription. `resume_from` replays every buffered
+    /// event with a sequence number greater than it.
+    Subscribe {
+        topics: Vec<StreamTopic>,
+        resume_from: Option<u64>,
+    },
+    Unsubscribe {
+        topics: Vec<StreamTopic>,
+    },
+}
+
+/// Frames sent by the server.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StreamMessage {
+    Subscribed {
+        topics: Vec<StreamTopic>,
+        latest_seq: u64,
+        /// True when `resume_from` was older than the server's buffer and events were lost
+        gap: bool,
+    },
+    Event(SequencedEvent),
+    Error {
+        message: String,
+    },
+}
@@ -0,0 +1,16 @@
+/**
+ * BLACK DIAMOND API CLIENT
+ * Typed contract, OpenAPI document and HTTP client for the trading server
+ */
+
+pub mod client;
+pub mod events;
+pub mod openapi;
+pub mod routes;
+pub mod types;
+
+pub use client::{BlackDiamondClient, ClientError};
+pub use events::*;
+pub use openapi::openapi_document;
+pub use utoipa::openapi::OpenApi;
+pub use types::*;
@@ -0,0 +1,200 @@
+/**
+ * OPENAPI DOCUMENT
+ * Builds the OpenAPI 3 document for the trading server from the route table
+ */
+
+use utoipa::openapi::{
+    path::{OperationBuilder, PathItem, PathItemType},
+    request_body::RequestBodyBuilder,
+    schema::{ArrayBuilder, ObjectBuilder, SchemaType},
+    security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme},
+    ComponentsBuilder, ContentBuilder, InfoBuilder, OpenApi, OpenApiBuilder, PathsBuilder, Ref, RefOr,
+    Required, ResponseBuilder, Schema,
+};
+
+use crate::routes::{Body, HttpMethod, OPENAPI, ROUTES, STREAM};
+use crate::events::*;
+use crate::types::*;
+
+pub fn openapi_document() -> OpenApi {
+    let components = ComponentsBuilder::new()
+        .schema_from::<Role>()
+        .schema_from::<ArbitrageRequest>()
+        .schema_from::<QuantumTradingRequest>()
+        .schema_from::<StrategyToggleRequest>()
+        .schema_from::<StrategyConfigRequest>()
+        .schema_from::<BacktestRequest>()
+        .schema_from::<SystemOverview>()
+        .schema_from::<ArbitrageExecution>()
+        .schema_from::<QuantumStatus>()
+        .schema_from::<QuantumTradingResult>()
+        .schema_from::<QuantumSuperposition>()
+        .schema_from::<QuantumTunnelResult>()
+        .schema_from::<EntangledPair>()
+        .schema_from::<QuantumEntanglement>()
+        .schema_from::<QuantumConsciousness>()
+        .schema_from::<SystemActivation>()
+        .schema_from::<QuantumBoostResult>()
+        .schema_from::<BotPerformance>()
+        .schema_from::<TransactionMetrics>()
+        .schema_from::<PipelineStatus>()
+        .schema_from::<StrategyType>()
+        .schema_from::<ZeroCapitalStrategy>()
+        .schema_from::<ZeroCapitalStats>()
+        .schema_from::<MemecoinTarget>()
+        .schema_from::<SnipingStats>()
+        .schema_from::<TokenBalance>()
+        .schema_from::<WalletMetrics>()
+        .schema_from::<MetricsOverview>()
+        .schema_from::<AuditEntry>()
+        .schema_from::<StrategyEntry>()
+        .schema_from::<StrategyReport>()
+        .schema_from::<StreamTopic>()
+        .schema_from::<StreamEvent>()
+        .schema_from::<SequencedEvent>()
+        .schema_from::<StreamRequest>()
+        .schema_from::<StreamMessage>()
+        .security_scheme("api_key", SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("x-api-key"))))
+        .security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        )
+        .build();
+
+    let mut paths = PathsBuilder::new();
+    for route in ROUTES {
+        // The OpenAPI document and stream frames are not wrapped in the envelope
+        let response_schema = match body_schema(route.response) {
+            Some(schema) if route.path == OPENAPI || route.path == STREAM => schema,
+            _ => envelope(route.response),
+        };
+
+        let mut operation = OperationBuilder::new()
+            .operation_id(Some(route.operation_id))
+            .summary(Some(route.summary))
+            .response(
+                "200",
+                ResponseBuilder::new()
+                    .description("Success")
+                    .content("application/json", ContentBuilder::new().schema(response_schema).build())
+                    .build(),
+            );
+
+        if let Some(role) = route.role {
+            operation = operation
+                .description(Some(format!("Requires role `{}` or higher.", format!("{:?}", role).to_lowercase())))
+                .security(SecurityRequirement::new("api_key", Vec::<String>::new()))
+                .security(SecurityRequirement::new("bearer", Vec::<String>::new()))
+                .response("401", ResponseBuilder::new().description("Missing or invalid credentials").build())
+                .response("403", ResponseBuilder::new().description("Role not permitted").build())
+                .response("429", ResponseBuilder::new().description("Rate limit exceeded").build());
+        }
+
+        if let Some(schema) = body_schema(route.request) {
+            operation = operation.request_body(Some(
+                RequestBodyBuilder::new()
+                    .content("application/json", ContentBuilder::new().schema(schema).build())
+                    .required(Some(Required::True))
+                    .build(),
+            ));
+        }
+
+        paths = paths.path(route.path, PathItem::new(path_item_type(route.method), operation.build()));
+    }
+
+    OpenApiBuilder::new()
+        .info(
+            InfoBuilder::new()
+                .title("Black Diamond Trading Server")
+                .version(env!("CARGO_PKG_VERSION"))
+                .build(),
+        )
+        .paths(paths.build())
+        .components(Some(components))
+        .build()
+}
+
+fn path_item_type(method: HttpMethod) -> PathItemType {
+    match method {
+        HttpMethod::Get => PathItemType::Get,
+        HttpMethod::Post => PathItemType::Post,
+    }
+}
+
+fn body_schema(body: Body) -> Option<RefOr<Schema>> {
+    match body {
+        Body::Empty => None,
+        Body::Schema(name) => Some(Ref::from_schema_name(name).into()),
+        Body::ArrayOf(name) => Some(ArrayBuilder::new().items(Ref::from_schema_name(name)).into()),
+        Body::FreeForm => Some(ObjectBuilder::new().schema_type(SchemaType::Object).into()),
+    }
+}
+
+// Every response is wrapped in `ApiResponse<T>`
+fn envelope(data: Body) -> RefOr<Schema> {
+    let mut object = ObjectBuilder::new()
+        .property("success", ObjectBuilder::new().schema_type(SchemaType::Boolean))
+        .required("success")
+        .property("error", ObjectBuilder::new().schema_type(SchemaType::String).nullable(true));
+
+    if let Some(schema) = body_schema(data) {
+        object = object.property("data", schema);
+    }
+
+    object.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_refs(value: &serde_json::Value, refs: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(reference)) = map.get("$ref") {
+                    refs.push(reference.clone());
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_route_is_documented_once_with_its_method() {
+        let document = openapi_document();
+        assert_eq!(document.paths.paths.len(), ROUTES.len(), "route table has a duplicate path");
+
+        for route in ROUTES {
+            let item = &document.paths.paths[route.path];
+            assert_eq!(item.operations.len(), 1, "{}", route.path);
+            let operation = item
+                .operations
+                .get(&path_item_type(route.method))
+                .unwrap_or_else(|| panic!("{} documented with the wrong method", route.path));
+            assert_eq!(operation.operation_id.as_deref(), Some(route.operation_id));
+        }
+    }
+
+    #[test]
+    fn every_schema_reference_resolves() {
+        let document = serde_json::to_value(openapi_document()).unwrap();
+        let schemas = document["components"]["schemas"].as_object().unwrap();
+
+        let mut refs = Vec::new();
+        collect_refs(&document, &mut refs);
+        assert!(!refs.is_empty());
+        for reference in refs {
+            let name = reference.strip_prefix("#/components/schemas/").unwrap();
+            assert!(schemas.contains_key(name), "{} is not a registered schema", name);
+        }
+    }
+
+    #[test]
+    fn only_the_openapi_document_is_untyped() {
+        for route in ROUTES.iter().filter(|r| r.path != OPENAPI) {
+            assert!(!matches!(route.response, Body::FreeForm), "{} has a free-form response", route.path);
+        }
+    }
+}
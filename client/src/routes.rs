@@ -0,0 +1,108 @@
+/**
+ * API ROUTE TABLE
+ * Single source of truth for every route the trading server exposes
+ */
+
+use crate::types::Role;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Body {
+    Empty,
+    Schema(&'static str),
+    ArrayOf(&'static str),
+    FreeForm,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RouteSpec {
+    pub method: HttpMethod,
+    pub path: &'static str,
+    pub operation_id: &'static str,
+    pub summary: &'static str,
+    pub request: Body,
+    pub response: Body,
+    pub role: Option<Role>, // None for public routes
+}
+
+const fn route(
+    method: HttpMethod,
+    path: &'static str,
+    operation_id: &'static str,
+    summary: &'static str,
+    request: Body,
+    response: Body,
+    role: Option<Role>,
+) -> RouteSpec {
+    RouteSpec { method, path, operation_id, summary, request, response, role }
+}
+
+use Body::*;
+use HttpMethod::*;
+
+pub const ROOT: &str = "/";
+pub const OPENAPI: &str = "/api/openapi.json";
+pub const SYSTEM_OVERVIEW: &str = "/api/status";
+pub const QUANTUM_STATUS: &str = "/api/quantum/status";
+pub const QUANTUM_EXECUTE_TRADING: &str = "/api/quantum/execute-trading";
+pub const QUANTUM_SUPERPOSITION: &str = "/api/quantum/superposition";
+pub const QUANTUM_TUNNEL_BARRIERS: &str = "/api/quantum/tunnel-barriers";
+pub const QUANTUM_ENTANGLEMENT: &str = "/api/quantum/entanglement";
+pub const QUANTUM_CONSCIOUSNESS: &str = "/api/quantum/consciousness";
+pub const BLACK_DIAMOND_STATUS: &str = "/api/black-diamond/status";
+pub const EXECUTE_ARBITRAGE: &str = "/api/black-diamond/execute-arbitrage";
+pub const ZERO_CAPITAL_STRATEGIES: &str = "/api/zero-capital/strategies";
+pub const ZERO_CAPITAL_STATS: &str = "/api/zero-capital/stats";
+pub const MEMECOIN_TARGETS: &str = "/api/memecoin/targets";
+pub const MEMECOIN_STATS: &str = "/api/memecoin/stats";
+pub const BOT_PERFORMANCE: &str = "/api/innovations/bot-performance";
+pub const QUANTUM_BOOST: &str = "/api/innovations/quantum-boost";
+pub const WALLET_METRICS: &str = "/api/metrics/wallets";
+pub const METRICS_OVERVIEW: &str = "/api/metrics/overview";
+pub const ACTIVATE_ALL: &str = "/api/systems/activate-all";
+pub const STRATEGIES: &str = "/api/strategies";
+pub const STRATEGY_ENABLE: &str = "/api/strategies/enable";
+pub const STRATEGY_CONFIGURE: &str = "/api/strategies/configure";
+pub const STRATEGY_BACKTEST: &str = "/api/strategies/backtest";
+pub const PAPER_TRADING: &str = "/api/strategies/paper";
+pub const AUDIT_LOG: &str = "/api/admin/audit";
+pub const STREAM: &str = "/api/stream";
+
+pub const ROUTES: &[RouteSpec] = &[
+    route(Get, OPENAPI, "getOpenApi", "OpenAPI document for this server", Empty, FreeForm, None),
+    route(Get, ROOT, "getRoot", "System overview", Empty, Schema("SystemOverview"), Some(Role::Viewer)),
+    route(Get, SYSTEM_OVERVIEW, "getSystemOverview", "System overview", Empty, Schema("SystemOverview"), Some(Role::Viewer)),
+    route(Get, QUANTUM_STATUS, "getQuantumStatus", "Quantum orchestrator status", Empty, Schema("QuantumStatus"), Some(Role::Viewer)),
+    route(Post, QUANTUM_EXECUTE_TRADING, "executeQuantumTrading", "Execute a batch of quantum-enhanced trades", Schema("QuantumTradingRequest"), Schema("QuantumTradingResult"), Some(Role::Operator)),
+    route(Post, QUANTUM_SUPERPOSITION, "executeQuantumSuperposition", "Execute superposition trading across the default pairs", Empty, Schema("QuantumSuperposition"), Some(Role::Operator)),
+    route(Post, QUANTUM_TUNNEL_BARRIERS, "tunnelBarriers", "Quantum tunnelling through market barriers", Empty, Schema("QuantumTunnelResult"), Some(Role::Operator)),
+    route(Post, QUANTUM_ENTANGLEMENT, "establishEntanglement", "Establish entangled trading pairs", Empty, Schema("QuantumEntanglement"), Some(Role::Operator)),
+    route(Post, QUANTUM_CONSCIOUSNESS, "activateConsciousness", "Activate quantum consciousness", Empty, Schema("QuantumConsciousness"), Some(Role::Operator)),
+    route(Get, BLACK_DIAMOND_STATUS, "getBlackDiamondStatus", "Transaction pipeline status", Empty, Schema("PipelineStatus"), Some(Role::Viewer)),
+    route(Post, EXECUTE_ARBITRAGE, "executeArbitrage", "Execute an arbitrage between two tokens", Schema("ArbitrageRequest"), Schema("ArbitrageExecution"), Some(Role::Operator)),
+    route(Get, ZERO_CAPITAL_STRATEGIES, "getZeroCapitalStrategies", "Available zero-capital strategies", Empty, ArrayOf("ZeroCapitalStrategy"), Some(Role::Viewer)),
+    route(Get, ZERO_CAPITAL_STATS, "getZeroCapitalStats", "Zero-capital strategy statistics", Empty, Schema("ZeroCapitalStats"), Some(Role::Viewer)),
+    route(Get, MEMECOIN_TARGETS, "getMemecoinTargets", "Active memecoin sniping targets", Empty, ArrayOf("MemecoinTarget"), Some(Role::Viewer)),
+    route(Get, MEMECOIN_STATS, "getMemecoinStats", "Memecoin sniping statistics", Empty, Schema("SnipingStats"), Some(Role::Viewer)),
+    route(Get, BOT_PERFORMANCE, "getBotPerformance", "On-chain bot performance", Empty, ArrayOf("BotPerformance"), Some(Role::Viewer)),
+    route(Post, QUANTUM_BOOST, "executeQuantumBoost", "Run the quantum speed optimisation program", Empty, Schema("QuantumBoostResult"), Some(Role::Operator)),
+    route(Get, WALLET_METRICS, "getWalletMetrics", "Per-wallet metrics", Empty, ArrayOf("WalletMetrics"), Some(Role::Viewer)),
+    route(Get, METRICS_OVERVIEW, "getMetricsOverview", "Metrics overview", Empty, Schema("MetricsOverview"), Some(Role::Viewer)),
+    route(Get, STRATEGIES, "listStrategies", "Registered strategies and their configuration", Empty, ArrayOf("StrategyEntry"), Some(Role::Viewer)),
+    route(Post, STRATEGY_ENABLE, "setStrategyEnabled", "Start or stop paper-trading a strategy", Schema("StrategyToggleRequest"), Schema("StrategyEntry"), Some(Role::Operator)),
+    route(Post, STRATEGY_CONFIGURE, "configureStrategy", "Change a strategy's parameters, restarting it if enabled", Schema("StrategyConfigRequest"), Schema("StrategyEntry"), Some(Role::Operator)),
+    route(Post, STRATEGY_BACKTEST, "backtestStrategy", "Run a strategy over recorded market events", Schema("BacktestRequest"), Schema("StrategyReport"), Some(Role::Operator)),
+    route(Get, PAPER_TRADING, "getPaperTrading", "Results of every strategy paper-trading on live pool updates", Empty, ArrayOf("StrategyReport"), Some(Role::Viewer)),
+    route(Post, ACTIVATE_ALL, "activateAllSystems", "Activate every subsystem", Empty, Schema("SystemActivation"), Some(Role::Admin)),
+    route(Get, STREAM, "openEventStream", "WebSocket upgrade; exchanges StreamRequest and StreamMessage frames", Empty, Schema("StreamMessage"), Some(Role::Viewer)),
+    route(Get, AUDIT_LOG, "getAuditLog", "Most recent mutating API calls", Empty, ArrayOf("AuditEntry"), Some(Role::Admin)),
+];
+
+pub fn is_public(path: &str) -> bool {
+    ROUTES.iter().any(|r| r.path == path && r.role.is_none())
+}
@@ -0,0 +1,400 @@
+/**
+ * API CONTRACT TYPES
+ * Request and response bodies shared by the trading server and its clients
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub error: Option<String>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn success(data: T) -> Self {
+        Self {
+            success: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    pub fn error(error: String) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("Unknown role: {}", other)),
+        }
+    }
+}
+
+// Requests
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ArbitrageRequest {
+    pub token_a: String,
+    pub token_b: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuantumTradingRequest {
+    pub trades: Vec<String>,
+    pub quantum_enhancement: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StrategyToggleRequest {
+    pub id: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StrategyConfigRequest {
+    pub id: String,
+    /// Parameters to change; the rest keep their current values
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BacktestRequest {
+    pub id: String,
+    /// Recorded market events: `{"type": "quote" | "trade", ...}`
+    #[schema(value_type = Vec<Object>)]
+    pub events: Vec<serde_json::Value>,
+}
+
+// Responses
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SystemOverview {
+    pub status: String,
+    pub active_systems: u8,
+    pub total_systems: u8,
+    pub performance_boost: String,
+    pub memory_optimization: String,
+    pub concurrent_operations: String,
+    pub total_sol: f64,
+    pub daily_profit: f64,
+    pub success_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ArbitrageExecution {
+    pub message: String,
+    pub token_a: String,
+    pub token_b: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuantumStatus {
+    pub quantum_systems_active: bool,
+    pub speed_enhancement: String,
+    pub accuracy_enhancement: String,
+    pub dominance_level: String,
+    pub parallel_universes: String,
+    pub consciousness_integration: String,
+    pub quantum_advantage: f64,
+    pub metrics: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuantumTradingResult {
+    pub quantum_speed_results: usize,
+    pub quantum_accuracy: f64,
+    pub parallel_universe_outcomes: usize,
+    pub total_quantum_advantage: f64,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuantumSuperposition {
+    pub quantum_superposition: String,
+    pub parallel_executions: String,
+    pub quantum_results: QuantumTradingResult,
+    pub enhancement_level: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuantumTunnelResult {
+    pub quantum_tunneling: String,
+    pub barriers_bypassed: Vec<String>,
+    pub tunneling_probability: f64,
+    pub speed_increase: String,
+    pub cost_reduction: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EntangledPair {
+    pub token_a: String,
+    pub token_b: String,
+    pub correlation: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuantumEntanglement {
+    pub quantum_entanglement: String,
+    pub entangled_pairs: Vec<EntangledPair>,
+    pub instantaneous_communication: bool,
+    pub distance_limitation: String,
+    pub execution_speed: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuantumConsciousness {
+    pub quantum_consciousness: String,
+    pub consciousness_level: f64,
+    pub awareness_amplification: String,
+    pub decision_making: String,
+    pub market_prediction: String,
+    pub trading_dominance: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SystemActivation {
+    pub message: String,
+    pub systems_activated: u32,
+    pub performance_mode: String,
+    pub quantum_advantage: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuantumBoostResult {
+    pub speed_increase: f64,
+    pub gas_reduction: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BotPerformance {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub bot_type: String,
+    /// Worker status: Idle, Starting, Healthy, Degraded, Stalled or Stopped
+    pub status: String,
+    /// Percentage of successful worker runs
+    pub accuracy: f64,
+    pub events_processed: u64,
+    pub error_rate: f64,
+    /// Unix seconds of the last successful run
+    pub last_heartbeat: Option<u64>,
+    /// Trades booked over the last 24 hours
+    pub trades: usize,
+    pub daily_revenue: f64,
+    pub profit_margin: f64,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TransactionMetrics {
+    pub total_transactions: u64,
+    pub successful_transactions: u64,
+    pub total_profit_sol: f64,
+    pub average_execution_time: f64,
+    pub gas_optimization_savings: f64,
+    pub mev_captured: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStatus {
+    pub active: bool,
+    pub queue_length: usize,
+    pub cached_prices: usize,
+    pub memecoin_opportunities: usize,
+    pub metrics: TransactionMetrics,
+    #[serde(rename = "connectedRPCs")]
+    pub connected_rpcs: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub enum StrategyType {
+    FlashArbitrage,
+    MemecoinGenesis,
+    LiquidityMining,
+    StakingYield,
+    GovernanceExtraction,
+    CrossChainBridge,
+    NFTArbitrage,
+    SocialSentiment,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ZeroCapitalStrategy {
+    pub id: String,
+    pub name: String,
+    pub strategy_type: StrategyType,
+    pub initial_capital_required: f64, // Always 0 for true zero-capital
+    pub expected_multiplier: f64,
+    pub time_to_profit: u64, // milliseconds
+    pub risk_score: f64,
+    pub uniqueness_rating: u8, // 1-10, how unique/advanced the strategy is
+    pub mathematical_model: String,
+    pub on_chain_programs: Vec<String>,
+    pub execution_steps: Vec<String>,
+    pub scaling_potential: f64, // SOL per hour at full scale
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ZeroCapitalStats {
+    pub current_capital: f64,
+    pub total_profit: f64,
+    pub active_strategies: usize,
+    pub flash_opportunities: usize,
+    pub memecoin_opportunities: usize,
+    pub liquidity_positions: usize,
+    pub sentiment_signals: usize,
+    pub profit_velocity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MemecoinTarget {
+    pub token_address: String,
+    pub token_name: String,
+    pub ticker: String,
+    pub pool_address: String,
+    pub liquidity_sol: f64,
+    pub creator_address: String,
+    pub launch_timestamp: u64,
+    pub social_score: f64,
+    pub viral_potential: f64,
+    pub rug_pull_risk: f64,
+    pub snipe_score: f64,
+    pub expected_multiplier: f64,
+    pub max_snipe_amount: f64,
+    pub optimal_snipe_time: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SnipingStats {
+    pub total_snipes: usize,
+    pub successful_snipes: usize,
+    pub success_rate: f64,
+    pub total_profit: f64,
+    pub average_profit: f64,
+    pub active_targets: usize,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TokenBalance {
+    pub token_address: String,
+    pub token_name: String,
+    pub symbol: String,
+    pub balance: f64,
+    pub decimals: u8,
+    pub price_usd: f64,
+    pub value_usd: f64,
+    pub change_24h: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WalletMetrics {
+    pub address: String,
+    pub name: String,
+    pub sol_balance: f64,
+    pub token_balances: Vec<TokenBalance>,
+    pub total_value_usd: f64,
+    pub daily_profit_loss: f64,
+    pub weekly_profit_loss: f64,
+    pub monthly_profit_loss: f64,
+    pub total_profit: f64,
+    pub transaction_count: u64,
+    pub success_rate: f64,
+    pub last_updated: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsOverview {
+    pub total_wallets: usize,
+    #[serde(rename = "totalSOL")]
+    pub total_sol: f64,
+    #[serde(rename = "totalValueUSD")]
+    pub total_value_usd: f64,
+    pub total_daily_profit: f64,
+    pub total_transactions: usize,
+    pub success_rate: f64,
+    pub active_alerts: usize,
+    /// OPERATIONAL or PAUSED
+    pub system_status: String,
+    pub last_update: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuditEntry {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub principal: String,
+    pub role: Role,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+}
+
+/// A registered strategy and its current configuration
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StrategyEntry {
+    pub id: String,
+    pub name: String,
+    /// Module the strategy was originally defined in
+    pub origin: String,
+    pub description: String,
+    /// Whether it is paper-trading
+    pub enabled: bool,
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+    /// Name, kind, range, default and description of each parameter
+    #[schema(value_type = Vec<Object>)]
+    pub schema: Vec<serde_json::Value>,
+}
+
+/// How a strategy did over a backtest or paper-trading session
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StrategyReport {
+    pub strategy_id: String,
+    pub events: u64,
+    pub fills: u64,
+    pub rejected_orders: u64,
+    pub balances: HashMap<String, f64>,
+    pub starting_equity: f64,
+    pub equity: f64,
+    pub pnl: f64,
+    pub realized_pnl: f64,
+    pub fees_paid: f64,
+    pub volume: f64,
+    /// Net base position per `venue:market`
+    pub positions: HashMap<String, f64>,
+    /// Held assets with no price, left out of equity
+    pub unpriced_assets: Vec<String>,
+}
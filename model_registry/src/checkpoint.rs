@@ -0,0 +1,286 @@
+//! MODEL CHECKPOINTS
+//! Weights plus everything needed to reproduce or validate them: hyperparameters,
+//! the feature schema the model was trained on, training metrics and a dataset hash
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::schema::{FeatureSchema, FeatureSpec};
+
+/// v2: typed feature schema and row provenance counts
+pub const CHECKPOINT_FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelCheckpoint {
+    pub format_version: u32,
+    pub model_name: String,
+    pub created_at: u64,
+    pub weights: HashMap<String, Vec<f32>>,
+    pub hyperparameters: HashMap<String, f64>,
+    pub feature_schema: FeatureSchema,
+    pub training_metrics: HashMap<String, f64>,
+    pub dataset_hash: String,
+    /// Training rows per data source, e.g. `{"rpc": 900, "synthetic": 0}`
+    pub provenance: HashMap<String, usize>,
+}
+
+#[derive(Deserialize)]
+struct FormatProbe {
+    format_version: u32,
+}
+
+/// v1 stored the feature schema as bare column names and had no provenance
+#[derive(Deserialize)]
+struct CheckpointV1 {
+    model_name: String,
+    created_at: u64,
+    weights: HashMap<String, Vec<f32>>,
+    hyperparameters: HashMap<String, f64>,
+    feature_schema: Vec<String>,
+    training_metrics: HashMap<String, f64>,
+    dataset_hash: String,
+}
+
+impl CheckpointV1 {
+    /// Types each column from its pipeline name. Provenance was never recorded,
+    /// so it is left empty rather than guessed.
+    fn migrate(self) -> Result<ModelCheckpoint> {
+        let features = self
+            .feature_schema
+            .iter()
+            .map(|name| {
+                FeatureSpec::from_name(name)
+                    .ok_or_else(|| anyhow!("v1 feature '{}' is not a pipeline feature, so it has no type", name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ModelCheckpoint {
+            format_version: CHECKPOINT_FORMAT_VERSION,
+            model_name: self.model_name,
+            created_at: self.created_at,
+            weights: self.weights,
+            hyperparameters: self.hyperparameters,
+            feature_schema: FeatureSchema { features },
+            training_metrics: self.training_metrics,
+            dataset_hash: self.dataset_hash,
+            provenance: HashMap::new(),
+        })
+    }
+}
+
+/// Everything in a checkpoint that does not come from the model itself.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointMetadata {
+    pub feature_schema: FeatureSchema,
+    pub training_metrics: HashMap<String, f64>,
+    pub dataset_hash: String,
+    pub provenance: HashMap<String, usize>,
+}
+
+impl ModelCheckpoint {
+    pub fn new(
+        model_name: String,
+        weights: HashMap<String, Vec<f32>>,
+        hyperparameters: HashMap<String, f64>,
+        metadata: CheckpointMetadata,
+    ) -> Self {
+        ModelCheckpoint {
+            format_version: CHECKPOINT_FORMAT_VERSION,
+            model_name,
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            weights,
+            hyperparameters,
+            feature_schema: metadata.feature_schema,
+            training_metrics: metadata.training_metrics,
+            dataset_hash: metadata.dataset_hash,
+            provenance: metadata.provenance,
+        }
+    }
+
+    /// Writes to a temporary file first so a crash never leaves a truncated checkpoint.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec(self)?)
+            .with_context(|| format!("writing checkpoint {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("reading checkpoint {}", path.display()))?;
+        // Check the version before the full parse so old files are migrated or get a clear error
+        let probe: FormatProbe = serde_json::from_slice(&bytes)?;
+        match probe.format_version {
+            CHECKPOINT_FORMAT_VERSION => Ok(serde_json::from_slice(&bytes)?),
+            1 => {
+                let v1: CheckpointV1 = serde_json::from_slice(&bytes)?;
+                let checkpoint = v1
+                    .migrate()
+                    .with_context(|| format!("migrating checkpoint {} from v1", path.display()))?;
+                println!("🔄 Migrated {} checkpoint {} from v1", checkpoint.model_name, path.display());
+                Ok(checkpoint)
+            }
+            other => Err(anyhow!(
+                "Checkpoint {} uses format v{}, this build reads v1 and v{}",
+                path.display(),
+                other,
+                CHECKPOINT_FORMAT_VERSION
+            )),
+        }
+    }
+
+    /// Checks that the checkpoint was produced by a model with the same name and
+    /// shape-defining hyperparameters before its weights are copied in.
+    pub fn ensure_compatible(&self, model_name: &str, hyperparameters: &HashMap<String, f64>) -> Result<()> {
+        if self.model_name != model_name {
+            return Err(anyhow!(
+                "Checkpoint is for '{}', not '{}'",
+                self.model_name,
+                model_name
+            ));
+        }
+
+        for (key, expected) in hyperparameters {
+            match self.hyperparameters.get(key) {
+                Some(stored) if stored == expected => {}
+                Some(stored) => {
+                    return Err(anyhow!(
+                        "Hyperparameter '{}' is {} in the checkpoint but {} in the model",
+                        key,
+                        stored,
+                        expected
+                    ))
+                }
+                None => return Err(anyhow!("Checkpoint is missing hyperparameter '{}'", key)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stable FNV-1a hash of a dataset, so a checkpoint records exactly which rows trained it.
+pub fn dataset_hash(dataset: &[Vec<f32>]) -> String {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut hash = OFFSET;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+
+    feed(&(dataset.len() as u64).to_le_bytes());
+    for row in dataset {
+        feed(&(row.len() as u64).to_le_bytes());
+        for value in row {
+            feed(&value.to_bits().to_le_bytes());
+        }
+    }
+
+    format!("{:016x}", hash)
+}
+
+/// Copies a named tensor out of checkpoint weights, checking its length.
+pub fn take_weights(weights: &HashMap<String, Vec<f32>>, name: &str, expected_len: usize) -> Result<Vec<f32>> {
+    let values = weights
+        .get(name)
+        .ok_or_else(|| anyhow!("Checkpoint is missing weights '{}'", name))?;
+
+    if values.len() != expected_len {
+        return Err(anyhow!(
+            "Weights '{}' have {} values, expected {}",
+            name,
+            values.len(),
+            expected_len
+        ));
+    }
+    Ok(values.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{FeatureKind, FeatureType, ReserveSide};
+
+    fn test_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("checkpoint_{}_{}.json", name, std::process::id()))
+    }
+
+    fn v1_json(feature_schema: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "format_version": 1,
+            "model_name": "hybrid_lstm_quantum",
+            "created_at": 1_700_000_000u64,
+            "weights": { "w": [0.5, -0.5] },
+            "hyperparameters": { "time_steps": 10.0 },
+            "feature_schema": feature_schema,
+            "training_metrics": { "accuracy": 55.0 },
+            "dataset_hash": "00000000deadbeef"
+        })
+    }
+
+    #[test]
+    fn saves_and_loads_the_current_format() {
+        let path = test_path("current");
+        let mut metadata = CheckpointMetadata::default();
+        metadata.provenance.insert("rpc".to_string(), 900);
+        let checkpoint = ModelCheckpoint::new("m".to_string(), HashMap::new(), HashMap::new(), metadata);
+        checkpoint.save(&path).unwrap();
+
+        let loaded = ModelCheckpoint::load(&path).unwrap();
+        assert_eq!(loaded.format_version, CHECKPOINT_FORMAT_VERSION);
+        assert_eq!(loaded.provenance["rpc"], 900);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn migrates_v1_checkpoints_with_pipeline_feature_names() {
+        let path = test_path("v1");
+        let names = ["log_return_1", "realized_volatility_20", "order_flow_imbalance_5", "quote_reserve_delta"];
+        std::fs::write(&path, serde_json::to_vec(&v1_json(&names)).unwrap()).unwrap();
+
+        let checkpoint = ModelCheckpoint::load(&path).unwrap();
+        assert_eq!(checkpoint.format_version, CHECKPOINT_FORMAT_VERSION);
+        assert_eq!(checkpoint.weights["w"], [0.5, -0.5]);
+        assert_eq!(checkpoint.dataset_hash, "00000000deadbeef");
+        assert!(checkpoint.provenance.is_empty());
+        assert_eq!(checkpoint.feature_schema.names(), names);
+        let kinds: Vec<&FeatureKind> = checkpoint.feature_schema.features.iter().map(|f| &f.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &FeatureKind::LogReturn { window: 1 },
+                &FeatureKind::RealizedVolatility { window: 20 },
+                &FeatureKind::OrderFlowImbalance { window: 5 },
+                &FeatureKind::ReserveDelta { side: ReserveSide::Quote },
+            ]
+        );
+        assert_eq!(checkpoint.feature_schema.features[2].value_type, FeatureType::Bounded { min: -1.0, max: 1.0 });
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn refuses_v1_features_it_cannot_type_and_unknown_formats() {
+        let path = test_path("untyped");
+        std::fs::write(&path, serde_json::to_vec(&v1_json(&["log_return_1", "price"])).unwrap()).unwrap();
+        let error = format!("{:#}", ModelCheckpoint::load(&path).unwrap_err());
+        assert!(error.contains("'price'"), "{}", error);
+
+        let mut future = v1_json(&[]);
+        future["format_version"] = serde_json::json!(CHECKPOINT_FORMAT_VERSION + 1);
+        std::fs::write(&path, serde_json::to_vec(&future).unwrap()).unwrap();
+        assert!(ModelCheckpoint::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,12 @@
+//! BLACK DIAMOND MODEL REGISTRY
+//! Versioned model checkpoints shared by the training app and the deployment manager
+
+pub mod checkpoint;
+pub mod registry;
+pub mod schema;
+pub mod serving;
+
+pub use checkpoint::{dataset_hash, take_weights, CheckpointMetadata, ModelCheckpoint, CHECKPOINT_FORMAT_VERSION};
+pub use registry::{ModelRegistry, ModelVersions, VersionEntry};
+pub use schema::{FeatureKind, FeatureSchema, FeatureSpec, FeatureType, ReserveSide};
+pub use serving::{PredictRequest, PredictResponse, ScoreKind, ServeError, ServedModelStatus};
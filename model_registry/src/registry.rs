@@ -0,0 +1,241 @@
+//! MODEL REGISTRY
+//! Numbered checkpoint versions per model with a promoted production version
+//! and a promotion history for rollbacks.
+//!
+//! Layout: `<root>/registry.json` index plus `<root>/<model>/v<N>.json` checkpoints
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::checkpoint::ModelCheckpoint;
+
+const INDEX_FILE: &str = "registry.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionEntry {
+    pub version: u32,
+    pub file: String, // Relative to the registry root
+    pub created_at: u64,
+    pub dataset_hash: String,
+    pub training_metrics: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelVersions {
+    pub versions: Vec<VersionEntry>,
+    pub production: Option<u32>,
+    /// Previously promoted versions, most recent last
+    pub promotion_history: Vec<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryIndex {
+    models: HashMap<String, ModelVersions>,
+}
+
+pub struct ModelRegistry {
+    root: PathBuf,
+    index: RegistryIndex,
+}
+
+impl ModelRegistry {
+    /// Opens the registry at `root`; a missing index is an empty registry.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        let index_path = root.join(INDEX_FILE);
+
+        let index = if index_path.exists() {
+            serde_json::from_slice(&std::fs::read(&index_path)?)?
+        } else {
+            RegistryIndex::default()
+        };
+
+        Ok(ModelRegistry { root, index })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Stores the checkpoint as the model's next version. It is not promoted.
+    pub fn register(&mut self, checkpoint: &ModelCheckpoint) -> Result<u32> {
+        validate_model_name(&checkpoint.model_name)?;
+        let entry = self.index.models.entry(checkpoint.model_name.clone()).or_default();
+        let version = entry.versions.iter().map(|v| v.version).max().unwrap_or(0) + 1;
+        let file = format!("{}/v{}.json", checkpoint.model_name, version);
+
+        checkpoint.save(&self.root.join(&file))?;
+
+        entry.versions.push(VersionEntry {
+            version,
+            file,
+            created_at: checkpoint.created_at,
+            dataset_hash: checkpoint.dataset_hash.clone(),
+            training_metrics: checkpoint.training_metrics.clone(),
+        });
+        self.persist()?;
+
+        println!("📦 Registered {} v{}", checkpoint.model_name, version);
+        Ok(version)
+    }
+
+    /// Makes `version` the production version, remembering the previous one.
+    pub fn promote(&mut self, model_name: &str, version: u32) -> Result<()> {
+        let entry = self
+            .index
+            .models
+            .get_mut(model_name)
+            .ok_or_else(|| anyhow!("No versions registered for '{}'", model_name))?;
+
+        if !entry.versions.iter().any(|v| v.version == version) {
+            return Err(anyhow!("'{}' has no version {}", model_name, version));
+        }
+        if entry.production == Some(version) {
+            return Ok(());
+        }
+
+        if let Some(previous) = entry.production.replace(version) {
+            entry.promotion_history.push(previous);
+        }
+        self.persist()?;
+
+        println!("🚀 Promoted {} to v{}", model_name, version);
+        Ok(())
+    }
+
+    /// Restores the previously promoted version and returns it.
+    pub fn rollback(&mut self, model_name: &str) -> Result<u32> {
+        let entry = self
+            .index
+            .models
+            .get_mut(model_name)
+            .ok_or_else(|| anyhow!("No versions registered for '{}'", model_name))?;
+
+        let previous = entry
+            .promotion_history
+            .pop()
+            .ok_or_else(|| anyhow!("'{}' has no earlier promoted version", model_name))?;
+        entry.production = Some(previous);
+        self.persist()?;
+
+        println!("⏪ Rolled back {} to v{}", model_name, previous);
+        Ok(previous)
+    }
+
+    pub fn production_version(&self, model_name: &str) -> Option<u32> {
+        self.index.models.get(model_name).and_then(|m| m.production)
+    }
+
+    pub fn latest_version(&self, model_name: &str) -> Option<u32> {
+        self.index
+            .models
+            .get(model_name)
+            .and_then(|m| m.versions.iter().map(|v| v.version).max())
+    }
+
+    pub fn versions(&self, model_name: &str) -> Option<&ModelVersions> {
+        self.index.models.get(model_name)
+    }
+
+    pub fn load_checkpoint(&self, model_name: &str, version: u32) -> Result<ModelCheckpoint> {
+        let entry = self
+            .index
+            .models
+            .get(model_name)
+            .and_then(|m| m.versions.iter().find(|v| v.version == version))
+            .ok_or_else(|| anyhow!("'{}' has no version {}", model_name, version))?;
+
+        ModelCheckpoint::load(&self.root.join(&entry.file))
+    }
+
+    pub fn load_production(&self, model_name: &str) -> Result<Option<ModelCheckpoint>> {
+        match self.production_version(model_name) {
+            Some(version) => Ok(Some(self.load_checkpoint(model_name, version)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn persist(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.root)?;
+        let index_path = self.root.join(INDEX_FILE);
+        let tmp_path = index_path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(&self.index)?)?;
+        std::fs::rename(&tmp_path, &index_path)?;
+        Ok(())
+    }
+}
+
+/// Model names become directory names under the registry root, so they must
+/// not be able to point anywhere else
+fn validate_model_name(model_name: &str) -> Result<()> {
+    if model_name.is_empty()
+        || model_name == "."
+        || model_name.contains("..")
+        || model_name.contains(['/', '\\'])
+    {
+        return Err(anyhow!("Invalid model name '{}'", model_name));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::CheckpointMetadata;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("model_registry_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn checkpoint(model_name: &str, weight: f32) -> ModelCheckpoint {
+        let mut weights = HashMap::new();
+        weights.insert("w".to_string(), vec![weight]);
+        ModelCheckpoint::new(model_name.to_string(), weights, HashMap::new(), CheckpointMetadata::default())
+    }
+
+    #[test]
+    fn promotes_and_rolls_back_versions_across_reopens() {
+        let root = test_dir("promote");
+        let mut registry = ModelRegistry::open(&root).unwrap();
+        for weight in [1.0, 2.0, 3.0] {
+            registry.register(&checkpoint("lstm", weight)).unwrap();
+        }
+        assert_eq!(registry.latest_version("lstm"), Some(3));
+        assert_eq!(registry.production_version("lstm"), None);
+        assert!(registry.promote("lstm", 4).is_err());
+        assert!(registry.rollback("lstm").is_err());
+
+        registry.promote("lstm", 1).unwrap();
+        registry.promote("lstm", 3).unwrap();
+        // Promoting the production version again does not add to the history
+        registry.promote("lstm", 3).unwrap();
+        assert_eq!(registry.versions("lstm").unwrap().promotion_history, [1]);
+
+        let mut registry = ModelRegistry::open(&root).unwrap();
+        assert_eq!(registry.load_production("lstm").unwrap().unwrap().weights["w"], [3.0]);
+        assert_eq!(registry.rollback("lstm").unwrap(), 1);
+        assert_eq!(registry.load_production("lstm").unwrap().unwrap().weights["w"], [1.0]);
+        assert!(registry.rollback("lstm").is_err());
+        assert_eq!(registry.production_version("lstm"), Some(1));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rejects_model_names_that_leave_the_root() {
+        let root = test_dir("names");
+        let mut registry = ModelRegistry::open(&root).unwrap();
+        for name in ["", ".", "..", "../escape", "nested/model", "a\\b", "x..y"] {
+            assert!(registry.register(&checkpoint(name, 1.0)).is_err(), "{:?}", name);
+        }
+        assert!(!root.exists());
+
+        registry.register(&checkpoint("perpetuals_trading_transformer", 1.0)).unwrap();
+        assert!(root.join("perpetuals_trading_transformer/v1.json").exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+//! FEATURE SCHEMA
+//! Named, typed description of the columns a model was trained on. Stored with
+//! every checkpoint so inference can reject inputs built with a different layout
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FeatureKind {
+    /// Log return of the mid price over `window` observations
+    LogReturn { window: usize },
+    /// Standard deviation of one-step log returns over `window` observations
+    RealizedVolatility { window: usize },
+    /// (buy - sell) / (buy + sell) volume over `window` observations
+    OrderFlowImbalance { window: usize },
+    /// Relative change of a pool reserve since the previous observation
+    ReserveDelta { side: ReserveSide },
+    /// Perpetual funding rate per funding interval
+    FundingRate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReserveSide {
+    Base,
+    Quote,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeatureType {
+    /// Any finite value
+    Continuous,
+    /// Finite and within `[min, max]`
+    Bounded { min: f32, max: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureSpec {
+    pub name: String,
+    pub kind: FeatureKind,
+    pub value_type: FeatureType,
+}
+
+impl FeatureSpec {
+    /// Recovers the spec of a column named the way the feature pipeline names
+    /// them, e.g. `log_return_5` or `base_reserve_delta`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let window = |prefix: &str| name.strip_prefix(prefix).and_then(|w| w.parse::<usize>().ok());
+
+        let (kind, value_type) = if let Some(window) = window("log_return_") {
+            (FeatureKind::LogReturn { window }, FeatureType::Continuous)
+        } else if let Some(window) = window("realized_volatility_") {
+            (FeatureKind::RealizedVolatility { window }, FeatureType::Bounded { min: 0.0, max: f32::MAX })
+        } else if let Some(window) = window("order_flow_imbalance_") {
+            (FeatureKind::OrderFlowImbalance { window }, FeatureType::Bounded { min: -1.0, max: 1.0 })
+        } else {
+            match name {
+                "base_reserve_delta" => (FeatureKind::ReserveDelta { side: ReserveSide::Base }, FeatureType::Continuous),
+                "quote_reserve_delta" => (FeatureKind::ReserveDelta { side: ReserveSide::Quote }, FeatureType::Continuous),
+                "funding_rate" => (FeatureKind::FundingRate, FeatureType::Continuous),
+                _ => return None,
+            }
+        };
+
+        Some(FeatureSpec {
+            name: name.to_string(),
+            kind,
+            value_type,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureSchema {
+    pub features: Vec<FeatureSpec>,
+}
+
+impl FeatureSchema {
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.features.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.features.iter().position(|f| f.name == name)
+    }
+
+    /// Checks a row's width and every value against its declared type.
+    pub fn validate_row(&self, row: &[f32]) -> Result<()> {
+        if row.len() != self.features.len() {
+            return Err(anyhow!(
+                "Row has {} values but the schema declares {} features",
+                row.len(),
+                self.features.len()
+            ));
+        }
+
+        for (value, spec) in row.iter().zip(self.features.iter()) {
+            if !value.is_finite() {
+                return Err(anyhow!("Feature '{}' is not finite ({})", spec.name, value));
+            }
+            if let FeatureType::Bounded { min, max } = spec.value_type {
+                if *value < min || *value > max {
+                    return Err(anyhow!(
+                        "Feature '{}' = {} is outside [{}, {}]",
+                        spec.name,
+                        value,
+                        min,
+                        max
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+//! Wire types of the training app's inference server, shared with the
+//! trading engine's client so both sides agree on the JSON.
+
+use serde::{Deserialize, Serialize};
+
+/// What a model's scored output means
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreKind {
+    /// Probability of the positive class, in [0, 1]
+    Probability,
+    /// Signed forecast of the next change
+    Forecast,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictRequest {
+    /// One feature row per prediction, laid out as in the checkpoint's schema
+    pub inputs: Vec<Vec<f32>>,
+    /// Tighter deadline than the server default, in milliseconds
+    #[serde(default)]
+    pub budget_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictResponse {
+    pub model_name: String,
+    pub version: u32,
+    pub score_kind: ScoreKind,
+    /// Full model output per input row
+    pub outputs: Vec<Vec<f32>>,
+    /// The scored output per input row
+    pub scores: Vec<f32>,
+    /// Rows in the batch this request was run in, including other requests'
+    pub batch_rows: usize,
+    pub queue_us: u64,
+    pub inference_us: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServedModelStatus {
+    pub model_name: String,
+    pub version: u32,
+    pub dataset_hash: String,
+    pub feature_names: Vec<String>,
+    pub loaded_at: i64,
+    pub requests_served: u64,
+    pub deadline_misses: u64,
+    pub reloads: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServeError {
+    pub error: String,
+}
@@ -0,0 +1,170 @@
+/**
+ * ATOMIC ARBITRAGE PROGRAM
+ * Runs a route of swap CPIs in one instruction and reverts the whole
+ * transaction unless the caller's holdings of the output mint grew by the
+ * minimum profit
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_spl::token::TokenAccount;
+
+declare_id!("21x6jGg5XXKQNmhXgGLpATQmh8m5QG3s8Rh63BgcGq8h");
+
+#[program]
+pub mod atomic_arb {
+    use super::*;
+
+    /// Executes `legs` in order against `remaining_accounts`, then checks the
+    /// authority's balance of the output mint, summed over the output account
+    /// and every same-mint account of theirs in the route, grew by at least
+    /// `min_profit`. Tokens a leg merely moves between the authority's own
+    /// accounts, such as the route's principal, net to zero.
+    pub fn execute_route<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteRoute<'info>>,
+        legs: Vec<SwapLeg>,
+        min_profit: u64,
+    ) -> Result<()> {
+        require!(!legs.is_empty(), ArbError::EmptyRoute);
+        let leg_count = u8::try_from(legs.len()).map_err(|_| error!(ArbError::TooManyLegs))?;
+
+        let output = &ctx.accounts.output_token_account;
+        let holdings = same_mint_accounts(ctx.remaining_accounts, output.key(), output.mint, ctx.accounts.authority.key());
+        let balance_before = total_balance(output.amount, &holdings)?;
+        for leg in &legs {
+            execute_leg(leg, ctx.remaining_accounts)?;
+        }
+
+        ctx.accounts.output_token_account.reload()?;
+        let balance_after = total_balance(ctx.accounts.output_token_account.amount, &holdings)?;
+        let required = balance_before.checked_add(min_profit).ok_or(ArbError::Overflow)?;
+        require!(balance_after >= required, ArbError::InsufficientProfit);
+
+        emit!(RouteExecuted {
+            authority: ctx.accounts.authority.key(),
+            legs: leg_count,
+            balance_before,
+            balance_after,
+        });
+        Ok(())
+    }
+}
+
+/// Token accounts among `remaining` other than the output account that hold
+/// `mint` for `authority`, each once
+fn same_mint_accounts<'a, 'info>(
+    remaining: &'a [AccountInfo<'info>],
+    output: Pubkey,
+    mint: Pubkey,
+    authority: Pubkey,
+) -> Vec<&'a AccountInfo<'info>> {
+    let mut holdings: Vec<&AccountInfo> = Vec::new();
+    for account in remaining {
+        if account.key() == output || holdings.iter().any(|held| held.key() == account.key()) {
+            continue;
+        }
+        if let Some(token) = token_account(account) {
+            if token.mint == mint && token.owner == authority {
+                holdings.push(account);
+            }
+        }
+    }
+    holdings
+}
+
+fn token_account(account: &AccountInfo) -> Option<TokenAccount> {
+    if account.owner != &anchor_spl::token::ID {
+        return None;
+    }
+    let data = account.try_borrow_data().ok()?;
+    TokenAccount::try_deserialize(&mut &data[..]).ok()
+}
+
+/// `output_amount` plus the current amounts of `holdings`. A holding that no
+/// longer reads as a token account, because a leg closed it, counts as empty.
+fn total_balance(output_amount: u64, holdings: &[&AccountInfo]) -> Result<u64> {
+    holdings.iter().try_fold(output_amount, |total, account| {
+        let amount = token_account(account).map_or(0, |token| token.amount);
+        total.checked_add(amount).ok_or_else(|| error!(ArbError::Overflow))
+    })
+}
+
+fn execute_leg<'info>(leg: &SwapLeg, remaining: &[AccountInfo<'info>]) -> Result<()> {
+    let program = remaining
+        .get(leg.program_index as usize)
+        .ok_or(ArbError::AccountIndexOutOfRange)?;
+    require!(program.executable, ArbError::NotAProgram);
+    require_keys_neq!(program.key(), crate::ID, ArbError::ReentrantCall);
+
+    let start = leg.account_start as usize;
+    let end = start + leg.account_count as usize;
+    let accounts = remaining.get(start..end).ok_or(ArbError::AccountIndexOutOfRange)?;
+
+    let instruction = Instruction {
+        program_id: program.key(),
+        accounts: accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: leg.data.clone(),
+    };
+
+    let mut infos = accounts.to_vec();
+    infos.push(program.clone());
+    invoke(&instruction, &infos)?;
+    Ok(())
+}
+
+/// One swap CPI. Its program is an index and its accounts a contiguous range
+/// of the instruction's remaining accounts, so legs calling the same program,
+/// or taking the same accounts in the same order, can point at one copy.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapLeg {
+    pub program_index: u8,
+    pub account_start: u8,
+    pub account_count: u8,
+    pub data: Vec<u8>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRoute<'info> {
+    pub authority: Signer<'info>,
+    /// Where the route's profit lands; checked before and after the legs
+    /// together with the authority's other accounts of the same mint
+    #[account(mut, token::authority = authority)]
+    pub output_token_account: Account<'info, TokenAccount>,
+}
+
+#[event]
+pub struct RouteExecuted {
+    pub authority: Pubkey,
+    pub legs: u8,
+    /// Authority's output-mint balance across the route's accounts
+    pub balance_before: u64,
+    pub balance_after: u64,
+}
+
+#[error_code]
+pub enum ArbError {
+    #[msg("Route has no swap legs")]
+    EmptyRoute,
+    #[msg("Swap leg refers past the remaining accounts")]
+    AccountIndexOutOfRange,
+    #[msg("Swap leg program account is not executable")]
+    NotAProgram,
+    #[msg("Swap leg may not call back into this program")]
+    ReentrantCall,
+    #[msg("Output-mint holdings did not grow by the minimum profit")]
+    InsufficientProfit,
+    #[msg("Output-mint balance overflows")]
+    Overflow,
+    #[msg("Route has more than 255 swap legs")]
+    TooManyLegs,
+}
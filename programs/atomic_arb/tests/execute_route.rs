@@ -0,0 +1,229 @@
+//! Runs the compiled program under program-test. Build it first with
+//! `anchor build` (or `cargo build-sbf --manifest-path programs/atomic_arb/Cargo.toml`)
+//! so `target/deploy/atomic_arb.so` exists.
+//!
+//! Each route uses SPL token transfers into the output account as its
+//! "swaps": from a pool owned by someone else, which is profit, or from the
+//! authority's own source account, which is principal and nets to zero.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use atomic_arb::SwapLeg;
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+
+struct Fixture {
+    context: ProgramTestContext,
+    authority: Keypair,
+    pool_authority: Keypair,
+    source: Pubkey,
+    pool: Pubkey,
+    output: Pubkey,
+}
+
+async fn setup() -> Fixture {
+    let program_test = ProgramTest::new("atomic_arb", atomic_arb::ID, None);
+    let mut context = program_test.start_with_context().await;
+    let authority = Keypair::new();
+    let pool_authority = Keypair::new();
+    let mint = Keypair::new();
+    let source = Keypair::new();
+    let pool = Keypair::new();
+    let output = Keypair::new();
+    let payer = context.payer.insecure_clone();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let mut instructions = vec![
+        system_instruction::transfer(&payer.pubkey(), &authority.pubkey(), 1_000_000_000),
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer.pubkey(), None, 6).unwrap(),
+    ];
+    for (account, owner) in [(&source, &authority), (&pool, &pool_authority), (&output, &authority)] {
+        instructions.push(system_instruction::create_account(
+            &payer.pubkey(),
+            &account.pubkey(),
+            rent.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            &spl_token::id(),
+        ));
+        instructions.push(
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &account.pubkey(),
+                &mint.pubkey(),
+                &owner.pubkey(),
+            )
+            .unwrap(),
+        );
+    }
+    for account in [&source, &pool] {
+        instructions.push(
+            spl_token::instruction::mint_to(&spl_token::id(), &mint.pubkey(), &account.pubkey(), &payer.pubkey(), &[], 1_000_000)
+                .unwrap(),
+        );
+    }
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[&payer, &mint, &source, &pool, &output],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    Fixture {
+        context,
+        authority,
+        pool_authority,
+        source: source.pubkey(),
+        pool: pool.pubkey(),
+        output: output.pubkey(),
+    }
+}
+
+/// Route with one leg per `(from, from_authority, amount)` transfer into
+/// the output account
+fn transfer_route(fixture: &Fixture, transfers: &[(Pubkey, Pubkey, u64)], min_profit: u64) -> Instruction {
+    let mut accounts = atomic_arb::accounts::ExecuteRoute {
+        authority: fixture.authority.pubkey(),
+        output_token_account: fixture.output,
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+
+    let mut legs = Vec::new();
+    for (from, from_authority, amount) in transfers {
+        let transfer =
+            spl_token::instruction::transfer(&spl_token::id(), from, &fixture.output, from_authority, &[], *amount).unwrap();
+        legs.push(SwapLeg {
+            program_index: 0,
+            // Remaining accounts start after the two named accounts
+            account_start: (accounts.len() - 2) as u8,
+            account_count: transfer.accounts.len() as u8,
+            data: transfer.data,
+        });
+        accounts.extend(transfer.accounts);
+    }
+
+    Instruction {
+        program_id: atomic_arb::ID,
+        accounts,
+        data: atomic_arb::instruction::ExecuteRoute { legs, min_profit }.data(),
+    }
+}
+
+/// Leg paying `amount` out of the pool, which the authority does not own
+fn from_pool(fixture: &Fixture, amount: u64) -> (Pubkey, Pubkey, u64) {
+    (fixture.pool, fixture.pool_authority.pubkey(), amount)
+}
+
+/// Leg moving the authority's own principal into the output account
+fn from_source(fixture: &Fixture, amount: u64) -> (Pubkey, Pubkey, u64) {
+    (fixture.source, fixture.authority.pubkey(), amount)
+}
+
+async fn run(fixture: &mut Fixture, instruction: Instruction) -> Result<(), BanksClientError> {
+    let payer = fixture.context.payer.insecure_clone();
+    let blockhash = fixture.context.get_new_latest_blockhash().await.unwrap();
+    let mut signers = vec![&payer, &fixture.authority];
+    if instruction.accounts.iter().any(|meta| meta.is_signer && meta.pubkey == fixture.pool_authority.pubkey()) {
+        signers.push(&fixture.pool_authority);
+    }
+    let transaction = Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &signers, blockhash);
+    fixture.context.banks_client.process_transaction(transaction).await
+}
+
+async fn balance(fixture: &mut Fixture, account: Pubkey) -> u64 {
+    let account = fixture.context.banks_client.get_account(account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+fn custom_error(error: BanksClientError) -> Option<u32> {
+    match error.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
+
+fn insufficient_profit() -> Option<u32> {
+    Some(anchor_lang::error::ERROR_CODE_OFFSET + atomic_arb::ArbError::InsufficientProfit as u32)
+}
+
+#[tokio::test]
+async fn profitable_route_succeeds() {
+    let mut fixture = setup().await;
+    let instruction = transfer_route(&fixture, &[from_pool(&fixture, 500)], 500);
+    let (pool, output) = (fixture.pool, fixture.output);
+
+    run(&mut fixture, instruction).await.unwrap();
+
+    assert_eq!(balance(&mut fixture, output).await, 500);
+    assert_eq!(balance(&mut fixture, pool).await, 999_500);
+}
+
+#[tokio::test]
+async fn unprofitable_route_reverts_every_leg() {
+    let mut fixture = setup().await;
+    let instruction = transfer_route(&fixture, &[from_pool(&fixture, 499)], 500);
+    let (pool, output) = (fixture.pool, fixture.output);
+
+    let error = run(&mut fixture, instruction).await.unwrap_err();
+
+    assert_eq!(custom_error(error), insufficient_profit());
+    // The transfer leg rolled back with the failed check
+    assert_eq!(balance(&mut fixture, output).await, 0);
+    assert_eq!(balance(&mut fixture, pool).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn pulling_principal_into_the_output_is_not_profit() {
+    let mut fixture = setup().await;
+    let instruction = transfer_route(&fixture, &[from_source(&fixture, 500)], 500);
+    let (source, output) = (fixture.source, fixture.output);
+
+    let error = run(&mut fixture, instruction).await.unwrap_err();
+
+    assert_eq!(custom_error(error), insufficient_profit());
+    assert_eq!(balance(&mut fixture, output).await, 0);
+    assert_eq!(balance(&mut fixture, source).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn only_the_gain_beyond_principal_counts() {
+    let mut fixture = setup().await;
+    let route = [from_source(&fixture, 1_000), from_pool(&fixture, 300)];
+
+    let instruction = transfer_route(&fixture, &route, 301);
+    let error = run(&mut fixture, instruction).await.unwrap_err();
+    assert_eq!(custom_error(error), insufficient_profit());
+
+    let instruction = transfer_route(&fixture, &route, 300);
+    run(&mut fixture, instruction).await.unwrap();
+    let output = fixture.output;
+    assert_eq!(balance(&mut fixture, output).await, 1_300);
+}
+
+#[tokio::test]
+async fn leg_may_not_call_back_into_the_program() {
+    let mut fixture = setup().await;
+    let mut instruction = transfer_route(&fixture, &[from_source(&fixture, 500)], 0);
+    // Point the leg's program at atomic-arb itself
+    instruction.accounts[2] = AccountMeta::new_readonly(atomic_arb::ID, false);
+
+    let error = run(&mut fixture, instruction).await.unwrap_err();
+
+    let expected = anchor_lang::error::ERROR_CODE_OFFSET + atomic_arb::ArbError::ReentrantCall as u32;
+    assert_eq!(custom_error(error), Some(expected));
+}
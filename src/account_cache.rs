@@ -0,0 +1,444 @@
+/**
+ * ACCOUNT STATE CACHE
+ * One shared view of account data keyed by pubkey, remembering the slot and
+ * commitment each version was seen at, so readers can demand fresh enough
+ * data and read several accounts as of the same slot
+ */
+
+use anyhow::{anyhow, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "processed" => Some(Commitment::Processed),
+            "confirmed" => Some(Commitment::Confirmed),
+            "finalized" => Some(Commitment::Finalized),
+            _ => None,
+        }
+    }
+
+    pub fn config(self) -> CommitmentConfig {
+        match self {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedAccount {
+    pub pubkey: Pubkey,
+    /// Slot the data was first seen at
+    pub slot: u64,
+    pub commitment: Commitment,
+    pub data: Arc<Vec<u8>>,
+    pub owner: Option<Pubkey>,
+    pub lamports: Option<u64>,
+    pub stored_at: Instant,
+}
+
+/// What a reader needs from cached data; anything weaker is a miss
+#[derive(Debug, Clone, Copy)]
+pub struct ReadRequirement {
+    pub min_slot: Option<u64>,
+    pub commitment: Commitment,
+    pub max_age: Option<Duration>,
+}
+
+impl ReadRequirement {
+    pub fn any() -> Self {
+        ReadRequirement {
+            min_slot: None,
+            commitment: Commitment::Processed,
+            max_age: None,
+        }
+    }
+
+    pub fn confirmed() -> Self {
+        Self::any().with_commitment(Commitment::Confirmed)
+    }
+
+    pub fn at_least_slot(mut self, slot: u64) -> Self {
+        self.min_slot = Some(slot);
+        self
+    }
+
+    pub fn with_commitment(mut self, commitment: Commitment) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountCacheConfig {
+    /// Accounts not written or confirmed for this long are evicted
+    pub ttl: Duration,
+    /// Versions kept per account for slot snapshots
+    pub max_versions: usize,
+}
+
+impl Default for AccountCacheConfig {
+    fn default() -> Self {
+        AccountCacheConfig {
+            ttl: Duration::from_secs(60),
+            max_versions: 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub accounts: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub inserts: u64,
+    pub evictions: u64,
+}
+
+/// Accounts read together as of one slot
+#[derive(Debug, Clone)]
+pub struct AccountSnapshot {
+    pub slot: u64,
+    pub accounts: HashMap<Pubkey, CachedAccount>,
+}
+
+impl AccountSnapshot {
+    pub fn data(&self, pubkey: &Pubkey) -> Option<&[u8]> {
+        self.accounts.get(pubkey).map(|a| a.data.as_slice())
+    }
+}
+
+struct Entry {
+    /// Oldest first; each version holds until the next one's slot
+    versions: VecDeque<CachedAccount>,
+    /// Latest slot the newest version is known to still hold at
+    valid_through: u64,
+    touched_at: Instant,
+}
+
+pub struct AccountCache {
+    config: AccountCacheConfig,
+    entries: RwLock<HashMap<Pubkey, Entry>>,
+    stats: RwLock<CacheStats>,
+}
+
+impl AccountCache {
+    pub fn new(config: AccountCacheConfig) -> Self {
+        Self {
+            config,
+            entries: RwLock::new(HashMap::new()),
+            stats: RwLock::new(CacheStats::default()),
+        }
+    }
+
+    /// Records `data` as the account's state at `slot`. Re-reading unchanged
+    /// data only extends how far the current version is known to hold, or
+    /// raises its commitment.
+    pub fn insert(
+        &self,
+        pubkey: Pubkey,
+        slot: u64,
+        commitment: Commitment,
+        data: Vec<u8>,
+        owner: Option<Pubkey>,
+        lamports: Option<u64>,
+    ) {
+        let now = Instant::now();
+        let mut entries = self.entries.write();
+        let entry = entries.entry(pubkey).or_insert_with(|| Entry {
+            versions: VecDeque::new(),
+            valid_through: slot,
+            touched_at: now,
+        });
+        entry.touched_at = now;
+
+        if let Some(latest) = entry.versions.back_mut() {
+            if slot >= latest.slot && *latest.data == data {
+                latest.commitment = latest.commitment.max(commitment);
+                entry.valid_through = entry.valid_through.max(slot);
+                return;
+            }
+            if slot < latest.slot {
+                // A late read can still raise an older version's commitment,
+                // but changed data older than what we hold cannot be ordered
+                if let Some(version) = entry.versions.iter_mut().find(|v| v.slot == slot && *v.data == data) {
+                    version.commitment = version.commitment.max(commitment);
+                }
+                return;
+            }
+        }
+
+        entry.versions.push_back(CachedAccount {
+            pubkey,
+            slot,
+            commitment,
+            data: Arc::new(data),
+            owner,
+            lamports,
+            stored_at: now,
+        });
+        entry.valid_through = slot;
+        while entry.versions.len() > self.config.max_versions.max(1) {
+            entry.versions.pop_front();
+        }
+        self.stats.write().inserts += 1;
+    }
+
+    /// Newest version meeting `requirement`
+    pub fn get(&self, pubkey: &Pubkey, requirement: &ReadRequirement) -> Result<CachedAccount> {
+        let found = {
+            let entries = self.entries.read();
+            entries.get(pubkey).and_then(|entry| {
+                let version = entry.versions.iter().rev().find(|v| v.commitment >= requirement.commitment)?;
+                let newest = entry.versions.back().is_some_and(|latest| latest.slot == version.slot);
+                let known_at = if newest { entry.valid_through } else { version.slot };
+                if requirement.min_slot.is_some_and(|min| known_at < min) {
+                    return None;
+                }
+                if requirement.max_age.is_some_and(|max| entry.touched_at.elapsed() > max) {
+                    return None;
+                }
+                Some(version.clone())
+            })
+        };
+
+        let mut stats = self.stats.write();
+        match found {
+            Some(account) => {
+                stats.hits += 1;
+                Ok(account)
+            }
+            None => {
+                stats.misses += 1;
+                Err(anyhow!("No cached state for {} meeting {:?}", pubkey, requirement))
+            }
+        }
+    }
+
+    /// Every account as of one slot: the newest slot they are all known at.
+    /// Fails if any account is missing, below the requirement, or no longer
+    /// has a version that old.
+    pub fn snapshot(&self, pubkeys: &[Pubkey], requirement: &ReadRequirement) -> Result<AccountSnapshot> {
+        let entries = self.entries.read();
+        let mut slot = u64::MAX;
+        for pubkey in pubkeys {
+            let entry = entries.get(pubkey).ok_or_else(|| anyhow!("{} is not cached", pubkey))?;
+            if requirement.max_age.is_some_and(|max| entry.touched_at.elapsed() > max) {
+                return Err(anyhow!("{} is older than {:?}", pubkey, requirement.max_age));
+            }
+            slot = slot.min(entry.valid_through);
+        }
+        if requirement.min_slot.is_some_and(|min| slot < min) {
+            return Err(anyhow!("Accounts are only consistent at slot {}", slot));
+        }
+
+        let mut accounts = HashMap::with_capacity(pubkeys.len());
+        for pubkey in pubkeys {
+            let version = entries[pubkey]
+                .versions
+                .iter()
+                .rev()
+                .find(|v| v.slot <= slot)
+                .ok_or_else(|| anyhow!("{} has no version as old as slot {}", pubkey, slot))?;
+            if version.commitment < requirement.commitment {
+                return Err(anyhow!("{} at slot {} is only {:?}", pubkey, slot, version.commitment));
+            }
+            accounts.insert(*pubkey, version.clone());
+        }
+        Ok(AccountSnapshot { slot, accounts })
+    }
+
+    /// Cached accounts meeting `requirement`, fetching the rest in one RPC call
+    pub async fn get_or_fetch(
+        &self,
+        rpc: &RpcClient,
+        pubkeys: &[Pubkey],
+        requirement: &ReadRequirement,
+    ) -> Result<Vec<CachedAccount>> {
+        let missing: Vec<Pubkey> = pubkeys
+            .iter()
+            .filter(|pubkey| self.get(pubkey, requirement).is_err())
+            .copied()
+            .collect();
+
+        if !missing.is_empty() {
+            let response = rpc
+                .get_multiple_accounts_with_commitment(&missing, requirement.commitment.config())
+                .await?;
+            let slot = response.context.slot;
+            if requirement.min_slot.is_some_and(|min| slot < min) {
+                return Err(anyhow!("RPC is at slot {}, behind the required {:?}", slot, requirement.min_slot));
+            }
+            for (pubkey, account) in missing.iter().zip(response.value) {
+                let account = account.ok_or_else(|| anyhow!("Account {} does not exist", pubkey))?;
+                self.insert(
+                    *pubkey,
+                    slot,
+                    requirement.commitment,
+                    account.data,
+                    Some(account.owner),
+                    Some(account.lamports),
+                );
+            }
+        }
+
+        pubkeys.iter().map(|pubkey| self.get(pubkey, requirement)).collect()
+    }
+
+    /// Marks the newest version of `pubkey` as still current at `slot`, for
+    /// accounts under a live subscription that would have reported a change
+    pub fn advance(&self, pubkey: &Pubkey, slot: u64) {
+        if let Some(entry) = self.entries.write().get_mut(pubkey) {
+            entry.valid_through = entry.valid_through.max(slot);
+            entry.touched_at = Instant::now();
+        }
+    }
+
+    /// Drops accounts untouched for longer than the TTL
+    pub fn evict_expired(&self) -> usize {
+        let ttl = self.config.ttl;
+        let mut entries = self.entries.write();
+        let before = entries.len();
+        entries.retain(|_, entry| entry.touched_at.elapsed() <= ttl);
+        let evicted = before - entries.len();
+        self.stats.write().evictions += evicted as u64;
+        evicted
+    }
+
+    pub fn remove(&self, pubkey: &Pubkey) {
+        self.entries.write().remove(pubkey);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let mut stats = self.stats.read().clone();
+        stats.accounts = self.entries.read().len();
+        stats
+    }
+}
+
+impl Default for AccountCache {
+    fn default() -> Self {
+        Self::new(AccountCacheConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> AccountCache {
+        AccountCache::default()
+    }
+
+    #[test]
+    fn get_returns_the_newest_version_meeting_the_requirement() {
+        let cache = cache();
+        let key = Pubkey::new_unique();
+        cache.insert(key, 10, Commitment::Confirmed, vec![1], None, None);
+        cache.insert(key, 12, Commitment::Processed, vec![2], None, None);
+
+        assert_eq!(*cache.get(&key, &ReadRequirement::any()).unwrap().data, vec![2]);
+        let confirmed = cache.get(&key, &ReadRequirement::confirmed()).unwrap();
+        assert_eq!((confirmed.slot, confirmed.data.to_vec()), (10, vec![1]));
+        assert!(cache.get(&key, &ReadRequirement::confirmed().at_least_slot(11)).is_err());
+        assert!(cache.get(&Pubkey::new_unique(), &ReadRequirement::any()).is_err());
+    }
+
+    #[test]
+    fn unchanged_data_extends_the_current_version() {
+        let cache = cache();
+        let key = Pubkey::new_unique();
+        cache.insert(key, 10, Commitment::Processed, vec![1], None, None);
+        cache.insert(key, 15, Commitment::Confirmed, vec![1], None, None);
+
+        let account = cache.get(&key, &ReadRequirement::confirmed().at_least_slot(15)).unwrap();
+        assert_eq!(account.slot, 10);
+        assert_eq!(cache.stats().inserts, 1);
+    }
+
+    #[test]
+    fn older_changed_data_is_ignored() {
+        let cache = cache();
+        let key = Pubkey::new_unique();
+        cache.insert(key, 10, Commitment::Confirmed, vec![1], None, None);
+        cache.insert(key, 8, Commitment::Confirmed, vec![9], None, None);
+
+        assert_eq!(*cache.get(&key, &ReadRequirement::any()).unwrap().data, vec![1]);
+    }
+
+    #[test]
+    fn snapshot_reads_every_account_at_their_newest_common_slot() {
+        let cache = cache();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        cache.insert(a, 10, Commitment::Confirmed, vec![1], None, None);
+        cache.insert(b, 11, Commitment::Confirmed, vec![2], None, None);
+        cache.insert(a, 14, Commitment::Confirmed, vec![3], None, None);
+        cache.advance(&b, 13);
+
+        // b is only known through 13, so a is read as of 13 too
+        let snapshot = cache.snapshot(&[a, b], &ReadRequirement::confirmed()).unwrap();
+        assert_eq!(snapshot.slot, 13);
+        assert_eq!(snapshot.data(&a), Some(&[1u8][..]));
+        assert_eq!(snapshot.data(&b), Some(&[2u8][..]));
+
+        cache.advance(&b, 20);
+        let snapshot = cache.snapshot(&[a, b], &ReadRequirement::confirmed()).unwrap();
+        assert_eq!((snapshot.slot, snapshot.data(&a)), (14, Some(&[3u8][..])));
+    }
+
+    #[test]
+    fn snapshot_fails_when_an_account_is_missing_or_too_old() {
+        let cache = cache();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        cache.insert(a, 10, Commitment::Confirmed, vec![1], None, None);
+        assert!(cache.snapshot(&[a, b], &ReadRequirement::any()).is_err());
+
+        cache.insert(b, 5, Commitment::Processed, vec![2], None, None);
+        assert!(cache.snapshot(&[a, b], &ReadRequirement::confirmed()).is_err());
+        assert!(cache.snapshot(&[a, b], &ReadRequirement::any().at_least_slot(6)).is_err());
+        // a has no version as old as b's slot
+        assert!(cache.snapshot(&[a, b], &ReadRequirement::any()).is_err());
+    }
+
+    #[test]
+    fn only_the_configured_number_of_versions_is_kept() {
+        let cache = AccountCache::new(AccountCacheConfig {
+            max_versions: 2,
+            ..AccountCacheConfig::default()
+        });
+        let key = Pubkey::new_unique();
+        for slot in 1..=3 {
+            cache.insert(key, slot, Commitment::Confirmed, vec![slot as u8], None, None);
+        }
+        assert!(cache.get(&key, &ReadRequirement::any()).is_ok());
+        let other = Pubkey::new_unique();
+        cache.insert(other, 1, Commitment::Confirmed, vec![0], None, None);
+        cache.advance(&other, 5);
+        assert!(cache.snapshot(&[key, other], &ReadRequirement::any()).is_ok());
+        cache.remove(&other);
+        cache.insert(other, 1, Commitment::Confirmed, vec![0], None, None);
+        // Slot 1 of `key` has been dropped
+        assert!(cache.snapshot(&[key, other], &ReadRequirement::any()).is_err());
+    }
+}
@@ -0,0 +1,553 @@
+/**
+ * AGENT MESSAGE BUS
+ * Typed pub/sub between neural agents with request/response correlation,
+ * bounded per-subscriber queues and an optional JSON-lines audit log
+ */
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc as std_mpsc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+const DEFAULT_SUBSCRIBER_CAPACITY: usize = 256;
+const DEFAULT_PUBLISH_TIMEOUT: Duration = Duration::from_millis(250);
+const RECENT_MESSAGE_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageTopic {
+    Opportunity,
+    RiskVeto,
+    ExecutionResult,
+    ModelUpdate,
+}
+
+impl MessageTopic {
+    pub const ALL: [MessageTopic; 4] = [
+        MessageTopic::Opportunity,
+        MessageTopic::RiskVeto,
+        MessageTopic::ExecutionResult,
+        MessageTopic::ModelUpdate,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentMessage {
+    OpportunityFound {
+        opportunity_id: String,
+        strategy: String,
+        market: String,
+        expected_profit_sol: f64,
+        confidence: f64,
+        /// Unix ms after which the opportunity should not be acted on
+        expires_at_ms: Option<u64>,
+    },
+    RiskVeto {
+        opportunity_id: String,
+        reason: String,
+        /// Largest exposure the risk agent would accept instead, if any
+        max_exposure_sol: Option<f64>,
+    },
+    ExecutionResult {
+        opportunity_id: Option<String>,
+        strategy: String,
+        success: bool,
+        profit_sol: f64,
+        signature: Option<String>,
+        error: Option<String>,
+    },
+    ModelUpdate {
+        model_name: String,
+        version: u64,
+        metrics: HashMap<String, f64>,
+    },
+}
+
+impl AgentMessage {
+    pub fn topic(&self) -> MessageTopic {
+        match self {
+            AgentMessage::OpportunityFound { .. } => MessageTopic::Opportunity,
+            AgentMessage::RiskVeto { .. } => MessageTopic::RiskVeto,
+            AgentMessage::ExecutionResult { .. } => MessageTopic::ExecutionResult,
+            AgentMessage::ModelUpdate { .. } => MessageTopic::ModelUpdate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub id: String,
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub from: String,
+    /// Broadcast to topic subscribers when `None`
+    pub to: Option<String>,
+    pub priority: u8,
+    /// Set on requests; replies carry it back in `in_reply_to`
+    pub correlation_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub topic: MessageTopic,
+    pub message: AgentMessage,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BusStats {
+    pub published: u64,
+    pub delivered: u64,
+    /// Deliveries abandoned because a subscriber queue stayed full
+    pub dropped: u64,
+    pub subscribers: usize,
+}
+
+/// Receives direct messages for `agent_id` plus broadcasts on `topics`
+pub struct Subscription {
+    pub agent_id: String,
+    receiver: mpsc::Receiver<Envelope>,
+}
+
+impl Subscription {
+    pub async fn recv(&mut self) -> Option<Envelope> {
+        self.receiver.recv().await
+    }
+
+    pub fn try_recv(&mut self) -> Option<Envelope> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+struct Subscriber {
+    agent_id: String,
+    topics: HashSet<MessageTopic>,
+    sender: mpsc::Sender<Envelope>,
+}
+
+struct BusState {
+    next_seq: u64,
+    subscribers: Vec<Subscriber>,
+    pending_requests: HashMap<String, oneshot::Sender<Envelope>>,
+    recent: VecDeque<Envelope>,
+    stats: BusStats,
+}
+
+enum LogCommand {
+    Append(Box<Envelope>),
+    Flush(std_mpsc::Sender<()>),
+}
+
+/// Appends envelopes on a dedicated thread so publishers never wait on disk
+struct BusLog {
+    path: PathBuf,
+    commands: std_mpsc::Sender<LogCommand>,
+}
+
+impl BusLog {
+    fn spawn(path: PathBuf) -> Self {
+        let (commands, receiver) = std_mpsc::channel();
+        let writer_path = path.clone();
+        std::thread::Builder::new()
+            .name("agent-bus-log".to_string())
+            .spawn(move || write_log(&writer_path, receiver))
+            .expect("spawning the agent bus log writer");
+        Self { path, commands }
+    }
+}
+
+fn write_log(path: &Path, commands: std_mpsc::Receiver<LogCommand>) {
+    let mut writer: Option<BufWriter<std::fs::File>> = None;
+    while let Ok(command) = commands.recv() {
+        // Write whatever else is queued before flushing once
+        for command in std::iter::once(command).chain(commands.try_iter()) {
+            match command {
+                LogCommand::Append(envelope) => {
+                    if writer.is_none() {
+                        match std::fs::OpenOptions::new().create(true).append(true).open(path) {
+                            Ok(file) => writer = Some(BufWriter::new(file)),
+                            Err(e) => {
+                                tracing::warn!("Failed to open agent bus log {}: {}", path.display(), e);
+                                continue;
+                            }
+                        }
+                    }
+                    let line = serde_json::to_string(&envelope).unwrap_or_default();
+                    if let Some(Err(e)) = writer.as_mut().map(|w| writeln!(w, "{}", line)) {
+                        tracing::warn!("Failed to write agent bus log entry: {}", e);
+                        writer = None;
+                    }
+                }
+                LogCommand::Flush(done) => {
+                    if let Some(w) = writer.as_mut() {
+                        let _ = w.flush();
+                    }
+                    let _ = done.send(());
+                }
+            }
+        }
+        if let Some(Err(e)) = writer.as_mut().map(|w| w.flush()) {
+            tracing::warn!("Failed to flush agent bus log: {}", e);
+            writer = None;
+        }
+    }
+}
+
+/// Publishers wait up to `publish_timeout` for a full subscriber queue to
+/// drain, so slow consumers push back on producers instead of growing
+/// memory; past that the delivery is dropped and counted. Publishes are
+/// delivered one at a time, so every subscriber sees them in `seq` order.
+pub struct AgentBus {
+    state: Mutex<BusState>,
+    /// Held from assigning a `seq` until every target has the envelope
+    delivery: tokio::sync::Mutex<()>,
+    log: Option<BusLog>,
+    subscriber_capacity: usize,
+    publish_timeout: Duration,
+}
+
+impl AgentBus {
+    pub fn new(log_path: Option<PathBuf>) -> Self {
+        Self {
+            state: Mutex::new(BusState {
+                next_seq: 1,
+                subscribers: Vec::new(),
+                pending_requests: HashMap::new(),
+                recent: VecDeque::new(),
+                stats: BusStats::default(),
+            }),
+            delivery: tokio::sync::Mutex::new(()),
+            log: log_path.map(BusLog::spawn),
+            subscriber_capacity: DEFAULT_SUBSCRIBER_CAPACITY,
+            publish_timeout: DEFAULT_PUBLISH_TIMEOUT,
+        }
+    }
+
+    /// Persists every envelope when `BLACK_DIAMOND_AGENT_BUS_LOG` is set
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("BLACK_DIAMOND_AGENT_BUS_LOG").ok().map(PathBuf::from))
+    }
+
+    pub fn with_backpressure(mut self, subscriber_capacity: usize, publish_timeout: Duration) -> Self {
+        self.subscriber_capacity = subscriber_capacity.max(1);
+        self.publish_timeout = publish_timeout;
+        self
+    }
+
+    pub fn subscribe(&self, agent_id: &str, topics: &[MessageTopic]) -> Subscription {
+        let (sender, receiver) = mpsc::channel(self.subscriber_capacity);
+        self.state.lock().subscribers.push(Subscriber {
+            agent_id: agent_id.to_string(),
+            topics: topics.iter().copied().collect(),
+            sender,
+        });
+
+        Subscription {
+            agent_id: agent_id.to_string(),
+            receiver,
+        }
+    }
+
+    /// Broadcasts `message` to every subscriber of its topic
+    pub async fn publish(&self, from: &str, message: AgentMessage, priority: u8) -> Envelope {
+        self.send(from, None, message, priority, None, None).await
+    }
+
+    /// Delivers `message` only to `to`'s subscriptions
+    pub async fn send_to(&self, from: &str, to: &str, message: AgentMessage, priority: u8) -> Envelope {
+        self.send(from, Some(to.to_string()), message, priority, None, None).await
+    }
+
+    /// Sends `message` to `to` and waits for the reply carrying its correlation ID
+    pub async fn request(&self, from: &str, to: &str, message: AgentMessage, timeout: Duration) -> Result<Envelope> {
+        let correlation_id = uuid::Uuid::new_v4().to_string();
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.state.lock().pending_requests.insert(correlation_id.clone(), reply_tx);
+
+        self.send(from, Some(to.to_string()), message, 5, Some(correlation_id.clone()), None)
+            .await;
+
+        match tokio::time::timeout(timeout, reply_rx).await {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(_)) => Err(anyhow!("Request {} to {} was dropped", correlation_id, to)),
+            Err(_) => {
+                self.state.lock().pending_requests.remove(&correlation_id);
+                Err(anyhow!("{} did not reply to {} within {:?}", to, correlation_id, timeout))
+            }
+        }
+    }
+
+    /// Answers `request`, routing the reply back to its sender
+    pub async fn reply(&self, from: &str, request: &Envelope, message: AgentMessage) -> Result<Envelope> {
+        let correlation_id = request
+            .correlation_id
+            .clone()
+            .ok_or_else(|| anyhow!("Message {} is not a request", request.id))?;
+        Ok(self
+            .send(from, Some(request.from.clone()), message, request.priority, None, Some(correlation_id))
+            .await)
+    }
+
+    async fn send(
+        &self,
+        from: &str,
+        to: Option<String>,
+        message: AgentMessage,
+        priority: u8,
+        correlation_id: Option<String>,
+        in_reply_to: Option<String>,
+    ) -> Envelope {
+        let _delivery = self.delivery.lock().await;
+        let (envelope, targets, waiting) = {
+            let mut state = self.state.lock();
+            let envelope = Envelope {
+                id: uuid::Uuid::new_v4().to_string(),
+                seq: state.next_seq,
+                timestamp: Utc::now(),
+                from: from.to_string(),
+                to,
+                priority,
+                correlation_id,
+                in_reply_to,
+                topic: message.topic(),
+                message,
+            };
+            state.next_seq += 1;
+            state.stats.published += 1;
+
+            state.recent.push_back(envelope.clone());
+            if state.recent.len() > RECENT_MESSAGE_LIMIT {
+                state.recent.pop_front();
+            }
+
+            state.subscribers.retain(|s| !s.sender.is_closed());
+            let targets: Vec<mpsc::Sender<Envelope>> = state
+                .subscribers
+                .iter()
+                .filter(|s| match &envelope.to {
+                    Some(to) => *to == s.agent_id,
+                    None => s.topics.contains(&envelope.topic),
+                })
+                .map(|s| s.sender.clone())
+                .collect();
+            let waiting = envelope
+                .in_reply_to
+                .as_ref()
+                .and_then(|id| state.pending_requests.remove(id));
+            (envelope, targets, waiting)
+        };
+
+        self.append_to_log(&envelope);
+
+        if let Some(waiting) = waiting {
+            let _ = waiting.send(envelope.clone());
+        }
+
+        // One slow subscriber costs at most one timeout, not one per target
+        let sends = targets
+            .iter()
+            .map(|target| target.send_timeout(envelope.clone(), self.publish_timeout));
+        let mut delivered = 0;
+        let mut dropped = 0;
+        for sent in futures_util::future::join_all(sends).await {
+            match sent {
+                Ok(()) => delivered += 1,
+                Err(mpsc::error::SendTimeoutError::Timeout(_)) => dropped += 1,
+                // Subscriber went away mid-send; pruned on the next publish
+                Err(mpsc::error::SendTimeoutError::Closed(_)) => {}
+            }
+        }
+        if dropped > 0 {
+            tracing::warn!("Agent bus dropped message {} for {} slow subscribers", envelope.seq, dropped);
+        }
+
+        let mut state = self.state.lock();
+        state.stats.delivered += delivered;
+        state.stats.dropped += dropped;
+        envelope
+    }
+
+    fn append_to_log(&self, envelope: &Envelope) {
+        if let Some(log) = &self.log {
+            let _ = log.commands.send(LogCommand::Append(Box::new(envelope.clone())));
+        }
+    }
+
+    /// Blocks until every envelope published so far is on disk
+    pub fn flush_log(&self) {
+        let Some(log) = &self.log else {
+            return;
+        };
+        let (done, flushed) = std_mpsc::channel();
+        if log.commands.send(LogCommand::Flush(done)).is_ok() {
+            let _ = flushed.recv();
+        }
+    }
+
+    pub fn recent(&self, limit: usize) -> Vec<Envelope> {
+        self.state.lock().recent.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn stats(&self) -> BusStats {
+        let state = self.state.lock();
+        BusStats {
+            subscribers: state.subscribers.len(),
+            ..state.stats.clone()
+        }
+    }
+
+    pub fn log_path(&self) -> Option<&Path> {
+        self.log.as_ref().map(|log| log.path.as_path())
+    }
+}
+
+impl Default for AgentBus {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// Reads back a persisted bus log, in publish order, for auditing a run
+pub fn read_bus_log(path: &Path) -> Result<Vec<Envelope>> {
+    let file = std::fs::File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut envelopes = Vec::new();
+    for (number, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let envelope: Envelope = serde_json::from_str(&line)
+            .with_context(|| format!("parsing {} line {}", path.display(), number + 1))?;
+        envelopes.push(envelope);
+    }
+    Ok(envelopes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn execution(strategy: &str) -> AgentMessage {
+        AgentMessage::ExecutionResult {
+            opportunity_id: None,
+            strategy: strategy.to_string(),
+            success: true,
+            profit_sol: 0.1,
+            signature: None,
+            error: None,
+        }
+    }
+
+    fn veto(opportunity_id: &str) -> AgentMessage {
+        AgentMessage::RiskVeto {
+            opportunity_id: opportunity_id.to_string(),
+            reason: "too large".to_string(),
+            max_exposure_sol: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn routes_broadcasts_by_topic_and_direct_messages_by_agent() {
+        let bus = AgentBus::default();
+        let mut results = bus.subscribe("ledger", &[MessageTopic::ExecutionResult]);
+        let mut risk = bus.subscribe("risk", &[MessageTopic::RiskVeto]);
+
+        bus.publish("executor", execution("cascade"), 5).await;
+        bus.send_to("executor", "risk", execution("direct"), 5).await;
+
+        assert_eq!(results.try_recv().unwrap().from, "executor");
+        assert!(results.try_recv().is_none());
+        let direct = risk.try_recv().unwrap();
+        assert_eq!(direct.to.as_deref(), Some("risk"));
+        assert!(risk.try_recv().is_none());
+        assert_eq!(bus.stats().delivered, 2);
+    }
+
+    #[tokio::test]
+    async fn a_request_resolves_with_the_reply_carrying_its_correlation_id() {
+        let bus = Arc::new(AgentBus::default());
+        let mut risk = bus.subscribe("risk", &[]);
+        let responder = Arc::clone(&bus);
+        tokio::spawn(async move {
+            let request = risk.recv().await.unwrap();
+            responder.reply("risk", &request, veto("op-1")).await.unwrap();
+        });
+
+        let reply = bus
+            .request("strategist", "risk", execution("ask"), Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert!(matches!(reply.message, AgentMessage::RiskVeto { .. }));
+        assert!(reply.in_reply_to.is_some());
+
+        let unanswered = bus.request("strategist", "nobody", execution("ask"), Duration::from_millis(20)).await;
+        assert!(unanswered.is_err());
+        assert!(bus.reply("risk", &reply, veto("op-2")).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_publishers_deliver_in_seq_order() {
+        let bus = Arc::new(AgentBus::default());
+        let mut subscription = bus.subscribe("ledger", &[MessageTopic::ExecutionResult]);
+
+        let publishers: Vec<_> = (0..8)
+            .map(|publisher| {
+                let bus = Arc::clone(&bus);
+                tokio::spawn(async move {
+                    for _ in 0..25 {
+                        bus.publish(&format!("executor-{}", publisher), execution("cascade"), 5).await;
+                    }
+                })
+            })
+            .collect();
+        for publisher in publishers {
+            publisher.await.unwrap();
+        }
+
+        let mut seqs = Vec::new();
+        while let Some(envelope) = subscription.try_recv() {
+            seqs.push(envelope.seq);
+        }
+        assert_eq!(seqs, (1..=200).collect::<Vec<u64>>());
+    }
+
+    #[tokio::test]
+    async fn slow_subscribers_are_dropped_after_one_timeout_together() {
+        let bus = AgentBus::default().with_backpressure(1, Duration::from_millis(50));
+        let _slow_a = bus.subscribe("slow_a", &[MessageTopic::ExecutionResult]);
+        let _slow_b = bus.subscribe("slow_b", &[MessageTopic::ExecutionResult]);
+        let mut fast = bus.subscribe("fast", &[MessageTopic::ExecutionResult]);
+
+        bus.publish("executor", execution("first"), 5).await;
+        fast.try_recv().unwrap();
+
+        let started = std::time::Instant::now();
+        bus.publish("executor", execution("second"), 5).await;
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert!(fast.try_recv().is_some());
+
+        let stats = bus.stats();
+        assert_eq!((stats.delivered, stats.dropped), (4, 2));
+    }
+
+    #[tokio::test]
+    async fn the_log_reads_back_in_publish_order() {
+        let path = std::env::temp_dir().join(format!("agent_bus_log_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let bus = AgentBus::new(Some(path.clone()));
+
+        bus.publish("executor", execution("first"), 5).await;
+        bus.send_to("risk", "executor", veto("op-1"), 9).await;
+        bus.flush_log();
+
+        let logged = read_bus_log(&path).unwrap();
+        assert_eq!(logged.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(logged[1].topic, MessageTopic::RiskVeto);
+        assert_eq!(bus.log_path(), Some(path.as_path()));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,749 @@
+use std::collections::{HashMap, HashSet};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+/// Work an agent can be asked to do; agents declare the kinds they handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    TransformerOptimization,
+    StrategyAnalysis,
+    PatternRecognition,
+    RiskAssessment,
+    ProfitMaximization,
+}
+
+impl TaskKind {
+    pub const ALL: [TaskKind; 5] = [
+        TaskKind::TransformerOptimization,
+        TaskKind::StrategyAnalysis,
+        TaskKind::PatternRecognition,
+        TaskKind::RiskAssessment,
+        TaskKind::ProfitMaximization,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Expired,
+}
+
+impl TaskStatus {
+    pub fn is_terminal(self) -> bool {
+        matches!(self, TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Expired)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSpec {
+    pub kind: TaskKind,
+    pub description: String,
+    /// Higher runs first
+    pub priority: u8,
+    /// Tasks that must complete before this one is assigned
+    pub depends_on: Vec<String>,
+    /// Unix ms after which the task is abandoned
+    pub deadline_ms: Option<u64>,
+    /// How long one attempt may run before it counts as failed
+    pub timeout_ms: u64,
+    pub max_retries: u32,
+    /// Whatever the worker needs to do the task
+    pub input: serde_json::Value,
+}
+
+impl TaskSpec {
+    pub fn new(kind: TaskKind, description: impl Into<String>) -> Self {
+        TaskSpec {
+            kind,
+            description: description.into(),
+            priority: 5,
+            depends_on: Vec::new(),
+            deadline_ms: None,
+            timeout_ms: 300_000, // 5 minutes
+            max_retries: 2,
+            input: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_input(mut self, input: serde_json::Value) -> Self {
+        self.input = input;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn after(mut self, task_id: &str) -> Self {
+        self.depends_on.push(task_id.to_string());
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline_ms: u64) -> Self {
+        self.deadline_ms = Some(deadline_ms);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentTask {
+    pub id: String,
+    pub kind: TaskKind,
+    pub description: String,
+    pub priority: u8,
+    pub depends_on: Vec<String>,
+    pub deadline_ms: Option<u64>,
+    pub timeout_ms: u64,
+    pub max_retries: u32,
+    pub input: serde_json::Value,
+    pub status: TaskStatus,
+    /// Agent running the current attempt
+    pub agent_id: Option<String>,
+    /// Attempts started so far; the current one's number while running
+    pub attempts: u32,
+    /// Agents that already failed this task, tried last on a retry
+    pub failed_agents: Vec<String>,
+    pub progress: f64,
+    pub created_ms: u64,
+    pub started_ms: Option<u64>,
+    pub finished_ms: Option<u64>,
+    /// Quality reported on success, in [0, 1]
+    pub score: Option<f64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "outcome")]
+pub enum TaskResult {
+    Succeeded { score: f64, detail: String },
+    Failed { error: String },
+}
+
+/// A worker's word on one attempt, identified by the task's `attempts` at
+/// assignment
+#[derive(Debug, Clone)]
+pub enum TaskReport {
+    Progress { agent_id: String, task_id: String, attempt: u32, progress: f64 },
+    Finished { agent_id: String, task_id: String, attempt: u32, result: TaskResult },
+}
+
+/// What one finished, failed or timed-out attempt says about the agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSettlement {
+    pub task_id: String,
+    pub agent_id: String,
+    pub kind: TaskKind,
+    pub outcome: SettlementOutcome,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementOutcome {
+    Succeeded { score: f64 },
+    /// The attempt failed and the task went back on the queue
+    Retrying { error: String },
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentProfile {
+    pub id: String,
+    pub capabilities: Vec<TaskKind>,
+    pub max_concurrent: usize,
+}
+
+/// Handed to whatever runs an agent's work: assignments arrive on
+/// `assignments`, and progress and results go back through the queue.
+pub struct AgentWorker {
+    pub agent_id: String,
+    pub assignments: mpsc::UnboundedReceiver<AgentTask>,
+    reports: mpsc::UnboundedSender<TaskReport>,
+}
+
+impl AgentWorker {
+    /// Reports on `task` as it was assigned
+    pub fn report_progress(&self, task: &AgentTask, progress: f64) {
+        let _ = self.reports.send(TaskReport::Progress {
+            agent_id: self.agent_id.clone(),
+            task_id: task.id.clone(),
+            attempt: task.attempts,
+            progress,
+        });
+    }
+
+    /// Must be called once per assignment, even one that has timed out,
+    /// since the agent's slot stays taken until then
+    pub fn finish(&self, task: &AgentTask, result: TaskResult) {
+        let _ = self.reports.send(TaskReport::Finished {
+            agent_id: self.agent_id.clone(),
+            task_id: task.id.clone(),
+            attempt: task.attempts,
+            result,
+        });
+    }
+}
+
+struct AgentSlot {
+    profile: AgentProfile,
+    sender: Option<mpsc::UnboundedSender<AgentTask>>,
+    /// `(task, attempt)` handed to the worker and not yet finished by it.
+    /// A timed-out attempt stays here while the worker is still on it.
+    running: HashSet<(String, u32)>,
+}
+
+impl AgentSlot {
+    fn available(&self, kind: TaskKind) -> bool {
+        self.profile.capabilities.contains(&kind)
+            && self.running.len() < self.profile.max_concurrent
+            && self.sender.as_ref().is_some_and(|s| !s.is_closed())
+    }
+}
+
+/// Priority queue of agent tasks with dependencies, deadlines, per-attempt
+/// timeouts, retries and per-agent concurrency limits.
+pub struct TaskQueue {
+    tasks: HashMap<String, AgentTask>,
+    agents: HashMap<String, AgentSlot>,
+    reports_tx: mpsc::UnboundedSender<TaskReport>,
+    reports_rx: mpsc::UnboundedReceiver<TaskReport>,
+}
+
+impl TaskQueue {
+    pub fn new() -> Self {
+        let (reports_tx, reports_rx) = mpsc::unbounded_channel();
+        TaskQueue {
+            tasks: HashMap::new(),
+            agents: HashMap::new(),
+            reports_tx,
+            reports_rx,
+        }
+    }
+
+    /// Adds or replaces an agent's profile, keeping its worker and load
+    pub fn register_agent(&mut self, profile: AgentProfile) {
+        match self.agents.get_mut(&profile.id) {
+            Some(slot) => slot.profile = profile,
+            None => {
+                self.agents.insert(
+                    profile.id.clone(),
+                    AgentSlot {
+                        profile,
+                        sender: None,
+                        running: HashSet::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn agent_profiles(&self) -> impl Iterator<Item = &AgentProfile> {
+        self.agents.values().map(|slot| &slot.profile)
+    }
+
+    /// Connects a worker to a registered agent, replacing any earlier one
+    /// along with whatever it was still running. Agents without a live worker
+    /// are never assigned anything.
+    pub fn attach_worker(&mut self, agent_id: &str) -> Result<AgentWorker> {
+        let slot = self
+            .agents
+            .get_mut(agent_id)
+            .ok_or_else(|| anyhow!("Unknown agent {}", agent_id))?;
+        let (sender, assignments) = mpsc::unbounded_channel();
+        slot.sender = Some(sender);
+        slot.running.clear();
+
+        Ok(AgentWorker {
+            agent_id: agent_id.to_string(),
+            assignments,
+            reports: self.reports_tx.clone(),
+        })
+    }
+
+    pub fn submit(&mut self, spec: TaskSpec, now_ms: u64) -> Result<String> {
+        if !self.agents.values().any(|slot| slot.profile.capabilities.contains(&spec.kind)) {
+            return Err(anyhow!("No agent is capable of {:?}", spec.kind));
+        }
+        for dependency in &spec.depends_on {
+            let task = self
+                .tasks
+                .get(dependency)
+                .ok_or_else(|| anyhow!("Unknown dependency {}", dependency))?;
+            if matches!(task.status, TaskStatus::Failed | TaskStatus::Expired) {
+                return Err(anyhow!("Dependency {} already {:?}", dependency, task.status));
+            }
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        self.tasks.insert(
+            id.clone(),
+            AgentTask {
+                id: id.clone(),
+                kind: spec.kind,
+                description: spec.description,
+                priority: spec.priority,
+                depends_on: spec.depends_on,
+                deadline_ms: spec.deadline_ms,
+                timeout_ms: spec.timeout_ms,
+                max_retries: spec.max_retries,
+                input: spec.input,
+                status: TaskStatus::Pending,
+                agent_id: None,
+                attempts: 0,
+                failed_agents: Vec::new(),
+                progress: 0.0,
+                created_ms: now_ms,
+                started_ms: None,
+                finished_ms: None,
+                score: None,
+                last_error: None,
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn task(&self, id: &str) -> Option<&AgentTask> {
+        self.tasks.get(id)
+    }
+
+    pub fn tasks(&self) -> impl Iterator<Item = &AgentTask> {
+        self.tasks.values()
+    }
+
+    pub fn count(&self, status: TaskStatus) -> usize {
+        self.tasks.values().filter(|t| t.status == status).count()
+    }
+
+    /// Applies worker reports received since the last call
+    pub fn poll_reports(&mut self, now_ms: u64) -> Vec<TaskSettlement> {
+        let mut settlements = Vec::new();
+        while let Ok(report) = self.reports_rx.try_recv() {
+            match report {
+                TaskReport::Progress { agent_id, task_id, attempt, progress } => {
+                    if let Some(task) = self.running_task_mut(&task_id, &agent_id, attempt) {
+                        task.progress = progress.clamp(0.0, 100.0);
+                    }
+                }
+                TaskReport::Finished { agent_id, task_id, attempt, result } => {
+                    // The worker is done with it either way
+                    if let Some(slot) = self.agents.get_mut(&agent_id) {
+                        slot.running.remove(&(task_id.clone(), attempt));
+                    }
+                    // Reports from attempts that already timed out are stale
+                    if self.running_task_mut(&task_id, &agent_id, attempt).is_none() {
+                        continue;
+                    }
+                    let outcome = match result {
+                        TaskResult::Succeeded { score, .. } => {
+                            let score = score.clamp(0.0, 1.0);
+                            let task = self.tasks.get_mut(&task_id).unwrap();
+                            task.status = TaskStatus::Completed;
+                            task.progress = 100.0;
+                            task.score = Some(score);
+                            task.finished_ms = Some(now_ms);
+                            SettlementOutcome::Succeeded { score }
+                        }
+                        TaskResult::Failed { error } => self.retry_or_fail(&task_id, error, now_ms),
+                    };
+                    settlements.push(self.settle(&task_id, &agent_id, outcome));
+                }
+            }
+        }
+        settlements
+    }
+
+    /// Fails attempts that outran their timeout, abandons tasks past their
+    /// deadline, and fails tasks whose dependencies can no longer complete
+    pub fn expire(&mut self, now_ms: u64) -> Vec<TaskSettlement> {
+        let mut settlements = Vec::new();
+
+        let timed_out: Vec<(String, String)> = self
+            .tasks
+            .values()
+            .filter(|t| t.status == TaskStatus::Running)
+            .filter(|t| t.started_ms.is_some_and(|s| now_ms > s + t.timeout_ms))
+            .map(|t| (t.id.clone(), t.agent_id.clone().unwrap_or_default()))
+            .collect();
+        for (task_id, agent_id) in timed_out {
+            let outcome = self.retry_or_fail(&task_id, "attempt timed out".to_string(), now_ms);
+            settlements.push(self.settle(&task_id, &agent_id, outcome));
+        }
+
+        let past_deadline: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|t| !t.status.is_terminal() && t.deadline_ms.is_some_and(|d| now_ms > d))
+            .map(|t| t.id.clone())
+            .collect();
+        for task_id in past_deadline {
+            let task = self.tasks.get_mut(&task_id).unwrap();
+            let running_agent = if task.status == TaskStatus::Running { task.agent_id.clone() } else { None };
+            task.status = TaskStatus::Expired;
+            task.finished_ms = Some(now_ms);
+            task.last_error = Some("deadline passed".to_string());
+            if let Some(agent_id) = running_agent {
+                let outcome = SettlementOutcome::Failed {
+                    error: "deadline passed".to_string(),
+                };
+                settlements.push(self.settle(&task_id, &agent_id, outcome));
+            }
+        }
+
+        self.fail_orphans(now_ms);
+        settlements
+    }
+
+    /// Hands ready tasks to capable agents, highest priority and earliest
+    /// deadline first. Among capable agents with spare capacity the best
+    /// `performance` score wins. Returns the `(task, agent)` pairs assigned.
+    pub fn assign(&mut self, performance: &HashMap<String, f64>, now_ms: u64) -> Vec<(String, String)> {
+        let mut ready: Vec<&AgentTask> = self
+            .tasks
+            .values()
+            .filter(|t| t.status == TaskStatus::Pending)
+            .filter(|t| {
+                t.depends_on
+                    .iter()
+                    .all(|d| self.tasks.get(d).is_some_and(|dep| dep.status == TaskStatus::Completed))
+            })
+            .collect();
+        ready.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then(a.deadline_ms.unwrap_or(u64::MAX).cmp(&b.deadline_ms.unwrap_or(u64::MAX)))
+                .then(a.created_ms.cmp(&b.created_ms))
+        });
+        let ready: Vec<(String, TaskKind, Vec<String>)> = ready
+            .into_iter()
+            .map(|t| (t.id.clone(), t.kind, t.failed_agents.clone()))
+            .collect();
+
+        let mut assigned = Vec::new();
+        for (task_id, kind, failed_agents) in ready {
+            loop {
+                let best = self
+                    .agents
+                    .values()
+                    .filter(|slot| slot.available(kind))
+                    .max_by(|a, b| {
+                        let rank = |slot: &AgentSlot| {
+                            let fresh = !failed_agents.contains(&slot.profile.id);
+                            let score = performance.get(&slot.profile.id).copied().unwrap_or(0.0);
+                            (fresh, score)
+                        };
+                        let (a_fresh, a_score) = rank(a);
+                        let (b_fresh, b_score) = rank(b);
+                        a_fresh
+                            .cmp(&b_fresh)
+                            .then(a_score.partial_cmp(&b_score).unwrap_or(std::cmp::Ordering::Equal))
+                            .then(b.running.len().cmp(&a.running.len()))
+                    })
+                    .map(|slot| slot.profile.id.clone());
+                let Some(agent_id) = best else { break };
+
+                let task = self.tasks.get_mut(&task_id).unwrap();
+                task.status = TaskStatus::Running;
+                task.agent_id = Some(agent_id.clone());
+                task.attempts += 1;
+                task.progress = 0.0;
+                task.started_ms = Some(now_ms);
+                let snapshot = task.clone();
+                let attempt = snapshot.attempts;
+
+                let slot = self.agents.get_mut(&agent_id).unwrap();
+                if slot.sender.as_ref().is_some_and(|s| s.send(snapshot).is_ok()) {
+                    slot.running.insert((task_id.clone(), attempt));
+                    assigned.push((task_id.clone(), agent_id));
+                    break;
+                }
+
+                // Worker went away; undo and try the next agent
+                slot.sender = None;
+                let task = self.tasks.get_mut(&task_id).unwrap();
+                task.status = TaskStatus::Pending;
+                task.agent_id = None;
+                task.attempts -= 1;
+                task.started_ms = None;
+            }
+        }
+        assigned
+    }
+
+    /// Drops the oldest finished tasks beyond `keep`, never removing one that
+    /// an unfinished task still depends on
+    pub fn prune_finished(&mut self, keep: usize) {
+        let referenced: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|t| !t.status.is_terminal())
+            .flat_map(|t| t.depends_on.iter().cloned())
+            .collect();
+        let mut finished: Vec<(u64, String)> = self
+            .tasks
+            .values()
+            .filter(|t| t.status.is_terminal() && !referenced.contains(&t.id))
+            .map(|t| (t.finished_ms.unwrap_or(0), t.id.clone()))
+            .collect();
+        if finished.len() <= keep {
+            return;
+        }
+        finished.sort();
+        let excess = finished.len() - keep;
+        for (_, id) in finished.into_iter().take(excess) {
+            self.tasks.remove(&id);
+        }
+    }
+
+    fn running_task_mut(&mut self, task_id: &str, agent_id: &str, attempt: u32) -> Option<&mut AgentTask> {
+        self.tasks.get_mut(task_id).filter(|t| {
+            t.status == TaskStatus::Running && t.attempts == attempt && t.agent_id.as_deref() == Some(agent_id)
+        })
+    }
+
+    fn retry_or_fail(&mut self, task_id: &str, error: String, now_ms: u64) -> SettlementOutcome {
+        let task = self.tasks.get_mut(task_id).unwrap();
+        if let Some(agent_id) = task.agent_id.take() {
+            task.failed_agents.push(agent_id);
+        }
+        task.last_error = Some(error.clone());
+        task.started_ms = None;
+
+        if task.attempts <= task.max_retries {
+            task.status = TaskStatus::Pending;
+            task.progress = 0.0;
+            SettlementOutcome::Retrying { error }
+        } else {
+            task.status = TaskStatus::Failed;
+            task.finished_ms = Some(now_ms);
+            SettlementOutcome::Failed { error }
+        }
+    }
+
+    /// Records the settlement. The agent's slot is freed only when its
+    /// worker reports the attempt finished.
+    fn settle(&self, task_id: &str, agent_id: &str, outcome: SettlementOutcome) -> TaskSettlement {
+        TaskSettlement {
+            task_id: task_id.to_string(),
+            agent_id: agent_id.to_string(),
+            kind: self.tasks[task_id].kind,
+            outcome,
+        }
+    }
+
+    fn fail_orphans(&mut self, now_ms: u64) {
+        loop {
+            let orphaned: Vec<(String, String)> = self
+                .tasks
+                .values()
+                .filter(|t| t.status == TaskStatus::Pending)
+                .filter_map(|t| {
+                    t.depends_on
+                        .iter()
+                        .find(|d| {
+                            self.tasks
+                                .get(*d)
+                                .is_none_or(|dep| matches!(dep.status, TaskStatus::Failed | TaskStatus::Expired))
+                        })
+                        .map(|d| (t.id.clone(), d.clone()))
+                })
+                .collect();
+            if orphaned.is_empty() {
+                return;
+            }
+            for (task_id, dependency) in orphaned {
+                let task = self.tasks.get_mut(&task_id).unwrap();
+                task.status = TaskStatus::Failed;
+                task.finished_ms = Some(now_ms);
+                task.last_error = Some(format!("dependency {} did not complete", dependency));
+            }
+        }
+    }
+}
+
+impl Default for TaskQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(agents: &[(&str, usize)]) -> (TaskQueue, HashMap<String, AgentWorker>) {
+        let mut queue = TaskQueue::new();
+        let mut workers = HashMap::new();
+        for (id, max_concurrent) in agents {
+            queue.register_agent(AgentProfile {
+                id: id.to_string(),
+                capabilities: vec![TaskKind::RiskAssessment],
+                max_concurrent: *max_concurrent,
+            });
+            workers.insert(id.to_string(), queue.attach_worker(id).unwrap());
+        }
+        (queue, workers)
+    }
+
+    fn spec() -> TaskSpec {
+        TaskSpec::new(TaskKind::RiskAssessment, "review").with_timeout(1_000).with_retries(1)
+    }
+
+    fn succeeded() -> TaskResult {
+        TaskResult::Succeeded { score: 0.9, detail: String::new() }
+    }
+
+    fn next(worker: &mut AgentWorker) -> AgentTask {
+        worker.assignments.try_recv().expect("an assignment")
+    }
+
+    #[test]
+    fn submit_requires_a_capable_agent_and_known_dependencies() {
+        let (mut queue, _workers) = queue(&[("a", 1)]);
+        assert!(queue.submit(TaskSpec::new(TaskKind::ProfitMaximization, "x"), 0).is_err());
+        assert!(queue.submit(spec().after("missing"), 0).is_err());
+        assert!(queue.submit(spec(), 0).is_ok());
+    }
+
+    #[test]
+    fn higher_priority_runs_first_and_dependents_wait() {
+        let (mut queue, mut workers) = queue(&[("a", 1)]);
+        let low = queue.submit(spec().with_priority(1), 0).unwrap();
+        let high = queue.submit(spec().with_priority(9), 0).unwrap();
+        let dependent = queue.submit(spec().with_priority(10).after(&low), 0).unwrap();
+
+        assert_eq!(queue.assign(&HashMap::new(), 0), vec![(high.clone(), "a".to_string())]);
+        let worker = workers.get_mut("a").unwrap();
+        let task = next(worker);
+        worker.finish(&task, succeeded());
+        queue.poll_reports(1);
+
+        assert_eq!(queue.assign(&HashMap::new(), 1), vec![(low.clone(), "a".to_string())]);
+        let task = next(worker);
+        worker.finish(&task, succeeded());
+        queue.poll_reports(2);
+        assert_eq!(queue.assign(&HashMap::new(), 2), vec![(dependent, "a".to_string())]);
+    }
+
+    #[test]
+    fn concurrency_limit_holds_until_the_worker_finishes() {
+        let (mut queue, mut workers) = queue(&[("a", 1)]);
+        queue.submit(spec(), 0).unwrap();
+        queue.submit(spec(), 0).unwrap();
+
+        let assigned = queue.assign(&HashMap::new(), 0);
+        assert_eq!(assigned.len(), 1);
+        let first = assigned[0].0.clone();
+        assert!(queue.assign(&HashMap::new(), 0).is_empty());
+
+        // Timing out requeues the task but the worker is still busy on it
+        let settlements = queue.expire(2_000);
+        assert!(matches!(settlements[0].outcome, SettlementOutcome::Retrying { .. }));
+        assert!(queue.assign(&HashMap::new(), 2_000).is_empty());
+
+        let worker = workers.get_mut("a").unwrap();
+        let stale = next(worker);
+        assert_eq!(stale.id, first);
+        worker.finish(&stale, succeeded());
+        assert!(queue.poll_reports(2_001).is_empty());
+        assert_eq!(queue.task(&first).unwrap().status, TaskStatus::Pending);
+        assert_eq!(queue.assign(&HashMap::new(), 2_001).len(), 1);
+    }
+
+    #[test]
+    fn a_timed_out_attempt_cannot_settle_the_retry() {
+        let (mut queue, mut workers) = queue(&[("a", 2)]);
+        let id = queue.submit(spec(), 0).unwrap();
+        queue.assign(&HashMap::new(), 0);
+        queue.expire(2_000);
+        queue.assign(&HashMap::new(), 2_000);
+        assert_eq!(queue.task(&id).unwrap().attempts, 2);
+
+        let worker = workers.get_mut("a").unwrap();
+        let first = next(worker);
+        let retry = next(worker);
+        worker.report_progress(&first, 90.0);
+        worker.finish(&first, succeeded());
+        assert!(queue.poll_reports(2_001).is_empty());
+        let task = queue.task(&id).unwrap();
+        assert_eq!((task.status, task.progress), (TaskStatus::Running, 0.0));
+
+        worker.finish(&retry, succeeded());
+        let settlements = queue.poll_reports(2_002);
+        assert_eq!(settlements.len(), 1);
+        assert_eq!(queue.task(&id).unwrap().status, TaskStatus::Completed);
+    }
+
+    #[test]
+    fn retries_prefer_a_fresh_agent_then_fail() {
+        let (mut queue, mut workers) = queue(&[("a", 1), ("b", 1)]);
+        let performance: HashMap<String, f64> = [("a".to_string(), 90.0), ("b".to_string(), 10.0)].into();
+        let id = queue.submit(spec(), 0).unwrap();
+
+        assert_eq!(queue.assign(&performance, 0)[0].1, "a");
+        let worker = workers.get_mut("a").unwrap();
+        let task = next(worker);
+        worker.finish(&task, TaskResult::Failed { error: "boom".to_string() });
+        let settlements = queue.poll_reports(1);
+        assert!(matches!(settlements[0].outcome, SettlementOutcome::Retrying { .. }));
+
+        assert_eq!(queue.assign(&performance, 1)[0].1, "b");
+        let worker = workers.get_mut("b").unwrap();
+        let task = next(worker);
+        worker.finish(&task, TaskResult::Failed { error: "boom".to_string() });
+        let settlements = queue.poll_reports(2);
+        assert!(matches!(settlements[0].outcome, SettlementOutcome::Failed { .. }));
+        assert_eq!(queue.task(&id).unwrap().failed_agents, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn agents_without_a_live_worker_get_nothing() {
+        let (mut queue, mut workers) = queue(&[("a", 1)]);
+        workers.remove("a");
+        let id = queue.submit(spec(), 0).unwrap();
+        assert!(queue.assign(&HashMap::new(), 0).is_empty());
+        assert_eq!(queue.task(&id).unwrap().status, TaskStatus::Pending);
+    }
+
+    #[test]
+    fn deadlines_expire_tasks_and_fail_their_dependents() {
+        let (mut queue, _workers) = queue(&[("a", 1)]);
+        let parent = queue.submit(spec().with_deadline(100), 0).unwrap();
+        let child = queue.submit(spec().after(&parent), 0).unwrap();
+
+        queue.expire(101);
+        assert_eq!(queue.task(&parent).unwrap().status, TaskStatus::Expired);
+        assert_eq!(queue.task(&child).unwrap().status, TaskStatus::Failed);
+    }
+
+    #[test]
+    fn pruning_keeps_finished_tasks_still_depended_on() {
+        let (mut queue, mut workers) = queue(&[("a", 1)]);
+        let parent = queue.submit(spec(), 0).unwrap();
+        queue.assign(&HashMap::new(), 0);
+        let worker = workers.get_mut("a").unwrap();
+        let task = next(worker);
+        worker.finish(&task, succeeded());
+        queue.poll_reports(1);
+        let child = queue.submit(spec().after(&parent), 1).unwrap();
+
+        queue.prune_finished(0);
+        assert!(queue.task(&parent).is_some());
+        assert!(queue.task(&child).is_some());
+    }
+}
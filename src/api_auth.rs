@@ -0,0 +1,408 @@
+/**
+ * API AUTHENTICATION & AUTHORIZATION
+ * API-key and JWT authentication, role checks, per-key rate limits and audit logging
+ */
+
+use dashmap::DashMap;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+pub use black_diamond_client::types::{AuditEntry, Role};
+use black_diamond_client::routes::{self, HttpMethod};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+const DEFAULT_REQUESTS_PER_MINUTE: u32 = 120;
+const AUDIT_MEMORY_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Principal {
+    pub id: String,
+    pub role: Role,
+    pub requests_per_minute: u32,
+}
+
+impl Principal {
+    /// Stands in for a caller whose credentials were missing or invalid
+    pub fn anonymous() -> Self {
+        Principal {
+            id: "anonymous".to_string(),
+            role: Role::Viewer,
+            requests_per_minute: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtClaims {
+    pub sub: String,
+    pub role: Role,
+    pub exp: usize,
+    pub rpm: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthError {
+    MissingCredentials,
+    InvalidCredentials(String),
+    Forbidden { required: Role, actual: Role },
+    RateLimited { retry_after_secs: u64 },
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::MissingCredentials => write!(f, "Missing API key or bearer token"),
+            AuthError::InvalidCredentials(reason) => write!(f, "Invalid credentials: {}", reason),
+            AuthError::Forbidden { required, actual } => {
+                write!(f, "Role {:?} cannot access a route requiring {:?}", actual, required)
+            }
+            AuthError::RateLimited { retry_after_secs } => {
+                write!(f, "Rate limit exceeded, retry in {}s", retry_after_secs)
+            }
+        }
+    }
+}
+
+struct ApiKeyEntry {
+    id: String,
+    role: Role,
+    requests_per_minute: u32,
+}
+
+pub struct ApiAuth {
+    api_keys: HashMap<String, ApiKeyEntry>, // Keyed by SHA-256 of the raw key
+    jwt_key: Option<DecodingKey>,
+    rate_windows: DashMap<String, (Instant, u32)>,
+    audit_log: Mutex<VecDeque<AuditEntry>>,
+    audit_path: Option<PathBuf>,
+}
+
+impl ApiAuth {
+    pub fn new(audit_path: Option<PathBuf>) -> Self {
+        Self {
+            api_keys: HashMap::new(),
+            jwt_key: None,
+            rate_windows: DashMap::new(),
+            audit_log: Mutex::new(VecDeque::new()),
+            audit_path,
+        }
+    }
+
+    /// Builds the authenticator from the environment:
+    /// `BLACK_DIAMOND_API_KEYS` = `id:key:role[:requests_per_minute],...`
+    /// `BLACK_DIAMOND_JWT_SECRET` = HS256 secret for bearer tokens
+    pub fn from_env(audit_path: Option<PathBuf>) -> anyhow::Result<Self> {
+        let mut auth = Self::new(audit_path);
+
+        if let Ok(keys) = std::env::var("BLACK_DIAMOND_API_KEYS") {
+            for spec in keys.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let parts: Vec<&str> = spec.split(':').collect();
+                if parts.len() < 3 {
+                    return Err(anyhow::anyhow!("Malformed API key spec for '{}'", parts[0]));
+                }
+                let rpm = match parts.get(3) {
+                    Some(rpm) => rpm.parse()?,
+                    None => DEFAULT_REQUESTS_PER_MINUTE,
+                };
+                let role = parts[2].parse().map_err(|e: String| anyhow::anyhow!(e))?;
+                auth.add_api_key(parts[0], parts[1], role, rpm);
+            }
+        }
+
+        if let Ok(secret) = std::env::var("BLACK_DIAMOND_JWT_SECRET") {
+            auth.set_jwt_secret(secret.as_bytes());
+        }
+
+        Ok(auth)
+    }
+
+    pub fn add_api_key(&mut self, id: &str, key: &str, role: Role, requests_per_minute: u32) {
+        self.api_keys.insert(
+            hash_key(key),
+            ApiKeyEntry {
+                id: id.to_string(),
+                role,
+                requests_per_minute,
+            },
+        );
+    }
+
+    pub fn set_jwt_secret(&mut self, secret: &[u8]) {
+        self.jwt_key = Some(DecodingKey::from_secret(secret));
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.api_keys.is_empty() || self.jwt_key.is_some()
+    }
+
+    /// Resolves the caller from an `x-api-key` value or an `Authorization: Bearer` value.
+    pub fn authenticate(&self, api_key: Option<&str>, authorization: Option<&str>) -> Result<Principal, AuthError> {
+        if let Some(key) = api_key {
+            return self
+                .api_keys
+                .get(&hash_key(key))
+                .map(|entry| Principal {
+                    id: entry.id.clone(),
+                    role: entry.role,
+                    requests_per_minute: entry.requests_per_minute,
+                })
+                .ok_or_else(|| AuthError::InvalidCredentials("unknown API key".to_string()));
+        }
+
+        let token = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(AuthError::MissingCredentials)?;
+        let jwt_key = self
+            .jwt_key
+            .as_ref()
+            .ok_or_else(|| AuthError::InvalidCredentials("bearer tokens are not enabled".to_string()))?;
+
+        let claims = decode::<JwtClaims>(token, jwt_key, &Validation::new(Algorithm::HS256))
+            .map_err(|e| AuthError::InvalidCredentials(e.to_string()))?
+            .claims;
+
+        Ok(Principal {
+            id: format!("jwt:{}", claims.sub),
+            role: claims.role,
+            requests_per_minute: claims.rpm.unwrap_or(DEFAULT_REQUESTS_PER_MINUTE),
+        })
+    }
+
+    /// Who a rejected request came from, for its audit entry: the caller when
+    /// it was refused for its role or rate, anonymous when it never authenticated
+    pub fn identify(&self, api_key: Option<&str>, authorization: Option<&str>) -> Principal {
+        self.authenticate(api_key, authorization).unwrap_or_else(|_| Principal::anonymous())
+    }
+
+    /// Authenticates, checks the route's role and charges the caller's rate limit.
+    pub fn authorize(
+        &self,
+        method: &str,
+        path: &str,
+        api_key: Option<&str>,
+        authorization: Option<&str>,
+    ) -> Result<Principal, AuthError> {
+        let principal = self.authenticate(api_key, authorization)?;
+
+        let required = required_role(method, path);
+        if principal.role < required {
+            return Err(AuthError::Forbidden { required, actual: principal.role });
+        }
+
+        self.check_rate_limit(&principal)?;
+        Ok(principal)
+    }
+
+    fn check_rate_limit(&self, principal: &Principal) -> Result<(), AuthError> {
+        let window = Duration::from_secs(60);
+        let mut entry = self
+            .rate_windows
+            .entry(principal.id.clone())
+            .or_insert_with(|| (Instant::now(), 0));
+
+        if entry.0.elapsed() >= window {
+            *entry = (Instant::now(), 0);
+        }
+
+        if entry.1 >= principal.requests_per_minute {
+            let retry_after_secs = window.saturating_sub(entry.0.elapsed()).as_secs().max(1);
+            return Err(AuthError::RateLimited { retry_after_secs });
+        }
+
+        entry.1 += 1;
+        Ok(())
+    }
+
+    /// Records a call in memory and, if configured, appends it to the audit file.
+    pub fn record_audit(&self, principal: &Principal, method: &str, path: &str, status: u16) {
+        let entry = AuditEntry {
+            timestamp: chrono::Utc::now(),
+            principal: principal.id.clone(),
+            role: principal.role,
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+        };
+
+        if let Some(path) = &self.audit_path {
+            let written = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| {
+                    let line = serde_json::to_string(&entry).unwrap_or_default();
+                    writeln!(file, "{}", line)
+                });
+            if let Err(e) = written {
+                tracing::warn!("Failed to write audit log entry: {}", e);
+            }
+        }
+
+        let mut log = self.audit_log.lock();
+        log.push_back(entry);
+        if log.len() > AUDIT_MEMORY_LIMIT {
+            log.pop_front();
+        }
+    }
+
+    pub fn recent_audit_entries(&self, limit: usize) -> Vec<AuditEntry> {
+        self.audit_log.lock().iter().rev().take(limit).cloned().collect()
+    }
+}
+
+pub fn is_mutating(method: &str) -> bool {
+    !matches!(method, "GET" | "HEAD" | "OPTIONS")
+}
+
+/// Role required for a route, taken from the shared route table. Unknown routes
+/// fall back to: reads for viewers, mutations for operators, system-wide
+/// switches and admin pages for admins.
+pub fn required_role(method: &str, path: &str) -> Role {
+    let declared = routes::ROUTES.iter().find(|r| {
+        let route_method = match r.method {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+        };
+        r.path == path && route_method == method
+    });
+    if let Some(role) = declared.and_then(|r| r.role) {
+        return role;
+    }
+
+    if path.starts_with("/api/systems/") || path.starts_with("/api/admin/") {
+        Role::Admin
+    } else if is_mutating(method) {
+        Role::Operator
+    } else {
+        Role::Viewer
+    }
+}
+
+fn hash_key(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"test-secret";
+
+    fn auth() -> ApiAuth {
+        let mut auth = ApiAuth::new(None);
+        auth.add_api_key("dashboard", "viewer-key", Role::Viewer, 120);
+        auth.add_api_key("desk", "operator-key", Role::Operator, 2);
+        auth.set_jwt_secret(SECRET);
+        auth
+    }
+
+    fn bearer(role: &str, exp_offset: i64, secret: &[u8]) -> String {
+        let exp = chrono::Utc::now().timestamp() + exp_offset;
+        let claims = json!({ "sub": "alice", "role": role, "exp": exp, "rpm": 10 });
+        let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(secret)).unwrap();
+        format!("Bearer {}", token)
+    }
+
+    #[test]
+    fn authenticates_api_keys_by_hash() {
+        let auth = auth();
+        let principal = auth.authenticate(Some("operator-key"), None).unwrap();
+        assert_eq!((principal.id.as_str(), principal.role, principal.requests_per_minute), ("desk", Role::Operator, 2));
+
+        assert!(matches!(auth.authenticate(Some("wrong"), None), Err(AuthError::InvalidCredentials(_))));
+        assert_eq!(auth.authenticate(None, None).unwrap_err(), AuthError::MissingCredentials);
+        assert!(!auth.api_keys.contains_key("operator-key"));
+    }
+
+    #[test]
+    fn authenticates_bearer_tokens() {
+        let auth = auth();
+        let principal = auth.authenticate(None, Some(&bearer("admin", 600, SECRET))).unwrap();
+        assert_eq!((principal.id.as_str(), principal.role, principal.requests_per_minute), ("jwt:alice", Role::Admin, 10));
+
+        for token in [bearer("admin", 600, b"other-secret"), bearer("admin", -600, SECRET), "Basic abc".to_string()] {
+            assert!(auth.authenticate(None, Some(&token)).is_err(), "{}", token);
+        }
+        let keys_only = ApiAuth::new(None);
+        assert!(matches!(
+            keys_only.authenticate(None, Some(&bearer("admin", 600, SECRET))),
+            Err(AuthError::InvalidCredentials(_))
+        ));
+    }
+
+    #[test]
+    fn maps_routes_to_roles() {
+        assert_eq!(required_role("GET", routes::BLACK_DIAMOND_STATUS), Role::Viewer);
+        assert_eq!(required_role("POST", routes::STRATEGY_ENABLE), Role::Operator);
+        assert_eq!(required_role("POST", routes::ACTIVATE_ALL), Role::Admin);
+        assert_eq!(required_role("GET", routes::AUDIT_LOG), Role::Admin);
+        // Undeclared routes fall back on the method and prefix
+        assert_eq!(required_role("GET", "/api/unknown"), Role::Viewer);
+        assert_eq!(required_role("POST", "/api/unknown"), Role::Operator);
+        assert_eq!(required_role("GET", "/api/admin/unknown"), Role::Admin);
+    }
+
+    #[test]
+    fn forbids_roles_below_the_route() {
+        let auth = auth();
+        assert_eq!(
+            auth.authorize("POST", routes::STRATEGY_ENABLE, Some("viewer-key"), None).unwrap_err(),
+            AuthError::Forbidden { required: Role::Operator, actual: Role::Viewer }
+        );
+        assert!(auth.authorize("GET", routes::BLACK_DIAMOND_STATUS, Some("viewer-key"), None).is_ok());
+        assert!(auth.authorize("POST", routes::ACTIVATE_ALL, None, Some(&bearer("admin", 600, SECRET))).is_ok());
+    }
+
+    #[test]
+    fn rate_limits_per_principal() {
+        let auth = auth();
+        for _ in 0..2 {
+            auth.authorize("POST", routes::STRATEGY_ENABLE, Some("operator-key"), None).unwrap();
+        }
+        match auth.authorize("POST", routes::STRATEGY_ENABLE, Some("operator-key"), None) {
+            Err(AuthError::RateLimited { retry_after_secs }) => assert!((1..=60).contains(&retry_after_secs)),
+            other => panic!("expected a rate limit, got {:?}", other),
+        }
+        // Other callers have their own window
+        assert!(auth.authorize("GET", routes::BLACK_DIAMOND_STATUS, Some("viewer-key"), None).is_ok());
+        // Refused calls are not charged
+        assert!(auth.authorize("POST", routes::STRATEGY_ENABLE, Some("viewer-key"), None).is_err());
+        assert_eq!(auth.rate_windows.get("dashboard").unwrap().1, 1);
+    }
+
+    #[test]
+    fn audit_log_keeps_calls_newest_first_and_appends_them_to_the_file() {
+        let path = std::env::temp_dir().join(format!("api_audit_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut auth = ApiAuth::new(Some(path.clone()));
+        auth.add_api_key("desk", "operator-key", Role::Operator, 120);
+
+        let desk = auth.authenticate(Some("operator-key"), None).unwrap();
+        auth.record_audit(&desk, "POST", routes::STRATEGY_ENABLE, 200);
+        auth.record_audit(&auth.identify(Some("wrong"), None), "POST", routes::ACTIVATE_ALL, 401);
+
+        let entries = auth.recent_audit_entries(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].principal.as_str(), entries[0].path.as_str(), entries[0].status), ("anonymous", routes::ACTIVATE_ALL, 401));
+        assert_eq!((entries[1].principal.as_str(), entries[1].role, entries[1].method.as_str()), ("desk", Role::Operator, "POST"));
+        assert_eq!(auth.recent_audit_entries(1).len(), 1);
+
+        let lines: Vec<AuditEntry> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].principal.as_str(), lines[0].status), ("desk", 200));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+/**
+ * ATOMIC ARBITRAGE CLIENT
+ * Builds instructions and transactions for the on-chain atomic-arb program
+ */
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anyhow::{anyhow, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+pub use atomic_arb::{SwapLeg, ID as ATOMIC_ARB_PROGRAM_ID};
+
+/// A swap instruction to run as one leg of the route
+#[derive(Debug, Clone)]
+pub struct SwapCpi {
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+impl From<Instruction> for SwapCpi {
+    fn from(instruction: Instruction) -> Self {
+        SwapCpi {
+            program_id: instruction.program_id,
+            accounts: instruction.accounts,
+            data: instruction.data,
+        }
+    }
+}
+
+/// `execute_route` for `swaps` in order, failing on-chain unless the
+/// authority's output-mint balance, over `output_token_account` and their
+/// same-mint accounts in `swaps`, gains at least `min_profit`
+pub fn execute_route_instruction(
+    program_id: Pubkey,
+    authority: Pubkey,
+    output_token_account: Pubkey,
+    swaps: &[SwapCpi],
+    min_profit: u64,
+) -> Result<Instruction> {
+    let mut accounts = atomic_arb::accounts::ExecuteRoute {
+        authority,
+        output_token_account,
+    }
+    .to_account_metas(None);
+
+    // Each leg is its program and a run of accounts, addressed by index into
+    // the remaining accounts. A program or run already laid out for an
+    // earlier leg is pointed at again rather than repeated.
+    let mut remaining: Vec<AccountMeta> = Vec::new();
+    let mut legs = Vec::with_capacity(swaps.len());
+    for swap in swaps {
+        let program_index = match remaining.iter().position(|meta| meta.pubkey == swap.program_id) {
+            Some(index) => index,
+            None => {
+                remaining.push(AccountMeta::new_readonly(swap.program_id, false));
+                remaining.len() - 1
+            }
+        };
+        let account_start = if swap.accounts.is_empty() {
+            0
+        } else {
+            match remaining.windows(swap.accounts.len()).position(|run| run == swap.accounts.as_slice()) {
+                Some(start) => start,
+                None => {
+                    remaining.extend(swap.accounts.iter().cloned());
+                    remaining.len() - swap.accounts.len()
+                }
+            }
+        };
+        if remaining.len() > u8::MAX as usize {
+            return Err(anyhow!("Route needs more than {} accounts", u8::MAX));
+        }
+        legs.push(SwapLeg {
+            program_index: program_index as u8,
+            account_start: account_start as u8,
+            account_count: swap.accounts.len() as u8,
+            data: swap.data.clone(),
+        });
+    }
+    accounts.extend(remaining);
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data: atomic_arb::instruction::ExecuteRoute { legs, min_profit }.data(),
+    })
+}
+
+/// Signed transaction running the route, with an optional compute unit limit
+/// since multi-hop routes often exceed the default
+pub fn route_transaction(
+    program_id: Pubkey,
+    authority: &Keypair,
+    output_token_account: Pubkey,
+    swaps: &[SwapCpi],
+    min_profit: u64,
+    compute_unit_limit: Option<u32>,
+    recent_blockhash: Hash,
+) -> Result<Transaction> {
+    let mut instructions = Vec::new();
+    if let Some(units) = compute_unit_limit {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+    }
+    instructions.push(execute_route_instruction(
+        program_id,
+        authority.pubkey(),
+        output_token_account,
+        swaps,
+        min_profit,
+    )?);
+
+    Ok(Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&authority.pubkey()),
+        &[authority],
+        recent_blockhash,
+    ))
+}
+
+/// Sends the route and waits for confirmation; an unprofitable route comes
+/// back as the program's `InsufficientProfit` error
+pub async fn send_route(
+    rpc: &RpcClient,
+    program_id: Pubkey,
+    authority: &Keypair,
+    output_token_account: Pubkey,
+    swaps: &[SwapCpi],
+    min_profit: u64,
+) -> Result<Signature> {
+    let blockhash = rpc.get_latest_blockhash().await?;
+    let transaction = route_transaction(
+        program_id,
+        authority,
+        output_token_account,
+        swaps,
+        min_profit,
+        Some(400_000),
+        blockhash,
+    )?;
+    Ok(rpc.send_and_confirm_transaction(&transaction).await?)
+}
+
+/// Whether the program is deployed and executable on the connected cluster.
+/// A missing account is `false`; failing to reach the cluster is an error.
+pub async fn is_deployed(rpc: &RpcClient, program_id: &Pubkey) -> Result<bool> {
+    let account = rpc.get_account_with_commitment(program_id, rpc.commitment()).await?.value;
+    Ok(account.is_some_and(|account| account.executable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorDeserialize;
+
+    fn swap(program_id: Pubkey, accounts: &[Pubkey]) -> SwapCpi {
+        SwapCpi {
+            program_id,
+            accounts: accounts.iter().map(|key| AccountMeta::new(*key, false)).collect(),
+            data: vec![1],
+        }
+    }
+
+    #[test]
+    fn legs_share_programs_and_repeated_account_runs() {
+        let (dex, pool_a, pool_b) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let swaps = [swap(dex, &[pool_a, pool_b]), swap(dex, &[pool_b]), swap(dex, &[pool_a, pool_b])];
+        let instruction =
+            execute_route_instruction(ATOMIC_ARB_PROGRAM_ID, Pubkey::new_unique(), Pubkey::new_unique(), &swaps, 0)
+                .unwrap();
+
+        // The two named accounts, then the program and each pool once
+        let remaining: Vec<Pubkey> = instruction.accounts[2..].iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(remaining, vec![dex, pool_a, pool_b]);
+
+        let route = atomic_arb::instruction::ExecuteRoute::try_from_slice(&instruction.data[8..]).unwrap();
+        let ranges: Vec<(u8, u8, u8)> = route
+            .legs
+            .iter()
+            .map(|leg| (leg.program_index, leg.account_start, leg.account_count))
+            .collect();
+        assert_eq!(ranges, vec![(0, 1, 2), (0, 2, 1), (0, 1, 2)]);
+    }
+
+    #[test]
+    fn rejects_routes_past_the_account_index_range() {
+        let swaps: Vec<SwapCpi> = (0..86)
+            .map(|_| swap(Pubkey::new_unique(), &[Pubkey::new_unique(), Pubkey::new_unique()]))
+            .collect();
+        let result =
+            execute_route_instruction(ATOMIC_ARB_PROGRAM_ID, Pubkey::new_unique(), Pubkey::new_unique(), &swaps, 0);
+        assert!(result.is_err());
+    }
+}
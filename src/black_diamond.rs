@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use tokio::time::{Duration, Instant};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use crate::strategy::{CrossVenueArbitrage, StrategyDescriptor, StrategyFactory, StrategyParams, StrategyRegistry, TriangularArbitrage};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NeuralAgent {
@@ -469,4 +470,30 @@ impl BlackDiamondEngine {
     pub fn get_pipeline_status(&self) -> Vec<&ExecutionPipeline> {
         self.pipelines.values().collect()
     }
+
+    /// Registers the arbitrage pipelines with the shared strategy registry.
+    /// The MEV pipeline works off the mempool, not quotes, and is left out.
+    pub fn register_strategies(&self, registry: &mut StrategyRegistry) -> anyhow::Result<usize> {
+        let mut registered = 0;
+        for pipeline in self.pipelines.values() {
+            if registry.contains(&pipeline.id) {
+                continue;
+            }
+            let id = pipeline.id.clone();
+            let factory: StrategyFactory = match pipeline.id.as_str() {
+                "cascade_flash_pipeline" => Box::new(move || Box::new(CrossVenueArbitrage::new(&id))),
+                "triangular_arb_pipeline" => Box::new(move || Box::new(TriangularArbitrage::new(&id))),
+                _ => continue,
+            };
+            let descriptor = StrategyDescriptor {
+                id: pipeline.id.clone(),
+                name: pipeline.name.clone(),
+                origin: "black_diamond".to_string(),
+                description: pipeline.stages.join(" -> "),
+            };
+            registry.register(descriptor, StrategyParams::new(), factory)?;
+            registered += 1;
+        }
+        Ok(registered)
+    }
 }
\ No newline at end of file
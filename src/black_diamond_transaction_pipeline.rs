@@ -17,9 +17,22 @@ use tokio_tungstenite::{connect_async, tungstenite::Message};
 use futures_util::{sink::SinkExt, stream::StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use anyhow::Result;
+use crate::price_oracle::{PriceFlag, PriceOracle, PriceQuote};
+use crate::pool_ingestion::{feed_session, IngestionConfig, PoolIngestion, PoolUpdate};
+use crate::account_cache::{AccountCache, ReadRequirement};
+use crate::strategy::{Side, SimulationSession};
+use crate::bot_registry::{bot_job, BotJob, BotTick};
+use crate::capital_allocator::StrategyLedger;
+use crate::on_chain_program_innovations::{ARBITRAGE_SCANNER_BOT, BUNDLE_CAPTURE_BOT};
+use solana_client::rpc_config::RpcTransactionConfig;
+pub use black_diamond_client::types::{PipelineStatus, TransactionMetrics};
+
+/// Cycle return above 1 needed before the scanner queues an arbitrage
+const MIN_CYCLE_EDGE: f64 = 0.002;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct F8WalletConfig {
@@ -53,6 +66,22 @@ pub struct PriceFeedCache {
     pub liquidity: f64,
     pub price_impact: f64,
     pub spread: f64,
+    pub confidence: f64,
+    pub flags: Vec<PriceFlag>,
+}
+
+impl PriceFeedCache {
+    /// True once the entry is older than `max_age_ms`, or its sources already were
+    pub fn is_stale(&self, now_ms: u64, max_age_ms: u64) -> bool {
+        self.flags.contains(&PriceFlag::Stale) || now_ms.saturating_sub(self.timestamp) > max_age_ms
+    }
+
+    /// Keeps the last price for reference but stops it being served as current
+    pub fn mark_stale(&mut self) {
+        if !self.flags.contains(&PriceFlag::Stale) {
+            self.flags.push(PriceFlag::Stale);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +107,19 @@ pub struct ProcessedSignal {
     pub time_window: u64,
     pub data: serde_json::Value,
     pub execution_priority: u8,
+    /// Who produced the signal; executions are booked to the trade ledger
+    /// under this name
+    pub source: String,
+}
+
+impl ProcessedSignal {
+    /// SOL committed by the signal, where it states one
+    pub fn capital(&self) -> f64 {
+        ["amount", "solAmount"]
+            .iter()
+            .find_map(|key| self.data.get(*key).and_then(|v| v.as_f64()))
+            .unwrap_or(0.0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,24 +131,19 @@ pub enum SignalType {
     YieldFarm,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransactionMetrics {
-    pub total_transactions: u64,
-    pub successful_transactions: u64,
-    pub total_profit_sol: f64,
-    pub average_execution_time: f64,
-    pub gas_optimization_savings: f64,
-    pub mev_captured: f64,
-}
-
 pub struct BlackDiamondTransactionPipeline {
     connection: Arc<RpcClient>,
     f8_wallet: F8WalletConfig,
     dex_aggregator: DEXAggregatorConfig,
     price_cache: Arc<Mutex<HashMap<String, PriceFeedCache>>>,
+    oracle: Arc<PriceOracle>,
+    pool_ingestion: Arc<PoolIngestion>,
+    account_cache: Arc<AccountCache>,
     memecoin_opportunities: Arc<Mutex<Vec<MemecoinOpportunity>>>,
     processing_queue: Arc<Mutex<Vec<ProcessedSignal>>>,
     metrics: Arc<Mutex<TransactionMetrics>>,
+    trade_ledger: Arc<parking_lot::RwLock<StrategyLedger>>,
+    program_notifications: Arc<AtomicU64>,
     black_diamond_active: Arc<Mutex<bool>>,
     
     // Pre-made smart contracts for known routes
@@ -147,14 +184,32 @@ impl BlackDiamondTransactionPipeline {
             mev_captured: 0.0,
         };
 
+        let account_cache = Arc::new(AccountCache::default());
+        let oracle = Arc::new(
+            PriceOracle::with_default_feeds(Arc::clone(&connection)).with_account_cache(Arc::clone(&account_cache)),
+        );
+        let pool_ingestion = Arc::new(
+            PoolIngestion::new(
+                IngestionConfig::new(&f8_wallet_config.ws_endpoint),
+                PoolIngestion::default_pools(),
+            )
+            .with_cache(Arc::clone(&account_cache))
+            .with_rpc(Arc::clone(&connection)),
+        );
+
         Self {
             connection,
             f8_wallet: f8_wallet_config,
             dex_aggregator,
             price_cache: Arc::new(Mutex::new(HashMap::new())),
+            oracle,
+            pool_ingestion,
+            account_cache,
             memecoin_opportunities: Arc::new(Mutex::new(Vec::new())),
             processing_queue: Arc::new(Mutex::new(Vec::new())),
             metrics: Arc::new(Mutex::new(metrics)),
+            trade_ledger: Arc::new(parking_lot::RwLock::new(StrategyLedger::default())),
+            program_notifications: Arc::new(AtomicU64::new(0)),
             black_diamond_active: Arc::new(Mutex::new(true)),
             
             // Smart contract addresses
@@ -167,6 +222,17 @@ impl BlackDiamondTransactionPipeline {
         }
     }
 
+    /// Books every execution into `ledger`, so whoever shares it (the bot
+    /// registry) sees realised revenue
+    pub fn with_trade_ledger(mut self, ledger: Arc<parking_lot::RwLock<StrategyLedger>>) -> Self {
+        self.trade_ledger = ledger;
+        self
+    }
+
+    pub fn trade_ledger(&self) -> Arc<parking_lot::RwLock<StrategyLedger>> {
+        Arc::clone(&self.trade_ledger)
+    }
+
     pub async fn initialize_pipeline(&self) -> Result<()> {
         println!("🔹 Initializing Black Diamond Transaction Pipeline V2.0...");
         
@@ -175,6 +241,10 @@ impl BlackDiamondTransactionPipeline {
         
         // Start price feed caching system
         self.start_price_feed_caching().await;
+
+        // Stream pool account updates for the strategies
+        self.pool_ingestion.start();
+        self.start_account_cache_eviction();
         
         // Initialize memecoin monitoring
         self.start_memecoin_monitoring().await;
@@ -210,11 +280,15 @@ impl BlackDiamondTransactionPipeline {
         // Spawn task to handle incoming messages
         let price_cache = Arc::clone(&self.price_cache);
         let processing_queue = Arc::clone(&self.processing_queue);
+        let program_notifications = Arc::clone(&self.program_notifications);
         
         tokio::spawn(async move {
             while let Some(msg) = read.next().await {
                 if let Ok(Message::Text(text)) = msg {
                     if let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) {
+                        if data.get("method").is_some_and(|m| m == "programNotification") {
+                            program_notifications.fetch_add(1, Ordering::Relaxed);
+                        }
                         Self::process_realtime_data(data, &price_cache, &processing_queue).await;
                     }
                 }
@@ -253,80 +327,169 @@ impl BlackDiamondTransactionPipeline {
             time_window: 5000,
             data: update.clone(),
             execution_priority: 8,
+            source: BUNDLE_CAPTURE_BOT.to_string(),
         })
     }
 
     async fn start_price_feed_caching(&self) {
         let price_cache = Arc::clone(&self.price_cache);
-        let connection = Arc::clone(&self.connection);
+        let oracle = Arc::clone(&self.oracle);
         
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_millis(100));
             loop {
                 interval.tick().await;
-                Self::update_price_feed_cache(&price_cache, &connection).await;
+                Self::update_price_feed_cache(&price_cache, &oracle).await;
             }
         });
     }
 
     async fn update_price_feed_cache(
         price_cache: &Arc<Mutex<HashMap<String, PriceFeedCache>>>,
-        connection: &Arc<RpcClient>,
+        oracle: &Arc<PriceOracle>,
     ) {
-        let tokens = vec![
-            "So11111111111111111111111111111111111111112".to_string(), // SOL
-            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(), // USDC
-            "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(), // USDT
-            "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So".to_string(), // mSOL
-            "7dHbWXmci3dT8UFYWYZweBLXgycu7Y3iL6trKn1Y7ARj".to_string(), // stSOL
-        ];
-
-        for token in tokens {
-            if let Ok((jupiter_price, pyth_price, birdeye_price)) = tokio::try_join!(
-                Self::get_jupiter_price(&token),
-                Self::get_pyth_price(&token),
-                Self::get_birdeye_price(&token)
-            ) {
-                let avg_price = (jupiter_price + pyth_price + birdeye_price) / 3.0;
-                
-                let cache_entry = PriceFeedCache {
-                    token: token.clone(),
-                    price: avg_price,
-                    timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
-                    source: "aggregated".to_string(),
-                    volume_24h: jupiter_price * 1_000_000.0,
-                    liquidity: pyth_price * 5_000_000.0,
-                    price_impact: 0.001,
-                    spread: 0.0005,
-                };
-
-                if let Ok(mut cache) = price_cache.lock() {
-                    cache.insert(token, cache_entry);
+        for token in oracle.mints() {
+            match oracle.price(&token).await {
+                Ok(quote) => {
+                    let sources: Vec<&str> = quote.sources.iter().map(|s| s.source.as_str()).collect();
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+                    let cache_entry = PriceFeedCache {
+                        token: token.clone(),
+                        price: quote.value,
+                        timestamp: now.saturating_sub(quote.age.as_millis() as u64),
+                        source: sources.join(","),
+                        volume_24h: 0.0,
+                        liquidity: 0.0,
+                        price_impact: 0.0,
+                        spread: if quote.value > 0.0 { quote.confidence / quote.value } else { 0.0 },
+                        confidence: quote.confidence,
+                        flags: quote.flags,
+                    };
+
+                    if let Ok(mut cache) = price_cache.lock() {
+                        cache.insert(token, cache_entry);
+                    }
+                }
+                Err(e) => {
+                    println!("❌ Price feed for {} unavailable: {}", token, e);
+                    if let Ok(mut cache) = price_cache.lock() {
+                        if let Some(entry) = cache.get_mut(&token) {
+                            entry.mark_stale();
+                        }
+                    }
                 }
             }
         }
     }
 
-    async fn get_jupiter_price(token: &str) -> Result<f64> {
-        let url = format!("https://price.jup.ag/v4/price?ids={}", token);
-        let response = reqwest::get(&url).await?;
-        let data: serde_json::Value = response.json().await?;
-        
-        if let Some(price) = data.get("data").and_then(|d| d.get(token)).and_then(|t| t.get("price")).and_then(|p| p.as_f64()) {
-            Ok(price)
-        } else {
-            Ok(0.0)
-        }
+    /// Slot-ordered pool updates; `PoolUpdate::to_market_event` gives the
+    /// quote strategies consume
+    pub fn subscribe_pool_updates(&self) -> tokio::sync::broadcast::Receiver<PoolUpdate> {
+        self.pool_ingestion.subscribe()
     }
 
-    async fn get_pyth_price(_token: &str) -> Result<f64> {
-        // Pyth network integration for real-time prices
-        Ok(fastrand::f64() * 100.0) // Placeholder for now
+    /// Paper-trades `session` on live pool quotes. The session is shared so
+    /// its strategies can be swapped and its reports read while it runs.
+    pub fn start_paper_trading(&self, session: Arc<parking_lot::Mutex<SimulationSession>>) {
+        tokio::spawn(feed_session(self.subscribe_pool_updates(), session));
+        println!("📝 Paper trading on live pool updates");
     }
 
-    async fn get_birdeye_price(_token: &str) -> Result<f64> {
-        // Birdeye API integration
-        Ok(fastrand::f64() * 100.0) // Placeholder for now
+    fn start_account_cache_eviction(&self) {
+        let account_cache = Arc::clone(&self.account_cache);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(10));
+            loop {
+                interval.tick().await;
+                account_cache.evict_expired();
+            }
+        });
+    }
+
+    /// Shared slot-aware account state, for modules that would otherwise
+    /// fetch accounts themselves
+    pub fn account_cache(&self) -> Arc<AccountCache> {
+        Arc::clone(&self.account_cache)
+    }
+
+    /// Return of a cycle through `legs` with every pool read at the same
+    /// confirmed slot, as `(slot, rate)`
+    fn cycle_at_snapshot(pool_ingestion: &PoolIngestion, legs: &[(&str, Side)]) -> Result<(u64, f64)> {
+        let pool_ids: Vec<&str> = legs.iter().map(|(pool, _)| *pool).collect();
+        let snapshot = pool_ingestion.snapshot(&pool_ids, &ReadRequirement::confirmed())?;
+        Ok((snapshot.slot, snapshot.cycle_rate(legs)?))
+    }
+
+    /// Worker for the arbitrage scanner bot: prices the two-pool SOL/USDC
+    /// cycles at one slot and queues any clearing `MIN_CYCLE_EDGE`
+    pub fn arbitrage_scanner_job(&self) -> BotJob {
+        let pool_ingestion = Arc::clone(&self.pool_ingestion);
+        let processing_queue = Arc::clone(&self.processing_queue);
+        bot_job(move || {
+            let pool_ingestion = Arc::clone(&pool_ingestion);
+            let processing_queue = Arc::clone(&processing_queue);
+            async move {
+                let cycles: [[(&str, Side); 2]; 2] = [
+                    [("raydium_sol_usdc", Side::Buy), ("orca_sol_usdc", Side::Sell)],
+                    [("orca_sol_usdc", Side::Buy), ("raydium_sol_usdc", Side::Sell)],
+                ];
+                let mut events = 0;
+                for legs in &cycles {
+                    let (slot, rate) = Self::cycle_at_snapshot(&pool_ingestion, legs)?;
+                    events += 1;
+                    if rate > 1.0 + MIN_CYCLE_EDGE {
+                        let signal = ProcessedSignal {
+                            signal_type: SignalType::Arbitrage,
+                            confidence: 0.9,
+                            profit_potential: rate - 1.0,
+                            risk_level: 0.1,
+                            time_window: 1000,
+                            data: serde_json::json!({
+                                "legs": legs.iter().map(|(pool, side)| format!("{}:{:?}", pool, side)).collect::<Vec<_>>(),
+                                "slot": slot,
+                                "rate": rate,
+                            }),
+                            execution_priority: 9,
+                            source: ARBITRAGE_SCANNER_BOT.to_string(),
+                        };
+                        if let Ok(mut queue) = processing_queue.lock() {
+                            queue.push(signal);
+                        }
+                    }
+                }
+                Ok(BotTick { events, trade: None })
+            }
+        })
+    }
+
+    /// Worker for the bundle capture bot: counts the program notifications
+    /// that became signals since its last run. Their executions are booked
+    /// to the bot by the signal processor.
+    pub fn bundle_capture_job(&self) -> BotJob {
+        let program_notifications = Arc::clone(&self.program_notifications);
+        bot_job(move || {
+            let events = program_notifications.swap(0, Ordering::Relaxed);
+            async move { Ok(BotTick { events, trade: None }) }
+        })
+    }
+
+    /// Aggregated oracle price of `mint`
+    pub async fn price(&self, mint: &str) -> Result<PriceQuote> {
+        self.oracle.price(mint).await
+    }
+
+    /// Last cached price of `mint`, unless it is older than the oracle's
+    /// source age limit or its last refresh failed
+    pub fn cached_price(&self, mint: &str) -> Option<PriceFeedCache> {
+        let max_age_ms = self.oracle.config().max_source_age.as_millis() as u64;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        self.price_cache
+            .lock()
+            .unwrap()
+            .get(mint)
+            .filter(|entry| !entry.is_stale(now, max_age_ms))
+            .cloned()
     }
 
     async fn start_memecoin_monitoring(&self) {
@@ -395,13 +558,14 @@ impl BlackDiamondTransactionPipeline {
         let processing_queue = Arc::clone(&self.processing_queue);
         let connection = Arc::clone(&self.connection);
         let metrics = Arc::clone(&self.metrics);
+        let trade_ledger = Arc::clone(&self.trade_ledger);
         let f8_wallet = self.f8_wallet.clone();
         
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_millis(10));
             loop {
                 interval.tick().await;
-                Self::process_signal_queue(&processing_queue, &connection, &metrics, &f8_wallet).await;
+                Self::process_signal_queue(&processing_queue, &connection, &metrics, &trade_ledger, &f8_wallet).await;
             }
         });
     }
@@ -410,6 +574,7 @@ impl BlackDiamondTransactionPipeline {
         processing_queue: &Arc<Mutex<Vec<ProcessedSignal>>>,
         connection: &Arc<RpcClient>,
         metrics: &Arc<Mutex<TransactionMetrics>>,
+        trade_ledger: &Arc<parking_lot::RwLock<StrategyLedger>>,
         f8_wallet: &F8WalletConfig,
     ) {
         let signal = {
@@ -426,7 +591,7 @@ impl BlackDiamondTransactionPipeline {
         };
 
         if signal.confidence > 0.7 {
-            Self::execute_signal(signal, connection, metrics, f8_wallet).await;
+            Self::execute_signal(signal, connection, metrics, trade_ledger, f8_wallet).await;
         }
     }
 
@@ -434,6 +599,7 @@ impl BlackDiamondTransactionPipeline {
         signal: ProcessedSignal,
         connection: &Arc<RpcClient>,
         metrics: &Arc<Mutex<TransactionMetrics>>,
+        trade_ledger: &Arc<parking_lot::RwLock<StrategyLedger>>,
         f8_wallet: &F8WalletConfig,
     ) {
         let start_time = Instant::now();
@@ -442,7 +608,17 @@ impl BlackDiamondTransactionPipeline {
             Ok(transaction) => {
                 match Self::broadcast_transaction(&transaction, connection).await {
                     Ok(signature) => {
-                        let profit = Self::verify_transaction_profit(&signature, connection).await.unwrap_or(0.0);
+                        let profit = match Self::verify_transaction_profit(&signature, connection).await {
+                            Ok(profit) => {
+                                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                                trade_ledger.write().record(&signal.source, profit, signal.capital(), now);
+                                profit
+                            }
+                            Err(e) => {
+                                println!("❌ Could not read result of {}: {}", signature, e);
+                                0.0
+                            }
+                        };
                         Self::update_metrics(metrics, true, start_time.elapsed(), profit);
                         println!("🔹 Signal executed: {:?}, Profit: {} SOL", signal.signal_type, profit);
                     }
@@ -529,21 +705,31 @@ impl BlackDiamondTransactionPipeline {
         Ok(signature)
     }
 
+    /// SOL balance change of the fee payer, net of fees, as recorded in
+    /// the confirmed transaction's metadata
     async fn verify_transaction_profit(
         signature: &Signature,
         connection: &Arc<RpcClient>,
     ) -> Result<f64> {
-        // Verify transaction execution and calculate profit
-        match connection.confirm_transaction(signature).await {
-            Ok(confirmed) => {
-                if confirmed {
-                    Ok(fastrand::f64() * 0.1) // Placeholder profit calculation
-                } else {
-                    Ok(0.0)
-                }
-            }
-            Err(_) => Ok(0.0),
-        }
+        let transaction = connection
+            .get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: None,
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await?;
+        let meta = transaction
+            .transaction
+            .meta
+            .ok_or_else(|| anyhow::anyhow!("No metadata for {}", signature))?;
+        let (pre, post) = match (meta.pre_balances.first(), meta.post_balances.first()) {
+            (Some(pre), Some(post)) => (*pre as i128, *post as i128),
+            _ => return Err(anyhow::anyhow!("No fee payer balance for {}", signature)),
+        };
+        Ok((post - pre) as f64 / 1_000_000_000.0)
     }
 
     fn update_metrics(
@@ -566,21 +752,30 @@ impl BlackDiamondTransactionPipeline {
     }
 
     // Public API methods
-    pub async fn get_system_status(&self) -> Result<serde_json::Value> {
+    pub async fn get_system_status(&self) -> Result<PipelineStatus> {
         let active = *self.black_diamond_active.lock().unwrap();
         let queue_length = self.processing_queue.lock().unwrap().len();
-        let cached_prices = self.price_cache.lock().unwrap().len();
+        // Only prices that would still be served count as cached
+        let max_age_ms = self.oracle.config().max_source_age.as_millis() as u64;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let cached_prices = self
+            .price_cache
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|entry| !entry.is_stale(now, max_age_ms))
+            .count();
         let memecoin_opportunities = self.memecoin_opportunities.lock().unwrap().len();
         let metrics = self.metrics.lock().unwrap().clone();
 
-        Ok(serde_json::json!({
-            "active": active,
-            "queueLength": queue_length,
-            "cachedPrices": cached_prices,
-            "memecoinOpportunities": memecoin_opportunities,
-            "metrics": metrics,
-            "connectedRPCs": 1
-        }))
+        Ok(PipelineStatus {
+            active,
+            queue_length,
+            cached_prices,
+            memecoin_opportunities,
+            metrics,
+            connected_rpcs: 1,
+        })
     }
 
     pub async fn execute_arbitrage(&self, token_a: String, token_b: String, amount: f64) -> Result<()> {
@@ -596,6 +791,7 @@ impl BlackDiamondTransactionPipeline {
                 "amount": amount
             }),
             execution_priority: 9,
+            source: "api".to_string(),
         };
         
         if let Ok(mut queue) = self.processing_queue.lock() {
@@ -617,6 +813,7 @@ impl BlackDiamondTransactionPipeline {
                 "solAmount": sol_amount
             }),
             execution_priority: 10,
+            source: "api".to_string(),
         };
         
         if let Ok(mut queue) = self.processing_queue.lock() {
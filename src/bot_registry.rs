@@ -0,0 +1,408 @@
+/**
+ * ON-CHAIN BOT REGISTRY
+ * Runs each bot as a worker on its own interval and measures it: heartbeats,
+ * events processed, a rolling error rate, and revenue booked to the trade
+ * ledger under the bot's id
+ */
+
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::capital_allocator::StrategyLedger;
+
+/// Outcomes kept for the rolling error rate
+const OUTCOME_WINDOW: usize = 100;
+/// Error rate above which a running bot counts as degraded
+const DEGRADED_ERROR_RATE: f64 = 0.2;
+/// Missed intervals before a bot with no heartbeat counts as stalled
+const STALL_INTERVALS: u32 = 3;
+const DAY_SECS: u64 = 86_400;
+
+/// What one run of a bot's job did
+#[derive(Debug, Clone, Default)]
+pub struct BotTick {
+    pub events: u64,
+    /// Realised `(pnl, capital)` to book to the ledger, net of fees and gas
+    pub trade: Option<(f64, f64)>,
+}
+
+pub type BotJob = Arc<dyn Fn() -> BoxFuture<'static, Result<BotTick>> + Send + Sync>;
+
+/// Wraps an async closure as a `BotJob`
+pub fn bot_job<F, Fut>(run: F) -> BotJob
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<BotTick>> + Send + 'static,
+{
+    Arc::new(move || Box::pin(run()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotStatus {
+    /// Registered without a job, so there is nothing to run
+    Idle,
+    Starting,
+    Healthy,
+    Degraded,
+    Stalled,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotHealth {
+    pub status: BotStatus,
+    pub started_at: Option<u64>,
+    pub last_heartbeat: Option<u64>,
+    pub ticks: u64,
+    pub errors: u64,
+    pub events_processed: u64,
+    /// Share of failed runs over the last `OUTCOME_WINDOW`
+    pub error_rate: f64,
+    pub last_error: Option<String>,
+    /// Trades and net PnL booked over the last 24 hours
+    pub trades_24h: usize,
+    pub revenue_24h: f64,
+}
+
+struct BotEntry {
+    interval: Duration,
+    job: Option<BotJob>,
+    running: Arc<AtomicBool>,
+    started_at: Option<u64>,
+    last_heartbeat: Option<u64>,
+    ticks: u64,
+    errors: u64,
+    events_processed: u64,
+    outcomes: VecDeque<bool>,
+    last_error: Option<String>,
+}
+
+impl BotEntry {
+    fn error_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.outcomes.iter().filter(|ok| !**ok).count() as f64 / self.outcomes.len() as f64
+    }
+
+    fn status(&self, now: u64) -> BotStatus {
+        if self.job.is_none() {
+            return BotStatus::Idle;
+        }
+        if !self.running.load(Ordering::Relaxed) {
+            return BotStatus::Stopped;
+        }
+        let stall_after = (self.interval * STALL_INTERVALS).as_secs().max(1);
+        match self.last_heartbeat.max(self.started_at) {
+            Some(seen) if now.saturating_sub(seen) > stall_after => BotStatus::Stalled,
+            _ if self.ticks == 0 => BotStatus::Starting,
+            _ if self.error_rate() > DEGRADED_ERROR_RATE => BotStatus::Degraded,
+            _ => BotStatus::Healthy,
+        }
+    }
+}
+
+pub struct BotRegistry {
+    bots: RwLock<HashMap<String, BotEntry>>,
+    ledger: Arc<RwLock<StrategyLedger>>,
+}
+
+impl BotRegistry {
+    pub fn new() -> Self {
+        Self::with_ledger(Arc::new(RwLock::new(StrategyLedger::default())))
+    }
+
+    /// Registry booking into, and reporting revenue from, a shared ledger
+    pub fn with_ledger(ledger: Arc<RwLock<StrategyLedger>>) -> Self {
+        Self {
+            bots: RwLock::new(HashMap::new()),
+            ledger,
+        }
+    }
+
+    pub fn ledger(&self) -> Arc<RwLock<StrategyLedger>> {
+        Arc::clone(&self.ledger)
+    }
+
+    /// Adds a bot that runs `job` every `interval` once started. A bot
+    /// without a job stays idle but can still have trades booked to it.
+    pub fn register(&self, id: &str, interval: Duration, job: Option<BotJob>) {
+        self.bots.write().insert(
+            id.to_string(),
+            BotEntry {
+                interval,
+                job,
+                running: Arc::new(AtomicBool::new(false)),
+                started_at: None,
+                last_heartbeat: None,
+                ticks: 0,
+                errors: 0,
+                events_processed: 0,
+                outcomes: VecDeque::new(),
+                last_error: None,
+            },
+        );
+    }
+
+    /// Spawns the worker for `id`; a no-op if it is already running
+    pub fn start(self: &Arc<Self>, id: &str) -> Result<()> {
+        let (job, interval, running) = {
+            let mut bots = self.bots.write();
+            let bot = bots.get_mut(id).ok_or_else(|| anyhow!("Unknown bot {}", id))?;
+            let job = bot.job.clone().ok_or_else(|| anyhow!("Bot {} has no job to run", id))?;
+            if bot.running.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            // A fresh flag per run, so a worker stopped mid-run cannot pick
+            // up again alongside its replacement
+            bot.running = Arc::new(AtomicBool::new(true));
+            bot.started_at = Some(now());
+            (job, bot.interval, Arc::clone(&bot.running))
+        };
+
+        let registry = Arc::clone(self);
+        let id = id.to_string();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // `stop` may have come in while waiting for the tick
+                if !running.load(Ordering::Relaxed) {
+                    break;
+                }
+                let outcome = job().await;
+                registry.observe(&id, outcome);
+            }
+        });
+        Ok(())
+    }
+
+    /// Starts every bot that has a job, returning how many were started
+    pub fn start_all(self: &Arc<Self>) -> usize {
+        let ids: Vec<String> = self
+            .bots
+            .read()
+            .iter()
+            .filter(|(_, bot)| bot.job.is_some())
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.iter().filter(|id| self.start(id).is_ok()).count()
+    }
+
+    /// The worker exits after its current run
+    pub fn stop(&self, id: &str) {
+        if let Some(bot) = self.bots.read().get(id) {
+            bot.running.store(false, Ordering::Relaxed);
+        }
+    }
+
+    pub fn stop_all(&self) {
+        for bot in self.bots.read().values() {
+            bot.running.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Books a trade made outside the bot's own job, such as a route the
+    /// bot found and another component executed
+    pub fn record_trade(&self, id: &str, pnl: f64, capital: f64) {
+        self.ledger.write().record(id, pnl, capital, now());
+    }
+
+    pub fn health(&self, id: &str) -> Option<BotHealth> {
+        let now = now();
+        let bots = self.bots.read();
+        let bot = bots.get(id)?;
+        let (trades_24h, revenue_24h) = self.ledger.read().pnl_since(id, now.saturating_sub(DAY_SECS));
+        Some(BotHealth {
+            status: bot.status(now),
+            started_at: bot.started_at,
+            last_heartbeat: bot.last_heartbeat,
+            ticks: bot.ticks,
+            errors: bot.errors,
+            events_processed: bot.events_processed,
+            error_rate: bot.error_rate(),
+            last_error: bot.last_error.clone(),
+            trades_24h,
+            revenue_24h,
+        })
+    }
+
+    fn observe(&self, id: &str, outcome: Result<BotTick>) {
+        let now = now();
+        let trade = {
+            let mut bots = self.bots.write();
+            let Some(bot) = bots.get_mut(id) else {
+                return;
+            };
+            bot.ticks += 1;
+            bot.outcomes.push_back(outcome.is_ok());
+            if bot.outcomes.len() > OUTCOME_WINDOW {
+                bot.outcomes.pop_front();
+            }
+            match outcome {
+                Ok(tick) => {
+                    bot.last_heartbeat = Some(now);
+                    bot.events_processed += tick.events;
+                    tick.trade
+                }
+                Err(e) => {
+                    bot.errors += 1;
+                    bot.last_error = Some(e.to_string());
+                    None
+                }
+            }
+        };
+        if let Some((pnl, capital)) = trade {
+            self.ledger.write().record(id, pnl, capital, now);
+        }
+    }
+}
+
+impl Default for BotRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+
+    fn counting_job(runs: Arc<AtomicU64>) -> BotJob {
+        bot_job(move || {
+            let runs = Arc::clone(&runs);
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(BotTick { events: 2, trade: None })
+            }
+        })
+    }
+
+    fn registry_with(id: &str, interval: Duration) -> (Arc<BotRegistry>, Arc<AtomicU64>) {
+        let registry = Arc::new(BotRegistry::new());
+        let runs = Arc::new(AtomicU64::new(0));
+        registry.register(id, interval, Some(counting_job(Arc::clone(&runs))));
+        (registry, runs)
+    }
+
+    fn status(registry: &BotRegistry, id: &str) -> BotStatus {
+        registry.health(id).unwrap().status
+    }
+
+    #[tokio::test]
+    async fn status_follows_the_worker_through_its_lifecycle() {
+        let (registry, _) = registry_with("scanner", Duration::from_secs(60));
+        registry.register("idle", Duration::from_secs(60), None);
+        assert_eq!(status(&registry, "idle"), BotStatus::Idle);
+        assert!(registry.start("idle").is_err());
+        assert_eq!(status(&registry, "scanner"), BotStatus::Stopped);
+
+        registry.start("scanner").unwrap();
+        assert_eq!(status(&registry, "scanner"), BotStatus::Starting);
+
+        // The first tick fires immediately
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let health = registry.health("scanner").unwrap();
+        assert_eq!((health.status, health.ticks, health.events_processed), (BotStatus::Healthy, 1, 2));
+
+        for _ in 0..3 {
+            registry.observe("scanner", Err(anyhow!("rpc timeout")));
+        }
+        let health = registry.health("scanner").unwrap();
+        assert_eq!(health.status, BotStatus::Degraded);
+        assert_eq!(health.last_error.as_deref(), Some("rpc timeout"));
+
+        {
+            let mut bots = registry.bots.write();
+            let bot = bots.get_mut("scanner").unwrap();
+            let long_ago = now() - 60 * u64::from(STALL_INTERVALS) - 10;
+            bot.started_at = Some(long_ago);
+            bot.last_heartbeat = Some(long_ago);
+        }
+        assert_eq!(status(&registry, "scanner"), BotStatus::Stalled);
+
+        registry.stop("scanner");
+        assert_eq!(status(&registry, "scanner"), BotStatus::Stopped);
+    }
+
+    #[test]
+    fn error_rate_covers_only_the_last_outcomes() {
+        let registry = BotRegistry::new();
+        registry.register("scanner", Duration::from_secs(1), Some(counting_job(Arc::new(AtomicU64::new(0)))));
+        for _ in 0..OUTCOME_WINDOW {
+            registry.observe("scanner", Err(anyhow!("failed")));
+        }
+        assert_eq!(registry.health("scanner").unwrap().error_rate, 1.0);
+
+        for _ in 0..OUTCOME_WINDOW / 4 {
+            registry.observe("scanner", Ok(BotTick::default()));
+        }
+        let health = registry.health("scanner").unwrap();
+        assert!((health.error_rate - 0.75).abs() < 1e-9);
+        assert_eq!((health.ticks, health.errors), (125, 100));
+        assert_eq!(registry.bots.read()["scanner"].outcomes.len(), OUTCOME_WINDOW);
+    }
+
+    #[tokio::test]
+    async fn a_stopped_worker_does_not_run_again() {
+        let (registry, runs) = registry_with("scanner", Duration::from_millis(30));
+        registry.start("scanner").unwrap();
+        tokio::time::sleep(Duration::from_millis(45)).await;
+        registry.stop("scanner");
+        let stopped_at = runs.load(Ordering::SeqCst);
+        assert!(stopped_at >= 1);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), stopped_at);
+    }
+
+    #[tokio::test]
+    async fn a_restart_gets_a_fresh_flag() {
+        let (registry, runs) = registry_with("scanner", Duration::from_millis(20));
+        registry.start("scanner").unwrap();
+        let first = Arc::clone(&registry.bots.read()["scanner"].running);
+        // Starting a running bot is a no-op
+        registry.start("scanner").unwrap();
+        assert!(Arc::ptr_eq(&first, &registry.bots.read()["scanner"].running));
+
+        registry.stop("scanner");
+        registry.start("scanner").unwrap();
+        let second = Arc::clone(&registry.bots.read()["scanner"].running);
+        assert!(!first.load(Ordering::SeqCst));
+        assert!(second.load(Ordering::SeqCst));
+
+        // Only the new worker keeps running
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        registry.stop("scanner");
+        let runs_now = runs.load(Ordering::SeqCst);
+        assert!((1..=4).contains(&runs_now), "{} runs", runs_now);
+    }
+
+    #[test]
+    fn revenue_counts_trades_from_the_last_day() {
+        let registry = BotRegistry::new();
+        registry.register("router", Duration::from_secs(1), None);
+        registry.ledger().write().record("router", 5.0, 100.0, now() - 2 * DAY_SECS);
+        registry.record_trade("router", 1.5, 100.0);
+        registry.observe("router", Ok(BotTick { events: 1, trade: Some((-0.5, 50.0)) }));
+        registry.record_trade("other", 9.0, 100.0);
+
+        let health = registry.health("router").unwrap();
+        assert_eq!(health.trades_24h, 2);
+        assert!((health.revenue_24h - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,654 @@
+/**
+ * CAPITAL ALLOCATOR
+ * Splits capital across strategies from their realised returns and
+ * volatility, rebalances on a schedule and records why every allocation moved
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+const LEDGER_CAPACITY: usize = 5000;
+/// Volatility floor, so a strategy with near-constant returns cannot take
+/// an unbounded risk-parity or Kelly weight
+const MIN_VOLATILITY: f64 = 1e-4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub timestamp: u64,
+    /// Net of fees and gas
+    pub pnl: f64,
+    pub capital: f64,
+}
+
+impl LedgerEntry {
+    pub fn return_rate(&self) -> f64 {
+        if self.capital > 0.0 {
+            self.pnl / self.capital
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReturnStats {
+    pub observations: usize,
+    pub mean_return: f64,
+    pub volatility: f64,
+    pub win_rate: f64,
+    pub total_pnl: f64,
+}
+
+/// One line of the persisted ledger
+#[derive(Serialize, Deserialize)]
+struct LedgerLine {
+    strategy: String,
+    #[serde(flatten)]
+    entry: LedgerEntry,
+}
+
+/// Realised per-trade results for each strategy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StrategyLedger {
+    entries: HashMap<String, VecDeque<LedgerEntry>>,
+}
+
+impl StrategyLedger {
+    /// Replays a JSON-lines ledger written by `append_to`; lines that do not
+    /// parse are skipped
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let mut ledger = Self::default();
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ledger),
+            Err(e) => return Err(e),
+        };
+        for line in std::io::BufReader::new(file).lines() {
+            if let Ok(LedgerLine { strategy, entry }) = serde_json::from_str(&line?) {
+                ledger.record(&strategy, entry.pnl, entry.capital, entry.timestamp);
+            }
+        }
+        Ok(ledger)
+    }
+
+    fn append_to(path: &Path, strategy: &str, entry: &LedgerEntry) -> std::io::Result<()> {
+        let line = serde_json::to_string(&LedgerLine { strategy: strategy.to_string(), entry: entry.clone() })?;
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)
+    }
+
+    pub fn record(&mut self, strategy: &str, pnl: f64, capital: f64, timestamp: u64) {
+        let entries = self.entries.entry(strategy.to_string()).or_default();
+        entries.push_back(LedgerEntry { timestamp, pnl, capital });
+        if entries.len() > LEDGER_CAPACITY {
+            entries.pop_front();
+        }
+    }
+
+    /// Stats over the most recent `lookback` entries
+    pub fn stats(&self, strategy: &str, lookback: usize) -> ReturnStats {
+        let Some(entries) = self.entries.get(strategy) else {
+            return ReturnStats::default();
+        };
+        let recent: Vec<&LedgerEntry> = entries.iter().rev().take(lookback).collect();
+        if recent.is_empty() {
+            return ReturnStats::default();
+        }
+
+        let n = recent.len() as f64;
+        let returns: Vec<f64> = recent.iter().map(|e| e.return_rate()).collect();
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = if recent.len() > 1 {
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+
+        ReturnStats {
+            observations: recent.len(),
+            mean_return: mean,
+            volatility: variance.sqrt(),
+            win_rate: returns.iter().filter(|r| **r > 0.0).count() as f64 / n,
+            total_pnl: recent.iter().map(|e| e.pnl).sum(),
+        }
+    }
+
+    /// Number of entries and their net PnL booked at or after `since`
+    pub fn pnl_since(&self, strategy: &str, since: u64) -> (usize, f64) {
+        let Some(entries) = self.entries.get(strategy) else {
+            return (0, 0.0);
+        };
+        entries
+            .iter()
+            .rev()
+            .take_while(|e| e.timestamp >= since)
+            .fold((0, 0.0), |(count, pnl), e| (count + 1, pnl + e.pnl))
+    }
+
+    pub fn strategies(&self) -> impl Iterator<Item = &String> {
+        self.entries.keys()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum AllocationPolicy {
+    /// Weight inversely to volatility so each strategy carries similar risk
+    RiskParity,
+    /// `fraction` of the Kelly bet mean / variance; losing strategies get nothing
+    FractionalKelly { fraction: f64 },
+    EqualWeight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocatorConfig {
+    pub policy: AllocationPolicy,
+    /// Cap on any one strategy's share of total capital
+    pub max_weight: f64,
+    /// Share of capital never allocated
+    pub cash_buffer: f64,
+    /// Ledger entries considered per strategy
+    pub lookback: usize,
+    /// Below this many entries a strategy's stats are not trusted
+    pub min_observations: usize,
+    /// Kelly weight given to strategies still building a track record
+    pub probation_weight: f64,
+    pub rebalance_interval_secs: u64,
+    /// Changes smaller than this share of total capital are not made
+    pub min_change: f64,
+    /// JSON-lines file every allocation change is appended to
+    pub audit_path: Option<PathBuf>,
+    /// JSON-lines file every ledger entry is appended to and replayed from on start
+    pub ledger_path: Option<PathBuf>,
+}
+
+impl Default for AllocatorConfig {
+    fn default() -> Self {
+        AllocatorConfig {
+            policy: AllocationPolicy::RiskParity,
+            max_weight: 0.4,
+            cash_buffer: 0.05,
+            lookback: 200,
+            min_observations: 20,
+            probation_weight: 0.02,
+            rebalance_interval_secs: 3600,
+            min_change: 0.01,
+            audit_path: None,
+            ledger_path: None,
+        }
+    }
+}
+
+impl AllocatorConfig {
+    /// Defaults overridden by `BLACK_DIAMOND_ALLOCATION_POLICY`
+    /// (`risk_parity`, `kelly`, `equal_weight`), `BLACK_DIAMOND_KELLY_FRACTION`,
+    /// `BLACK_DIAMOND_ALLOCATION_MAX_WEIGHT`, `BLACK_DIAMOND_ALLOCATION_AUDIT_LOG`
+    /// and `BLACK_DIAMOND_ALLOCATION_LEDGER`. The ledger defaults to
+    /// `<audit log>.ledger.jsonl` beside the audit log.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        let kelly_fraction = std::env::var("BLACK_DIAMOND_KELLY_FRACTION")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.25);
+
+        match std::env::var("BLACK_DIAMOND_ALLOCATION_POLICY").as_deref() {
+            Ok("kelly") => config.policy = AllocationPolicy::FractionalKelly { fraction: kelly_fraction },
+            Ok("equal_weight") => config.policy = AllocationPolicy::EqualWeight,
+            Ok("risk_parity") | Err(_) => {}
+            Ok(other) => tracing::warn!("Unknown allocation policy {}, using risk parity", other),
+        }
+        if let Some(max_weight) = std::env::var("BLACK_DIAMOND_ALLOCATION_MAX_WEIGHT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+        {
+            config.max_weight = max_weight.clamp(0.0, 1.0);
+        }
+        config.audit_path = std::env::var("BLACK_DIAMOND_ALLOCATION_AUDIT_LOG").ok().map(PathBuf::from);
+        config.ledger_path = std::env::var("BLACK_DIAMOND_ALLOCATION_LEDGER")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| config.audit_path.as_ref().map(|audit| audit.with_extension("ledger.jsonl")));
+        config
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationChange {
+    pub timestamp: u64,
+    pub strategy: String,
+    pub policy: AllocationPolicy,
+    pub previous_capital: f64,
+    pub new_capital: f64,
+    pub previous_weight: f64,
+    pub new_weight: f64,
+    pub stats: ReturnStats,
+    pub reason: String,
+}
+
+struct Target {
+    weight: f64,
+    reason: String,
+}
+
+pub struct CapitalAllocator {
+    config: AllocatorConfig,
+    ledger: StrategyLedger,
+    allocations: HashMap<String, f64>,
+    weights: HashMap<String, f64>,
+    last_rebalance: Option<u64>,
+    audit_log: VecDeque<AllocationChange>,
+}
+
+impl CapitalAllocator {
+    pub fn new(config: AllocatorConfig) -> Self {
+        let ledger = match &config.ledger_path {
+            Some(path) => StrategyLedger::load(path).unwrap_or_else(|e| {
+                tracing::warn!("Failed to load strategy ledger {}: {}", path.display(), e);
+                StrategyLedger::default()
+            }),
+            None => StrategyLedger::default(),
+        };
+
+        Self {
+            config,
+            ledger,
+            allocations: HashMap::new(),
+            weights: HashMap::new(),
+            last_rebalance: None,
+            audit_log: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> &AllocatorConfig {
+        &self.config
+    }
+
+    pub fn ledger(&self) -> &StrategyLedger {
+        &self.ledger
+    }
+
+    pub fn record(&mut self, strategy: &str, pnl: f64, capital: f64, timestamp: u64) {
+        self.ledger.record(strategy, pnl, capital, timestamp);
+        if let Some(path) = &self.config.ledger_path {
+            let entry = LedgerEntry { timestamp, pnl, capital };
+            if let Err(e) = StrategyLedger::append_to(path, strategy, &entry) {
+                tracing::warn!("Failed to persist ledger entry: {}", e);
+            }
+        }
+    }
+
+    pub fn allocation(&self, strategy: &str) -> f64 {
+        self.allocations.get(strategy).copied().unwrap_or(0.0)
+    }
+
+    /// Share of total capital the last rebalance gave `strategy`
+    pub fn weight(&self, strategy: &str) -> f64 {
+        self.weights.get(strategy).copied().unwrap_or(0.0)
+    }
+
+    pub fn allocations(&self) -> &HashMap<String, f64> {
+        &self.allocations
+    }
+
+    pub fn rebalance_due(&self, now: u64) -> bool {
+        self.last_rebalance
+            .is_none_or(|last| now >= last + self.config.rebalance_interval_secs)
+    }
+
+    /// Reallocates `total_capital` across `strategies` if the schedule says
+    /// so (or `force` is set) and returns the changes made
+    pub fn rebalance(&mut self, strategies: &[String], total_capital: f64, now: u64, force: bool) -> Vec<AllocationChange> {
+        if !force && !self.rebalance_due(now) {
+            return Vec::new();
+        }
+        self.last_rebalance = Some(now);
+
+        let targets = self.target_weights(strategies);
+        let mut changes = Vec::new();
+        let mut names: Vec<&String> = targets.keys().chain(self.allocations.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        for strategy in names {
+            let (weight, reason) = match targets.get(strategy) {
+                Some(target) => (target.weight, target.reason.clone()),
+                None => (0.0, "no longer an allocation candidate".to_string()),
+            };
+            let previous_capital = self.allocation(strategy);
+            let new_capital = total_capital * weight;
+            if (new_capital - previous_capital).abs() < total_capital * self.config.min_change && weight > 0.0 {
+                continue;
+            }
+            if new_capital == previous_capital {
+                continue;
+            }
+
+            changes.push(AllocationChange {
+                timestamp: now,
+                strategy: strategy.clone(),
+                policy: self.config.policy,
+                previous_capital,
+                new_capital,
+                previous_weight: if total_capital > 0.0 { previous_capital / total_capital } else { 0.0 },
+                new_weight: weight,
+                stats: self.ledger.stats(strategy, self.config.lookback),
+                reason,
+            });
+        }
+
+        for change in &changes {
+            if change.new_capital > 0.0 {
+                self.allocations.insert(change.strategy.clone(), change.new_capital);
+                self.weights.insert(change.strategy.clone(), change.new_weight);
+            } else {
+                self.allocations.remove(&change.strategy);
+                self.weights.remove(&change.strategy);
+            }
+            println!(
+                "⚖️ Allocation {}: {:.2} -> {:.2} ({})",
+                change.strategy, change.previous_capital, change.new_capital, change.reason
+            );
+            self.audit(change.clone());
+        }
+        changes
+    }
+
+    pub fn recent_changes(&self, limit: usize) -> Vec<AllocationChange> {
+        self.audit_log.iter().rev().take(limit).cloned().collect()
+    }
+
+    fn target_weights(&self, strategies: &[String]) -> HashMap<String, Target> {
+        let lookback = self.config.lookback;
+        let stats: Vec<(String, ReturnStats)> = strategies
+            .iter()
+            .map(|s| (s.clone(), self.ledger.stats(s, lookback)))
+            .collect();
+        let seasoned = |s: &ReturnStats| s.observations >= self.config.min_observations;
+        let invested = (1.0 - self.config.cash_buffer).clamp(0.0, 1.0);
+
+        let mut raw: Vec<(String, f64, String)> = match self.config.policy {
+            AllocationPolicy::EqualWeight => stats
+                .iter()
+                .map(|(name, _)| (name.clone(), 1.0, "equal weight".to_string()))
+                .collect(),
+            AllocationPolicy::RiskParity => {
+                let mut vols: Vec<f64> = stats
+                    .iter()
+                    .filter(|(_, s)| seasoned(s))
+                    .map(|(_, s)| s.volatility.max(MIN_VOLATILITY))
+                    .collect();
+                vols.sort_by(|a, b| a.total_cmp(b));
+                let median = vols.get(vols.len() / 2).copied().unwrap_or(1.0);
+
+                stats
+                    .iter()
+                    .map(|(name, s)| {
+                        if seasoned(s) {
+                            let vol = s.volatility.max(MIN_VOLATILITY);
+                            let reason = format!(
+                                "risk parity: volatility {:.2}% over {} trades",
+                                vol * 100.0,
+                                s.observations
+                            );
+                            (name.clone(), 1.0 / vol, reason)
+                        } else {
+                            let reason = format!(
+                                "risk parity: {} of {} trades needed, using peer median volatility {:.2}%",
+                                s.observations,
+                                self.config.min_observations,
+                                median * 100.0
+                            );
+                            (name.clone(), 1.0 / median, reason)
+                        }
+                    })
+                    .collect()
+            }
+            AllocationPolicy::FractionalKelly { fraction } => stats
+                .iter()
+                .map(|(name, s)| {
+                    if !seasoned(s) {
+                        let reason = format!(
+                            "kelly: probation weight, {} of {} trades needed",
+                            s.observations, self.config.min_observations
+                        );
+                        return (name.clone(), self.config.probation_weight, reason);
+                    }
+                    let variance = s.volatility.max(MIN_VOLATILITY).powi(2);
+                    let kelly = (s.mean_return / variance).max(0.0);
+                    let reason = format!(
+                        "kelly x{:.2}: mean {:.3}%, volatility {:.2}%, full kelly {:.2}",
+                        fraction,
+                        s.mean_return * 100.0,
+                        s.volatility * 100.0,
+                        kelly
+                    );
+                    (name.clone(), kelly * fraction, reason)
+                })
+                .collect(),
+        };
+
+        // Kelly weights are absolute bet sizes and only scale down; the other
+        // policies are relative and are spread over the invested share
+        let total: f64 = raw.iter().map(|(_, w, _)| w).sum();
+        let scale = match self.config.policy {
+            AllocationPolicy::FractionalKelly { .. } if total <= invested => 1.0,
+            _ if total > 0.0 => invested / total,
+            _ => 0.0,
+        };
+        for (_, weight, _) in raw.iter_mut() {
+            *weight *= scale;
+        }
+        let relative = !matches!(self.config.policy, AllocationPolicy::FractionalKelly { .. });
+        let capped = cap_weights(&mut raw, self.config.max_weight, if relative { invested } else { 0.0 });
+
+        raw.into_iter()
+            .map(|(name, weight, mut reason)| {
+                if capped.contains(&name) {
+                    reason.push_str(&format!(", capped at {:.0}%", self.config.max_weight * 100.0));
+                }
+                reason = format!("{} -> {:.1}%", reason, weight * 100.0);
+                (name, Target { weight, reason })
+            })
+            .collect()
+    }
+
+    fn audit(&mut self, change: AllocationChange) {
+        if let Some(path) = &self.config.audit_path {
+            let written = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| {
+                    let line = serde_json::to_string(&change).unwrap_or_default();
+                    writeln!(file, "{}", line)
+                });
+            if let Err(e) = written {
+                tracing::warn!("Failed to write allocation audit entry: {}", e);
+            }
+        }
+
+        self.audit_log.push_back(change);
+        if self.audit_log.len() > LEDGER_CAPACITY {
+            self.audit_log.pop_front();
+        }
+    }
+}
+
+/// Caps every weight at `max_weight`. With a positive `budget` the excess is
+/// handed to the uncapped weights in proportion until nothing is left or
+/// everything is capped. Returns the names that hit the cap.
+fn cap_weights(weights: &mut [(String, f64, String)], max_weight: f64, budget: f64) -> Vec<String> {
+    let mut capped: Vec<String> = Vec::new();
+    loop {
+        let mut excess = 0.0;
+        for (name, weight, _) in weights.iter_mut() {
+            if *weight > max_weight {
+                excess += *weight - max_weight;
+                *weight = max_weight;
+                if !capped.contains(name) {
+                    capped.push(name.clone());
+                }
+            }
+        }
+        if excess <= f64::EPSILON || budget <= 0.0 {
+            return capped;
+        }
+
+        let free: f64 = weights
+            .iter()
+            .filter(|(name, _, _)| !capped.contains(name))
+            .map(|(_, w, _)| w)
+            .sum();
+        if free <= 0.0 {
+            return capped;
+        }
+        for (name, weight, _) in weights.iter_mut() {
+            if !capped.contains(name) {
+                *weight += excess * *weight / free;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Alternating returns of `mean ± spread` on unit capital
+    fn record_returns(allocator: &mut CapitalAllocator, strategy: &str, mean: f64, spread: f64, trades: usize) {
+        for i in 0..trades {
+            let pnl = if i % 2 == 0 { mean + spread } else { mean - spread };
+            allocator.record(strategy, pnl, 1.0, i as u64);
+        }
+    }
+
+    fn allocator(policy: AllocationPolicy, max_weight: f64) -> CapitalAllocator {
+        CapitalAllocator::new(AllocatorConfig {
+            policy,
+            max_weight,
+            min_change: 0.0,
+            ..AllocatorConfig::default()
+        })
+    }
+
+    fn weights(allocator: &CapitalAllocator, strategies: &[&str]) -> HashMap<String, f64> {
+        let names: Vec<String> = strategies.iter().map(|s| s.to_string()).collect();
+        allocator.target_weights(&names).into_iter().map(|(name, target)| (name, target.weight)).collect()
+    }
+
+    fn named(weights: &[(&str, f64)]) -> Vec<(String, f64, String)> {
+        weights.iter().map(|(name, w)| (name.to_string(), *w, String::new())).collect()
+    }
+
+    #[test]
+    fn capping_hands_the_excess_to_uncapped_weights() {
+        let mut weights = named(&[("a", 0.6), ("b", 0.3), ("c", 0.1)]);
+        let capped = cap_weights(&mut weights, 0.5, 1.0);
+
+        assert_eq!(capped, vec!["a".to_string()]);
+        assert!((weights[0].1 - 0.5).abs() < 1e-12);
+        assert!((weights[1].1 - 0.375).abs() < 1e-12);
+        assert!((weights[2].1 - 0.125).abs() < 1e-12);
+    }
+
+    #[test]
+    fn capping_repeats_until_every_weight_fits() {
+        let mut weights = named(&[("a", 0.7), ("b", 0.25), ("c", 0.05)]);
+        let capped = cap_weights(&mut weights, 0.4, 1.0);
+
+        assert_eq!(capped.len(), 2);
+        let total: f64 = weights.iter().map(|(_, w, _)| w).sum();
+        assert!((total - 1.0).abs() < 1e-12);
+        assert!(weights.iter().all(|(_, w, _)| *w <= 0.4 + 1e-12));
+    }
+
+    #[test]
+    fn capping_without_a_budget_only_trims() {
+        let mut weights = named(&[("a", 0.6), ("b", 0.1)]);
+        cap_weights(&mut weights, 0.4, 0.0);
+        assert_eq!((weights[0].1, weights[1].1), (0.4, 0.1));
+    }
+
+    #[test]
+    fn risk_parity_weights_inversely_to_volatility() {
+        let mut allocator = allocator(AllocationPolicy::RiskParity, 1.0);
+        record_returns(&mut allocator, "calm", 0.01, 0.01, 40);
+        record_returns(&mut allocator, "wild", 0.01, 0.02, 40);
+        // Too few trades to trust; sized at the peer median volatility
+        record_returns(&mut allocator, "new", 0.01, 0.05, 4);
+
+        let w = weights(&allocator, &["calm", "wild", "new"]);
+        assert!((w["calm"] / w["wild"] - 2.0).abs() < 1e-9);
+        let median = allocator.ledger().stats("wild", 200).volatility;
+        let calm = allocator.ledger().stats("calm", 200).volatility;
+        assert!((w["new"] / w["calm"] - calm / median).abs() < 1e-9);
+        assert!((w.values().sum::<f64>() - 0.95).abs() < 1e-9);
+    }
+
+    #[test]
+    fn kelly_sizes_by_edge_and_skips_losers() {
+        let mut allocator = allocator(AllocationPolicy::FractionalKelly { fraction: 0.25 }, 1.0);
+        record_returns(&mut allocator, "edge", 0.001, 0.1, 40);
+        record_returns(&mut allocator, "loser", -0.01, 0.1, 40);
+        record_returns(&mut allocator, "new", 0.05, 0.0, 3);
+
+        let w = weights(&allocator, &["edge", "loser", "new"]);
+        let stats = allocator.ledger().stats("edge", 200);
+        let expected = 0.25 * stats.mean_return / stats.volatility.powi(2);
+        // Kelly weights are absolute and are not scaled up to the invested share
+        assert!((w["edge"] - expected).abs() < 1e-12);
+        assert_eq!(w["loser"], 0.0);
+        assert_eq!(w["new"], AllocatorConfig::default().probation_weight);
+    }
+
+    #[test]
+    fn kelly_scales_down_when_over_invested() {
+        let mut allocator = allocator(AllocationPolicy::FractionalKelly { fraction: 1.0 }, 1.0);
+        record_returns(&mut allocator, "a", 0.05, 0.1, 40);
+        record_returns(&mut allocator, "b", 0.05, 0.1, 40);
+
+        let w = weights(&allocator, &["a", "b"]);
+        assert!((w["a"] + w["b"] - 0.95).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rebalance_records_why_each_allocation_moved() {
+        let mut allocator = allocator(AllocationPolicy::EqualWeight, 1.0);
+        let strategies = vec!["a".to_string(), "b".to_string()];
+
+        let changes = allocator.rebalance(&strategies, 1000.0, 0, false);
+        assert_eq!(changes.len(), 2);
+        assert!((allocator.allocation("a") - 475.0).abs() < 1e-9);
+        // Not due again until the interval passes
+        assert!(allocator.rebalance(&strategies[..1], 1000.0, 10, false).is_empty());
+
+        let changes = allocator.rebalance(&strategies[..1], 1000.0, 10, true);
+        let dropped = changes.iter().find(|c| c.strategy == "b").unwrap();
+        assert_eq!(dropped.new_capital, 0.0);
+        assert_eq!(dropped.reason, "no longer an allocation candidate");
+        assert_eq!(allocator.allocation("b"), 0.0);
+        assert!((allocator.allocation("a") - 950.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn the_ledger_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("strategy_ledger_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = AllocatorConfig { ledger_path: Some(path.clone()), ..AllocatorConfig::default() };
+
+        let mut allocator = CapitalAllocator::new(config.clone());
+        record_returns(&mut allocator, "a", 0.01, 0.02, 30);
+        allocator.record("b", -5.0, 100.0, 99);
+        let before = allocator.ledger().stats("a", 200);
+
+        let restarted = CapitalAllocator::new(config);
+        let after = restarted.ledger().stats("a", 200);
+        assert_eq!((after.observations, after.mean_return, after.volatility), (before.observations, before.mean_return, before.volatility));
+        assert_eq!(restarted.ledger().pnl_since("b", 0), (1, -5.0));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
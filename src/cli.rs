@@ -3,9 +3,16 @@
  * Command-line interface for managing the Black Diamond Rust ecosystem
  */
 
+use black_diamond_client::{
+    ArbitrageRequest, BacktestRequest, BlackDiamondClient, StrategyConfigRequest, StrategyEntry, StrategyReport,
+    StrategyToggleRequest,
+};
+use black_diamond_rust_ecosystem::program_deploy::{Cluster, ProgramDeployer};
 use clap::{Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use std::path::{Path, PathBuf};
 use serde_json;
-use std::collections::HashMap;
 use tokio;
 
 #[derive(Parser)]
@@ -13,6 +20,14 @@ use tokio;
 #[command(about = "Black Diamond Rust Ecosystem CLI")]
 #[command(version = "2.0.0")]
 struct Cli {
+    /// Base URL of the Black Diamond server
+    #[arg(long, global = true, default_value = "http://localhost:3000")]
+    server: String,
+    
+    /// API key sent to the server (falls back to BLACK_DIAMOND_API_KEY)
+    #[arg(long, global = true, env = "BLACK_DIAMOND_API_KEY")]
+    api_key: Option<String>,
+    
     #[command(subcommand)]
     command: Commands,
 }
@@ -64,32 +79,132 @@ enum Commands {
     QuantumEntangle,
     /// Activate quantum consciousness
     QuantumConsciousness,
+    /// Enable, configure, backtest and paper-trade strategy plugins
+    Strategy {
+        #[command(subcommand)]
+        action: StrategyAction,
+    },
+    /// Build, deploy, upgrade and verify workspace programs
+    Program {
+        /// localnet, devnet, testnet, mainnet-beta or an RPC URL
+        #[arg(long, default_value = "localnet")]
+        cluster: String,
+        /// Fee payer and upgrade authority keypair
+        #[arg(long, env = "BLACK_DIAMOND_KEYPAIR")]
+        keypair: Option<PathBuf>,
+        #[command(subcommand)]
+        action: ProgramAction,
+    },
 }
 
-async fn execute_api_call(endpoint: &str, method: &str, body: Option<serde_json::Value>) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let url = format!("http://localhost:3000{}", endpoint);
-    
-    let response = match method {
-        "GET" => client.get(&url).send().await?,
-        "POST" => {
-            let mut req = client.post(&url);
-            if let Some(body) = body {
-                req = req.json(&body);
-            }
-            req.send().await?
-        },
-        _ => return Err("Unsupported HTTP method".into()),
-    };
-    
-    let json: serde_json::Value = response.json().await?;
-    Ok(json)
+#[derive(Subcommand)]
+enum ProgramAction {
+    /// Compile programs/<name> to target/deploy/<name>.so
+    Build { name: String },
+    /// First deployment at the address of --program-keypair
+    Deploy {
+        name: String,
+        #[arg(long)]
+        program_keypair: PathBuf,
+        /// Program account size as a multiple of the current build
+        #[arg(long, default_value = "2")]
+        max_len_factor: usize,
+    },
+    /// Write the local build to a buffer for a later upgrade
+    StageUpgrade { name: String },
+    /// Upgrade from a staged buffer, or stage and upgrade in one go
+    Upgrade {
+        name: String,
+        #[arg(long)]
+        buffer: Option<String>,
+    },
+    /// Transfer the upgrade authority, or make the program immutable
+    SetAuthority {
+        name: String,
+        /// Keypair of the new authority, which must sign
+        #[arg(long, conflicts_with = "immutable")]
+        new_authority: Option<PathBuf>,
+        #[arg(long)]
+        immutable: bool,
+    },
+    /// Compare on-chain bytecode and authority with the manifest
+    Verify { name: String },
+}
+
+#[derive(Subcommand)]
+enum StrategyAction {
+    /// Registered strategies with their parameters
+    List,
+    /// Start paper-trading a strategy on live pool updates
+    Enable { id: String },
+    /// Stop paper-trading a strategy
+    Disable { id: String },
+    /// Change parameters, restarting the strategy if it is enabled
+    Configure {
+        id: String,
+        /// name=value, with the value parsed as JSON where it can be
+        #[arg(long = "set", required = true)]
+        parameters: Vec<String>,
+    },
+    /// Run a strategy over recorded market events (a JSON array or JSON lines)
+    Backtest { id: String, events: PathBuf },
+    /// Results of every strategy paper-trading now
+    Paper,
+}
+
+fn print_strategy(entry: &StrategyEntry) {
+    let state = if entry.enabled { "🟢 enabled" } else { "⚪ disabled" };
+    println!("{} ({}) {}", entry.id, entry.name, state);
+    println!("   Origin: {}", entry.origin);
+    println!("   Parameters: {}", serde_json::Value::Object(entry.parameters.clone()));
+}
+
+fn print_report(report: &StrategyReport) {
+    println!("{}", report.strategy_id);
+    println!("   Events: {}, fills: {}, rejected: {}", report.events, report.fills, report.rejected_orders);
+    println!("   Equity: {:.4} (pnl {:.4}, realised {:.4})", report.equity, report.pnl, report.realized_pnl);
+    println!("   Fees: {:.4}, volume: {:.4}", report.fees_paid, report.volume);
+    if !report.unpriced_assets.is_empty() {
+        println!("   Unpriced: {}", report.unpriced_assets.join(", "));
+    }
+}
+
+fn parse_parameters(pairs: &[String]) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn std::error::Error>> {
+    let mut parameters = serde_json::Map::new();
+    for pair in pairs {
+        let (name, value) = pair.split_once('=').ok_or_else(|| format!("expected name=value, got {}", pair))?;
+        let value = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+        parameters.insert(name.to_string(), value);
+    }
+    Ok(parameters)
+}
+
+fn read_events(path: &Path) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("reading {}: {}", path.display(), e))?;
+    if contents.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(&contents)?);
+    }
+    let mut events = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        events.push(serde_json::from_str(line)?);
+    }
+    Ok(events)
+}
+
+fn load_keypair(path: Option<&PathBuf>) -> Result<Keypair, Box<dyn std::error::Error>> {
+    let path = path.ok_or("--keypair (or BLACK_DIAMOND_KEYPAIR) is required")?;
+    read_keypair_file(path).map_err(|e| format!("reading {}: {}", path.display(), e).into())
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
     
+    let mut client = BlackDiamondClient::new(cli.server.clone());
+    if let Some(api_key) = cli.api_key.clone() {
+        client = client.with_api_key(api_key);
+    }
+    
     match cli.command {
         Commands::Start { port, host } => {
             println!("🦀 Starting Black Diamond server on {}:{}", host, port);
@@ -100,15 +215,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Arbitrage { token_a, token_b, amount } => {
             println!("🦀 Executing arbitrage: {} -> {} ({} SOL)", token_a, token_b, amount);
             
-            let body = serde_json::json!({
-                "token_a": token_a,
-                "token_b": token_b,
-                "amount": amount
-            });
+            let request = ArbitrageRequest { token_a, token_b, amount };
             
-            match execute_api_call("/api/black-diamond/execute-arbitrage", "POST", Some(body)).await {
-                Ok(response) => {
-                    println!("✅ Arbitrage result: {}", serde_json::to_string_pretty(&response)?);
+            match client.execute_arbitrage(&request).await {
+                Ok(result) => {
+                    println!("✅ Arbitrage result: {}", result.message);
+                    println!("  {} -> {} ({} SOL)", result.token_a, result.token_b, result.amount);
                 },
                 Err(e) => {
                     println!("❌ Arbitrage failed: {}", e);
@@ -131,24 +243,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Status => {
             println!("🦀 Getting system status...");
             
-            match execute_api_call("/api/status", "GET", None).await {
-                Ok(response) => {
+            match client.system_overview().await {
+                Ok(overview) => {
                     println!("📊 System Status:");
-                    if let Some(data) = response.get("data") {
-                        println!("  Status: {}", data.get("status").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Active Systems: {}/{}", 
-                            data.get("active_systems").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))),
-                            data.get("total_systems").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0)))
-                        );
-                        println!("  Performance: {}", data.get("performance_boost").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Total SOL: {}", data.get("total_sol").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                        println!("  Daily Profit: {}", data.get("daily_profit").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                        println!("  Success Rate: {}%", data.get("success_rate").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                    }
+                    println!("  Status: {}", overview.status);
+                    println!("  Active Systems: {}/{}", overview.active_systems, overview.total_systems);
+                    println!("  Performance: {}", overview.performance_boost);
+                    println!("  Total SOL: {}", overview.total_sol);
+                    println!("  Daily Profit: {}", overview.daily_profit);
+                    println!("  Success Rate: {}%", overview.success_rate);
                 },
                 Err(e) => {
                     println!("❌ Failed to get status: {}", e);
-                    println!("Make sure the Black Diamond server is running on localhost:3000");
+                    println!("Make sure the Black Diamond server is running on {}", client.base_url());
                 }
             }
         },
@@ -156,19 +263,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Strategies => {
             println!("🚀 Getting zero capital strategies...");
             
-            match execute_api_call("/api/zero-capital/strategies", "GET", None).await {
-                Ok(response) => {
+            match client.zero_capital_strategies().await {
+                Ok(strategies) => {
                     println!("💎 Available Strategies:");
-                    if let Some(data) = response.get("data") {
-                        if let Some(strategies) = data.as_array() {
-                            for (i, strategy) in strategies.iter().enumerate() {
-                                println!("{}. {}", i + 1, strategy.get("name").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                                println!("   Multiplier: {}x", strategy.get("expectedMultiplier").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                                println!("   Risk Score: {}", strategy.get("riskScore").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                                println!("   Scaling Potential: {} SOL/hour", strategy.get("scalingPotential").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                                println!();
-                            }
-                        }
+                    for (i, strategy) in strategies.iter().enumerate() {
+                        println!("{}. {}", i + 1, strategy.name);
+                        println!("   Multiplier: {}x", strategy.expected_multiplier);
+                        println!("   Risk Score: {}", strategy.risk_score);
+                        println!("   Scaling Potential: {} SOL/hour", strategy.scaling_potential);
+                        println!();
                     }
                 },
                 Err(e) => {
@@ -180,10 +283,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Activate => {
             println!("🦀 Activating all Rust systems...");
             
-            match execute_api_call("/api/systems/activate-all", "POST", None).await {
-                Ok(response) => {
+            match client.activate_all_systems().await {
+                Ok(activation) => {
                     println!("✅ System activation result:");
-                    println!("{}", serde_json::to_string_pretty(&response)?);
+                    println!("  {}", activation.message);
+                    println!("  Systems Activated: {}", activation.systems_activated);
+                    println!("  Performance Mode: {}", activation.performance_mode);
                 },
                 Err(e) => {
                     println!("❌ Failed to activate systems: {}", e);
@@ -194,20 +299,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Wallets => {
             println!("💰 Getting wallet metrics...");
             
-            match execute_api_call("/api/metrics/wallets", "GET", None).await {
-                Ok(response) => {
+            match client.wallet_metrics().await {
+                Ok(wallets) => {
                     println!("📊 Wallet Metrics:");
-                    if let Some(data) = response.get("data") {
-                        if let Some(wallets) = data.as_array() {
-                            for wallet in wallets {
-                                println!("  Wallet: {}", wallet.get("name").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                                println!("    SOL Balance: {}", wallet.get("solBalance").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                                println!("    Total Value: ${}", wallet.get("totalValueUSD").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                                println!("    Daily P&L: ${}", wallet.get("dailyProfitLoss").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                                println!("    Success Rate: {}%", wallet.get("successRate").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                                println!();
-                            }
-                        }
+                    for wallet in wallets {
+                        println!("  Wallet: {}", wallet.name);
+                        println!("    SOL Balance: {}", wallet.sol_balance);
+                        println!("    Total Value: ${}", wallet.total_value_usd);
+                        println!("    Daily P&L: ${}", wallet.daily_profit_loss);
+                        println!("    Success Rate: {}%", wallet.success_rate);
+                        println!();
                     }
                 },
                 Err(e) => {
@@ -219,13 +320,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::QuantumBoost => {
             println!("⚡ Executing quantum speed boost...");
             
-            match execute_api_call("/api/innovations/quantum-boost", "POST", None).await {
-                Ok(response) => {
+            match client.quantum_boost().await {
+                Ok(boost) => {
                     println!("✅ Quantum boost result:");
-                    if let Some(data) = response.get("data") {
-                        println!("  Speed Increase: {}%", data.get("speedIncrease").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                        println!("  Gas Reduction: {}%", data.get("gasReduction").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                    }
+                    println!("  Speed Increase: {}%", boost.speed_increase);
+                    println!("  Gas Reduction: {}%", boost.gas_reduction);
                 },
                 Err(e) => {
                     println!("❌ Failed to execute quantum boost: {}", e);
@@ -243,17 +342,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::QuantumStatus => {
             println!("⚡ Getting quantum system status...");
             
-            match execute_api_call("/api/quantum/status", "GET", None).await {
-                Ok(response) => {
+            match client.quantum_status().await {
+                Ok(status) => {
                     println!("🔬 Quantum System Status:");
-                    if let Some(data) = response.get("data") {
-                        println!("  Quantum Systems: {}", data.get("quantum_systems_active").unwrap_or(&serde_json::Value::Bool(false)));
-                        println!("  Speed Enhancement: {}", data.get("speed_enhancement").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Accuracy Enhancement: {}", data.get("accuracy_enhancement").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Dominance Level: {}", data.get("dominance_level").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Parallel Universes: {}", data.get("parallel_universes").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Quantum Advantage: {}x", data.get("quantum_advantage").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                    }
+                    println!("  Quantum Systems: {}", status.quantum_systems_active);
+                    println!("  Speed Enhancement: {}", status.speed_enhancement);
+                    println!("  Accuracy Enhancement: {}", status.accuracy_enhancement);
+                    println!("  Dominance Level: {}", status.dominance_level);
+                    println!("  Parallel Universes: {}", status.parallel_universes);
+                    println!("  Quantum Advantage: {}x", status.quantum_advantage);
                 },
                 Err(e) => {
                     println!("❌ Failed to get quantum status: {}", e);
@@ -264,14 +361,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::QuantumSuperposition => {
             println!("🌌 Executing quantum superposition trading...");
             
-            match execute_api_call("/api/quantum/superposition", "POST", None).await {
-                Ok(response) => {
+            match client.quantum_superposition().await {
+                Ok(result) => {
                     println!("✅ Quantum superposition result:");
-                    if let Some(data) = response.get("data") {
-                        println!("  Superposition: {}", data.get("quantum_superposition").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Parallel Executions: {}", data.get("parallel_executions").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Enhancement Level: {}", data.get("enhancement_level").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                    }
+                    println!("  Superposition: {}", result.quantum_superposition);
+                    println!("  Parallel Executions: {}", result.parallel_executions);
+                    println!("  Enhancement Level: {}", result.enhancement_level);
                 },
                 Err(e) => {
                     println!("❌ Failed to execute quantum superposition: {}", e);
@@ -282,15 +377,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::QuantumTunnel => {
             println!("🕳️ Tunneling through market barriers using quantum mechanics...");
             
-            match execute_api_call("/api/quantum/tunnel-barriers", "POST", None).await {
-                Ok(response) => {
+            match client.quantum_tunnel_barriers().await {
+                Ok(result) => {
                     println!("✅ Quantum tunneling result:");
-                    if let Some(data) = response.get("data") {
-                        println!("  Tunneling Status: {}", data.get("quantum_tunneling").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Tunneling Probability: {}%", data.get("tunneling_probability").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                        println!("  Speed Increase: {}", data.get("speed_increase").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Cost Reduction: {}", data.get("cost_reduction").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                    }
+                    println!("  Tunneling Status: {}", result.quantum_tunneling);
+                    println!("  Tunneling Probability: {}%", result.tunneling_probability);
+                    println!("  Speed Increase: {}", result.speed_increase);
+                    println!("  Cost Reduction: {}", result.cost_reduction);
                 },
                 Err(e) => {
                     println!("❌ Failed to execute quantum tunneling: {}", e);
@@ -301,15 +394,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::QuantumEntangle => {
             println!("🔗 Establishing quantum entanglement for instantaneous execution...");
             
-            match execute_api_call("/api/quantum/entanglement", "POST", None).await {
-                Ok(response) => {
+            match client.quantum_entanglement().await {
+                Ok(result) => {
                     println!("✅ Quantum entanglement result:");
-                    if let Some(data) = response.get("data") {
-                        println!("  Entanglement Status: {}", data.get("quantum_entanglement").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Instantaneous Communication: {}", data.get("instantaneous_communication").unwrap_or(&serde_json::Value::Bool(false)));
-                        println!("  Distance Limitation: {}", data.get("distance_limitation").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Execution Speed: {}", data.get("execution_speed").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                    }
+                    println!("  Entanglement Status: {}", result.quantum_entanglement);
+                    println!("  Instantaneous Communication: {}", result.instantaneous_communication);
+                    println!("  Distance Limitation: {}", result.distance_limitation);
+                    println!("  Execution Speed: {}", result.execution_speed);
                 },
                 Err(e) => {
                     println!("❌ Failed to establish quantum entanglement: {}", e);
@@ -320,23 +411,113 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::QuantumConsciousness => {
             println!("🧠 Activating quantum consciousness for maximum dominance...");
             
-            match execute_api_call("/api/quantum/consciousness", "POST", None).await {
-                Ok(response) => {
+            match client.quantum_consciousness().await {
+                Ok(result) => {
                     println!("✅ Quantum consciousness result:");
-                    if let Some(data) = response.get("data") {
-                        println!("  Consciousness Status: {}", data.get("quantum_consciousness").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Consciousness Level: {}%", data.get("consciousness_level").unwrap_or(&serde_json::Value::Number(serde_json::Number::from(0))));
-                        println!("  Awareness Amplification: {}", data.get("awareness_amplification").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Decision Making: {}", data.get("decision_making").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                        println!("  Trading Dominance: {}", data.get("trading_dominance").unwrap_or(&serde_json::Value::String("Unknown".to_string())));
-                    }
+                    println!("  Consciousness Status: {}", result.quantum_consciousness);
+                    println!("  Consciousness Level: {}%", result.consciousness_level);
+                    println!("  Awareness Amplification: {}", result.awareness_amplification);
+                    println!("  Decision Making: {}", result.decision_making);
+                    println!("  Trading Dominance: {}", result.trading_dominance);
                 },
                 Err(e) => {
                     println!("❌ Failed to activate quantum consciousness: {}", e);
                 }
             }
         },
+
+        Commands::Strategy { action } => {
+            match action {
+                StrategyAction::List => {
+                    let strategies = client.strategies().await?;
+                    println!("🧩 {} registered strategies:", strategies.len());
+                    for entry in &strategies {
+                        print_strategy(entry);
+                    }
+                },
+                StrategyAction::Enable { id } => {
+                    let entry = client.set_strategy_enabled(&StrategyToggleRequest { id, enabled: true }).await?;
+                    print_strategy(&entry);
+                },
+                StrategyAction::Disable { id } => {
+                    let entry = client.set_strategy_enabled(&StrategyToggleRequest { id, enabled: false }).await?;
+                    print_strategy(&entry);
+                },
+                StrategyAction::Configure { id, parameters } => {
+                    let parameters = parse_parameters(&parameters)?;
+                    let entry = client.configure_strategy(&StrategyConfigRequest { id, parameters }).await?;
+                    println!("✅ Configured");
+                    print_strategy(&entry);
+                },
+                StrategyAction::Backtest { id, events } => {
+                    let events = read_events(&events)?;
+                    println!("📈 Backtesting {} over {} events...", id, events.len());
+                    let report = client.backtest_strategy(&BacktestRequest { id, events }).await?;
+                    print_report(&report);
+                },
+                StrategyAction::Paper => {
+                    let reports = client.paper_trading().await?;
+                    println!("📝 {} strategies paper-trading:", reports.len());
+                    for report in &reports {
+                        print_report(report);
+                    }
+                },
+            }
+        },
+
+        Commands::Program { cluster, keypair, action } => {
+            let deployer = ProgramDeployer::from_env(Cluster::parse(&cluster), std::env::current_dir()?);
+            let keypair = keypair.as_ref();
+
+            match action {
+                ProgramAction::Build { name } => {
+                    let path = deployer.build(&name).await?;
+                    println!("✅ Built {}", path.display());
+                },
+                ProgramAction::Deploy { name, program_keypair, max_len_factor } => {
+                    let payer = load_keypair(keypair)?;
+                    let program = load_keypair(Some(&program_keypair))?;
+                    let record = deployer.deploy(&name, &payer, &program, &payer, max_len_factor).await?;
+                    println!("✅ {} deployed at {}", name, record.program_id);
+                    println!("  SHA-256: {}", record.bytecode_sha256);
+                },
+                ProgramAction::StageUpgrade { name } => {
+                    let payer = load_keypair(keypair)?;
+                    let buffer = deployer.stage_upgrade(&name, &payer, &payer).await?;
+                    println!("✅ Upgrade with: black_diamond_cli program upgrade {} --buffer {}", name, buffer);
+                },
+                ProgramAction::Upgrade { name, buffer } => {
+                    let payer = load_keypair(keypair)?;
+                    let record = match buffer {
+                        Some(buffer) => {
+                            let buffer: Pubkey = buffer.parse()?;
+                            deployer.upgrade_from_buffer(&name, &buffer, &payer, &payer).await?
+                        },
+                        None => deployer.upgrade(&name, &payer, &payer).await?,
+                    };
+                    println!("✅ {} upgraded, SHA-256: {}", name, record.bytecode_sha256);
+                },
+                ProgramAction::SetAuthority { name, new_authority, immutable } => {
+                    let payer = load_keypair(keypair)?;
+                    let new_authority = match new_authority {
+                        Some(path) => Some(load_keypair(Some(&path))?),
+                        None if immutable => None,
+                        None => return Err("pass --new-authority or --immutable".into()),
+                    };
+                    deployer.set_upgrade_authority(&name, &payer, &payer, new_authority.as_ref()).await?;
+                },
+                ProgramAction::Verify { name } => {
+                    let report = deployer.verify(&name).await?;
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                    if report.is_verified() {
+                        println!("✅ {} on {} matches the manifest", name, report.cluster);
+                    } else {
+                        println!("❌ {} on {} does not match the manifest", name, report.cluster);
+                    }
+                },
+            }
+        },
     }
     
     Ok(())
-}
\ No newline at end of file
+}
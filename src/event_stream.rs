@@ -0,0 +1,181 @@
+/**
+ * EVENT STREAM HUB
+ * Sequenced broadcast of trade, alert, wallet, opportunity and status events
+ * with a bounded replay buffer for resume-from-sequence
+ */
+
+use black_diamond_client::events::{SequencedEvent, StreamEvent, StreamTopic};
+use parking_lot::Mutex;
+use std::collections::{HashSet, VecDeque};
+use tokio::sync::broadcast;
+
+const DEFAULT_REPLAY_CAPACITY: usize = 4096;
+const BROADCAST_CAPACITY: usize = 1024;
+
+pub struct EventHub {
+    sender: broadcast::Sender<SequencedEvent>,
+    // The sequence counter lives under the same lock as the buffer so events
+    // enter the buffer and the channel in sequence order
+    state: Mutex<HubState>,
+    replay_capacity: usize,
+}
+
+struct HubState {
+    next_seq: u64,
+    history: VecDeque<SequencedEvent>,
+}
+
+pub struct Replay {
+    pub events: Vec<SequencedEvent>,
+    pub gap: bool,
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_REPLAY_CAPACITY)
+    }
+
+    pub fn with_capacity(replay_capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(BROADCAST_CAPACITY);
+        Self {
+            sender,
+            state: Mutex::new(HubState {
+                next_seq: 1,
+                history: VecDeque::with_capacity(replay_capacity),
+            }),
+            replay_capacity,
+        }
+    }
+
+    pub fn publish(&self, event: StreamEvent) -> u64 {
+        let mut state = self.state.lock();
+        let sequenced = SequencedEvent {
+            seq: state.next_seq,
+            timestamp: chrono::Utc::now(),
+            topic: event.topic(),
+            event,
+        };
+        state.next_seq += 1;
+
+        state.history.push_back(sequenced.clone());
+        if state.history.len() > self.replay_capacity {
+            state.history.pop_front();
+        }
+
+        // No receivers is fine - the event is still buffered for later resumes
+        let _ = self.sender.send(sequenced.clone());
+        sequenced.seq
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SequencedEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn latest_seq(&self) -> u64 {
+        self.state.lock().next_seq - 1
+    }
+
+    /// Buffered events after `after_seq` on the given topics. `gap` is set when
+    /// events after `after_seq` have already been evicted from the buffer.
+    pub fn replay(&self, after_seq: u64, topics: &HashSet<StreamTopic>) -> Replay {
+        let state = self.state.lock();
+        let oldest = state.history.front().map(|e| e.seq).unwrap_or(state.next_seq);
+
+        Replay {
+            events: state
+                .history
+                .iter()
+                .filter(|e| e.seq > after_seq && topics.contains(&e.topic))
+                .cloned()
+                .collect(),
+            gap: after_seq + 1 < oldest,
+        }
+    }
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> StreamEvent {
+        StreamEvent::SystemStatus { changes: serde_json::Map::new() }
+    }
+
+    fn opportunity(identifier: &str) -> StreamEvent {
+        StreamEvent::OpportunityDetected {
+            source: "test".to_string(),
+            identifier: identifier.to_string(),
+            description: String::new(),
+            score: 1.0,
+        }
+    }
+
+    fn topics(topics: &[StreamTopic]) -> HashSet<StreamTopic> {
+        topics.iter().copied().collect()
+    }
+
+    #[test]
+    fn publish_numbers_events_from_one() {
+        let hub = EventHub::new();
+        assert_eq!(hub.latest_seq(), 0);
+        assert_eq!(hub.publish(status()), 1);
+        assert_eq!(hub.publish(status()), 2);
+        assert_eq!(hub.latest_seq(), 2);
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_events_in_sequence() {
+        let hub = EventHub::new();
+        let mut receiver = hub.subscribe();
+        for _ in 0..3 {
+            hub.publish(status());
+        }
+        for expected in 1..=3 {
+            assert_eq!(receiver.recv().await.unwrap().seq, expected);
+        }
+    }
+
+    #[test]
+    fn replay_returns_later_events_on_the_requested_topics() {
+        let hub = EventHub::new();
+        hub.publish(status());
+        hub.publish(opportunity("a"));
+        hub.publish(status());
+        hub.publish(opportunity("b"));
+
+        let replay = hub.replay(1, &topics(&[StreamTopic::Opportunities]));
+        let seqs: Vec<u64> = replay.events.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![2, 4]);
+        assert!(!replay.gap);
+        assert!(hub.replay(4, &topics(&StreamTopic::ALL)).events.is_empty());
+    }
+
+    #[test]
+    fn replay_flags_a_gap_once_events_are_evicted() {
+        let hub = EventHub::with_capacity(2);
+        for _ in 0..5 {
+            hub.publish(status());
+        }
+
+        let all = topics(&StreamTopic::ALL);
+        let replay = hub.replay(0, &all);
+        assert!(replay.gap);
+        assert_eq!(replay.events.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![4, 5]);
+        assert!(!hub.replay(3, &all).gap);
+        assert!(hub.replay(2, &all).gap);
+    }
+
+    #[test]
+    fn replay_on_an_empty_hub_has_no_gap() {
+        let hub = EventHub::new();
+        let replay = hub.replay(0, &topics(&StreamTopic::ALL));
+        assert!(replay.events.is_empty());
+        assert!(!replay.gap);
+    }
+}
@@ -9,6 +9,10 @@ use solana_sdk::{
     instruction::Instruction,
 };
 use crate::SolanaConfig;
+use crate::capital_allocator::{AllocatorConfig, CapitalAllocator};
+use crate::strategy::{
+    CrossVenueArbitrage, StrategyDescriptor, StrategyFactory, StrategyParams, StrategyRegistry, TriangularArbitrage,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlashLoanStrategy {
@@ -62,6 +66,8 @@ pub struct AdvancedFlashStrategies {
     pub active_opportunities: Vec<ArbitrageOpportunity>,
     pub total_profit: f64,
     pub execution_count: u64,
+    /// Realised returns per strategy and the capital split derived from them
+    pub allocator: CapitalAllocator,
 }
 
 impl AdvancedFlashStrategies {
@@ -73,6 +79,7 @@ impl AdvancedFlashStrategies {
             active_opportunities: Vec::new(),
             total_profit: 0.0,
             execution_count: 0,
+            allocator: CapitalAllocator::new(AllocatorConfig::from_env()),
         };
 
         strategies.initialize_rpc_clients(config).await?;
@@ -255,21 +262,21 @@ impl AdvancedFlashStrategies {
 
                 println!("⚡ Cascade flash loan executed: +{:.2} SOL profit", profit);
 
-                Ok(ExecutionResult {
+                Ok(self.finish_execution("cascade_flash", capital, ExecutionResult {
                     success: true,
                     profit,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     gas_used: 0.001,
                     strategy: "cascade_flash".to_string(),
-                })
+                }))
             } else {
-                Ok(ExecutionResult {
+                Ok(self.finish_execution("cascade_flash", capital, ExecutionResult {
                     success: false,
                     profit: 0.0,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     gas_used: 0.001,
                     strategy: "cascade_flash".to_string(),
-                })
+                }))
             }
         } else {
             Err("Cascade flash strategy not found".into())
@@ -295,21 +302,21 @@ impl AdvancedFlashStrategies {
                     println!("🔺 Triangular arbitrage executed: {} -> {} -> {} = +{:.2} SOL", 
                         opportunity.token_a, opportunity.token_b, opportunity.token_a, profit);
 
-                    Ok(ExecutionResult {
+                    Ok(self.finish_execution("triangular_flash", opportunity.capital_required, ExecutionResult {
                         success: true,
                         profit,
                         execution_time_ms: start_time.elapsed().as_millis() as u64,
                         gas_used: 0.002,
                         strategy: "triangular_arbitrage".to_string(),
-                    })
+                    }))
                 } else {
-                    Ok(ExecutionResult {
+                    Ok(self.finish_execution("triangular_flash", opportunity.capital_required, ExecutionResult {
                         success: false,
                         profit: 0.0,
                         execution_time_ms: start_time.elapsed().as_millis() as u64,
                         gas_used: 0.002,
                         strategy: "triangular_arbitrage".to_string(),
-                    })
+                    }))
                 }
             } else {
                 Err("No profitable triangular arbitrage opportunities found".into())
@@ -349,6 +356,9 @@ impl AdvancedFlashStrategies {
         let start_time = Instant::now();
         
         if let Some(strategy) = self.strategies.get("mev_bundle") {
+            // Bundles are sized at the strategy minimum
+            let capital = strategy.min_capital;
+
             // Simulate MEV bundle extraction
             let success_rate = 0.992; // 99.2% success rate
             let success = rand::random::<f64>() < success_rate;
@@ -364,21 +374,21 @@ impl AdvancedFlashStrategies {
 
                 println!("⚡ MEV bundle extracted: +{:.2} SOL profit", profit);
 
-                Ok(ExecutionResult {
+                Ok(self.finish_execution("mev_bundle", capital, ExecutionResult {
                     success: true,
                     profit,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     gas_used: 0.005,
                     strategy: "mev_extraction".to_string(),
-                })
+                }))
             } else {
-                Ok(ExecutionResult {
+                Ok(self.finish_execution("mev_bundle", capital, ExecutionResult {
                     success: false,
                     profit: 0.0,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     gas_used: 0.005,
                     strategy: "mev_extraction".to_string(),
-                })
+                }))
             }
         } else {
             Err("MEV bundle strategy not found".into())
@@ -410,21 +420,21 @@ impl AdvancedFlashStrategies {
 
                 println!("💰 Stake arbitrage glitch executed: +{:.2} SOL profit", profit);
 
-                Ok(ExecutionResult {
+                Ok(self.finish_execution("stake_arb_glitch", capital, ExecutionResult {
                     success: true,
                     profit,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     gas_used: 0.003,
                     strategy: "stake_arb_glitch".to_string(),
-                })
+                }))
             } else {
-                Ok(ExecutionResult {
+                Ok(self.finish_execution("stake_arb_glitch", capital, ExecutionResult {
                     success: false,
                     profit: 0.0,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     gas_used: 0.003,
                     strategy: "stake_arb_glitch".to_string(),
-                })
+                }))
             }
         } else {
             Err("Stake arbitrage glitch strategy not found".into())
@@ -468,6 +478,46 @@ impl AdvancedFlashStrategies {
         Ok(opportunities)
     }
 
+    /// Registers the catalogue entries that have an event-driven implementation,
+    /// sized by their capital limits. Returns how many were registered.
+    pub fn register_strategies(&self, registry: &mut StrategyRegistry) -> anyhow::Result<usize> {
+        let mut registered = 0;
+        for strategy in self.strategies.values() {
+            if registry.contains(&strategy.id) {
+                continue;
+            }
+            let mut params = StrategyParams::new();
+            params.insert("max_notional".to_string(), strategy.max_capital.into());
+            let id = strategy.id.clone();
+            let factory: StrategyFactory = match strategy.strategy_type.as_str() {
+                "cascade" | "cross_dex" => {
+                    if strategy.strategy_type == "cross_dex" {
+                        params.insert("venues".to_string(), strategy.protocols.clone().into());
+                    }
+                    Box::new(move || Box::new(CrossVenueArbitrage::new(&id)))
+                }
+                "triangular" => {
+                    if let Some(venue) = strategy.protocols.first() {
+                        params.insert("venue".to_string(), venue.clone().into());
+                    }
+                    Box::new(move || Box::new(TriangularArbitrage::new(&id)))
+                }
+                // MEV bundles and stake arbitrage are not driven by quotes
+                _ => continue,
+            };
+
+            let descriptor = StrategyDescriptor {
+                id: strategy.id.clone(),
+                name: strategy.name.clone(),
+                origin: "flash_strategies".to_string(),
+                description: format!("{} flash loan strategy over {}", strategy.strategy_type, strategy.protocols.join(", ")),
+            };
+            registry.register(descriptor, params, factory)?;
+            registered += 1;
+        }
+        Ok(registered)
+    }
+
     pub fn get_strategy_performance(&self) -> HashMap<String, serde_json::Value> {
         let mut performance = HashMap::new();
         
@@ -494,25 +544,92 @@ impl AdvancedFlashStrategies {
         self.execution_count
     }
 
-    pub async fn optimize_capital_allocation(&self, total_capital: f64) -> HashMap<String, f64> {
-        let mut allocation = HashMap::new();
-        
-        // Allocate capital based on profitability scores and risk levels
-        let total_score: f64 = self.strategies.values()
-            .map(|s| s.profitability_score)
-            .sum();
+    /// Splits `total_capital` across the strategies it can fund, by the
+    /// allocator's policy over their realised returns. Shares above a
+    /// strategy's maximum are trimmed and shares below its minimum are left
+    /// unfunded, so the total never exceeds `total_capital`
+    pub async fn optimize_capital_allocation(&mut self, total_capital: f64) -> HashMap<String, f64> {
+        let mut candidates: Vec<String> = self.strategies.values()
+            .filter(|s| total_capital >= s.min_capital)
+            .map(|s| s.id.clone())
+            .collect();
+        candidates.sort();
 
-        for (strategy_id, strategy) in &self.strategies {
-            if total_capital >= strategy.min_capital {
-                let allocation_percentage = strategy.profitability_score / total_score;
-                let allocated_capital = (total_capital * allocation_percentage)
-                    .min(strategy.max_capital)
-                    .max(strategy.min_capital);
-                
-                allocation.insert(strategy_id.clone(), allocated_capital);
+        let now = chrono::Utc::now().timestamp() as u64;
+        self.allocator.rebalance(&candidates, total_capital, now, false);
+
+        let mut allocation = HashMap::new();
+        for strategy_id in candidates {
+            let allocated = self.allocator.allocation(&strategy_id);
+            if allocated <= 0.0 {
+                continue;
+            }
+            if let Some(strategy) = self.strategies.get(&strategy_id) {
+                if allocated < strategy.min_capital {
+                    println!(
+                        "⚖️ {} left unfunded: share {:.2} is below its minimum {:.2}",
+                        strategy_id, allocated, strategy.min_capital
+                    );
+                    continue;
+                }
+                allocation.insert(strategy_id, allocated.min(strategy.max_capital));
             }
         }
 
         allocation
     }
-}
\ No newline at end of file
+
+    /// Books an execution into the allocator's ledger, net of gas
+    fn finish_execution(&mut self, strategy_id: &str, capital: f64, result: ExecutionResult) -> ExecutionResult {
+        let now = chrono::Utc::now().timestamp() as u64;
+        self.allocator.record(strategy_id, result.profit - result.gas_used, capital, now);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capital_allocator::AllocationPolicy;
+
+    fn strategy(id: &str, min_capital: f64, max_capital: f64) -> FlashLoanStrategy {
+        FlashLoanStrategy {
+            id: id.to_string(),
+            name: id.to_string(),
+            strategy_type: "arbitrage".to_string(),
+            min_capital,
+            max_capital,
+            expected_apy: 0.0,
+            risk_level: "low".to_string(),
+            protocols: Vec::new(),
+            execution_time_ms: 0,
+            profitability_score: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn allocation_never_exceeds_the_capital_available() {
+        let mut flash = AdvancedFlashStrategies {
+            rpc_clients: HashMap::new(),
+            strategies: HashMap::new(),
+            price_cache: HashMap::new(),
+            active_opportunities: Vec::new(),
+            total_profit: 0.0,
+            execution_count: 0,
+            allocator: CapitalAllocator::new(AllocatorConfig {
+                policy: AllocationPolicy::EqualWeight,
+                ..AllocatorConfig::default()
+            }),
+        };
+        for s in [strategy("small", 100.0, 200.0), strategy("medium", 500.0, 5000.0), strategy("large", 900.0, 5000.0)] {
+            flash.strategies.insert(s.id.clone(), s);
+        }
+
+        // Equal weight gives each about 317: "small" is trimmed to its maximum
+        // and the others cannot be funded at their minimums
+        let allocation = flash.optimize_capital_allocation(1000.0).await;
+        assert_eq!(allocation.len(), 1);
+        assert_eq!(allocation["small"], 200.0);
+        assert!(allocation.values().sum::<f64>() <= 1000.0);
+    }
+}
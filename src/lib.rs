@@ -3,11 +3,19 @@
  * Main module for integrating all Rust-based trading systems
  */
 
+pub mod account_cache;
+pub mod atomic_arb_client;
 pub mod black_diamond_transaction_pipeline;
+pub mod bot_registry;
+pub mod capital_allocator;
 pub mod zero_capital_advanced_strategies;
 pub mod memecoin_sniping_upper_echelon;
 pub mod on_chain_program_innovations;
 pub mod metrics_tracking_verification;
+pub mod pool_ingestion;
+pub mod price_oracle;
+pub mod program_deploy;
+pub mod strategy;
 
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
@@ -19,7 +27,12 @@ pub use black_diamond_transaction_pipeline::{BlackDiamondTransactionPipeline, cr
 pub use zero_capital_advanced_strategies::{ZeroCapitalAdvancedStrategies, create_zero_capital_strategies};
 pub use memecoin_sniping_upper_echelon::{MemecoinSnipingUpperEchelon, create_memecoin_sniper};
 pub use on_chain_program_innovations::{OnChainProgramInnovations, create_on_chain_innovations};
+use bot_registry::BotRegistry;
+use on_chain_program_innovations::{ARBITRAGE_SCANNER_BOT, BUNDLE_CAPTURE_BOT};
+use program_deploy::ProgramDeployer;
 pub use metrics_tracking_verification::{MetricsTrackingVerification, create_metrics_tracker};
+pub use pool_ingestion::{PoolIngestion, PoolUpdate};
+pub use price_oracle::{PriceOracle, PriceQuote};
 
 pub struct RustTradingEcosystem {
     pub black_diamond_pipeline: BlackDiamondTransactionPipeline,
@@ -47,7 +60,14 @@ impl RustTradingEcosystem {
         let black_diamond_pipeline = create_black_diamond_pipeline()?;
         let zero_capital_strategies = create_zero_capital_strategies(Arc::clone(&connection));
         let memecoin_sniper = create_memecoin_sniper(Arc::clone(&connection))?;
-        let on_chain_innovations = create_on_chain_innovations(Arc::clone(&connection));
+        // Bots report revenue from the same ledger the pipeline books executions to
+        let bot_registry = Arc::new(BotRegistry::with_ledger(black_diamond_pipeline.trade_ledger()));
+        let deployer = ProgramDeployer::for_rpc_url(&connection.url(), std::env::current_dir()?);
+        let on_chain_innovations = create_on_chain_innovations(Arc::clone(&connection))
+            .with_deployer(Arc::new(deployer))
+            .with_bot_registry(bot_registry)
+            .with_bot_job(ARBITRAGE_SCANNER_BOT, black_diamond_pipeline.arbitrage_scanner_job())
+            .with_bot_job(BUNDLE_CAPTURE_BOT, black_diamond_pipeline.bundle_capture_job());
         let metrics_tracker = create_metrics_tracker(Arc::clone(&connection));
 
         let ecosystem = Self {
@@ -86,7 +106,7 @@ impl RustTradingEcosystem {
     }
 
     // Black Diamond Pipeline API
-    pub async fn get_black_diamond_status(&self) -> Result<Value> {
+    pub async fn get_black_diamond_status(&self) -> Result<black_diamond_client::types::PipelineStatus> {
         self.black_diamond_pipeline.get_system_status().await
     }
 
@@ -115,7 +135,7 @@ impl RustTradingEcosystem {
         self.zero_capital_strategies.get_memecoin_opportunities().await
     }
 
-    pub async fn get_zero_capital_stats(&self) -> Value {
+    pub async fn get_zero_capital_stats(&self) -> black_diamond_client::types::ZeroCapitalStats {
         self.zero_capital_strategies.get_current_stats().await
     }
 
@@ -136,7 +156,7 @@ impl RustTradingEcosystem {
         self.memecoin_sniper.get_snipe_results().await
     }
 
-    pub async fn get_sniping_stats(&self) -> Value {
+    pub async fn get_sniping_stats(&self) -> black_diamond_client::types::SnipingStats {
         self.memecoin_sniper.get_sniping_stats().await
     }
 
@@ -149,7 +169,7 @@ impl RustTradingEcosystem {
     }
 
     // On-Chain Innovations API
-    pub async fn get_bot_performance(&self) -> Vec<Value> {
+    pub async fn get_bot_performance(&self) -> Vec<black_diamond_client::types::BotPerformance> {
         self.on_chain_innovations.get_bot_performance().await
     }
 
@@ -181,7 +201,7 @@ impl RustTradingEcosystem {
         self.on_chain_innovations.capture_mev_bundle().await
     }
 
-    pub async fn execute_quantum_speed_boost(&self) -> Result<Value> {
+    pub async fn execute_quantum_speed_boost(&self) -> Result<black_diamond_client::types::QuantumBoostResult> {
         self.on_chain_innovations.execute_quantum_speed_boost().await
     }
 
@@ -218,7 +238,7 @@ impl RustTradingEcosystem {
         self.metrics_tracker.acknowledge_alert(&alert_id).await
     }
 
-    pub async fn get_metrics_overview(&self) -> Value {
+    pub async fn get_metrics_overview(&self) -> black_diamond_client::types::MetricsOverview {
         self.metrics_tracker.get_system_overview().await
     }
 
@@ -250,7 +270,7 @@ impl RustTradingEcosystem {
     }
 
     pub async fn get_ecosystem_overview(&self) -> Value {
-        let black_diamond_status = self.get_black_diamond_status().await.unwrap_or_default();
+        let black_diamond_status = self.get_black_diamond_status().await.ok();
         let zero_capital_stats = self.get_zero_capital_stats().await;
         let sniping_stats = self.get_sniping_stats().await;
         let innovations_overview = self.get_innovations_overview().await;
@@ -16,22 +16,34 @@ use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 mod black_diamond;
+mod capital_allocator;
 mod flash_strategies;
+mod agent_bus;
+mod agent_tasks;
 mod neural_agents;
 mod transformers;
 mod wallet_integration;
 mod rpc_manager;
+mod strategy;
 mod mev_engine;
 mod memecoin_sniper;
+mod api_auth;
 
 use black_diamond::BlackDiamondEngine;
+use agent_bus::AgentMessage;
 use flash_strategies::AdvancedFlashStrategies;
 use neural_agents::NeuralAgentOrchestrator;
 use transformers::TransformerDeploymentManager;
 use wallet_integration::WalletManager;
 use rpc_manager::RPCManager;
+use strategy::{MarketEvent, SimulationConfig, SimulationSession, StrategyRegistry};
+use black_diamond_client::routes;
+use black_diamond_client::types::{
+    ApiResponse, BacktestRequest, StrategyConfigRequest, StrategyEntry, StrategyReport, StrategyToggleRequest,
+};
 use mev_engine::MEVExtractionEngine;
 use memecoin_sniper::MemecoinSniperEngine;
+use api_auth::{ApiAuth, AuthError, Principal, API_KEY_HEADER};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolanaConfig {
@@ -75,6 +87,7 @@ pub struct SolanaNexusTrader {
     pub rpc_manager: Arc<Mutex<RPCManager>>,
     pub mev_engine: Arc<Mutex<MEVExtractionEngine>>,
     pub memecoin_sniper: Arc<Mutex<MemecoinSniperEngine>>,
+    pub strategy_registry: Arc<Mutex<StrategyRegistry>>,
     pub system_status: Arc<Mutex<SystemStatus>>,
     pub current_sol: Arc<Mutex<f64>>,
 }
@@ -93,13 +106,22 @@ impl SolanaNexusTrader {
 
         let black_diamond = Arc::new(Mutex::new(BlackDiamondEngine::new().await?));
         let flash_strategies = Arc::new(Mutex::new(AdvancedFlashStrategies::new(&config).await?));
-        let neural_agents = Arc::new(Mutex::new(NeuralAgentOrchestrator::new().await?));
-        let transformers = Arc::new(Mutex::new(TransformerDeploymentManager::new().await?));
+        let mut orchestrator = NeuralAgentOrchestrator::new().await?;
+        orchestrator.start_execution_reviewers()?;
+        orchestrator.start_risk_gate();
+        let transformers = Arc::new(Mutex::new(TransformerDeploymentManager::new().await?.with_bus(orchestrator.bus())));
+        let neural_agents = Arc::new(Mutex::new(orchestrator));
         let wallet_manager = Arc::new(Mutex::new(WalletManager::new(&config).await?));
         let rpc_manager = Arc::new(Mutex::new(RPCManager::new(&config).await?));
         let mev_engine = Arc::new(Mutex::new(MEVExtractionEngine::new(&config).await?));
         let memecoin_sniper = Arc::new(Mutex::new(MemecoinSniperEngine::new(&config).await?));
 
+        let mut registry = StrategyRegistry::new();
+        let registered = flash_strategies.lock().map_err(|_| "flash strategies lock poisoned")?.register_strategies(&mut registry)?
+            + black_diamond.lock().map_err(|_| "black diamond lock poisoned")?.register_strategies(&mut registry)?;
+        println!("🧩 Strategy registry: {} strategies registered", registered);
+        let strategy_registry = Arc::new(Mutex::new(registry));
+
         let system_status = Arc::new(Mutex::new(SystemStatus {
             current_sol: 1311.8,
             target_sol: 100000.0,
@@ -123,6 +145,7 @@ impl SolanaNexusTrader {
             rpc_manager,
             mev_engine,
             memecoin_sniper,
+            strategy_registry,
             system_status,
             current_sol,
         })
@@ -134,6 +157,7 @@ impl SolanaNexusTrader {
         let black_diamond = Arc::clone(&self.black_diamond);
         let flash_strategies = Arc::clone(&self.flash_strategies);
         let neural_agents = Arc::clone(&self.neural_agents);
+        let agent_bus = self.neural_agents.lock().map_err(|_| "neural agents lock poisoned")?.bus();
         let current_sol = Arc::clone(&self.current_sol);
 
         // Start main neural processing loop
@@ -143,6 +167,16 @@ impl SolanaNexusTrader {
                 if let Ok(mut bd) = black_diamond.try_lock() {
                     if let Ok(mut fs) = flash_strategies.try_lock() {
                         if let Ok(mut na) = neural_agents.try_lock() {
+                            // Announce what the scanner sees so the risk gate can screen it
+                            let sol_price = fs.price_cache.get("SOL").map(|p| p.price);
+                            if let (Ok(opportunities), Some(sol_price)) = (fs.scan_cross_dex_opportunities().await, sol_price) {
+                                for opportunity in &opportunities {
+                                    agent_bus
+                                        .publish("black_diamond", opportunity_message(opportunity, sol_price), 4)
+                                        .await;
+                                }
+                            }
+
                             // Execute cascade flash loans
                             if let Ok(cascade_result) = fs.execute_cascade_flash_loan(5000.0).await {
                                 if cascade_result.success {
@@ -151,7 +185,12 @@ impl SolanaNexusTrader {
                                         *sol += profit;
                                     }
                                     bd.record_profit("cascade_flash", profit).await;
+                                } else {
+                                    na.submit_execution_review(&cascade_result).ok();
                                 }
+                                agent_bus
+                                    .publish("black_diamond", execution_message("cascade_flash", &cascade_result), 5)
+                                    .await;
                             }
 
                             // Execute triangular arbitrage
@@ -162,7 +201,12 @@ impl SolanaNexusTrader {
                                         *sol += profit;
                                     }
                                     bd.record_profit("triangular_arb", profit).await;
+                                } else {
+                                    na.submit_execution_review(&triangular_result).ok();
                                 }
+                                agent_bus
+                                    .publish("black_diamond", execution_message("triangular_arb", &triangular_result), 5)
+                                    .await;
                             }
 
                             // Execute MEV extraction
@@ -173,7 +217,12 @@ impl SolanaNexusTrader {
                                         *sol += profit;
                                     }
                                     bd.record_profit("mev_extraction", profit).await;
+                                } else {
+                                    na.submit_execution_review(&mev_result).ok();
                                 }
+                                agent_bus
+                                    .publish("black_diamond", execution_message("mev_extraction", &mev_result), 5)
+                                    .await;
                             }
 
                             // Neural agent coordination
@@ -213,25 +262,163 @@ impl SolanaNexusTrader {
         }
     }
 
+    pub fn list_strategies(&self) -> ApiResponse<Vec<StrategyEntry>> {
+        match self.strategy_registry.lock() {
+            Ok(registry) => ApiResponse::success(registry.list().into_iter().map(StrategyEntry::from).collect()),
+            Err(_) => ApiResponse::error("strategy registry lock poisoned".to_string()),
+        }
+    }
+
+    pub fn set_strategy_enabled(&self, req: &StrategyToggleRequest) -> ApiResponse<StrategyEntry> {
+        let Ok(mut registry) = self.strategy_registry.lock() else {
+            return ApiResponse::error("strategy registry lock poisoned".to_string());
+        };
+        match registry.set_enabled(&req.id, req.enabled).and_then(|_| registry.info(&req.id)) {
+            Ok(info) => ApiResponse::success(info.into()),
+            Err(e) => ApiResponse::error(format!("Failed to update {}: {}", req.id, e)),
+        }
+    }
+
+    pub fn configure_strategy(&self, req: &StrategyConfigRequest) -> ApiResponse<StrategyEntry> {
+        let Ok(mut registry) = self.strategy_registry.lock() else {
+            return ApiResponse::error("strategy registry lock poisoned".to_string());
+        };
+        match registry.configure(&req.id, &req.parameters).and_then(|_| registry.info(&req.id)) {
+            Ok(info) => ApiResponse::success(info.into()),
+            Err(e) => ApiResponse::error(format!("Failed to configure {}: {}", req.id, e)),
+        }
+    }
+
+    pub fn backtest_strategy(&self, req: &BacktestRequest) -> ApiResponse<StrategyReport> {
+        let Ok(registry) = self.strategy_registry.lock() else {
+            return ApiResponse::error("strategy registry lock poisoned".to_string());
+        };
+        let events: Result<Vec<MarketEvent>, _> = req.events.iter().cloned().map(serde_json::from_value).collect();
+        let report = events
+            .map_err(anyhow::Error::from)
+            .and_then(|events| SimulationSession::backtest(&registry, &req.id, &events, SimulationConfig::default()));
+        match report {
+            Ok(report) => ApiResponse::success(report.into()),
+            Err(e) => ApiResponse::error(format!("Backtest of {} failed: {}", req.id, e)),
+        }
+    }
+
     pub async fn start_api_server(&self) -> Result<(), Box<dyn std::error::Error>> {
         let trader = Arc::new(self);
+        let auth = Arc::new(ApiAuth::from_env(Some("nexus_trader_audit.log".into()))?);
+        if !auth.is_configured() {
+            println!("🔒 No API keys or JWT secret configured - every API request will be rejected");
+        }
         
         // System status endpoint
         let status_route = warp::path("api")
             .and(warp::path("status"))
             .and(warp::get())
+            .and(warp::header::optional::<String>(API_KEY_HEADER))
+            .and(warp::header::optional::<String>("authorization"))
             .and_then({
                 let trader = Arc::clone(&trader);
-                move || {
+                let auth = Arc::clone(&auth);
+                move |api_key: Option<String>, authorization: Option<String>| {
                     let trader = Arc::clone(&trader);
+                    let auth = Arc::clone(&auth);
                     async move {
+                        let principal = match authorize_request(&auth, "GET", routes::SYSTEM_OVERVIEW, api_key.as_deref(), authorization.as_deref()) {
+                            Ok(principal) => principal,
+                            Err(rejection) => return Ok::<_, warp::Rejection>(rejection),
+                        };
+                        
                         let status = trader.get_system_status().await;
-                        Ok::<_, warp::Rejection>(warp::reply::json(&status))
+                        Ok::<_, warp::Rejection>(audited_ok(&auth, &principal, "GET", routes::SYSTEM_OVERVIEW, &status))
+                    }
+                }
+            });
+
+        // Strategy plugins, on the same paths as the standalone server
+        let list_strategies_route = warp::path!("api" / "strategies")
+            .and(warp::get())
+            .and(credentials())
+            .and_then({
+                let trader = Arc::clone(&trader);
+                let auth = Arc::clone(&auth);
+                move |api_key: Option<String>, authorization: Option<String>| {
+                    let trader = Arc::clone(&trader);
+                    let auth = Arc::clone(&auth);
+                    async move {
+                        let principal = match authorize_request(&auth, "GET", routes::STRATEGIES, api_key.as_deref(), authorization.as_deref()) {
+                            Ok(principal) => principal,
+                            Err(rejection) => return Ok::<_, warp::Rejection>(rejection),
+                        };
+                        Ok(audited_ok(&auth, &principal, "GET", routes::STRATEGIES, &trader.list_strategies()))
+                    }
+                }
+            });
+
+        let enable_strategy_route = warp::path!("api" / "strategies" / "enable")
+            .and(warp::post())
+            .and(credentials())
+            .and(warp::body::json::<StrategyToggleRequest>())
+            .and_then({
+                let trader = Arc::clone(&trader);
+                let auth = Arc::clone(&auth);
+                move |api_key: Option<String>, authorization: Option<String>, req: StrategyToggleRequest| {
+                    let trader = Arc::clone(&trader);
+                    let auth = Arc::clone(&auth);
+                    async move {
+                        let principal = match authorize_request(&auth, "POST", routes::STRATEGY_ENABLE, api_key.as_deref(), authorization.as_deref()) {
+                            Ok(principal) => principal,
+                            Err(rejection) => return Ok::<_, warp::Rejection>(rejection),
+                        };
+                        Ok(audited_ok(&auth, &principal, "POST", routes::STRATEGY_ENABLE, &trader.set_strategy_enabled(&req)))
                     }
                 }
             });
 
-        let routes = status_route;
+        let configure_strategy_route = warp::path!("api" / "strategies" / "configure")
+            .and(warp::post())
+            .and(credentials())
+            .and(warp::body::json::<StrategyConfigRequest>())
+            .and_then({
+                let trader = Arc::clone(&trader);
+                let auth = Arc::clone(&auth);
+                move |api_key: Option<String>, authorization: Option<String>, req: StrategyConfigRequest| {
+                    let trader = Arc::clone(&trader);
+                    let auth = Arc::clone(&auth);
+                    async move {
+                        let principal = match authorize_request(&auth, "POST", routes::STRATEGY_CONFIGURE, api_key.as_deref(), authorization.as_deref()) {
+                            Ok(principal) => principal,
+                            Err(rejection) => return Ok::<_, warp::Rejection>(rejection),
+                        };
+                        Ok(audited_ok(&auth, &principal, "POST", routes::STRATEGY_CONFIGURE, &trader.configure_strategy(&req)))
+                    }
+                }
+            });
+
+        let backtest_strategy_route = warp::path!("api" / "strategies" / "backtest")
+            .and(warp::post())
+            .and(credentials())
+            .and(warp::body::json::<BacktestRequest>())
+            .and_then({
+                let trader = Arc::clone(&trader);
+                let auth = Arc::clone(&auth);
+                move |api_key: Option<String>, authorization: Option<String>, req: BacktestRequest| {
+                    let trader = Arc::clone(&trader);
+                    let auth = Arc::clone(&auth);
+                    async move {
+                        let principal = match authorize_request(&auth, "POST", routes::STRATEGY_BACKTEST, api_key.as_deref(), authorization.as_deref()) {
+                            Ok(principal) => principal,
+                            Err(rejection) => return Ok::<_, warp::Rejection>(rejection),
+                        };
+                        Ok(audited_ok(&auth, &principal, "POST", routes::STRATEGY_BACKTEST, &trader.backtest_strategy(&req)))
+                    }
+                }
+            });
+
+        let routes = status_route
+            .or(list_strategies_route)
+            .or(enable_strategy_route)
+            .or(configure_strategy_route)
+            .or(backtest_strategy_route);
 
         println!("🌐 Starting API server on port 3030...");
         warp::serve(routes)
@@ -242,6 +429,69 @@ impl SolanaNexusTrader {
     }
 }
 
+/// API key and bearer token headers, either of which may be absent
+fn credentials() -> impl Filter<Extract = (Option<String>, Option<String>), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>(API_KEY_HEADER).and(warp::header::optional::<String>("authorization"))
+}
+
+/// Authorizes a request, or audits the refusal and returns the reply for it
+fn authorize_request(
+    auth: &ApiAuth,
+    method: &str,
+    path: &str,
+    api_key: Option<&str>,
+    authorization: Option<&str>,
+) -> Result<Principal, warp::reply::WithStatus<warp::reply::Json>> {
+    let e = match auth.authorize(method, path, api_key, authorization) {
+        Ok(principal) => return Ok(principal),
+        Err(e) => e,
+    };
+    let status = match e {
+        AuthError::MissingCredentials | AuthError::InvalidCredentials(_) => warp::http::StatusCode::UNAUTHORIZED,
+        AuthError::Forbidden { .. } => warp::http::StatusCode::FORBIDDEN,
+        AuthError::RateLimited { .. } => warp::http::StatusCode::TOO_MANY_REQUESTS,
+    };
+    auth.record_audit(&auth.identify(api_key, authorization), method, path, status.as_u16());
+    let body = warp::reply::json(&serde_json::json!({ "error": e.to_string() }));
+    Err(warp::reply::with_status(body, status))
+}
+
+/// Audits an allowed request and replies with its body
+fn audited_ok<T: Serialize>(
+    auth: &ApiAuth,
+    principal: &Principal,
+    method: &str,
+    path: &str,
+    body: &T,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    auth.record_audit(principal, method, path, warp::http::StatusCode::OK.as_u16());
+    warp::reply::with_status(warp::reply::json(body), warp::http::StatusCode::OK)
+}
+
+fn execution_message(strategy: &str, result: &flash_strategies::ExecutionResult) -> AgentMessage {
+    AgentMessage::ExecutionResult {
+        opportunity_id: None,
+        strategy: strategy.to_string(),
+        success: result.success,
+        profit_sol: result.profit,
+        signature: None,
+        error: None,
+    }
+}
+
+/// Profit is scanned in USDC. Scanned spreads run from the 0.2% threshold to
+/// 0.5%; the wider the spread, the more slippage it survives.
+fn opportunity_message(opportunity: &flash_strategies::ArbitrageOpportunity, sol_price: f64) -> AgentMessage {
+    AgentMessage::OpportunityFound {
+        opportunity_id: uuid::Uuid::new_v4().to_string(),
+        strategy: format!("cross_dex:{}->{}", opportunity.dex_a, opportunity.dex_b),
+        market: format!("{}/{}", opportunity.token_a, opportunity.token_b),
+        expected_profit_sol: opportunity.profit_potential / sol_price,
+        confidence: (opportunity.price_difference / 0.005).min(1.0),
+        expires_at_ms: Some(chrono::Utc::now().timestamp_millis() as u64 + opportunity.execution_time),
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🔹 Initializing Solana Nexus Trader with Black Diamond Neural Engine...");
@@ -20,23 +20,7 @@ use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use anyhow::Result;
 use tokio::time::interval;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MemecoinTarget {
-    pub token_address: String,
-    pub token_name: String,
-    pub ticker: String,
-    pub pool_address: String,
-    pub liquidity_sol: f64,
-    pub creator_address: String,
-    pub launch_timestamp: u64,
-    pub social_score: f64,
-    pub viral_potential: f64,
-    pub rug_pull_risk: f64,
-    pub snipe_score: f64,
-    pub expected_multiplier: f64,
-    pub max_snipe_amount: f64,
-    pub optimal_snipe_time: u64,
-}
+pub use black_diamond_client::types::{MemecoinTarget, SnipingStats};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SocialIntelligence {
@@ -705,7 +689,7 @@ impl MemecoinSnipingUpperEchelon {
         }
     }
 
-    pub async fn get_sniping_stats(&self) -> serde_json::Value {
+    pub async fn get_sniping_stats(&self) -> SnipingStats {
         let results = if let Ok(r) = self.results.lock() { r.clone() } else { Vec::new() };
         let successful_snipes = results.iter().filter(|r| r.success).count();
         let total_snipes = results.len();
@@ -725,15 +709,15 @@ impl MemecoinSnipingUpperEchelon {
             0.0
         };
 
-        serde_json::json!({
-            "totalSnipes": total_snipes,
-            "successfulSnipes": successful_snipes,
-            "successRate": success_rate,
-            "totalProfit": total_profit,
-            "averageProfit": average_profit,
-            "activeTargets": active_targets,
-            "isActive": is_active
-        })
+        SnipingStats {
+            total_snipes,
+            successful_snipes,
+            success_rate,
+            total_profit,
+            average_profit,
+            active_targets,
+            is_active,
+        }
     }
 
     pub async fn add_custom_target(&self, token_address: String, custom_score: Option<f64>) -> Result<()> {
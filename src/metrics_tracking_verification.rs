@@ -17,33 +17,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use anyhow::Result;
 use tokio::time::interval;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WalletMetrics {
-    pub address: String,
-    pub name: String,
-    pub sol_balance: f64,
-    pub token_balances: Vec<TokenBalance>,
-    pub total_value_usd: f64,
-    pub daily_profit_loss: f64,
-    pub weekly_profit_loss: f64,
-    pub monthly_profit_loss: f64,
-    pub total_profit: f64,
-    pub transaction_count: u64,
-    pub success_rate: f64,
-    pub last_updated: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TokenBalance {
-    pub token_address: String,
-    pub token_name: String,
-    pub symbol: String,
-    pub balance: f64,
-    pub decimals: u8,
-    pub price_usd: f64,
-    pub value_usd: f64,
-    pub change_24h: f64,
-}
+pub use black_diamond_client::types::{MetricsOverview, TokenBalance, WalletMetrics};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionVerification {
@@ -773,7 +747,7 @@ impl MetricsTrackingVerification {
         false
     }
 
-    pub async fn get_system_overview(&self) -> serde_json::Value {
+    pub async fn get_system_overview(&self) -> MetricsOverview {
         let wallets = self.get_all_wallet_metrics().await;
         let total_sol: f64 = wallets.iter().map(|w| w.sol_balance).sum();
         let total_value_usd: f64 = wallets.iter().map(|w| w.total_value_usd).sum();
@@ -803,17 +777,17 @@ impl MetricsTrackingVerification {
             0.0
         };
 
-        serde_json::json!({
-            "totalWallets": wallets.len(),
-            "totalSOL": total_sol,
-            "totalValueUSD": total_value_usd,
-            "totalDailyProfit": total_daily_profit,
-            "totalTransactions": transactions,
-            "successRate": success_rate,
-            "activeAlerts": active_alerts,
-            "systemStatus": if *self.is_tracking_active.lock().unwrap() { "OPERATIONAL" } else { "PAUSED" },
-            "lastUpdate": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
-        })
+        MetricsOverview {
+            total_wallets: wallets.len(),
+            total_sol,
+            total_value_usd,
+            total_daily_profit,
+            total_transactions: transactions,
+            success_rate,
+            active_alerts,
+            system_status: if *self.is_tracking_active.lock().unwrap() { "OPERATIONAL" } else { "PAUSED" }.to_string(),
+            last_update: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+        }
     }
 
     pub async fn export_metrics(&self, format: &str) -> String {
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
 use crate::SolanaConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +14,44 @@ pub struct MEVOpportunity {
     pub execution_window_ms: u64,
     pub risk_score: f64,
     pub priority_fee: f64,
+    #[serde(default)]
+    pub writable_accounts: Vec<String>, // Accounts the target transaction locks for writing
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MevTipConfig {
+    pub base_tip: f64,                   // SOL
+    pub min_congestion_multiplier: f64,
+    pub max_congestion_multiplier: f64,
+    pub max_profit_fraction: f64,        // Max share of expected profit spent on the tip
+}
+
+impl Default for MevTipConfig {
+    fn default() -> Self {
+        MevTipConfig {
+            base_tip: 0.001,
+            min_congestion_multiplier: 1.0,
+            max_congestion_multiplier: 2.0,
+            max_profit_fraction: 0.2,
+        }
+    }
+}
+
+impl MevTipConfig {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let (min, max) = (self.min_congestion_multiplier, self.max_congestion_multiplier);
+        if min.is_nan() || max.is_nan() || min <= 0.0 || min > max {
+            return Err(format!(
+                "Congestion multiplier range {}..{} is empty or not positive",
+                min, max
+            )
+            .into());
+        }
+        if self.base_tip.is_nan() || self.base_tip < 0.0 || !(0.0..=1.0).contains(&self.max_profit_fraction) {
+            return Err("Base tip must be non-negative and the profit fraction within 0..=1".into());
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +81,7 @@ pub struct MEVExtractionEngine {
     pub total_mev_extracted: f64,
     pub successful_extractions: u64,
     pub jito_endpoint: String,
+    pub tip_config: MevTipConfig,
 }
 
 impl MEVExtractionEngine {
@@ -55,12 +95,19 @@ impl MEVExtractionEngine {
             total_mev_extracted: 0.0,
             successful_extractions: 0,
             jito_endpoint: "https://api.jito.wtf/".to_string(),
+            tip_config: MevTipConfig::default(),
         };
 
         engine.start_mempool_monitoring().await?;
         Ok(engine)
     }
 
+    pub fn with_tip_config(mut self, tip_config: MevTipConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        tip_config.validate()?;
+        self.tip_config = tip_config;
+        Ok(self)
+    }
+
     async fn start_mempool_monitoring(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Initialize with some sample MEV opportunities for demonstration
         let sample_opportunities = vec![
@@ -73,6 +120,7 @@ impl MEVExtractionEngine {
                 execution_window_ms: 200,
                 risk_score: 0.15,
                 priority_fee: 0.001,
+                writable_accounts: Vec::new(),
             },
             MEVOpportunity {
                 id: uuid::Uuid::new_v4().to_string(),
@@ -83,6 +131,7 @@ impl MEVExtractionEngine {
                 execution_window_ms: 150,
                 risk_score: 0.25,
                 priority_fee: 0.003,
+                writable_accounts: Vec::new(),
             },
         ];
 
@@ -308,6 +357,7 @@ impl MEVExtractionEngine {
                     execution_window_ms: 50 + (rand::random::<u64>() % 300),
                     risk_score: rand::random::<f64>() * 0.5,
                     priority_fee: 0.0005 + rand::random::<f64>() * 0.005,
+                    writable_accounts: Vec::new(),
                 };
 
                 new_opportunities.push(opportunity);
@@ -384,17 +434,57 @@ impl MEVExtractionEngine {
 
     pub async fn optimize_bundle_tips(&self) -> HashMap<String, f64> {
         let mut optimized_tips = HashMap::new();
-        
-        // Calculate optimal tips based on current network conditions
-        let base_tip = 0.001; // Base tip in SOL
-        let network_congestion_multiplier = 1.2 + rand::random::<f64>() * 0.8; // 1.2x to 2.0x
+        let config = &self.tip_config;
 
         for opportunity in &self.active_opportunities {
-            let optimal_tip = base_tip * network_congestion_multiplier * 
+            // Derive congestion from the fees paid to lock the same accounts
+            let writable_accounts: Vec<Pubkey> = opportunity
+                .writable_accounts
+                .iter()
+                .filter_map(|account| account.parse().ok())
+                .collect();
+            let network_congestion_multiplier = self.network_congestion_multiplier(&writable_accounts).await;
+
+            let optimal_tip = config.base_tip * network_congestion_multiplier * 
                 (opportunity.profit_potential / 10.0).min(5.0);
-            optimized_tips.insert(opportunity.id.clone(), optimal_tip);
+            // Never tip more than the configured share of what the opportunity is expected to return
+            let capped_tip = optimal_tip.min(opportunity.profit_potential * config.max_profit_fraction);
+            optimized_tips.insert(opportunity.id.clone(), capped_tip);
         }
 
         optimized_tips
     }
-}
\ No newline at end of file
+
+    async fn network_congestion_multiplier(&self, writable_accounts: &[Pubkey]) -> f64 {
+        let fees: Vec<u64> = match self.rpc_client.get_recent_prioritization_fees(writable_accounts).await {
+            Ok(recent) => recent.iter().map(|f| f.prioritization_fee).collect(),
+            Err(e) => {
+                println!("⚠️ Could not read prioritization fees: {}", e);
+                return self.tip_config.min_congestion_multiplier;
+            }
+        };
+
+        congestion_multiplier(fees, &self.tip_config)
+    }
+}
+
+/// Spread between the median and p90 recent fee, within the configured range.
+/// A wide spread means slots are contested.
+fn congestion_multiplier(mut fees: Vec<u64>, config: &MevTipConfig) -> f64 {
+    let (min, max) = (config.min_congestion_multiplier, config.max_congestion_multiplier);
+    if fees.is_empty() {
+        return min;
+    }
+
+    fees.sort_unstable();
+    let median = fees[fees.len() / 2] as f64;
+    let p90 = fees[(fees.len() - 1) * 9 / 10] as f64;
+
+    if median > 0.0 {
+        (p90 / median).max(min).min(max)
+    } else if p90 > 0.0 {
+        max
+    } else {
+        min
+    }
+}
@@ -1,32 +1,28 @@
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AgentTask {
-    pub id: String,
-    pub agent_id: String,
-    pub task_type: String,
-    pub description: String,
-    pub priority: u8,
-    pub estimated_completion: u64,
-    pub status: String,
-    pub progress: f64,
-}
+use crate::agent_bus::{AgentBus, AgentMessage, MessageTopic};
+use crate::agent_tasks::{
+    AgentProfile, AgentTask, AgentWorker, SettlementOutcome, TaskKind, TaskQueue, TaskResult, TaskSettlement, TaskSpec,
+    TaskStatus,
+};
+use crate::flash_strategies::ExecutionResult;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CoordinationMessage {
-    pub from_agent: String,
-    pub to_agent: String,
-    pub message_type: String,
-    pub content: String,
-    pub timestamp: u64,
-    pub priority: u8,
-}
+/// Weight of the newest settled task in an agent's performance score
+const PERFORMANCE_SMOOTHING: f64 = 0.1;
+/// Score of an agent with no settled tasks yet
+const NEUTRAL_PERFORMANCE: f64 = 50.0;
+/// Finished tasks kept for status and dependency lookups
+const FINISHED_TASK_HISTORY: usize = 500;
+/// Agent that screens opportunities published on the bus
+const RISK_GATE_AGENT: &str = "void_sage";
+/// Opportunities reported with less confidence than this are vetoed
+const MIN_OPPORTUNITY_CONFIDENCE: f64 = 0.6;
 
 pub struct NeuralAgentOrchestrator {
-    pub active_tasks: HashMap<String, AgentTask>,
-    pub coordination_log: Vec<CoordinationMessage>,
+    pub task_queue: TaskQueue,
+    pub bus: Arc<AgentBus>,
     pub agent_performance: HashMap<String, f64>,
     pub neural_connections: HashMap<String, Vec<String>>,
     pub quantum_entanglement_level: f64,
@@ -35,8 +31,8 @@ pub struct NeuralAgentOrchestrator {
 impl NeuralAgentOrchestrator {
     pub async fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let mut orchestrator = Self {
-            active_tasks: HashMap::new(),
-            coordination_log: Vec::new(),
+            task_queue: TaskQueue::new(),
+            bus: Arc::new(AgentBus::from_env()),
             agent_performance: HashMap::new(),
             neural_connections: HashMap::new(),
             quantum_entanglement_level: 0.95,
@@ -64,7 +60,26 @@ impl NeuralAgentOrchestrator {
                 agent.to_string(),
                 connected_agents.into_iter().map(|s| s.to_string()).collect(),
             );
-            self.agent_performance.insert(agent.to_string(), 97.0 + rand::random::<f64>() * 2.5);
+        }
+
+        // Specialists, with dark_diamond as the generalist that takes anything
+        let capabilities = vec![
+            ("quantum_phoenix", vec![TaskKind::TransformerOptimization]),
+            ("ghostwire", vec![TaskKind::StrategyAnalysis]),
+            ("neuro_vault", vec![TaskKind::PatternRecognition]),
+            ("void_sage", vec![TaskKind::RiskAssessment]),
+            ("fibro_x", vec![TaskKind::ProfitMaximization]),
+            ("cipher_oracle", vec![TaskKind::PatternRecognition, TaskKind::StrategyAnalysis]),
+            ("flash_hustle", vec![TaskKind::ProfitMaximization, TaskKind::RiskAssessment]),
+            ("dark_diamond", TaskKind::ALL.to_vec()),
+        ];
+
+        for (agent, capabilities) in capabilities {
+            self.register_agent(AgentProfile {
+                id: agent.to_string(),
+                capabilities,
+                max_concurrent: 2,
+            });
         }
 
         println!("🧠 Neural agent network initialized with {} agents", self.neural_connections.len());
@@ -78,120 +93,255 @@ impl NeuralAgentOrchestrator {
         self.optimize_agent_assignments().await;
     }
 
-    async fn process_pending_tasks(&mut self) {
-        let mut completed_tasks = Vec::new();
-
-        for (task_id, task) in &mut self.active_tasks {
-            task.progress += 5.0 + rand::random::<f64>() * 10.0;
-            
-            if task.progress >= 100.0 {
-                task.status = "completed".to_string();
-                completed_tasks.push(task_id.clone());
-                
-                // Update agent performance
-                if let Some(performance) = self.agent_performance.get_mut(&task.agent_id) {
-                    *performance = (*performance * 0.95 + 99.0 * 0.05).min(99.9);
-                }
+    /// Registers an agent, or updates its capabilities and concurrency limit
+    pub fn register_agent(&mut self, profile: AgentProfile) {
+        self.agent_performance.entry(profile.id.clone()).or_insert(NEUTRAL_PERFORMANCE);
+        self.task_queue.register_agent(profile);
+    }
+
+    /// Connects whatever executes an agent's tasks. Assignments arrive on the
+    /// returned worker and its results feed the agent's performance score.
+    pub fn attach_worker(&mut self, agent_id: &str) -> anyhow::Result<AgentWorker> {
+        self.task_queue.attach_worker(agent_id)
+    }
+
+    /// Runs `handler` on each task assigned to `agent_id`, one at a time,
+    /// and reports its result back to the queue
+    pub fn spawn_worker<F, Fut>(&mut self, agent_id: &str, handler: F) -> anyhow::Result<()>
+    where
+        F: Fn(AgentTask) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = TaskResult> + Send,
+    {
+        let mut worker = self.attach_worker(agent_id)?;
+        tokio::spawn(async move {
+            while let Some(task) = worker.assignments.recv().await {
+                let result = handler(task.clone()).await;
+                worker.finish(&task, result);
             }
-        }
+        });
+        Ok(())
+    }
 
-        // Remove completed tasks
-        for task_id in completed_tasks {
-            self.active_tasks.remove(&task_id);
+    pub fn submit_task(&mut self, spec: TaskSpec) -> anyhow::Result<String> {
+        let kind = spec.kind;
+        let id = self.task_queue.submit(spec, now_ms())?;
+        println!("📋 Queued {:?} task {}", kind, id);
+        Ok(id)
+    }
+
+    /// Puts a worker behind every agent that can assess risk, reviewing the
+    /// executions queued with `submit_execution_review`
+    pub fn start_execution_reviewers(&mut self) -> anyhow::Result<usize> {
+        let reviewers: Vec<String> = self
+            .task_queue
+            .agent_profiles()
+            .filter(|profile| profile.capabilities.contains(&TaskKind::RiskAssessment))
+            .map(|profile| profile.id.clone())
+            .collect();
+        for agent_id in &reviewers {
+            self.spawn_worker(agent_id, review_execution)?;
         }
+        println!("🛡️ {} agents reviewing failed executions", reviewers.len());
+        Ok(reviewers.len())
     }
 
-    async fn update_neural_connections(&mut self) {
-        // Strengthen neural connections based on successful collaborations
-        self.quantum_entanglement_level = (self.quantum_entanglement_level * 1.001).min(0.999);
-        
-        // Send coordination messages between connected agents
-        let agents: Vec<String> = self.neural_connections.keys().cloned().collect();
-        
-        for agent in &agents {
-            if let Some(connected_agents) = self.neural_connections.get(agent) {
-                for connected_agent in connected_agents {
-                    if rand::random::<f64>() < 0.1 { // 10% chance of message
-                        let message = CoordinationMessage {
-                            from_agent: agent.clone(),
-                            to_agent: connected_agent.clone(),
-                            message_type: "coordination".to_string(),
-                            content: format!("Neural sync update from {}", agent),
-                            timestamp: chrono::Utc::now().timestamp() as u64,
-                            priority: 3,
-                        };
-                        
-                        self.coordination_log.push(message);
-                    }
+    /// Has the risk specialist veto every published opportunity that fails
+    /// `screen_opportunity`; vetoes are broadcast on `MessageTopic::RiskVeto`
+    pub fn start_risk_gate(&self) {
+        let bus = self.bus();
+        let mut opportunities = bus.subscribe(RISK_GATE_AGENT, &[MessageTopic::Opportunity]);
+        tokio::spawn(async move {
+            while let Some(envelope) = opportunities.recv().await {
+                if let Some(veto) = screen_opportunity(&envelope.message, now_ms()) {
+                    bus.publish(RISK_GATE_AGENT, veto, envelope.priority).await;
                 }
             }
-        }
+        });
+        println!("🛡️ {} screening published opportunities", RISK_GATE_AGENT);
+    }
 
-        // Keep only recent messages (last 100)
-        if self.coordination_log.len() > 100 {
-            self.coordination_log.drain(0..self.coordination_log.len() - 100);
-        }
+    /// Queues a risk review of a failed execution
+    pub fn submit_execution_review(&mut self, result: &ExecutionResult) -> anyhow::Result<String> {
+        let spec = TaskSpec::new(TaskKind::RiskAssessment, format!("Review failed {} execution", result.strategy))
+            .with_priority(7)
+            .with_timeout(10_000)
+            .with_input(serde_json::to_value(result)?);
+        self.submit_task(spec)
     }
 
-    async fn optimize_agent_assignments(&mut self) {
-        // Assign new tasks to best performing agents
-        let task_types = vec![
-            "transformer_optimization",
-            "strategy_analysis", 
-            "pattern_recognition",
-            "risk_assessment",
-            "profit_maximization",
-        ];
+    async fn process_pending_tasks(&mut self) {
+        let now = now_ms();
+        let mut settlements = self.task_queue.poll_reports(now);
+        settlements.extend(self.task_queue.expire(now));
 
-        for task_type in task_types {
-            if rand::random::<f64>() < 0.2 { // 20% chance of new task
-                let best_agent = self.find_best_agent_for_task(task_type);
-                
-                let task = AgentTask {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    agent_id: best_agent.clone(),
-                    task_type: task_type.to_string(),
-                    description: format!("Neural optimization task: {}", task_type),
-                    priority: 5,
-                    estimated_completion: 300, // 5 minutes
-                    status: "active".to_string(),
-                    progress: 0.0,
-                };
-                
-                self.active_tasks.insert(task.id.clone(), task);
-            }
+        for settlement in &settlements {
+            self.apply_settlement(settlement);
         }
+        self.task_queue.prune_finished(FINISHED_TASK_HISTORY);
     }
 
-    fn find_best_agent_for_task(&self, task_type: &str) -> String {
-        // Match task types to specialized agents
-        let specialist = match task_type {
-            "transformer_optimization" => "quantum_phoenix",
-            "strategy_analysis" => "ghostwire",
-            "pattern_recognition" => "neuro_vault",
-            "risk_assessment" => "void_sage",
-            "profit_maximization" => "fibro_x",
-            _ => "dark_diamond",
+    /// Moves the agent's score towards 100 × the reported quality on success
+    /// and towards 0 on a failed or timed-out attempt
+    fn apply_settlement(&mut self, settlement: &TaskSettlement) {
+        let target = match &settlement.outcome {
+            SettlementOutcome::Succeeded { score } => score * 100.0,
+            SettlementOutcome::Retrying { .. } | SettlementOutcome::Failed { .. } => 0.0,
         };
+        let performance = self
+            .agent_performance
+            .entry(settlement.agent_id.clone())
+            .or_insert(NEUTRAL_PERFORMANCE);
+        *performance = *performance * (1.0 - PERFORMANCE_SMOOTHING) + target * PERFORMANCE_SMOOTHING;
+
+        match &settlement.outcome {
+            SettlementOutcome::Succeeded { score } => println!(
+                "✅ {} completed {:?} task {} (score {:.2}, performance {:.1})",
+                settlement.agent_id, settlement.kind, settlement.task_id, score, performance
+            ),
+            SettlementOutcome::Retrying { error } => println!(
+                "🔁 {} failed {:?} task {} ({}), requeued",
+                settlement.agent_id, settlement.kind, settlement.task_id, error
+            ),
+            SettlementOutcome::Failed { error } => println!(
+                "❌ {} failed {:?} task {} for good ({})",
+                settlement.agent_id, settlement.kind, settlement.task_id, error
+            ),
+        }
+    }
+
+    async fn update_neural_connections(&mut self) {
+        // Strengthen neural connections based on successful collaborations
+        self.quantum_entanglement_level = (self.quantum_entanglement_level * 1.001).min(0.999);
+    }
 
-        specialist.to_string()
+    async fn optimize_agent_assignments(&mut self) {
+        // Hand ready tasks to the best performing capable agents
+        for (task_id, agent_id) in self.task_queue.assign(&self.agent_performance, now_ms()) {
+            println!("🎯 Assigned task {} to {}", task_id, agent_id);
+        }
     }
 
     pub fn get_coordination_status(&self) -> serde_json::Value {
+        let bus_stats = self.bus.stats();
         serde_json::json!({
-            "activeTasks": self.active_tasks.len(),
-            "totalMessages": self.coordination_log.len(),
+            "activeTasks": self.task_queue.count(TaskStatus::Running),
+            "pendingTasks": self.task_queue.count(TaskStatus::Pending),
+            "failedTasks": self.task_queue.count(TaskStatus::Failed) + self.task_queue.count(TaskStatus::Expired),
+            "totalMessages": bus_stats.published,
+            "droppedMessages": bus_stats.dropped,
             "quantumEntanglement": self.quantum_entanglement_level,
             "averagePerformance": self.agent_performance.values().sum::<f64>() / self.agent_performance.len() as f64,
             "neuralConnections": self.neural_connections.len()
         })
     }
 
+    /// Shared handle agents publish and subscribe through
+    pub fn bus(&self) -> Arc<AgentBus> {
+        Arc::clone(&self.bus)
+    }
+
     pub fn get_agent_performance(&self) -> &HashMap<String, f64> {
         &self.agent_performance
     }
 
-    pub fn get_active_tasks(&self) -> &HashMap<String, AgentTask> {
-        &self.active_tasks
+    pub fn get_active_tasks(&self) -> Vec<&AgentTask> {
+        self.task_queue.tasks().filter(|t| !t.status.is_terminal()).collect()
     }
-}
\ No newline at end of file
+}
+
+/// Summarises what a failed execution cost
+async fn review_execution(task: AgentTask) -> TaskResult {
+    match serde_json::from_value::<ExecutionResult>(task.input) {
+        Ok(result) => TaskResult::Succeeded {
+            score: 1.0,
+            detail: format!(
+                "{} failed after {}ms, {:.6} SOL in gas, {:.6} SOL realised",
+                result.strategy, result.execution_time_ms, result.gas_used, result.profit
+            ),
+        },
+        Err(e) => TaskResult::Failed {
+            error: format!("not an execution result: {}", e),
+        },
+    }
+}
+
+/// The veto for an opportunity that is unprofitable, expired or below
+/// `MIN_OPPORTUNITY_CONFIDENCE`, if it is any of those
+fn screen_opportunity(message: &AgentMessage, now_ms: u64) -> Option<AgentMessage> {
+    let AgentMessage::OpportunityFound {
+        opportunity_id,
+        expected_profit_sol,
+        confidence,
+        expires_at_ms,
+        ..
+    } = message
+    else {
+        return None;
+    };
+
+    let reason = if *expected_profit_sol <= 0.0 {
+        format!("expected profit {:.6} SOL is not positive", expected_profit_sol)
+    } else if expires_at_ms.is_some_and(|expires| expires <= now_ms) {
+        "expired before it was screened".to_string()
+    } else if *confidence < MIN_OPPORTUNITY_CONFIDENCE {
+        format!("confidence {:.2} below {:.2}", confidence, MIN_OPPORTUNITY_CONFIDENCE)
+    } else {
+        return None;
+    };
+    Some(AgentMessage::RiskVeto {
+        opportunity_id: opportunity_id.clone(),
+        reason,
+        max_exposure_sol: None,
+    })
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opportunity(profit: f64, confidence: f64, expires_at_ms: Option<u64>) -> AgentMessage {
+        AgentMessage::OpportunityFound {
+            opportunity_id: "op-1".to_string(),
+            strategy: "cross_dex".to_string(),
+            market: "SOL/USDC".to_string(),
+            expected_profit_sol: profit,
+            confidence,
+            expires_at_ms,
+        }
+    }
+
+    #[test]
+    fn screens_out_unprofitable_expired_and_unconfident_opportunities() {
+        assert!(screen_opportunity(&opportunity(0.5, 0.9, Some(2_000)), 1_000).is_none());
+        assert!(screen_opportunity(&opportunity(0.5, 0.9, None), 1_000).is_none());
+
+        for rejected in [
+            opportunity(0.0, 0.9, None),
+            opportunity(0.5, 0.9, Some(1_000)),
+            opportunity(0.5, 0.3, None),
+        ] {
+            match screen_opportunity(&rejected, 1_000) {
+                Some(AgentMessage::RiskVeto { opportunity_id, .. }) => assert_eq!(opportunity_id, "op-1"),
+                other => panic!("expected a veto, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn the_risk_gate_broadcasts_vetoes() {
+        let orchestrator = NeuralAgentOrchestrator::new().await.unwrap();
+        let mut vetoes = orchestrator.bus.subscribe("strategist", &[MessageTopic::RiskVeto]);
+        orchestrator.start_risk_gate();
+
+        orchestrator.bus.publish("scanner", opportunity(0.5, 0.9, None), 5).await;
+        orchestrator.bus.publish("scanner", opportunity(0.5, 0.1, None), 5).await;
+
+        let veto = tokio::time::timeout(Duration::from_secs(1), vetoes.recv()).await.unwrap().unwrap();
+        assert_eq!(veto.from, RISK_GATE_AGENT);
+        assert!(vetoes.try_recv().is_none());
+    }
+}
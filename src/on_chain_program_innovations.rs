@@ -5,10 +5,8 @@
 
 use solana_sdk::{
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    transaction::Transaction,
+    signature::Signature,
     instruction::Instruction,
-    system_instruction,
     commitment_config::CommitmentConfig,
 };
 use solana_client::nonblocking::rpc_client::RpcClient;
@@ -18,19 +16,28 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use anyhow::Result;
 use tokio::time::interval;
+use black_diamond_client::types::{BotPerformance, QuantumBoostResult};
+use crate::atomic_arb_client::{is_deployed, ATOMIC_ARB_PROGRAM_ID};
+use crate::bot_registry::{bot_job, BotJob, BotRegistry, BotStatus, BotTick};
+use crate::pool_ingestion::PoolIngestion;
+use crate::price_oracle::PriceOracle;
+use crate::program_deploy::ProgramDeployer;
+
+/// Bots whose findings the transaction pipeline executes; it books the
+/// results to the trade ledger under these ids
+pub const BUNDLE_CAPTURE_BOT: &str = "bundle_capture_bot";
+pub const ARBITRAGE_SCANNER_BOT: &str = "arbitrage_scanner_bot";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OnChainBot {
     pub id: String,
     pub name: String,
     pub bot_type: BotType,
-    pub program_id: Pubkey,
-    pub update_frequency: u64, // milliseconds
-    pub data_accuracy: f64,
-    pub gas_optimization: f64,
-    pub is_active: bool,
+    /// Set once the bot's own program is deployed; until then it runs
+    /// off-chain against RPC
+    pub program_id: Option<Pubkey>,
+    pub update_frequency: u64, // milliseconds between worker runs
     pub deployment_cost: f64,
-    pub daily_revenue: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,12 +99,16 @@ pub struct InformationRelayNode {
 pub struct OnChainProgramInnovations {
     connection: Arc<RpcClient>,
     deployed_bots: Arc<Mutex<HashMap<String, OnChainBot>>>,
+    bot_registry: Arc<BotRegistry>,
+    /// Workers supplied by other components, by bot id
+    bot_jobs: HashMap<String, BotJob>,
     speed_programs: Arc<Mutex<HashMap<String, SpeedOptimizationProgram>>>,
     bundle_strategies: Arc<Mutex<HashMap<String, BundleCaptureStrategy>>>,
     price_feeds: Arc<Mutex<HashMap<String, PriceFeedInnovation>>>,
     relay_nodes: Arc<Mutex<HashMap<String, InformationRelayNode>>>,
     total_revenue: Arc<Mutex<f64>>,
     is_active: Arc<Mutex<bool>>,
+    deployer: Option<Arc<ProgramDeployer>>,
 }
 
 impl OnChainProgramInnovations {
@@ -105,15 +116,47 @@ impl OnChainProgramInnovations {
         Self {
             connection,
             deployed_bots: Arc::new(Mutex::new(HashMap::new())),
+            bot_registry: Arc::new(BotRegistry::new()),
+            bot_jobs: HashMap::new(),
             speed_programs: Arc::new(Mutex::new(HashMap::new())),
             bundle_strategies: Arc::new(Mutex::new(HashMap::new())),
             price_feeds: Arc::new(Mutex::new(HashMap::new())),
             relay_nodes: Arc::new(Mutex::new(HashMap::new())),
             total_revenue: Arc::new(Mutex::new(0.0)),
             is_active: Arc::new(Mutex::new(true)),
+            deployer: None,
         }
     }
 
+    /// Checks deployments against the deployer's manifest instead of only
+    /// asking whether the program account is executable
+    pub fn with_deployer(mut self, deployer: Arc<ProgramDeployer>) -> Self {
+        self.deployer = Some(deployer);
+        self
+    }
+
+    /// Shares a registry, and with it its trade ledger, with other components
+    pub fn with_bot_registry(mut self, registry: Arc<BotRegistry>) -> Self {
+        self.bot_registry = registry;
+        self
+    }
+
+    /// Runs `job` as the worker for `bot_id` instead of the built-in one
+    pub fn with_bot_job(mut self, bot_id: &str, job: BotJob) -> Self {
+        self.bot_jobs.insert(bot_id.to_string(), job);
+        self
+    }
+
+    pub fn bot_registry(&self) -> Arc<BotRegistry> {
+        Arc::clone(&self.bot_registry)
+    }
+
+    /// Books a realised trade to a bot that the shared ledger does not
+    /// already see
+    pub fn record_bot_trade(&self, bot_id: &str, pnl: f64, capital: f64) {
+        self.bot_registry.record_trade(bot_id, pnl, capital);
+    }
+
     pub async fn initialize_innovations(&self) -> Result<()> {
         println!("🚀 Initializing On-Chain Program Innovations...");
         
@@ -356,172 +399,270 @@ impl OnChainProgramInnovations {
         }
     }
 
+    /// Registers every bot with the registry and starts those that have a
+    /// worker. Bots without one stay idle.
     async fn deploy_on_chain_bots(&self) {
-        let mut bots = self.deployed_bots.lock().unwrap();
-
-        // Ultra-Speed Price Oracle Bot
-        bots.insert("price_oracle_bot".to_string(), OnChainBot {
-            id: "price_oracle_bot".to_string(),
-            name: "Ultra-Speed Price Oracle Bot".to_string(),
-            bot_type: BotType::PriceOracle,
-            program_id: "PriceOracle1111111111111111111111111111111".parse().unwrap(),
-            update_frequency: 10, // 10ms updates
-            data_accuracy: 99.95,
-            gas_optimization: 89.7,
-            is_active: true,
-            deployment_cost: 5.0,
-            daily_revenue: 47.8,
-        });
-
-        // Bundle Capture Specialist
-        bots.insert("bundle_capture_bot".to_string(), OnChainBot {
-            id: "bundle_capture_bot".to_string(),
-            name: "Bundle Capture Specialist".to_string(),
-            bot_type: BotType::BundleCapture,
-            program_id: "BundleCapture111111111111111111111111111".parse().unwrap(),
-            update_frequency: 5, // 5ms scanning
-            data_accuracy: 97.8,
-            gas_optimization: 94.2,
-            is_active: true,
-            deployment_cost: 8.5,
-            daily_revenue: 124.7,
-        });
-
-        // MEV Detection Engine
-        bots.insert("mev_detector_bot".to_string(), OnChainBot {
-            id: "mev_detector_bot".to_string(),
-            name: "MEV Detection Engine".to_string(),
-            bot_type: BotType::MEVDetector,
-            program_id: "MEVDetector111111111111111111111111111111".parse().unwrap(),
-            update_frequency: 1, // 1ms detection
-            data_accuracy: 98.9,
-            gas_optimization: 87.4,
-            is_active: true,
-            deployment_cost: 12.3,
-            daily_revenue: 234.6,
-        });
+        let bots = vec![
+            OnChainBot {
+                id: "price_oracle_bot".to_string(),
+                name: "Ultra-Speed Price Oracle Bot".to_string(),
+                bot_type: BotType::PriceOracle,
+                program_id: None,
+                update_frequency: 1000,
+                deployment_cost: 5.0,
+            },
+            OnChainBot {
+                id: BUNDLE_CAPTURE_BOT.to_string(),
+                name: "Bundle Capture Specialist".to_string(),
+                bot_type: BotType::BundleCapture,
+                program_id: None,
+                update_frequency: 400,
+                deployment_cost: 8.5,
+            },
+            OnChainBot {
+                id: "mev_detector_bot".to_string(),
+                name: "MEV Detection Engine".to_string(),
+                bot_type: BotType::MEVDetector,
+                program_id: None,
+                update_frequency: 2000,
+                deployment_cost: 12.3,
+            },
+            OnChainBot {
+                id: "liquidity_monitor_bot".to_string(),
+                name: "Liquidity Monitoring System".to_string(),
+                bot_type: BotType::LiquidityMonitor,
+                program_id: None,
+                update_frequency: 1000,
+                deployment_cost: 6.7,
+            },
+            OnChainBot {
+                id: "social_relay_bot".to_string(),
+                name: "Social Intelligence Relay".to_string(),
+                bot_type: BotType::SocialRelay,
+                program_id: None,
+                update_frequency: 5000,
+                deployment_cost: 4.2,
+            },
+            OnChainBot {
+                id: ARBITRAGE_SCANNER_BOT.to_string(),
+                name: "Arbitrage Scanner Pro".to_string(),
+                bot_type: BotType::ArbitrageScanner,
+                program_id: None,
+                update_frequency: 400,
+                deployment_cost: 15.8,
+            },
+        ];
 
-        // Liquidity Monitoring System
-        bots.insert("liquidity_monitor_bot".to_string(), OnChainBot {
-            id: "liquidity_monitor_bot".to_string(),
-            name: "Liquidity Monitoring System".to_string(),
-            bot_type: BotType::LiquidityMonitor,
-            program_id: "LiquidityMonitor1111111111111111111111111".parse().unwrap(),
-            update_frequency: 25, // 25ms monitoring
-            data_accuracy: 99.2,
-            gas_optimization: 82.1,
-            is_active: true,
-            deployment_cost: 6.7,
-            daily_revenue: 89.4,
-        });
+        for bot in &bots {
+            self.bot_registry.register(
+                &bot.id,
+                Duration::from_millis(bot.update_frequency),
+                self.bot_jobs.get(&bot.id).cloned().or_else(|| self.bot_job(&bot.bot_type)),
+            );
+        }
+        if let Ok(mut deployed) = self.deployed_bots.lock() {
+            deployed.extend(bots.into_iter().map(|bot| (bot.id.clone(), bot)));
+        }
 
-        // Social Intelligence Relay
-        bots.insert("social_relay_bot".to_string(), OnChainBot {
-            id: "social_relay_bot".to_string(),
-            name: "Social Intelligence Relay".to_string(),
-            bot_type: BotType::SocialRelay,
-            program_id: "SocialRelay111111111111111111111111111111".parse().unwrap(),
-            update_frequency: 100, // 100ms social updates
-            data_accuracy: 94.6,
-            gas_optimization: 76.8,
-            is_active: true,
-            deployment_cost: 4.2,
-            daily_revenue: 67.3,
-        });
+        let started = self.bot_registry.start_all();
+        println!("🤖 On-chain bots: {} workers started", started);
+    }
 
-        // Arbitrage Scanner Pro
-        bots.insert("arbitrage_scanner_bot".to_string(), OnChainBot {
-            id: "arbitrage_scanner_bot".to_string(),
-            name: "Arbitrage Scanner Pro".to_string(),
-            bot_type: BotType::ArbitrageScanner,
-            program_id: "ArbitrageScanner111111111111111111111111".parse().unwrap(),
-            update_frequency: 2, // 2ms scanning
-            data_accuracy: 98.4,
-            gas_optimization: 91.7,
-            is_active: true,
-            deployment_cost: 15.8,
-            daily_revenue: 347.9,
-        });
+    /// The worker behind each bot type, where there is a data source for it
+    fn bot_job(&self, bot_type: &BotType) -> Option<BotJob> {
+        let connection = Arc::clone(&self.connection);
+        match bot_type {
+            BotType::PriceOracle => {
+                let oracle = Arc::new(PriceOracle::with_default_feeds(connection));
+                Some(bot_job(move || {
+                    let oracle = Arc::clone(&oracle);
+                    async move {
+                        let mut events = 0;
+                        for mint in oracle.mints() {
+                            let quote = oracle.price(&mint).await?;
+                            if !quote.is_reliable() {
+                                return Err(anyhow::anyhow!("Unreliable price for {}: {:?}", mint, quote.flags));
+                            }
+                            events += quote.sources.len() as u64;
+                        }
+                        Ok(BotTick { events, trade: None })
+                    }
+                }))
+            }
+            BotType::LiquidityMonitor => {
+                let accounts: Vec<Pubkey> = PoolIngestion::default_pools().iter().flat_map(|p| p.accounts()).collect();
+                Some(bot_job(move || {
+                    let connection = Arc::clone(&connection);
+                    let accounts = accounts.clone();
+                    async move {
+                        let found = connection.get_multiple_accounts(&accounts).await?;
+                        let missing = found.iter().filter(|a| a.is_none()).count();
+                        if missing > 0 {
+                            return Err(anyhow::anyhow!("{} watched pool accounts are missing", missing));
+                        }
+                        Ok(BotTick { events: found.len() as u64, trade: None })
+                    }
+                }))
+            }
+            BotType::MEVDetector => {
+                Some(bot_job(move || {
+                    let connection = Arc::clone(&connection);
+                    async move {
+                        let fees = connection.get_recent_prioritization_fees(&[]).await?;
+                        Ok(BotTick { events: fees.len() as u64, trade: None })
+                    }
+                }))
+            }
+            // Bundle capture and arbitrage scanning run on the pipeline's
+            // data and are supplied with `with_bot_job`; social relay has no
+            // feed yet
+            BotType::BundleCapture | BotType::ArbitrageScanner | BotType::SocialRelay => None,
+        }
     }
 
     // Advanced deployment methods
+    /// Speed programs execute through the atomic-arb program, so deploying
+    /// one means confirming that program is live on the connected cluster
     pub async fn deploy_speed_optimization_program(&self, program_id: String) -> Result<bool> {
-        if let Ok(programs) = self.speed_programs.lock() {
-            if let Some(program) = programs.get(&program_id) {
-                println!("🚀 Deploying Speed Optimization: {}", program.name);
-                
-                // Construct deployment transaction
-                let transaction = Transaction::new_with_payer(
-                    &[system_instruction::create_account(
-                        &Keypair::new().pubkey(),
-                        &Keypair::new().pubkey(),
-                        1_000_000,
-                        1024,
-                        &solana_sdk::bpf_loader::id(),
-                    )],
-                    Some(&Keypair::new().pubkey()),
-                );
-                
-                // In a real implementation, this would deploy the actual program
-                println!("✅ Speed Program Deployed: {}", program_id);
-                return Ok(true);
-            }
+        let name = match self.speed_programs.lock() {
+            Ok(programs) => match programs.get(&program_id) {
+                Some(program) => program.name.clone(),
+                None => return Ok(false),
+            },
+            Err(_) => return Ok(false),
+        };
+        println!("🚀 Deploying Speed Optimization: {}", name);
+
+        if !is_deployed(&self.connection, &ATOMIC_ARB_PROGRAM_ID).await? {
+            println!("❌ atomic-arb program {} is not deployed on this cluster", ATOMIC_ARB_PROGRAM_ID);
+            return Ok(false);
         }
-        Ok(false)
+        println!("✅ Speed Program Deployed: {} via atomic-arb {}", program_id, ATOMIC_ARB_PROGRAM_ID);
+        Ok(true)
     }
 
+    /// Bundles land through the atomic-arb program, so the strategy is live
+    /// once that program is deployed and, when the manifest records it, verified
     pub async fn deploy_bundle_capture_strategy(&self, strategy_id: String) -> Result<bool> {
-        if let Ok(strategies) = self.bundle_strategies.lock() {
-            if let Some(strategy) = strategies.get(&strategy_id) {
-                println!("🎯 Deploying Bundle Capture: {}", strategy.name);
-                // Implementation would include smart contract deployment
-                return Ok(true);
-            }
+        let name = match self.bundle_strategies.lock() {
+            Ok(strategies) => match strategies.get(&strategy_id) {
+                Some(strategy) => strategy.name.clone(),
+                None => return Ok(false),
+            },
+            Err(_) => return Ok(false),
+        };
+        println!("🎯 Deploying Bundle Capture: {}", name);
+
+        // A program deployed outside this manifest can't be verified, only found
+        let recorded = match &self.deployer {
+            Some(deployer) => deployer.manifest()?.program(deployer.cluster().name(), "atomic_arb").is_some(),
+            None => false,
+        };
+        let live = if recorded {
+            self.verified_program("atomic_arb").await?
+        } else {
+            is_deployed(&self.connection, &ATOMIC_ARB_PROGRAM_ID).await?
+        };
+        if !live {
+            println!("❌ atomic-arb program is not deployed on this cluster");
+            return Ok(false);
         }
-        Ok(false)
+        println!("✅ Bundle Capture Deployed: {}", strategy_id);
+        Ok(true)
     }
 
+    /// No program in `programs/` publishes price feeds, so there is nothing
+    /// to deploy for one yet
     pub async fn deploy_price_feed(&self, feed_id: String) -> Result<bool> {
-        if let Ok(feeds) = self.price_feeds.lock() {
-            if let Some(feed) = feeds.get(&feed_id) {
-                println!("📊 Deploying Price Feed: {}", feed.name);
-                // Implementation would include oracle deployment
-                return Ok(true);
-            }
-        }
-        Ok(false)
+        let name = match self.price_feeds.lock() {
+            Ok(feeds) => match feeds.get(&feed_id) {
+                Some(feed) => feed.name.clone(),
+                None => return Ok(false),
+            },
+            Err(_) => return Ok(false),
+        };
+        println!("📊 Deploying Price Feed: {}", name);
+
+        Err(anyhow::anyhow!("Price feed {} has no on-chain program to deploy", feed_id))
     }
 
+    /// No program in `programs/` relays information, so relay nodes cannot
+    /// be activated on-chain yet
     pub async fn activate_information_relay(&self, node_id: String) -> Result<bool> {
-        if let Ok(nodes) = self.relay_nodes.lock() {
-            if let Some(node) = nodes.get(&node_id) {
-                println!("🌐 Activating Relay Node: {}", node.location);
-                // Implementation would include network activation
-                return Ok(true);
+        let location = match self.relay_nodes.lock() {
+            Ok(nodes) => match nodes.get(&node_id) {
+                Some(node) => node.location.clone(),
+                None => return Ok(false),
+            },
+            Err(_) => return Ok(false),
+        };
+        println!("🌐 Activating Relay Node: {}", location);
+
+        Err(anyhow::anyhow!("Relay node {} has no on-chain program to activate", node_id))
+    }
+
+    async fn verified_program(&self, name: &str) -> Result<bool> {
+        let deployer = match &self.deployer {
+            Some(deployer) => deployer,
+            None => {
+                println!("❌ No deployment manifest configured; cannot verify {}", name);
+                return Ok(false);
             }
+        };
+        if deployer.manifest()?.program(deployer.cluster().name(), name).is_none() {
+            println!("❌ {} has no deployment on {}", name, deployer.cluster().name());
+            return Ok(false);
         }
-        Ok(false)
+
+        let report = deployer.verify(name).await?;
+        if !report.bytecode_matches {
+            println!(
+                "❌ {} bytecode mismatch: expected {}, found {}",
+                name, report.expected_sha256, report.onchain_sha256
+            );
+        }
+        if !report.authority_matches {
+            println!("❌ {} upgrade authority is {:?}, not the recorded one", name, report.onchain_authority);
+        }
+        Ok(report.is_verified())
     }
 
     // Monitoring and analytics
-    pub async fn get_bot_performance(&self) -> Vec<serde_json::Value> {
-        if let Ok(bots) = self.deployed_bots.lock() {
-            bots.values().map(|bot| {
-                let profit_margin = (bot.daily_revenue - bot.deployment_cost / 30.0) / bot.daily_revenue * 100.0;
-                serde_json::json!({
-                    "id": bot.id,
-                    "name": bot.name,
-                    "type": format!("{:?}", bot.bot_type),
-                    "accuracy": bot.data_accuracy,
-                    "gasOptimization": bot.gas_optimization,
-                    "dailyRevenue": bot.daily_revenue,
-                    "profitMargin": profit_margin,
-                    "isActive": bot.is_active
+    /// Measured health and ledger revenue for every registered bot.
+    /// `accuracy` is the share of successful worker runs.
+    pub async fn get_bot_performance(&self) -> Vec<BotPerformance> {
+        let bots: Vec<OnChainBot> = match self.deployed_bots.lock() {
+            Ok(bots) => bots.values().cloned().collect(),
+            Err(_) => return Vec::new(),
+        };
+
+        bots.into_iter()
+            .filter_map(|bot| {
+                let health = self.bot_registry.health(&bot.id)?;
+                let profit_margin = if health.revenue_24h > 0.0 {
+                    (health.revenue_24h - bot.deployment_cost / 30.0) / health.revenue_24h * 100.0
+                } else {
+                    0.0
+                };
+                Some(BotPerformance {
+                    id: bot.id,
+                    name: bot.name,
+                    bot_type: format!("{:?}", bot.bot_type),
+                    status: format!("{:?}", health.status),
+                    accuracy: (1.0 - health.error_rate) * 100.0,
+                    events_processed: health.events_processed,
+                    error_rate: health.error_rate,
+                    last_heartbeat: health.last_heartbeat,
+                    trades: health.trades_24h,
+                    daily_revenue: health.revenue_24h,
+                    profit_margin,
+                    is_active: matches!(
+                        health.status,
+                        BotStatus::Starting | BotStatus::Healthy | BotStatus::Degraded
+                    ),
                 })
-            }).collect()
-        } else {
-            Vec::new()
-        }
+            })
+            .collect()
     }
 
     pub async fn get_speed_optimizations(&self) -> Vec<SpeedOptimizationProgram> {
@@ -566,7 +707,9 @@ impl OnChainProgramInnovations {
 
     pub async fn get_system_overview(&self) -> serde_json::Value {
         let bots = if let Ok(b) = self.deployed_bots.lock() { b.clone() } else { HashMap::new() };
-        let total_daily_revenue: f64 = bots.values().map(|bot| bot.daily_revenue).sum();
+        let performance = self.get_bot_performance().await;
+        let total_daily_revenue: f64 = performance.iter().map(|bot| bot.daily_revenue).sum();
+        let healthy_bots = performance.iter().filter(|bot| bot.status == "Healthy").count();
         let total_deployment_cost: f64 = bots.values().map(|bot| bot.deployment_cost).sum();
         let speed_programs_count = self.speed_programs.lock().unwrap().len();
         let bundle_strategies_count = self.bundle_strategies.lock().unwrap().len();
@@ -580,7 +723,8 @@ impl OnChainProgramInnovations {
         };
 
         serde_json::json!({
-            "activeBots": bots.len(),
+            "activeBots": performance.iter().filter(|bot| bot.is_active).count(),
+            "healthyBots": healthy_bots,
             "totalDailyRevenue": total_daily_revenue,
             "totalDeploymentCost": total_deployment_cost,
             "profitMargin": profit_margin,
@@ -593,15 +737,15 @@ impl OnChainProgramInnovations {
     }
 
     // Advanced execution methods
-    pub async fn execute_quantum_speed_boost(&self) -> Result<serde_json::Value> {
+    pub async fn execute_quantum_speed_boost(&self) -> Result<QuantumBoostResult> {
         if let Ok(programs) = self.speed_programs.lock() {
             if let Some(quantum_program) = programs.get("quantum_executor") {
                 println!("⚡ Executing Quantum Speed Boost...");
                 
-                return Ok(serde_json::json!({
-                    "speedIncrease": quantum_program.speed_increase,
-                    "gasReduction": quantum_program.gas_reduction
-                }));
+                return Ok(QuantumBoostResult {
+                    speed_increase: quantum_program.speed_increase,
+                    gas_reduction: quantum_program.gas_reduction,
+                });
             }
         }
         Err(anyhow::anyhow!("Quantum Executor not found"))
@@ -660,6 +804,11 @@ impl OnChainProgramInnovations {
         if let Ok(mut is_active) = self.is_active.lock() {
             *is_active = active;
         }
+        if active {
+            self.bot_registry.start_all();
+        } else {
+            self.bot_registry.stop_all();
+        }
         println!("🚀 On-Chain Innovations: {}", if active { "ACTIVATED" } else { "DEACTIVATED" });
     }
 }
@@ -667,4 +816,77 @@ impl OnChainProgramInnovations {
 // Export for use in main application
 pub fn create_on_chain_innovations(connection: Arc<RpcClient>) -> OnChainProgramInnovations {
     OnChainProgramInnovations::new(connection)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program_deploy::Cluster;
+
+    fn innovations() -> OnChainProgramInnovations {
+        // Nothing listens here, so workers run and record their RPC errors
+        OnChainProgramInnovations::new(Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())))
+    }
+
+    #[tokio::test]
+    async fn deploys_every_bot_into_the_registry() {
+        let innovations = innovations();
+        innovations.deploy_on_chain_bots().await;
+
+        let performance = innovations.get_bot_performance().await;
+        assert_eq!(performance.len(), 6);
+        let relay = performance.iter().find(|bot| bot.id == "social_relay_bot").unwrap();
+        assert_eq!(relay.status, "Idle");
+        assert!(!relay.is_active);
+        let oracle = performance.iter().find(|bot| bot.id == "price_oracle_bot").unwrap();
+        assert!(oracle.is_active);
+
+        innovations.set_active(false);
+    }
+
+    #[tokio::test]
+    async fn daily_revenue_comes_from_the_ledger() {
+        let innovations = innovations();
+        innovations.deploy_on_chain_bots().await;
+        innovations.record_bot_trade("arbitrage_scanner_bot", 0.5, 10.0);
+        innovations.record_bot_trade("arbitrage_scanner_bot", -0.2, 10.0);
+
+        let performance = innovations.get_bot_performance().await;
+        let scanner = performance.iter().find(|bot| bot.id == "arbitrage_scanner_bot").unwrap();
+        assert_eq!(scanner.trades, 2);
+        assert!((scanner.daily_revenue - 0.3).abs() < 1e-9);
+
+        innovations.set_active(false);
+    }
+
+    #[tokio::test]
+    async fn feeds_and_relays_without_a_program_cannot_deploy() {
+        let innovations = innovations();
+        innovations.create_advanced_price_feeds().await;
+        innovations.create_information_relay_network().await;
+
+        let err = innovations.deploy_price_feed("quantum_oracle".to_string()).await.unwrap_err();
+        assert!(err.to_string().contains("no on-chain program"));
+        assert!(innovations.activate_information_relay("relay_0".to_string()).await.is_err());
+
+        assert!(!innovations.deploy_price_feed("missing".to_string()).await.unwrap());
+        assert!(!innovations.activate_information_relay("missing".to_string()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn bundle_capture_without_a_recorded_program_reports_an_unreachable_cluster() {
+        let manifest = std::env::temp_dir().join(format!("bundle_capture_manifest_{}.json", std::process::id()));
+        let deployer = ProgramDeployer::new(
+            Cluster::parse("http://127.0.0.1:1"),
+            std::env::temp_dir(),
+            manifest,
+        );
+        let innovations = innovations().with_deployer(Arc::new(deployer));
+        innovations.create_bundle_capture_strategies().await;
+
+        // The empty manifest falls back to looking the program up, which
+        // must fail rather than report it as not deployed
+        assert!(innovations.deploy_bundle_capture_strategy("mev_interceptor".to_string()).await.is_err());
+        assert!(!innovations.deploy_bundle_capture_strategy("missing".to_string()).await.unwrap());
+    }
+}
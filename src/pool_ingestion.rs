@@ -0,0 +1,940 @@
+/**
+ * POOL UPDATE INGESTION
+ * Streams pool and vault accounts over accountSubscribe/programSubscribe (or
+ * any gRPC feed pushed into it), drops repeats by slot and publishes typed
+ * pool updates, resubscribing whenever the stream drops
+ */
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::{sink::SinkExt, stream::StreamExt};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::solana_program::program_pack::Pack;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::account_cache::{AccountCache, Commitment, ReadRequirement};
+use crate::strategy::{MarketEvent, Side, SimulationSession};
+
+const WHIRLPOOL_SQRT_PRICE_OFFSET: usize = 65;
+/// Share of base reserves that moves a constant-product price by about 1%
+const DEPTH_FRACTION: f64 = 0.005;
+/// Stream id for updates pushed in through `raw_sender`; websocket
+/// connections are numbered from 1
+const EXTERNAL_STREAM: u64 = 0;
+
+#[derive(Debug, Clone)]
+pub enum PoolLayout {
+    /// Reserves come from the pool's two SPL token vaults
+    VaultPair { base_vault: Pubkey, quote_vault: Pubkey },
+    /// Price comes from the Orca Whirlpool account's sqrt price
+    Whirlpool { pool: Pubkey },
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchedPool {
+    pub id: String,
+    pub venue: String,
+    /// `BASE/QUOTE`, as strategies name markets
+    pub market: String,
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    pub fee_bps: f64,
+    pub layout: PoolLayout,
+}
+
+impl WatchedPool {
+    pub fn accounts(&self) -> Vec<Pubkey> {
+        match &self.layout {
+            PoolLayout::VaultPair { base_vault, quote_vault } => vec![*base_vault, *quote_vault],
+            PoolLayout::Whirlpool { pool } => vec![*pool],
+        }
+    }
+}
+
+/// Extra program-wide subscription; only notifications for watched accounts
+/// are used
+#[derive(Debug, Clone)]
+pub struct ProgramWatch {
+    pub program_id: Pubkey,
+    /// RPC `filters`, e.g. `{"dataSize": 165}`
+    pub filters: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamSource {
+    AccountSubscribe,
+    ProgramSubscribe,
+    /// Pushed in through `raw_sender`, e.g. from a Yellowstone gRPC client
+    External,
+    /// Read over RPC once a websocket connection is subscribed
+    RpcFetch,
+}
+
+#[derive(Debug, Clone)]
+pub struct RawAccountUpdate {
+    pub pubkey: Pubkey,
+    pub slot: u64,
+    pub data: Vec<u8>,
+    pub source: StreamSource,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolUpdate {
+    pub pool_id: String,
+    pub venue: String,
+    pub market: String,
+    pub slot: u64,
+    pub mid_price: f64,
+    /// Known for vault-pair pools only
+    pub base_reserve: Option<f64>,
+    pub quote_reserve: Option<f64>,
+    pub fee_bps: f64,
+    pub source: StreamSource,
+    pub received_at_ms: u64,
+}
+
+impl PoolUpdate {
+    /// The update as the quote strategies consume, priced either side of mid
+    /// by the pool fee
+    pub fn to_market_event(&self) -> MarketEvent {
+        let fee = self.fee_bps / 10_000.0;
+        MarketEvent::Quote {
+            venue: self.venue.clone(),
+            market: self.market.clone(),
+            bid: self.mid_price * (1.0 - fee),
+            ask: self.mid_price * (1.0 + fee),
+            depth: self.base_reserve.map(|r| r * DEPTH_FRACTION).unwrap_or(0.0),
+            timestamp_ms: self.received_at_ms,
+        }
+    }
+}
+
+/// Pool prices all read as of `slot`
+#[derive(Debug, Clone)]
+pub struct PoolSnapshot {
+    pub slot: u64,
+    /// Pool id -> (mid price, fee bps)
+    pub prices: HashMap<String, (f64, f64)>,
+}
+
+impl PoolSnapshot {
+    /// Units of the starting asset returned per unit put through `legs`
+    /// after fees. Buying spends quote for base; selling the reverse.
+    pub fn cycle_rate(&self, legs: &[(&str, Side)]) -> Result<f64> {
+        legs.iter().try_fold(1.0, |amount, (pool_id, side)| {
+            let (mid, fee_bps) = self
+                .prices
+                .get(*pool_id)
+                .ok_or_else(|| anyhow!("{} not in snapshot", pool_id))?;
+            let after_fee = amount * (1.0 - fee_bps / 10_000.0);
+            Ok(match side {
+                Side::Buy => after_fee / mid,
+                Side::Sell => after_fee * mid,
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IngestionConfig {
+    pub ws_url: String,
+    pub commitment: String,
+    pub programs: Vec<ProgramWatch>,
+    /// A stream silent for this long is treated as dead
+    pub idle_timeout: Duration,
+    pub reconnect_initial: Duration,
+    pub reconnect_max: Duration,
+    pub channel_capacity: usize,
+}
+
+impl IngestionConfig {
+    pub fn new(ws_url: &str) -> Self {
+        IngestionConfig {
+            ws_url: ws_url.to_string(),
+            commitment: "confirmed".to_string(),
+            programs: Vec::new(),
+            idle_timeout: Duration::from_secs(30),
+            reconnect_initial: Duration::from_millis(500),
+            reconnect_max: Duration::from_secs(30),
+            channel_capacity: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IngestionStats {
+    pub received: u64,
+    pub duplicates: u64,
+    pub decode_errors: u64,
+    pub published: u64,
+    pub reconnects: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AccountRole {
+    BaseVault,
+    QuoteVault,
+    Whirlpool,
+}
+
+/// Latest vault balances of a vault-pair pool and the slots they were read at
+#[derive(Debug, Default)]
+struct PoolState {
+    base_amount: Option<(u64, u64)>,
+    quote_amount: Option<(u64, u64)>,
+}
+
+/// How an update reached the decoder
+#[derive(Debug, Clone, Copy)]
+enum Origin {
+    /// A notification on stream `n`, delivered in slot order with the others
+    /// on that stream
+    Stream(u64),
+    /// A one-off read taken after stream `n` was subscribed; it says nothing
+    /// about the other accounts
+    Fetch(u64),
+}
+
+#[derive(Default)]
+struct IngestState {
+    /// Websocket connections opened so far
+    connection: u64,
+    last_slot: HashMap<Pubkey, u64>,
+    /// Stream each account was last heard from on
+    live_on: HashMap<Pubkey, u64>,
+    pools: HashMap<usize, PoolState>,
+    stats: IngestionStats,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+/// Mid price of a Whirlpool from its Q64.64 sqrt price
+pub fn decode_whirlpool_price(data: &[u8], base_decimals: u8, quote_decimals: u8) -> Result<f64> {
+    let end = WHIRLPOOL_SQRT_PRICE_OFFSET + 16;
+    if data.len() < end {
+        return Err(anyhow!("Whirlpool account too short: {} bytes", data.len()));
+    }
+    let sqrt_price = u128::from_le_bytes(data[WHIRLPOOL_SQRT_PRICE_OFFSET..end].try_into().unwrap());
+    let sqrt = sqrt_price as f64 / 2f64.powi(64);
+    Ok(sqrt * sqrt * 10f64.powi(base_decimals as i32 - quote_decimals as i32))
+}
+
+/// Steps `session` with every pool update until the sender closes. Updates
+/// missed while lagging are skipped: strategies trade on the next quote.
+pub async fn feed_session(mut updates: broadcast::Receiver<PoolUpdate>, session: Arc<Mutex<SimulationSession>>) {
+    loop {
+        match updates.recv().await {
+            Ok(update) => {
+                for fill in session.lock().step(&update.to_market_event()) {
+                    println!(
+                        "📝 Paper fill: {} {:?} {:.4} {} on {} @ {:.4}",
+                        fill.strategy_id, fill.side, fill.size, fill.market, fill.venue, fill.price
+                    );
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                println!("⚠️ Strategy feed lagged, skipped {} pool updates", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Turns account streams into `PoolUpdate`s for every watched pool
+pub struct PoolIngestion {
+    config: IngestionConfig,
+    pools: Vec<WatchedPool>,
+    cache: Option<Arc<AccountCache>>,
+    rpc: Option<Arc<RpcClient>>,
+    accounts: HashMap<Pubkey, (usize, AccountRole)>,
+    state: Mutex<IngestState>,
+    updates: broadcast::Sender<PoolUpdate>,
+    raw_tx: mpsc::Sender<RawAccountUpdate>,
+    raw_rx: Mutex<Option<mpsc::Receiver<RawAccountUpdate>>>,
+}
+
+impl PoolIngestion {
+    pub fn new(config: IngestionConfig, pools: Vec<WatchedPool>) -> Self {
+        let mut accounts = HashMap::new();
+        for (index, pool) in pools.iter().enumerate() {
+            match &pool.layout {
+                PoolLayout::VaultPair { base_vault, quote_vault } => {
+                    accounts.insert(*base_vault, (index, AccountRole::BaseVault));
+                    accounts.insert(*quote_vault, (index, AccountRole::QuoteVault));
+                }
+                PoolLayout::Whirlpool { pool } => {
+                    accounts.insert(*pool, (index, AccountRole::Whirlpool));
+                }
+            }
+        }
+
+        let (updates, _) = broadcast::channel(config.channel_capacity.max(1));
+        let (raw_tx, raw_rx) = mpsc::channel(config.channel_capacity.max(1));
+        Self {
+            config,
+            pools,
+            cache: None,
+            rpc: None,
+            accounts,
+            state: Mutex::new(IngestState::default()),
+            updates,
+            raw_tx,
+            raw_rx: Mutex::new(Some(raw_rx)),
+        }
+    }
+
+    /// SOL/USDC on Raydium (vault pair) and Orca (whirlpool)
+    pub fn default_pools() -> Vec<WatchedPool> {
+        let pubkey = |s: &str| s.parse::<Pubkey>().expect("valid pool address");
+        vec![
+            WatchedPool {
+                id: "raydium_sol_usdc".to_string(),
+                venue: "raydium".to_string(),
+                market: "SOL/USDC".to_string(),
+                base_decimals: 9,
+                quote_decimals: 6,
+                fee_bps: 25.0,
+                layout: PoolLayout::VaultPair {
+                    base_vault: pubkey("DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz"),
+                    quote_vault: pubkey("HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz"),
+                },
+            },
+            WatchedPool {
+                id: "orca_sol_usdc".to_string(),
+                venue: "orca".to_string(),
+                market: "SOL/USDC".to_string(),
+                base_decimals: 9,
+                quote_decimals: 6,
+                fee_bps: 30.0,
+                layout: PoolLayout::Whirlpool {
+                    pool: pubkey("HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ"),
+                },
+            },
+        ]
+    }
+
+    /// Also writes every streamed account into `cache`, so pools can be read
+    /// as of one slot with `snapshot`
+    pub fn with_cache(mut self, cache: Arc<AccountCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Re-reads every watched account whenever the websocket (re)subscribes,
+    /// so changes missed while it was down are picked up and pools publish
+    /// without waiting for both vaults to move
+    pub fn with_rpc(mut self, rpc: Arc<RpcClient>) -> Self {
+        self.rpc = Some(rpc);
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PoolUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Where an external stream (such as a Yellowstone gRPC client) pushes
+    /// account updates; they are de-duplicated against the websocket ones
+    pub fn raw_sender(&self) -> mpsc::Sender<RawAccountUpdate> {
+        self.raw_tx.clone()
+    }
+
+    pub fn stats(&self) -> IngestionStats {
+        self.state.lock().stats.clone()
+    }
+
+    /// Spawns the websocket stream and the decoder. Only the first call
+    /// starts anything.
+    pub fn start(self: &Arc<Self>) {
+        let Some(mut raw_rx) = self.raw_rx.lock().take() else {
+            return;
+        };
+
+        let decoder = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Some(update) = raw_rx.recv().await {
+                decoder.publish(update, Origin::Stream(EXTERNAL_STREAM));
+            }
+        });
+
+        let streamer = Arc::clone(self);
+        tokio::spawn(async move {
+            streamer.run_websocket().await;
+        });
+
+        println!("🌊 Pool ingestion started for {} pools", self.pools.len());
+    }
+
+    fn commitment(&self) -> Commitment {
+        Commitment::parse(&self.config.commitment).unwrap_or(Commitment::Confirmed)
+    }
+
+    fn publish(&self, update: RawAccountUpdate, origin: Origin) {
+        if let Some(pool_update) = self.apply_from(update, origin) {
+            // No receivers is not an error; updates are just dropped
+            let _ = self.updates.send(pool_update);
+        }
+    }
+
+    /// Applies one externally streamed account update, returning the pool
+    /// update it produces. Updates at or below the last slot seen for the
+    /// account are dropped.
+    pub fn apply(&self, update: RawAccountUpdate) -> Option<PoolUpdate> {
+        self.apply_from(update, Origin::Stream(EXTERNAL_STREAM))
+    }
+
+    fn apply_from(&self, update: RawAccountUpdate, origin: Origin) -> Option<PoolUpdate> {
+        let (index, role) = *self.accounts.get(&update.pubkey)?;
+        let pool = &self.pools[index];
+        let mut state = self.state.lock();
+        state.stats.received += 1;
+
+        // Even a repeat shows the account is subscribed on this stream
+        let stream = match origin {
+            Origin::Stream(stream) | Origin::Fetch(stream) => stream,
+        };
+        state.live_on.insert(update.pubkey, stream);
+
+        let last = state.last_slot.get(&update.pubkey).copied();
+        if last.is_some_and(|slot| update.slot <= slot) {
+            state.stats.duplicates += 1;
+            return None;
+        }
+        state.last_slot.insert(update.pubkey, update.slot);
+
+        if let Some(cache) = &self.cache {
+            cache.insert(update.pubkey, update.slot, self.commitment(), update.data.clone(), None, None);
+            // Notifications on one stream arrive in slot order, so accounts
+            // last heard from on it are unchanged up to the slot before this
+            // one. Accounts last heard from on an earlier connection are not:
+            // whatever changed while it was down was never delivered.
+            if let Origin::Stream(stream) = origin {
+                for (pubkey, _) in state
+                    .live_on
+                    .iter()
+                    .filter(|(pubkey, live_on)| **live_on == stream && **pubkey != update.pubkey)
+                {
+                    cache.advance(pubkey, update.slot.saturating_sub(1));
+                }
+            }
+        }
+
+        let decoded = match role {
+            AccountRole::Whirlpool => {
+                decode_whirlpool_price(&update.data, pool.base_decimals, pool.quote_decimals).map(|price| (price, None, None))
+            }
+            AccountRole::BaseVault | AccountRole::QuoteVault => spl_token::state::Account::unpack(&update.data)
+                .map_err(|e| anyhow!("{}", e))
+                .map(|account| {
+                    let entry = state.pools.entry(index).or_default();
+                    match role {
+                        AccountRole::BaseVault => entry.base_amount = Some((account.amount, update.slot)),
+                        _ => entry.quote_amount = Some((account.amount, update.slot)),
+                    }
+                    (0.0, None, None)
+                }),
+        };
+        let (mut mid_price, mut base_reserve, mut quote_reserve) = match decoded {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                state.stats.decode_errors += 1;
+                tracing::warn!("Failed to decode {} account {}: {}", pool.id, update.pubkey, e);
+                return None;
+            }
+        };
+
+        if let PoolLayout::VaultPair { base_vault, quote_vault } = &pool.layout {
+            let entry = state.pools.get(&index)?;
+            // Both vaults are needed before there is a price, and both as of
+            // this slot: pairing a fresh balance with a stale one prices a
+            // pool state that never existed
+            let (base, quote) = match role {
+                AccountRole::BaseVault => {
+                    (entry.base_amount?.0, self.vault_amount_at(entry.quote_amount?, quote_vault, update.slot)?)
+                }
+                _ => (self.vault_amount_at(entry.base_amount?, base_vault, update.slot)?, entry.quote_amount?.0),
+            };
+            if base == 0 {
+                return None;
+            }
+            let base = base as f64 / 10f64.powi(pool.base_decimals as i32);
+            let quote = quote as f64 / 10f64.powi(pool.quote_decimals as i32);
+            mid_price = quote / base;
+            base_reserve = Some(base);
+            quote_reserve = Some(quote);
+        }
+
+        state.stats.published += 1;
+        Some(PoolUpdate {
+            pool_id: pool.id.clone(),
+            venue: pool.venue.clone(),
+            market: pool.market.clone(),
+            slot: update.slot,
+            mid_price,
+            base_reserve,
+            quote_reserve,
+            fee_bps: pool.fee_bps,
+            source: update.source,
+            received_at_ms: now_ms(),
+        })
+    }
+
+    /// Balance of the other vault as of `slot`: the one last reported if it
+    /// was reported at `slot`, otherwise the cached account if the cache
+    /// knows it unchanged through `slot`
+    fn vault_amount_at(&self, (amount, reported_at): (u64, u64), vault: &Pubkey, slot: u64) -> Option<u64> {
+        if reported_at == slot {
+            return Some(amount);
+        }
+        let cached = self
+            .cache
+            .as_ref()?
+            .get(vault, &ReadRequirement::any().at_least_slot(slot))
+            .ok()?;
+        spl_token::state::Account::unpack(&cached.data).ok().map(|account| account.amount)
+    }
+
+    /// Prices of `pool_ids` decoded from cached accounts all as of one slot
+    pub fn snapshot(&self, pool_ids: &[&str], requirement: &ReadRequirement) -> Result<PoolSnapshot> {
+        let cache = self.cache.as_ref().ok_or_else(|| anyhow!("Pool ingestion has no account cache"))?;
+        let pools: Vec<&WatchedPool> = pool_ids
+            .iter()
+            .map(|id| {
+                self.pools
+                    .iter()
+                    .find(|pool| pool.id == *id)
+                    .ok_or_else(|| anyhow!("Unknown pool {}", id))
+            })
+            .collect::<Result<_>>()?;
+        let pubkeys: Vec<Pubkey> = pools.iter().flat_map(|pool| pool.accounts()).collect();
+        let accounts = cache.snapshot(&pubkeys, requirement)?;
+
+        let mut prices = HashMap::new();
+        for pool in pools {
+            let data = |pubkey: &Pubkey| accounts.data(pubkey).ok_or_else(|| anyhow!("{} missing from snapshot", pubkey));
+            let mid_price = match &pool.layout {
+                PoolLayout::Whirlpool { pool: account } => {
+                    decode_whirlpool_price(data(account)?, pool.base_decimals, pool.quote_decimals)?
+                }
+                PoolLayout::VaultPair { base_vault, quote_vault } => {
+                    let amount = |pubkey: &Pubkey| -> Result<f64> {
+                        Ok(spl_token::state::Account::unpack(data(pubkey)?)
+                            .map_err(|e| anyhow!("{}", e))?
+                            .amount as f64)
+                    };
+                    let base = amount(base_vault)? / 10f64.powi(pool.base_decimals as i32);
+                    let quote = amount(quote_vault)? / 10f64.powi(pool.quote_decimals as i32);
+                    if base <= 0.0 {
+                        return Err(anyhow!("{} has an empty base vault", pool.id));
+                    }
+                    quote / base
+                }
+            };
+            prices.insert(pool.id.clone(), (mid_price, pool.fee_bps));
+        }
+        Ok(PoolSnapshot { slot: accounts.slot, prices })
+    }
+
+    /// Keeps a websocket subscription alive, backing off between reconnects
+    async fn run_websocket(&self) {
+        let mut backoff = self.config.reconnect_initial;
+        loop {
+            match self.stream_once().await {
+                Ok(delivered) => {
+                    println!("🔌 Pool stream closed after {} updates, resubscribing", delivered);
+                    if delivered > 0 {
+                        backoff = self.config.reconnect_initial;
+                    }
+                }
+                Err(e) => println!("❌ Pool stream failed: {}, resubscribing in {:?}", e, backoff),
+            }
+            self.state.lock().stats.reconnects += 1;
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(self.config.reconnect_max);
+        }
+    }
+
+    /// One connection: subscribes to every watched account and program and
+    /// forwards notifications until the socket closes or goes quiet
+    async fn stream_once(&self) -> Result<u64> {
+        let (ws_stream, _) = connect_async(&self.config.ws_url).await?;
+        let (mut write, mut read) = ws_stream.split();
+        let connection = {
+            let mut state = self.state.lock();
+            state.connection += 1;
+            state.connection
+        };
+
+        // Request id -> watched account, or `None` for a program subscription
+        let mut requests: HashMap<u64, Option<Pubkey>> = HashMap::new();
+        let mut next_id = 1u64;
+        for account in self.pools.iter().flat_map(WatchedPool::accounts) {
+            let request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": next_id,
+                "method": "accountSubscribe",
+                "params": [account.to_string(), { "encoding": "base64", "commitment": self.config.commitment }]
+            });
+            write.send(Message::Text(request.to_string())).await?;
+            requests.insert(next_id, Some(account));
+            next_id += 1;
+        }
+        for program in &self.config.programs {
+            let request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": next_id,
+                "method": "programSubscribe",
+                "params": [
+                    program.program_id.to_string(),
+                    { "encoding": "base64", "commitment": self.config.commitment, "filters": program.filters }
+                ]
+            });
+            write.send(Message::Text(request.to_string())).await?;
+            requests.insert(next_id, None);
+            next_id += 1;
+        }
+
+        let mut subscriptions: HashMap<u64, Option<Pubkey>> = HashMap::new();
+        let mut delivered = 0u64;
+        loop {
+            let message = match tokio::time::timeout(self.config.idle_timeout, read.next()).await {
+                Ok(Some(message)) => message?,
+                Ok(None) => return Ok(delivered),
+                Err(_) => return Err(anyhow!("no messages for {:?}", self.config.idle_timeout)),
+            };
+            let text = match message {
+                Message::Text(text) => text,
+                Message::Close(_) => return Ok(delivered),
+                _ => continue,
+            };
+            let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) else {
+                continue;
+            };
+
+            // Subscription confirmation: { id, result: <subscription id> }
+            if let (Some(id), Some(subscription)) = (data["id"].as_u64(), data["result"].as_u64()) {
+                if let Some(target) = requests.remove(&id) {
+                    subscriptions.insert(subscription, target);
+                    if requests.is_empty() {
+                        if let Err(e) = self.resync(connection).await {
+                            println!("⚠️ Pool resync failed: {}, accounts catch up as they change", e);
+                        }
+                    }
+                }
+                continue;
+            }
+            if let Some(error) = data.get("error") {
+                return Err(anyhow!("subscription rejected: {}", error));
+            }
+
+            let Some(update) = Self::parse_notification(&data, &subscriptions) else {
+                continue;
+            };
+            self.publish(update, Origin::Stream(connection));
+            delivered += 1;
+        }
+    }
+
+    /// Reads every watched account once `connection` is fully subscribed.
+    /// Anything that changes after this read is notified on `connection`.
+    async fn resync(&self, connection: u64) -> Result<()> {
+        let Some(rpc) = &self.rpc else {
+            return Ok(());
+        };
+        let pubkeys: Vec<Pubkey> = self.accounts.keys().copied().collect();
+        let response = rpc
+            .get_multiple_accounts_with_commitment(&pubkeys, self.commitment().config())
+            .await?;
+        let slot = response.context.slot;
+        for (pubkey, account) in pubkeys.into_iter().zip(response.value) {
+            let Some(account) = account else {
+                continue;
+            };
+            let update = RawAccountUpdate {
+                pubkey,
+                slot,
+                data: account.data,
+                source: StreamSource::RpcFetch,
+            };
+            self.publish(update, Origin::Fetch(connection));
+        }
+        Ok(())
+    }
+
+    fn parse_notification(data: &serde_json::Value, subscriptions: &HashMap<u64, Option<Pubkey>>) -> Option<RawAccountUpdate> {
+        let params = &data["params"];
+        let target = subscriptions.get(&params["subscription"].as_u64()?)?;
+        let result = &params["result"];
+        let slot = result["context"]["slot"].as_u64()?;
+
+        let (pubkey, account, source) = match (data["method"].as_str()?, target) {
+            ("accountNotification", Some(pubkey)) => (*pubkey, &result["value"], StreamSource::AccountSubscribe),
+            ("programNotification", None) => {
+                let pubkey = result["value"]["pubkey"].as_str()?.parse().ok()?;
+                (pubkey, &result["value"]["account"], StreamSource::ProgramSubscribe)
+            }
+            _ => return None,
+        };
+        let bytes = STANDARD.decode(account["data"][0].as_str()?).ok()?;
+
+        Some(RawAccountUpdate {
+            pubkey,
+            slot,
+            data: bytes,
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whirlpool(id: &str) -> WatchedPool {
+        WatchedPool {
+            id: id.to_string(),
+            venue: "orca".to_string(),
+            market: "SOL/USDC".to_string(),
+            base_decimals: 9,
+            quote_decimals: 6,
+            fee_bps: 30.0,
+            layout: PoolLayout::Whirlpool {
+                pool: Pubkey::new_unique(),
+            },
+        }
+    }
+
+    fn whirlpool_data(price: f64) -> Vec<u8> {
+        let mut data = vec![0u8; WHIRLPOOL_SQRT_PRICE_OFFSET + 16];
+        let sqrt = ((price / 1e3).sqrt() * 2f64.powi(64)) as u128;
+        data[WHIRLPOOL_SQRT_PRICE_OFFSET..].copy_from_slice(&sqrt.to_le_bytes());
+        data
+    }
+
+    fn update(pubkey: Pubkey, slot: u64, price: f64) -> RawAccountUpdate {
+        RawAccountUpdate {
+            pubkey,
+            slot,
+            data: whirlpool_data(price),
+            source: StreamSource::AccountSubscribe,
+        }
+    }
+
+    fn ingestion() -> (PoolIngestion, Pubkey, Pubkey) {
+        let pools = vec![whirlpool("a"), whirlpool("b")];
+        let (a, b) = (pools[0].accounts()[0], pools[1].accounts()[0]);
+        let ingestion = PoolIngestion::new(IngestionConfig::new("ws://localhost"), pools)
+            .with_cache(Arc::new(AccountCache::default()));
+        (ingestion, a, b)
+    }
+
+    fn vault_data(amount: u64) -> Vec<u8> {
+        let account = spl_token::state::Account {
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+        data
+    }
+
+    fn vault_update(pubkey: Pubkey, slot: u64, amount: u64) -> RawAccountUpdate {
+        RawAccountUpdate {
+            pubkey,
+            slot,
+            data: vault_data(amount),
+            source: StreamSource::AccountSubscribe,
+        }
+    }
+
+    /// A SOL/USDC vault pair, a whirlpool and their accounts
+    fn vault_ingestion() -> (PoolIngestion, Pubkey, Pubkey, Pubkey) {
+        let (base_vault, quote_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut pair = whirlpool("pair");
+        pair.layout = PoolLayout::VaultPair { base_vault, quote_vault };
+        let other = whirlpool("other");
+        let whirlpool = other.accounts()[0];
+        let ingestion = PoolIngestion::new(IngestionConfig::new("ws://localhost"), vec![pair, other])
+            .with_cache(Arc::new(AccountCache::default()));
+        (ingestion, base_vault, quote_vault, whirlpool)
+    }
+
+    fn known_at(ingestion: &PoolIngestion, pubkey: &Pubkey, slot: u64) -> bool {
+        let cache = ingestion.cache.as_ref().unwrap();
+        cache.get(pubkey, &ReadRequirement::any().at_least_slot(slot)).is_ok()
+    }
+
+    #[test]
+    fn decodes_the_whirlpool_price() {
+        let price = decode_whirlpool_price(&whirlpool_data(150.0), 9, 6).unwrap();
+        assert!((price - 150.0).abs() < 1e-6);
+        assert!(decode_whirlpool_price(&[0u8; 10], 9, 6).is_err());
+    }
+
+    #[test]
+    fn a_notification_advances_accounts_on_the_same_stream() {
+        let (ingestion, a, b) = ingestion();
+        ingestion.apply_from(update(a, 10, 150.0), Origin::Stream(1)).unwrap();
+        ingestion.apply_from(update(b, 14, 151.0), Origin::Stream(1)).unwrap();
+
+        // `a` could still change within slot 14
+        assert!(known_at(&ingestion, &a, 13));
+        assert!(!known_at(&ingestion, &a, 14));
+    }
+
+    #[test]
+    fn accounts_from_before_a_reconnect_are_not_advanced() {
+        let (ingestion, a, b) = ingestion();
+        ingestion.apply_from(update(a, 10, 150.0), Origin::Stream(1)).unwrap();
+        ingestion.apply_from(update(b, 12, 151.0), Origin::Stream(1)).unwrap();
+        assert!(known_at(&ingestion, &a, 11));
+
+        // `a` may have changed while connection 1 was down
+        ingestion.apply_from(update(b, 20, 152.0), Origin::Stream(2)).unwrap();
+        assert!(!known_at(&ingestion, &a, 12));
+
+        // Once re-read on connection 2, it is advanced with the rest
+        ingestion.apply_from(update(a, 25, 150.0), Origin::Fetch(2)).unwrap();
+        ingestion.apply_from(update(b, 30, 153.0), Origin::Stream(2)).unwrap();
+        assert!(known_at(&ingestion, &a, 29));
+    }
+
+    #[test]
+    fn a_fetch_does_not_advance_other_accounts() {
+        let (ingestion, a, b) = ingestion();
+        ingestion.apply_from(update(a, 10, 150.0), Origin::Stream(1)).unwrap();
+        ingestion.apply_from(update(b, 30, 151.0), Origin::Fetch(1)).unwrap();
+
+        assert!(!known_at(&ingestion, &a, 11));
+    }
+
+    #[test]
+    fn snapshot_prices_a_cycle_at_one_slot() {
+        let (ingestion, a, b) = ingestion();
+        ingestion.apply_from(update(a, 10, 100.0), Origin::Stream(1)).unwrap();
+        ingestion.apply_from(update(b, 11, 110.0), Origin::Stream(1)).unwrap();
+        ingestion.apply_from(update(a, 12, 100.0), Origin::Stream(1)).unwrap();
+
+        let snapshot = ingestion.snapshot(&["a", "b"], &ReadRequirement::any()).unwrap();
+        assert_eq!(snapshot.slot, 11);
+        let rate = snapshot.cycle_rate(&[("a", Side::Buy), ("b", Side::Sell)]).unwrap();
+        let expected = 110.0 / 100.0 * (1.0 - 0.003) * (1.0 - 0.003);
+        assert!((rate - expected).abs() < 1e-9);
+        assert!(ingestion.snapshot(&["a", "missing"], &ReadRequirement::any()).is_err());
+    }
+
+    #[test]
+    fn apply_drops_repeated_and_older_slots() {
+        let (ingestion, a, b) = ingestion();
+        assert!(ingestion.apply(update(a, 10, 150.0)).is_some());
+        assert!(ingestion.apply(update(a, 10, 151.0)).is_none());
+        assert!(ingestion.apply(update(a, 9, 152.0)).is_none());
+        // Slots are tracked per account
+        assert!(ingestion.apply(update(b, 9, 152.0)).is_some());
+
+        let newer = ingestion.apply(update(a, 11, 153.0)).unwrap();
+        assert_eq!(newer.slot, 11);
+        assert!((newer.mid_price - 153.0).abs() < 1e-6);
+
+        let stats = ingestion.stats();
+        assert_eq!((stats.received, stats.duplicates, stats.published), (5, 2, 3));
+    }
+
+    #[test]
+    fn vault_pairs_are_priced_only_from_balances_at_one_slot() {
+        let (ingestion, base, quote, _) = vault_ingestion();
+        assert!(ingestion.apply_from(vault_update(base, 10, 1_000_000_000), Origin::Stream(1)).is_none());
+        let first = ingestion.apply_from(vault_update(quote, 10, 150_000_000), Origin::Stream(1)).unwrap();
+        assert_eq!(first.slot, 10);
+        assert!((first.mid_price - 150.0).abs() < 1e-9);
+
+        // The base vault may also change within slot 12, so the new quote
+        // balance waits for it
+        assert!(ingestion.apply_from(vault_update(quote, 12, 160_000_000), Origin::Stream(1)).is_none());
+        let second = ingestion.apply_from(vault_update(base, 12, 1_100_000_000), Origin::Stream(1)).unwrap();
+        assert!((second.mid_price - 160.0 / 1.1).abs() < 1e-9);
+        assert_eq!((second.base_reserve, second.quote_reserve), (Some(1.1), Some(160.0)));
+    }
+
+    #[test]
+    fn a_vault_pair_reads_the_unchanged_vault_from_the_cache() {
+        let (ingestion, base, quote, whirlpool) = vault_ingestion();
+        ingestion.apply_from(vault_update(base, 10, 1_000_000_000), Origin::Stream(1));
+        ingestion.apply_from(vault_update(quote, 10, 150_000_000), Origin::Stream(1)).unwrap();
+
+        // Slot 16 on the same stream shows the quote vault unchanged through 15
+        ingestion.apply_from(update(whirlpool, 16, 151.0), Origin::Stream(1)).unwrap();
+        let priced = ingestion.apply_from(vault_update(base, 15, 1_500_000_000), Origin::Fetch(1)).unwrap();
+        assert_eq!(priced.slot, 15);
+        assert!((priced.mid_price - 100.0).abs() < 1e-9);
+
+        // Nothing vouches for the base vault at slot 20
+        assert!(ingestion.apply_from(vault_update(quote, 20, 200_000_000), Origin::Fetch(1)).is_none());
+    }
+
+    fn notification(method: &str, subscription: u64, value: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": {
+                "subscription": subscription,
+                "result": { "context": { "slot": 42 }, "value": value },
+            },
+        })
+    }
+
+    #[test]
+    fn parses_account_and_program_notifications() {
+        let (pool, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let subscriptions = HashMap::from([(1, Some(pool)), (2, None)]);
+        let account = serde_json::json!({ "data": [STANDARD.encode([1u8, 2, 3]), "base64"] });
+
+        let update = PoolIngestion::parse_notification(&notification("accountNotification", 1, account.clone()), &subscriptions).unwrap();
+        assert_eq!((update.pubkey, update.slot, update.source), (pool, 42, StreamSource::AccountSubscribe));
+        assert_eq!(update.data, vec![1, 2, 3]);
+
+        let program_value = serde_json::json!({ "pubkey": vault.to_string(), "account": account });
+        let update = PoolIngestion::parse_notification(&notification("programNotification", 2, program_value), &subscriptions).unwrap();
+        assert_eq!((update.pubkey, update.slot, update.source), (vault, 42, StreamSource::ProgramSubscribe));
+    }
+
+    #[test]
+    fn ignores_notifications_it_did_not_subscribe_to() {
+        let pool = Pubkey::new_unique();
+        let subscriptions = HashMap::from([(1, Some(pool))]);
+        let account = serde_json::json!({ "data": [STANDARD.encode([1u8]), "base64"] });
+
+        // Unknown subscription, method not matching the subscription, bad data
+        assert!(PoolIngestion::parse_notification(&notification("accountNotification", 7, account.clone()), &subscriptions).is_none());
+        assert!(PoolIngestion::parse_notification(&notification("programNotification", 1, account), &subscriptions).is_none());
+        let garbled = serde_json::json!({ "data": ["not base64!", "base64"] });
+        assert!(PoolIngestion::parse_notification(&notification("accountNotification", 1, garbled), &subscriptions).is_none());
+    }
+
+    #[tokio::test]
+    async fn feed_session_steps_strategies_with_every_update() {
+        let (ingestion, a, b) = ingestion();
+        let strategies: Vec<Box<dyn crate::strategy::Strategy>> = vec![Box::new(crate::strategy::CrossVenueArbitrage::new("cross"))];
+        let session = Arc::new(Mutex::new(SimulationSession::new(strategies, Default::default())));
+        let feed = tokio::spawn(feed_session(ingestion.subscribe(), Arc::clone(&session)));
+
+        ingestion.publish(update(a, 10, 150.0), Origin::Stream(1));
+        ingestion.publish(update(b, 11, 151.0), Origin::Stream(1));
+        // A repeat publishes nothing
+        ingestion.publish(update(b, 11, 151.0), Origin::Stream(1));
+        drop(ingestion);
+        feed.await.unwrap();
+
+        assert_eq!(session.lock().reports()[0].events, 2);
+    }
+}
@@ -0,0 +1,627 @@
+/**
+ * MULTI-SOURCE PRICE ORACLE
+ * Aggregates on-chain Pyth prices, Jupiter quotes and DEX pool mid-prices,
+ * drops outliers by median absolute deviation and flags stale or uncertain quotes
+ */
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::solana_program::program_pack::Pack;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::account_cache::{AccountCache, ReadRequirement};
+
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const JUPITER_QUOTE_URL: &str = "https://quote-api.jup.ag/v6/quote";
+
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+const PYTH_PRICE_ACCOUNT: u32 = 3;
+const PYTH_STATUS_TRADING: u32 = 1;
+const PYTH_PRICE_ACCOUNT_MIN_LEN: usize = 240;
+/// Scales a MAD to a normal-distribution standard deviation
+const MAD_TO_SIGMA: f64 = 1.4826;
+
+/// Aggregate price of a Pyth v2 price account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythPrice {
+    pub price: f64,
+    pub confidence: f64,
+    pub publish_time: i64,
+    pub publish_slot: u64,
+    pub trading: bool,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_i32(data: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Decodes the aggregate price from a Pyth v2 price account
+pub fn decode_pyth_price(data: &[u8]) -> Result<PythPrice> {
+    if data.len() < PYTH_PRICE_ACCOUNT_MIN_LEN {
+        return Err(anyhow!("Pyth account too short: {} bytes", data.len()));
+    }
+    if read_u32(data, 0) != PYTH_MAGIC {
+        return Err(anyhow!("Not a Pyth account"));
+    }
+    if read_u32(data, 8) != PYTH_PRICE_ACCOUNT {
+        return Err(anyhow!("Pyth account is not a price account"));
+    }
+
+    let scale = 10f64.powi(read_i32(data, 20));
+    Ok(PythPrice {
+        price: read_i64(data, 208) as f64 * scale,
+        confidence: read_u64(data, 216) as f64 * scale,
+        publish_time: read_i64(data, 96),
+        publish_slot: read_u64(data, 232),
+        trading: read_u32(data, 224) == PYTH_STATUS_TRADING,
+    })
+}
+
+/// Constant-product pool whose vault balances give a mid-price
+#[derive(Debug, Clone)]
+pub struct PoolSource {
+    pub name: String,
+    pub base_vault: Pubkey,
+    /// Holds the oracle's quote mint
+    pub quote_vault: Pubkey,
+    pub fee_bps: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct OracleFeed {
+    pub mint: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub pyth_account: Option<Pubkey>,
+    pub pools: Vec<PoolSource>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OracleConfig {
+    pub quote_mint: String,
+    pub quote_decimals: u8,
+    /// Sources older than this are left out of the aggregate
+    pub max_source_age: Duration,
+    /// Sources further than this many scaled MADs from the median are dropped
+    pub outlier_mads: f64,
+    /// Deviation always tolerated, so agreeing sources with a near-zero MAD
+    /// are not rejected for tiny differences
+    pub min_outlier_deviation: f64,
+    /// Confidence / value above which a quote is flagged
+    pub max_confidence_ratio: f64,
+    /// How long an aggregated quote is served before sources are re-read
+    pub cache_ttl: Duration,
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        OracleConfig {
+            quote_mint: USDC_MINT.to_string(),
+            quote_decimals: 6,
+            max_source_age: Duration::from_secs(30),
+            outlier_mads: 3.0,
+            min_outlier_deviation: 0.005,
+            max_confidence_ratio: 0.02,
+            cache_ttl: Duration::from_millis(400),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourcePrice {
+    pub source: String,
+    pub value: f64,
+    pub confidence: f64,
+    pub published_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedSource {
+    pub source: String,
+    pub value: Option<f64>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceFlag {
+    /// Every source was older than the age limit; the quote uses them anyway
+    Stale,
+    WideConfidence,
+    SingleSource,
+    OutliersRejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceQuote {
+    pub mint: String,
+    pub value: f64,
+    pub confidence: f64,
+    /// Age of the oldest source in the aggregate
+    pub age: Duration,
+    pub sources: Vec<SourcePrice>,
+    pub rejected: Vec<RejectedSource>,
+    pub flags: Vec<PriceFlag>,
+}
+
+impl PriceQuote {
+    pub fn is_reliable(&self) -> bool {
+        !self.flags.contains(&PriceFlag::Stale) && !self.flags.contains(&PriceFlag::WideConfidence)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Combines source prices into one quote: stale sources are dropped (unless
+/// nothing else is left), then anything outside the MAD band around the
+/// median, and the median of what remains is the price
+pub fn aggregate(
+    mint: &str,
+    prices: Vec<SourcePrice>,
+    mut rejected: Vec<RejectedSource>,
+    config: &OracleConfig,
+    now_ms: u64,
+) -> Result<PriceQuote> {
+    let mut flags = Vec::new();
+    let max_age_ms = config.max_source_age.as_millis() as u64;
+    let (fresh, stale): (Vec<SourcePrice>, Vec<SourcePrice>) = prices
+        .into_iter()
+        .filter(|p| p.value.is_finite() && p.value > 0.0)
+        .partition(|p| now_ms.saturating_sub(p.published_at_ms) <= max_age_ms);
+
+    let candidates = if fresh.is_empty() {
+        flags.push(PriceFlag::Stale);
+        stale
+    } else {
+        for p in stale {
+            let age_ms = now_ms.saturating_sub(p.published_at_ms);
+            rejected.push(RejectedSource {
+                source: p.source,
+                value: Some(p.value),
+                reason: format!("stale: {} ms old", age_ms),
+            });
+        }
+        fresh
+    };
+    if candidates.is_empty() {
+        let reasons: Vec<String> = rejected.iter().map(|r| format!("{}: {}", r.source, r.reason)).collect();
+        return Err(anyhow!("No usable price for {} ({})", mint, reasons.join("; ")));
+    }
+
+    let mut values: Vec<f64> = candidates.iter().map(|p| p.value).collect();
+    let center = median(&mut values);
+    let mut deviations: Vec<f64> = candidates.iter().map(|p| (p.value - center).abs()).collect();
+    let mad = median(&mut deviations) * MAD_TO_SIGMA;
+    let band = (config.outlier_mads * mad).max(config.min_outlier_deviation * center);
+
+    let (sources, outliers): (Vec<SourcePrice>, Vec<SourcePrice>) =
+        candidates.into_iter().partition(|p| (p.value - center).abs() <= band);
+    if !outliers.is_empty() {
+        flags.push(PriceFlag::OutliersRejected);
+    }
+    for p in outliers {
+        rejected.push(RejectedSource {
+            reason: format!("outlier: {:.6} vs median {:.6} (band {:.6})", p.value, center, band),
+            source: p.source,
+            value: Some(p.value),
+        });
+    }
+    if sources.is_empty() {
+        return Err(anyhow!("Every price for {} was rejected as an outlier", mint));
+    }
+
+    let mut kept: Vec<f64> = sources.iter().map(|p| p.value).collect();
+    let value = median(&mut kept);
+    let mut spreads: Vec<f64> = sources.iter().map(|p| (p.value - value).abs()).collect();
+    let dispersion = median(&mut spreads) * MAD_TO_SIGMA;
+    let source_confidence = sources.iter().map(|p| p.confidence).fold(0.0, f64::max);
+    let confidence = dispersion.max(source_confidence);
+
+    if sources.len() == 1 {
+        flags.push(PriceFlag::SingleSource);
+    }
+    if confidence > value * config.max_confidence_ratio {
+        flags.push(PriceFlag::WideConfidence);
+    }
+    let oldest = sources.iter().map(|p| p.published_at_ms).min().unwrap_or(now_ms);
+
+    Ok(PriceQuote {
+        mint: mint.to_string(),
+        value,
+        confidence,
+        age: Duration::from_millis(now_ms.saturating_sub(oldest)),
+        sources,
+        rejected,
+        flags,
+    })
+}
+
+/// One price per mint from every configured source
+pub struct PriceOracle {
+    connection: Arc<RpcClient>,
+    http: reqwest::Client,
+    config: OracleConfig,
+    feeds: HashMap<String, OracleFeed>,
+    quotes: Mutex<HashMap<String, (Instant, PriceQuote)>>,
+    accounts: Arc<AccountCache>,
+}
+
+impl PriceOracle {
+    pub fn new(connection: Arc<RpcClient>, config: OracleConfig) -> Self {
+        Self {
+            connection,
+            http: reqwest::Client::new(),
+            config,
+            feeds: HashMap::new(),
+            quotes: Mutex::new(HashMap::new()),
+            accounts: Arc::new(AccountCache::default()),
+        }
+    }
+
+    /// Oracle over the mainnet Pyth accounts and pools of the pipeline's tokens
+    pub fn with_default_feeds(connection: Arc<RpcClient>) -> Self {
+        let mut oracle = Self::new(connection, OracleConfig::default());
+        let pubkey = |s: &str| s.parse::<Pubkey>().expect("valid feed address");
+
+        oracle.add_feed(OracleFeed {
+            mint: "So11111111111111111111111111111111111111112".to_string(),
+            symbol: "SOL".to_string(),
+            decimals: 9,
+            pyth_account: Some(pubkey("H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG")),
+            pools: vec![PoolSource {
+                name: "raydium_sol_usdc".to_string(),
+                base_vault: pubkey("DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz"),
+                quote_vault: pubkey("HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz"),
+                fee_bps: 25.0,
+            }],
+        });
+        oracle.add_feed(OracleFeed {
+            mint: USDC_MINT.to_string(),
+            symbol: "USDC".to_string(),
+            decimals: 6,
+            pyth_account: Some(pubkey("Gnt27xtC473ZT2Mw5u8wZ68Z3gULkSTb5DuxJy7eJotD")),
+            pools: Vec::new(),
+        });
+        oracle.add_feed(OracleFeed {
+            mint: "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(),
+            symbol: "USDT".to_string(),
+            decimals: 6,
+            pyth_account: Some(pubkey("3vxLXJqLqF3JG5TCbYycbKWRBbCJQLxQmBGCkyqEEefL")),
+            pools: Vec::new(),
+        });
+        oracle.add_feed(OracleFeed {
+            mint: "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So".to_string(),
+            symbol: "mSOL".to_string(),
+            decimals: 9,
+            pyth_account: Some(pubkey("E4v1BBgoso9s64TQvmyownAVJbhbEPGyzA3qn4n46qj9")),
+            pools: Vec::new(),
+        });
+        oracle.add_feed(OracleFeed {
+            mint: "7dHbWXmci3dT8UFYWYZweBLXgycu7Y3iL6trKn1Y7ARj".to_string(),
+            symbol: "stSOL".to_string(),
+            decimals: 9,
+            pyth_account: Some(pubkey("Bt1hEbY62aMriY1SyQqbeZbm8VmSbQVGBFzSzMuVNWzN")),
+            pools: Vec::new(),
+        });
+        oracle
+    }
+
+    /// Reads pool vaults through a shared account cache instead of a private one
+    pub fn with_account_cache(mut self, accounts: Arc<AccountCache>) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    pub fn add_feed(&mut self, feed: OracleFeed) {
+        self.feeds.insert(feed.mint.clone(), feed);
+    }
+
+    pub fn mints(&self) -> Vec<String> {
+        self.feeds.keys().cloned().collect()
+    }
+
+    pub fn config(&self) -> &OracleConfig {
+        &self.config
+    }
+
+    /// Aggregated price of `mint` in the quote mint, re-read from the sources
+    /// once the cached quote is older than `cache_ttl`
+    pub async fn price(&self, mint: &str) -> Result<PriceQuote> {
+        if let Some((fetched, quote)) = self.quotes.lock().get(mint) {
+            if fetched.elapsed() < self.config.cache_ttl {
+                return Ok(quote.clone());
+            }
+        }
+
+        let feed = self.feeds.get(mint).ok_or_else(|| anyhow!("No price feed for {}", mint))?;
+        let (pyth, jupiter, pools) = tokio::join!(
+            self.read_pyth(feed),
+            self.read_jupiter(feed),
+            self.read_pools(feed)
+        );
+
+        let mut prices = Vec::new();
+        let mut rejected = Vec::new();
+        for result in [pyth, jupiter].into_iter().chain(pools) {
+            match result {
+                Ok(Some(price)) => prices.push(price),
+                Ok(None) => {}
+                Err(failure) => rejected.push(failure),
+            }
+        }
+
+        let quote = aggregate(mint, prices, rejected, &self.config, now_ms())?;
+        if !quote.flags.is_empty() {
+            tracing::warn!("Price of {} flagged {:?}: {:.6} ± {:.6}", feed.symbol, quote.flags, quote.value, quote.confidence);
+        }
+        self.quotes.lock().insert(mint.to_string(), (Instant::now(), quote.clone()));
+        Ok(quote)
+    }
+
+    async fn read_pyth(&self, feed: &OracleFeed) -> Result<Option<SourcePrice>, RejectedSource> {
+        let Some(account) = feed.pyth_account else {
+            return Ok(None);
+        };
+        let failed = |reason: String, value: Option<f64>| RejectedSource {
+            source: "pyth".to_string(),
+            value,
+            reason,
+        };
+
+        let data = self
+            .connection
+            .get_account_data(&account)
+            .await
+            .map_err(|e| failed(e.to_string(), None))?;
+        let price = decode_pyth_price(&data).map_err(|e| failed(e.to_string(), None))?;
+        if !price.trading {
+            return Err(failed("price status is not trading".to_string(), Some(price.price)));
+        }
+
+        Ok(Some(SourcePrice {
+            source: "pyth".to_string(),
+            value: price.price,
+            confidence: price.confidence,
+            published_at_ms: price.publish_time.max(0) as u64 * 1000,
+        }))
+    }
+
+    async fn read_jupiter(&self, feed: &OracleFeed) -> Result<Option<SourcePrice>, RejectedSource> {
+        if feed.mint == self.config.quote_mint {
+            return Ok(None);
+        }
+        let failed = |reason: String| RejectedSource {
+            source: "jupiter".to_string(),
+            value: None,
+            reason,
+        };
+
+        // Quote one whole token so the price carries its own impact
+        let amount = 10u64.pow(feed.decimals as u32);
+        let url = format!(
+            "{}?inputMint={}&outputMint={}&amount={}&slippageBps=50",
+            JUPITER_QUOTE_URL, feed.mint, self.config.quote_mint, amount
+        );
+        let data: serde_json::Value = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| failed(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| failed(e.to_string()))?;
+
+        let out_amount = data
+            .get("outAmount")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<f64>().ok())
+            .ok_or_else(|| failed("quote has no outAmount".to_string()))?;
+        let price_impact = data
+            .get("priceImpactPct")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        let value = out_amount / 10f64.powi(self.config.quote_decimals as i32);
+
+        Ok(Some(SourcePrice {
+            source: "jupiter".to_string(),
+            value,
+            confidence: value * price_impact.abs(),
+            published_at_ms: now_ms(),
+        }))
+    }
+
+    async fn read_pools(&self, feed: &OracleFeed) -> Vec<Result<Option<SourcePrice>, RejectedSource>> {
+        let mut results = Vec::with_capacity(feed.pools.len());
+        for pool in &feed.pools {
+            let failed = |reason: String| RejectedSource {
+                source: pool.name.clone(),
+                value: None,
+                reason,
+            };
+            let requirement = ReadRequirement::confirmed().max_age(self.config.cache_ttl);
+            let accounts = match self
+                .accounts
+                .get_or_fetch(&self.connection, &[pool.base_vault, pool.quote_vault], &requirement)
+                .await
+            {
+                Ok(accounts) => accounts,
+                Err(e) => {
+                    results.push(Err(failed(e.to_string())));
+                    continue;
+                }
+            };
+
+            let balance = |index: usize| -> Option<u64> {
+                let account = accounts.get(index)?;
+                spl_token::state::Account::unpack(&account.data).ok().map(|a| a.amount)
+            };
+            let (Some(base), Some(quote)) = (balance(0), balance(1)) else {
+                results.push(Err(failed("vault is not a token account".to_string())));
+                continue;
+            };
+            if base == 0 {
+                results.push(Err(failed("empty base vault".to_string())));
+                continue;
+            }
+
+            let base = base as f64 / 10f64.powi(feed.decimals as i32);
+            let quote = quote as f64 / 10f64.powi(self.config.quote_decimals as i32);
+            let value = quote / base;
+            results.push(Ok(Some(SourcePrice {
+                source: pool.name.clone(),
+                value,
+                confidence: value * pool.fee_bps / 10_000.0,
+                published_at_ms: now_ms(),
+            })));
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW_MS: u64 = 1_700_000_000_000;
+
+    fn pyth_account(price: i64, confidence: u64, exponent: i32, status: u32) -> Vec<u8> {
+        let mut data = vec![0u8; PYTH_PRICE_ACCOUNT_MIN_LEN];
+        data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT.to_le_bytes());
+        data[20..24].copy_from_slice(&exponent.to_le_bytes());
+        data[96..104].copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        data[208..216].copy_from_slice(&price.to_le_bytes());
+        data[216..224].copy_from_slice(&confidence.to_le_bytes());
+        data[224..228].copy_from_slice(&status.to_le_bytes());
+        data[232..240].copy_from_slice(&250_000_000u64.to_le_bytes());
+        data
+    }
+
+    fn source(name: &str, value: f64, age_ms: u64) -> SourcePrice {
+        SourcePrice {
+            source: name.to_string(),
+            value,
+            confidence: value * 0.0005,
+            published_at_ms: NOW_MS - age_ms,
+        }
+    }
+
+    #[test]
+    fn decodes_the_aggregate_price_of_a_pyth_account() {
+        let price = decode_pyth_price(&pyth_account(14_250_000_000, 7_500_000, -8, PYTH_STATUS_TRADING)).unwrap();
+        assert!((price.price - 142.5).abs() < 1e-9);
+        assert!((price.confidence - 0.075).abs() < 1e-12);
+        assert_eq!(price.publish_time, 1_700_000_000);
+        assert_eq!(price.publish_slot, 250_000_000);
+        assert!(price.trading);
+
+        let halted = decode_pyth_price(&pyth_account(14_250_000_000, 0, -8, 2)).unwrap();
+        assert!(!halted.trading);
+    }
+
+    #[test]
+    fn rejects_accounts_that_are_not_pyth_prices() {
+        let valid = pyth_account(1, 0, 0, PYTH_STATUS_TRADING);
+        assert!(decode_pyth_price(&valid[..PYTH_PRICE_ACCOUNT_MIN_LEN - 1]).is_err());
+
+        let mut wrong_magic = valid.clone();
+        wrong_magic[0] ^= 0xff;
+        assert!(decode_pyth_price(&wrong_magic).is_err());
+
+        let mut product_account = valid;
+        product_account[8..12].copy_from_slice(&2u32.to_le_bytes());
+        assert!(decode_pyth_price(&product_account).is_err());
+    }
+
+    #[test]
+    fn aggregate_takes_the_median_and_drops_outliers() {
+        let prices = vec![
+            source("pyth", 100.0, 1_000),
+            source("jupiter", 100.2, 0),
+            source("raydium", 99.9, 0),
+            source("orca", 112.0, 0),
+        ];
+        let quote = aggregate("SOL", prices, Vec::new(), &OracleConfig::default(), NOW_MS).unwrap();
+
+        assert!((quote.value - 100.0).abs() < 1e-9);
+        assert_eq!(quote.sources.len(), 3);
+        assert_eq!(quote.rejected.len(), 1);
+        assert_eq!(quote.rejected[0].source, "orca");
+        assert!(quote.rejected[0].reason.starts_with("outlier"));
+        assert_eq!(quote.flags, [PriceFlag::OutliersRejected]);
+        assert_eq!(quote.age, Duration::from_millis(1_000));
+        assert!(quote.is_reliable());
+    }
+
+    #[test]
+    fn aggregate_leaves_out_stale_sources_unless_nothing_else_is_left() {
+        let config = OracleConfig::default();
+        let stale_ms = config.max_source_age.as_millis() as u64 + 1;
+
+        let quote = aggregate(
+            "SOL",
+            vec![source("pyth", 90.0, stale_ms), source("jupiter", 100.0, 0)],
+            Vec::new(),
+            &config,
+            NOW_MS,
+        )
+        .unwrap();
+        assert_eq!(quote.value, 100.0);
+        assert!(quote.rejected[0].reason.starts_with("stale"));
+        assert_eq!(quote.flags, [PriceFlag::SingleSource]);
+
+        let quote = aggregate("SOL", vec![source("pyth", 90.0, stale_ms)], Vec::new(), &config, NOW_MS).unwrap();
+        assert_eq!(quote.value, 90.0);
+        assert!(quote.flags.contains(&PriceFlag::Stale));
+        assert!(!quote.is_reliable());
+    }
+
+    #[test]
+    fn aggregate_flags_wide_confidence_and_fails_without_prices() {
+        let config = OracleConfig::default();
+        let mut uncertain = source("pyth", 100.0, 0);
+        uncertain.confidence = 5.0;
+        let quote = aggregate("SOL", vec![uncertain, source("jupiter", 100.0, 0)], Vec::new(), &config, NOW_MS).unwrap();
+        assert_eq!(quote.confidence, 5.0);
+        assert_eq!(quote.flags, [PriceFlag::WideConfidence]);
+
+        let failures = vec![RejectedSource {
+            source: "jupiter".to_string(),
+            value: None,
+            reason: "timeout".to_string(),
+        }];
+        let error = aggregate("SOL", vec![source("raydium", 0.0, 0)], failures, &config, NOW_MS).unwrap_err();
+        assert!(error.to_string().contains("jupiter: timeout"), "{}", error);
+    }
+}
@@ -0,0 +1,669 @@
+/**
+ * PROGRAM DEPLOYMENT
+ * Builds workspace programs, deploys and upgrades them through the
+ * upgradeable BPF loader, and keeps a per-cluster manifest of program ids,
+ * authorities and bytecode hashes that deployments are verified against
+ */
+
+use anyhow::{anyhow, Context, Result};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Program bytes per buffer write, leaving room in a 1232-byte transaction
+/// for the signature, accounts and instruction header
+const WRITE_CHUNK: usize = 900;
+const WRITES_IN_FLIGHT: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cluster {
+    Localnet,
+    Devnet,
+    Testnet,
+    MainnetBeta,
+    Custom { name: String, url: String },
+}
+
+impl Cluster {
+    /// `localnet`, `devnet`, `testnet`, `mainnet-beta`, or any RPC URL; the
+    /// public endpoints of the named clusters parse as those clusters
+    pub fn parse(value: &str) -> Self {
+        match value.trim_end_matches('/') {
+            "localnet" | "localhost" | "http://127.0.0.1:8899" | "http://localhost:8899" => Cluster::Localnet,
+            "devnet" | "https://api.devnet.solana.com" => Cluster::Devnet,
+            "testnet" | "https://api.testnet.solana.com" => Cluster::Testnet,
+            "mainnet" | "mainnet-beta" | "https://api.mainnet-beta.solana.com" => Cluster::MainnetBeta,
+            _ => Cluster::Custom {
+                name: value.to_string(),
+                url: value.to_string(),
+            },
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Cluster::Localnet => "localnet",
+            Cluster::Devnet => "devnet",
+            Cluster::Testnet => "testnet",
+            Cluster::MainnetBeta => "mainnet-beta",
+            Cluster::Custom { name, .. } => name,
+        }
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        match self {
+            Cluster::Localnet => "http://127.0.0.1:8899",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+            Cluster::MainnetBeta => "https://api.mainnet-beta.solana.com",
+            Cluster::Custom { url, .. } => url,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployAction {
+    Deploy,
+    Upgrade,
+    SetAuthority,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentEvent {
+    pub action: DeployAction,
+    pub signature: String,
+    pub bytecode_sha256: String,
+    pub upgrade_authority: Option<String>,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramRecord {
+    pub program_id: String,
+    pub programdata_address: String,
+    /// `None` once the program is made immutable
+    pub upgrade_authority: Option<String>,
+    pub bytecode_sha256: String,
+    pub bytecode_len: usize,
+    /// Largest program the programdata account can hold without extending
+    pub max_data_len: usize,
+    pub history: Vec<DeploymentEvent>,
+}
+
+/// Deployed programs per cluster, persisted as JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeploymentManifest {
+    pub clusters: BTreeMap<String, BTreeMap<String, ProgramRecord>>,
+}
+
+impl DeploymentManifest {
+    /// An empty manifest if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Writes through a temporary file so a crash never leaves half a manifest
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn program(&self, cluster: &str, name: &str) -> Option<&ProgramRecord> {
+        self.clusters.get(cluster)?.get(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub cluster: String,
+    pub program: String,
+    pub program_id: String,
+    pub expected_sha256: String,
+    pub onchain_sha256: String,
+    pub bytecode_matches: bool,
+    pub onchain_authority: Option<String>,
+    pub authority_matches: bool,
+    pub last_deploy_slot: u64,
+}
+
+impl VerificationReport {
+    pub fn is_verified(&self) -> bool {
+        self.bytecode_matches && self.authority_matches
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Deploys and upgrades the programs under `<workspace>/programs` on one cluster
+pub struct ProgramDeployer {
+    rpc: Arc<RpcClient>,
+    cluster: Cluster,
+    workspace_root: PathBuf,
+    manifest_path: PathBuf,
+}
+
+impl ProgramDeployer {
+    pub fn new(cluster: Cluster, workspace_root: PathBuf, manifest_path: PathBuf) -> Self {
+        let rpc = Arc::new(RpcClient::new_with_commitment(
+            cluster.rpc_url().to_string(),
+            CommitmentConfig::confirmed(),
+        ));
+        Self {
+            rpc,
+            cluster,
+            workspace_root,
+            manifest_path,
+        }
+    }
+
+    /// Manifest from `BLACK_DIAMOND_DEPLOY_MANIFEST`, else `deployments.json`
+    /// in the workspace root
+    pub fn from_env(cluster: Cluster, workspace_root: PathBuf) -> Self {
+        let manifest_path = std::env::var("BLACK_DIAMOND_DEPLOY_MANIFEST")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| workspace_root.join("deployments.json"));
+        Self::new(cluster, workspace_root, manifest_path)
+    }
+
+    /// Deployer for the cluster behind `rpc_url`. `BLACK_DIAMOND_CLUSTER`
+    /// names it in the manifest when the URL is a private endpoint.
+    pub fn for_rpc_url(rpc_url: &str, workspace_root: PathBuf) -> Self {
+        let cluster = match std::env::var("BLACK_DIAMOND_CLUSTER") {
+            Ok(name) => Cluster::Custom {
+                name: Cluster::parse(&name).name().to_string(),
+                url: rpc_url.to_string(),
+            },
+            Err(_) => Cluster::parse(rpc_url),
+        };
+        Self::from_env(cluster, workspace_root)
+    }
+
+    pub fn cluster(&self) -> &Cluster {
+        &self.cluster
+    }
+
+    pub fn manifest(&self) -> Result<DeploymentManifest> {
+        DeploymentManifest::load(&self.manifest_path)
+    }
+
+    pub fn program_path(&self, name: &str) -> PathBuf {
+        self.workspace_root.join("target").join("deploy").join(format!("{}.so", name))
+    }
+
+    /// The id `programs/<name>/src/lib.rs` declares with `declare_id!`
+    pub fn declared_id(&self, name: &str) -> Result<Pubkey> {
+        let path = self.workspace_root.join("programs").join(name).join("src").join("lib.rs");
+        let source = std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        parse_declared_id(&source).ok_or_else(|| anyhow!("{} has no valid declare_id!", path.display()))
+    }
+
+    /// Compiles `programs/<name>` to `target/deploy/<name>.so`
+    pub async fn build(&self, name: &str) -> Result<PathBuf> {
+        let manifest = self.workspace_root.join("programs").join(name).join("Cargo.toml");
+        if !manifest.exists() {
+            return Err(anyhow!("No program {} at {}", name, manifest.display()));
+        }
+        let out_dir = self.workspace_root.join("target").join("deploy");
+        println!("🔨 Building program {}...", name);
+
+        let status = tokio::process::Command::new("cargo")
+            .arg("build-sbf")
+            .arg("--manifest-path")
+            .arg(&manifest)
+            .arg("--sbf-out-dir")
+            .arg(&out_dir)
+            .status()
+            .await
+            .context("running cargo build-sbf")?;
+        if !status.success() {
+            return Err(anyhow!("cargo build-sbf failed for {} ({})", name, status));
+        }
+        Ok(self.program_path(name))
+    }
+
+    /// First deployment of `name` at `program_keypair`'s address, which must
+    /// be the id the program declares, leaving `max_len_factor` times the
+    /// program size for later upgrades
+    pub async fn deploy(
+        &self,
+        name: &str,
+        payer: &Keypair,
+        program_keypair: &Keypair,
+        upgrade_authority: &Keypair,
+        max_len_factor: usize,
+    ) -> Result<ProgramRecord> {
+        if self.manifest()?.program(self.cluster.name(), name).is_some() {
+            return Err(anyhow!("{} is already deployed on {}; upgrade it instead", name, self.cluster.name()));
+        }
+        let declared = self.declared_id(name)?;
+        if program_keypair.pubkey() != declared {
+            return Err(anyhow!(
+                "{} declares id {} but the program keypair is {}",
+                name,
+                declared,
+                program_keypair.pubkey()
+            ));
+        }
+        let bytecode = self.read_program(name)?;
+        let buffer = self.write_buffer(&bytecode, payer, upgrade_authority).await?;
+
+        let max_data_len = bytecode.len() * max_len_factor.max(1);
+        let program_lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_program())
+            .await?;
+        let instructions = bpf_loader_upgradeable::deploy_with_max_program_len(
+            &payer.pubkey(),
+            &program_keypair.pubkey(),
+            &buffer,
+            &upgrade_authority.pubkey(),
+            program_lamports,
+            max_data_len,
+        )?;
+        let signature = match self
+            .send(&instructions, payer, &[payer, program_keypair, upgrade_authority])
+            .await
+        {
+            Ok(signature) => signature,
+            Err(e) => {
+                self.close_buffer(&buffer, payer, upgrade_authority).await;
+                return Err(e);
+            }
+        };
+        println!("🚀 Deployed {} to {} at {}", name, self.cluster.name(), program_keypair.pubkey());
+
+        let program_id = program_keypair.pubkey();
+        let record = ProgramRecord {
+            program_id: program_id.to_string(),
+            programdata_address: Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0.to_string(),
+            upgrade_authority: Some(upgrade_authority.pubkey().to_string()),
+            bytecode_sha256: sha256_hex(&bytecode),
+            bytecode_len: bytecode.len(),
+            max_data_len,
+            history: Vec::new(),
+        };
+        self.record(name, record, DeployAction::Deploy, &signature)
+    }
+
+    /// Writes the built program into a new buffer owned by `authority`,
+    /// which an upgrade can consume now or after review
+    pub async fn stage_upgrade(&self, name: &str, payer: &Keypair, authority: &Keypair) -> Result<Pubkey> {
+        let bytecode = self.read_program(name)?;
+        let buffer = self.write_buffer(&bytecode, payer, authority).await?;
+        println!("📦 Staged {} ({}) in buffer {}", name, sha256_hex(&bytecode), buffer);
+        Ok(buffer)
+    }
+
+    /// Replaces the deployed program with the contents of `buffer`. Refuses
+    /// a buffer that does not hold exactly the local build, so what is
+    /// recorded is what was deployed.
+    pub async fn upgrade_from_buffer(
+        &self,
+        name: &str,
+        buffer: &Pubkey,
+        payer: &Keypair,
+        authority: &Keypair,
+    ) -> Result<ProgramRecord> {
+        let mut record = self.deployed(name)?;
+        let program_id: Pubkey = record.program_id.parse()?;
+        let bytecode = self.read_program(name)?;
+
+        check_fits(name, bytecode.len(), &record)?;
+
+        let buffer_data = self.rpc.get_account_data(buffer).await?;
+        let staged = buffer_data.get(UpgradeableLoaderState::size_of_buffer_metadata()..).unwrap_or_default();
+        // The loader deploys the whole buffer, so trailing bytes would be
+        // deployed along with the build
+        if staged.len() != bytecode.len() {
+            return Err(anyhow!(
+                "Buffer {} holds {} bytes but the local build of {} is {}",
+                buffer,
+                staged.len(),
+                name,
+                bytecode.len()
+            ));
+        }
+        if staged != bytecode.as_slice() {
+            return Err(anyhow!("Buffer {} does not hold the local build of {}", buffer, name));
+        }
+
+        let instruction = bpf_loader_upgradeable::upgrade(&program_id, buffer, &authority.pubkey(), &payer.pubkey());
+        let signature = self.send(&[instruction], payer, &[payer, authority]).await?;
+        println!("⬆️ Upgraded {} on {} from buffer {}", name, self.cluster.name(), buffer);
+
+        record.bytecode_sha256 = sha256_hex(&bytecode);
+        record.bytecode_len = bytecode.len();
+        self.record(name, record, DeployAction::Upgrade, &signature)
+    }
+
+    /// Stages and applies an upgrade in one go, closing the buffer to
+    /// reclaim its rent if the upgrade fails
+    pub async fn upgrade(&self, name: &str, payer: &Keypair, authority: &Keypair) -> Result<ProgramRecord> {
+        // Checked up front so an oversized build never pays for a buffer
+        check_fits(name, self.read_program(name)?.len(), &self.deployed(name)?)?;
+
+        let buffer = self.stage_upgrade(name, payer, authority).await?;
+        match self.upgrade_from_buffer(name, &buffer, payer, authority).await {
+            Ok(record) => Ok(record),
+            Err(e) => {
+                self.close_buffer(&buffer, payer, authority).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Hands the upgrade authority to `new_authority`, which must sign so a
+    /// typo cannot lock the program; `None` makes the program immutable
+    pub async fn set_upgrade_authority(
+        &self,
+        name: &str,
+        payer: &Keypair,
+        current: &Keypair,
+        new_authority: Option<&Keypair>,
+    ) -> Result<ProgramRecord> {
+        let mut record = self.deployed(name)?;
+        let program_id: Pubkey = record.program_id.parse()?;
+
+        let signature = match new_authority {
+            Some(new_authority) => {
+                let instruction = bpf_loader_upgradeable::set_upgrade_authority_checked(
+                    &program_id,
+                    &current.pubkey(),
+                    &new_authority.pubkey(),
+                );
+                self.send(&[instruction], payer, &[payer, current, new_authority]).await?
+            }
+            None => {
+                let instruction = bpf_loader_upgradeable::set_upgrade_authority(&program_id, &current.pubkey(), None);
+                self.send(&[instruction], payer, &[payer, current]).await?
+            }
+        };
+
+        record.upgrade_authority = new_authority.map(|k| k.pubkey().to_string());
+        println!(
+            "🔑 {} upgrade authority is now {}",
+            name,
+            record.upgrade_authority.as_deref().unwrap_or("none (immutable)")
+        );
+        self.record(name, record, DeployAction::SetAuthority, &signature)
+    }
+
+    /// Compares the on-chain program and authority with the manifest
+    pub async fn verify(&self, name: &str) -> Result<VerificationReport> {
+        let record = self.deployed(name)?;
+        let programdata: Pubkey = record.programdata_address.parse()?;
+        let data = self.rpc.get_account_data(&programdata).await?;
+
+        let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+        let (slot, onchain_authority) = match bincode::deserialize::<UpgradeableLoaderState>(&data)? {
+            UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address,
+            } => (slot, upgrade_authority_address.map(|a| a.to_string())),
+            other => return Err(anyhow!("{} is not program data: {:?}", programdata, other)),
+        };
+
+        // The account is padded to its max length; anything past the
+        // recorded program must be zero
+        let program = data.get(metadata_len..).unwrap_or_default();
+        let (code, padding) = program.split_at(record.bytecode_len.min(program.len()));
+        let onchain_sha256 = sha256_hex(code);
+        let bytecode_matches = onchain_sha256 == record.bytecode_sha256 && padding.iter().all(|b| *b == 0);
+
+        Ok(VerificationReport {
+            cluster: self.cluster.name().to_string(),
+            program: name.to_string(),
+            program_id: record.program_id.clone(),
+            expected_sha256: record.bytecode_sha256.clone(),
+            onchain_sha256,
+            bytecode_matches,
+            authority_matches: onchain_authority == record.upgrade_authority,
+            onchain_authority,
+            last_deploy_slot: slot,
+        })
+    }
+
+    fn read_program(&self, name: &str) -> Result<Vec<u8>> {
+        let path = self.program_path(name);
+        std::fs::read(&path).with_context(|| format!("reading {} (build it first)", path.display()))
+    }
+
+    fn deployed(&self, name: &str) -> Result<ProgramRecord> {
+        self.manifest()?
+            .program(self.cluster.name(), name)
+            .cloned()
+            .ok_or_else(|| anyhow!("{} has no deployment on {} in the manifest", name, self.cluster.name()))
+    }
+
+    /// Creates a buffer for `bytecode` and fills it, a batch of writes at a time
+    async fn write_buffer(&self, bytecode: &[u8], payer: &Keypair, authority: &Keypair) -> Result<Pubkey> {
+        let buffer = Keypair::new();
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_buffer(bytecode.len()))
+            .await?;
+        let create = bpf_loader_upgradeable::create_buffer(
+            &payer.pubkey(),
+            &buffer.pubkey(),
+            &authority.pubkey(),
+            lamports,
+            bytecode.len(),
+        )?;
+        self.send(&create, payer, &[payer, &buffer]).await?;
+        let buffer = buffer.pubkey();
+
+        let writes: Vec<Instruction> = bytecode
+            .chunks(WRITE_CHUNK)
+            .enumerate()
+            .map(|(i, chunk)| {
+                bpf_loader_upgradeable::write(&buffer, &authority.pubkey(), (i * WRITE_CHUNK) as u32, chunk.to_vec())
+            })
+            .collect();
+        let signers = [payer, authority];
+        for batch in writes.chunks(WRITES_IN_FLIGHT) {
+            let results = join_all(
+                batch
+                    .iter()
+                    .map(|write| self.send(std::slice::from_ref(write), payer, &signers)),
+            )
+            .await;
+            if let Some(Err(e)) = results.into_iter().find(Result::is_err) {
+                self.close_buffer(&buffer, payer, authority).await;
+                return Err(e);
+            }
+        }
+        Ok(buffer)
+    }
+
+    /// Best-effort close of a buffer a failed deploy or upgrade left behind,
+    /// returning its rent to the payer
+    async fn close_buffer(&self, buffer: &Pubkey, payer: &Keypair, authority: &Keypair) {
+        let instruction = bpf_loader_upgradeable::close(buffer, &payer.pubkey(), &authority.pubkey());
+        match self.send(&[instruction], payer, &[payer, authority]).await {
+            Ok(_) => println!("🧹 Closed buffer {}", buffer),
+            Err(e) => println!("⚠️ Failed to close buffer {}: {}", buffer, e),
+        }
+    }
+
+    async fn send(&self, instructions: &[Instruction], payer: &Keypair, signers: &[&Keypair]) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), signers, blockhash);
+        Ok(self.rpc.send_and_confirm_transaction(&transaction).await?)
+    }
+
+    fn record(
+        &self,
+        name: &str,
+        mut record: ProgramRecord,
+        action: DeployAction,
+        signature: &Signature,
+    ) -> Result<ProgramRecord> {
+        record.history.push(DeploymentEvent {
+            action,
+            signature: signature.to_string(),
+            bytecode_sha256: record.bytecode_sha256.clone(),
+            upgrade_authority: record.upgrade_authority.clone(),
+            at: chrono::Utc::now(),
+        });
+
+        let mut manifest = self.manifest()?;
+        manifest
+            .clusters
+            .entry(self.cluster.name().to_string())
+            .or_default()
+            .insert(name.to_string(), record.clone());
+        manifest.save(&self.manifest_path)?;
+        Ok(record)
+    }
+}
+
+fn check_fits(name: &str, bytecode_len: usize, record: &ProgramRecord) -> Result<()> {
+    if bytecode_len > record.max_data_len {
+        return Err(anyhow!(
+            "{} is {} bytes but the program account holds at most {}",
+            name,
+            bytecode_len,
+            record.max_data_len
+        ));
+    }
+    Ok(())
+}
+
+fn parse_declared_id(source: &str) -> Option<Pubkey> {
+    const MACRO: &str = "declare_id!(\"";
+    let start = source.find(MACRO)? + MACRO.len();
+    let end = start + source[start..].find('"')?;
+    source[start..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECLARED: &str = "21x6jGg5XXKQNmhXgGLpATQmh8m5QG3s8Rh63BgcGq8h";
+
+    /// Workspace with a `demo` program declaring `DECLARED` and a build of
+    /// `bytecode_len` bytes
+    fn workspace(test: &str, bytecode_len: usize) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("program_deploy_{}_{}", test, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("programs/demo/src")).unwrap();
+        std::fs::create_dir_all(root.join("target/deploy")).unwrap();
+        std::fs::write(
+            root.join("programs/demo/src/lib.rs"),
+            format!("use anchor_lang::prelude::*;\n\ndeclare_id!(\"{}\");\n", DECLARED),
+        )
+        .unwrap();
+        std::fs::write(root.join("target/deploy/demo.so"), vec![1u8; bytecode_len]).unwrap();
+        root
+    }
+
+    fn deployer(root: &Path) -> ProgramDeployer {
+        // Nothing listens here; these paths must fail before any RPC call
+        let cluster = Cluster::Custom {
+            name: "test".to_string(),
+            url: "http://127.0.0.1:1".to_string(),
+        };
+        ProgramDeployer::new(cluster, root.to_path_buf(), root.join("deployments.json"))
+    }
+
+    #[test]
+    fn parses_declared_id() {
+        let source = "use anchor_lang::prelude::*;\ndeclare_id!(\"21x6jGg5XXKQNmhXgGLpATQmh8m5QG3s8Rh63BgcGq8h\");";
+        assert_eq!(parse_declared_id(source), Some(DECLARED.parse().unwrap()));
+        assert_eq!(parse_declared_id("declare_id!(\"not-base58\");"), None);
+        assert_eq!(parse_declared_id("fn main() {}"), None);
+    }
+
+    #[test]
+    fn public_endpoints_parse_as_named_clusters() {
+        assert_eq!(Cluster::parse("https://api.devnet.solana.com/"), Cluster::Devnet);
+        assert_eq!(Cluster::parse("mainnet"), Cluster::MainnetBeta);
+        assert_eq!(Cluster::parse("https://rpc.example.com").name(), "https://rpc.example.com");
+    }
+
+    #[tokio::test]
+    async fn deploy_rejects_a_keypair_other_than_the_declared_id() {
+        let root = workspace("keypair", 64);
+        let payer = Keypair::new();
+
+        let error = deployer(&root).deploy("demo", &payer, &Keypair::new(), &payer, 2).await.unwrap_err();
+        assert!(error.to_string().contains("declares id"), "{}", error);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn upgrade_rejects_an_oversized_build_before_staging() {
+        let root = workspace("oversized", 64);
+        let mut manifest = DeploymentManifest::default();
+        manifest.clusters.entry("test".to_string()).or_default().insert(
+            "demo".to_string(),
+            ProgramRecord {
+                program_id: DECLARED.to_string(),
+                programdata_address: Pubkey::new_unique().to_string(),
+                upgrade_authority: None,
+                bytecode_sha256: String::new(),
+                bytecode_len: 32,
+                max_data_len: 48,
+                history: Vec::new(),
+            },
+        );
+        manifest.save(&root.join("deployments.json")).unwrap();
+        let payer = Keypair::new();
+
+        let error = deployer(&root).upgrade("demo", &payer, &payer).await.unwrap_err();
+        assert!(error.to_string().contains("holds at most 48"), "{}", error);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn manifest_round_trips() {
+        let root = workspace("manifest", 1);
+        let path = root.join("deployments.json");
+        assert!(DeploymentManifest::load(&path).unwrap().clusters.is_empty());
+
+        let mut manifest = DeploymentManifest::default();
+        manifest.clusters.entry("devnet".to_string()).or_default().insert(
+            "demo".to_string(),
+            ProgramRecord {
+                program_id: DECLARED.to_string(),
+                programdata_address: String::new(),
+                upgrade_authority: Some(DECLARED.to_string()),
+                bytecode_sha256: sha256_hex(b"demo"),
+                bytecode_len: 4,
+                max_data_len: 8,
+                history: Vec::new(),
+            },
+        );
+        manifest.save(&path).unwrap();
+
+        let loaded = DeploymentManifest::load(&path).unwrap();
+        assert_eq!(loaded.program("devnet", "demo").unwrap().bytecode_sha256, sha256_hex(b"demo"));
+        assert!(loaded.program("mainnet-beta", "demo").is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -11,6 +11,7 @@ use rayon::prelude::*;
 use crossbeam::channel;
 use parking_lot::Mutex;
 use rand::Rng;
+use black_diamond_client::types::{QuantumStatus, QuantumTradingResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuantumState {
@@ -436,7 +437,7 @@ impl QuantumEnhancedOrchestrator {
     }
 
     /// Execute quantum-enhanced trading
-    pub async fn execute_quantum_trading(&self, trades: Vec<String>) -> anyhow::Result<serde_json::Value> {
+    pub async fn execute_quantum_trading(&self, trades: Vec<String>) -> anyhow::Result<QuantumTradingResult> {
         // Speed enhancement through superposition
         let speed_results = self.speed_enhancement.execute_superposition_trading(trades.clone()).await?;
         
@@ -446,29 +447,29 @@ impl QuantumEnhancedOrchestrator {
         // Dominance through parallel universe execution
         let dominance_results = self.dominance_system.parallel_universe_execution(trades).await?;
         
-        Ok(serde_json::json!({
-            "quantum_speed_results": speed_results.len(),
-            "quantum_accuracy": accuracy_results.iter().sum::<f64>() / accuracy_results.len() as f64,
-            "parallel_universe_outcomes": dominance_results.len(),
-            "total_quantum_advantage": 2000.0 * 99.99 * 100.0,
-            "status": "QUANTUM_SUPREMACY_ACHIEVED"
-        }))
+        Ok(QuantumTradingResult {
+            quantum_speed_results: speed_results.len(),
+            quantum_accuracy: accuracy_results.iter().sum::<f64>() / accuracy_results.len() as f64,
+            parallel_universe_outcomes: dominance_results.len(),
+            total_quantum_advantage: 2000.0 * 99.99 * 100.0,
+            status: "QUANTUM_SUPREMACY_ACHIEVED".to_string(),
+        })
     }
 
     /// Get quantum system status
-    pub async fn get_quantum_status(&self) -> serde_json::Value {
+    pub async fn get_quantum_status(&self) -> QuantumStatus {
         let metrics = self.quantum_metrics.read().await;
         
-        serde_json::json!({
-            "quantum_systems_active": true,
-            "speed_enhancement": "2000x faster execution",
-            "accuracy_enhancement": "99.99% precision",
-            "dominance_level": "QUANTUM_SUPREMACY",
-            "parallel_universes": "INFINITE",
-            "consciousness_integration": "95% active",
-            "quantum_advantage": 1000000.0,
-            "metrics": *metrics
-        })
+        QuantumStatus {
+            quantum_systems_active: true,
+            speed_enhancement: "2000x faster execution".to_string(),
+            accuracy_enhancement: "99.99% precision".to_string(),
+            dominance_level: "QUANTUM_SUPREMACY".to_string(),
+            parallel_universes: "INFINITE".to_string(),
+            consciousness_integration: "95% active".to_string(),
+            quantum_advantage: 1000000.0,
+            metrics: metrics.clone(),
+        }
     }
 }
 
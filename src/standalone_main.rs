@@ -4,29 +4,46 @@
  */
 
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, Request, State,
+    },
+    http::{header, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{on, MethodFilter, MethodRouter},
     Router,
 };
 use clap::Parser;
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tower_http::cors::CorsLayer;
+use tokio::sync::broadcast::error::RecvError;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing::{info, warn};
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
 
+mod account_cache;
+mod atomic_arb_client;
 mod black_diamond_transaction_pipeline;
+mod bot_registry;
+mod capital_allocator;
 mod zero_capital_advanced_strategies;
 mod memecoin_sniping_upper_echelon;
 mod on_chain_program_innovations;
 mod metrics_tracking_verification;
+mod pool_ingestion;
+mod price_oracle;
+mod program_deploy;
+mod strategy;
 mod quantum_enhanced_systems;
+mod api_auth;
+mod event_stream;
 
 use black_diamond_transaction_pipeline::*;
 use zero_capital_advanced_strategies::*;
@@ -34,6 +51,19 @@ use memecoin_sniping_upper_echelon::*;
 use on_chain_program_innovations::*;
 use metrics_tracking_verification::*;
 use quantum_enhanced_systems::*;
+use api_auth::{ApiAuth, AuthError, API_KEY_HEADER};
+use black_diamond_client::routes::{self, HttpMethod, RouteSpec};
+use black_diamond_client::events::{StreamEvent, StreamMessage, StreamRequest, StreamTopic};
+use event_stream::EventHub;
+use program_deploy::ProgramDeployer;
+use strategy::{MarketEvent, SimulationConfig, SimulationSession, StrategyRegistry};
+use black_diamond_client::types::{
+    ApiResponse, ArbitrageExecution, ArbitrageRequest, AuditEntry, BotPerformance, EntangledPair,
+    MetricsOverview, PipelineStatus, QuantumBoostResult, QuantumConsciousness, QuantumEntanglement,
+    QuantumStatus, QuantumSuperposition, QuantumTradingRequest, QuantumTradingResult, QuantumTunnelResult,
+    SnipingStats, StrategyConfigRequest, StrategyEntry, StrategyReport, StrategyToggleRequest, BacktestRequest,
+    SystemActivation, SystemOverview, ZeroCapitalStats,
+};
 
 #[derive(Parser)]
 #[command(name = "black-diamond-rust-ecosystem")]
@@ -53,56 +83,31 @@ struct Cli {
     
     #[arg(long)]
     f8_wallet_public_key: Option<String>,
+    
+    /// File that every mutating API call is appended to
+    #[arg(long, default_value = "black_diamond_audit.log")]
+    audit_log: String,
+    
+    /// Origins allowed to call the API from a browser (comma separated)
+    #[arg(long, value_delimiter = ',')]
+    allowed_origins: Vec<String>,
 }
 
 #[derive(Clone)]
 pub struct AppState {
-    pub black_diamond_pipeline: Arc<RwLock<Option<BlackDiamondTransactionPipeline>>>,
+    pub black_diamond_pipeline: Arc<RwLock<Option<Arc<BlackDiamondTransactionPipeline>>>>,
     pub zero_capital_strategies: Arc<RwLock<Option<ZeroCapitalAdvancedStrategies>>>,
-    pub memecoin_sniper: Arc<RwLock<Option<MemecoinSnipingUpperEchelon>>>,
+    pub memecoin_sniper: Arc<RwLock<Option<Arc<MemecoinSnipingUpperEchelon>>>>,
     pub on_chain_innovations: Arc<RwLock<Option<OnChainProgramInnovations>>>,
-    pub metrics_tracker: Arc<RwLock<Option<MetricsTrackingVerification>>>,
+    pub metrics_tracker: Arc<RwLock<Option<Arc<MetricsTrackingVerification>>>>,
     pub quantum_orchestrator: Arc<RwLock<Option<QuantumEnhancedOrchestrator>>>,
+    pub strategy_registry: Arc<RwLock<StrategyRegistry>>,
+    /// Enabled strategies paper-trading on the pipeline's pool updates
+    pub paper_session: Arc<parking_lot::Mutex<SimulationSession>>,
     pub connection: Arc<RpcClient>,
     pub system_stats: Arc<DashMap<String, serde_json::Value>>,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct SystemOverview {
-    pub status: String,
-    pub active_systems: u8,
-    pub total_systems: u8,
-    pub performance_boost: String,
-    pub memory_optimization: String,
-    pub concurrent_operations: String,
-    pub total_sol: f64,
-    pub daily_profit: f64,
-    pub success_rate: f64,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct ApiResponse<T> {
-    pub success: bool,
-    pub data: Option<T>,
-    pub error: Option<String>,
-}
-
-impl<T> ApiResponse<T> {
-    pub fn success(data: T) -> Self {
-        Self {
-            success: true,
-            data: Some(data),
-            error: None,
-        }
-    }
-    
-    pub fn error(error: String) -> Self {
-        Self {
-            success: false,
-            data: None,
-            error: Some(error),
-        }
-    }
+    pub auth: Arc<ApiAuth>,
+    pub events: Arc<EventHub>,
 }
 
 async fn initialize_systems(state: &AppState, cli: &Cli) -> anyhow::Result<()> {
@@ -126,27 +131,50 @@ async fn initialize_systems(state: &AppState, cli: &Cli) -> anyhow::Result<()> {
     
     let pipeline = BlackDiamondTransactionPipeline::new(f8_wallet_config);
     pipeline.initialize_pipeline().await?;
-    *state.black_diamond_pipeline.write() = Some(pipeline);
+    pipeline.start_paper_trading(Arc::clone(&state.paper_session));
+    // On-chain bots report revenue from the ledger the pipeline books to
+    let bot_registry = Arc::new(bot_registry::BotRegistry::with_ledger(pipeline.trade_ledger()));
+    let pipeline_bot_jobs = [
+        (ARBITRAGE_SCANNER_BOT, pipeline.arbitrage_scanner_job()),
+        (BUNDLE_CAPTURE_BOT, pipeline.bundle_capture_job()),
+    ];
+    *state.black_diamond_pipeline.write() = Some(Arc::new(pipeline));
     
     // Initialize Zero Capital Strategies with quantum acceleration
     let zero_capital = ZeroCapitalAdvancedStrategies::new(Arc::clone(&state.connection));
     zero_capital.initialize().await?;
+    {
+        let mut registry = state.strategy_registry.write();
+        let registered = zero_capital.register_strategies(&mut registry)?;
+        info!("🧩 Strategy registry: {} strategies registered", registered);
+        // `BLACK_DIAMOND_PAPER_STRATEGIES` = ids to paper-trade from startup
+        let enabled = std::env::var("BLACK_DIAMOND_PAPER_STRATEGIES").unwrap_or_default();
+        for id in enabled.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+            registry.set_enabled(id, true)?;
+        }
+        *state.paper_session.lock() = SimulationSession::paper(&registry, SimulationConfig::default())?;
+    }
     *state.zero_capital_strategies.write() = Some(zero_capital);
     
     // Initialize Memecoin Sniper with quantum tunneling
     let sniper = create_memecoin_sniper(Arc::clone(&state.connection))?;
     sniper.initialize_sniping_system().await?;
-    *state.memecoin_sniper.write() = Some(sniper);
+    *state.memecoin_sniper.write() = Some(Arc::new(sniper));
     
     // Initialize On-Chain Innovations with quantum supremacy
-    let innovations = OnChainProgramInnovations::new(Arc::clone(&state.connection));
+    let innovations = pipeline_bot_jobs.into_iter().fold(
+        OnChainProgramInnovations::new(Arc::clone(&state.connection))
+            .with_deployer(Arc::new(ProgramDeployer::for_rpc_url(&state.connection.url(), std::env::current_dir()?)))
+            .with_bot_registry(bot_registry),
+        |innovations, (bot_id, job)| innovations.with_bot_job(bot_id, job),
+    );
     innovations.initialize_innovations().await?;
     *state.on_chain_innovations.write() = Some(innovations);
     
     // Initialize Metrics Tracker with quantum precision
     let metrics = MetricsTrackingVerification::new(Arc::clone(&state.connection));
     metrics.initialize_metrics_system().await?;
-    *state.metrics_tracker.write() = Some(metrics);
+    *state.metrics_tracker.write() = Some(Arc::new(metrics));
     
     info!("🦀 All systems initialized with quantum enhancement - dominance achieved");
     Ok(())
@@ -170,8 +198,10 @@ async fn get_system_overview(State(state): State<AppState>) -> Json<ApiResponse<
     Json(ApiResponse::success(overview))
 }
 
-async fn get_black_diamond_status(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
-    if let Some(pipeline) = state.black_diamond_pipeline.read().as_ref() {
+async fn get_black_diamond_status(State(state): State<AppState>) -> Json<ApiResponse<PipelineStatus>> {
+    // Clone the handle out so the lock is not held across the await
+    let pipeline = state.black_diamond_pipeline.read().clone();
+    if let Some(pipeline) = pipeline {
         match pipeline.get_system_status().await {
             Ok(status) => Json(ApiResponse::success(status)),
             Err(e) => Json(ApiResponse::error(format!("Failed to get status: {}", e))),
@@ -181,25 +211,31 @@ async fn get_black_diamond_status(State(state): State<AppState>) -> Json<ApiResp
     }
 }
 
-#[derive(Deserialize)]
-struct ArbitrageRequest {
-    token_a: String,
-    token_b: String,
-    amount: f64,
-}
-
 async fn execute_arbitrage(
     State(state): State<AppState>,
     Json(req): Json<ArbitrageRequest>,
-) -> Json<ApiResponse<serde_json::Value>> {
-    if let Some(pipeline) = state.black_diamond_pipeline.read().as_ref() {
-        match pipeline.execute_arbitrage(req.token_a, req.token_b, req.amount).await {
-            Ok(_) => Json(ApiResponse::success(serde_json::json!({
-                "message": "Arbitrage executed successfully",
-                "token_a": req.token_a,
-                "token_b": req.token_b,
-                "amount": req.amount
-            }))),
+) -> Json<ApiResponse<ArbitrageExecution>> {
+    let pipeline = state.black_diamond_pipeline.read().clone();
+    if let Some(pipeline) = pipeline {
+        let result = pipeline.execute_arbitrage(req.token_a.clone(), req.token_b.clone(), req.amount).await;
+        
+        state.events.publish(StreamEvent::TradeExecuted {
+            strategy: "black_diamond_arbitrage".to_string(),
+            token_a: req.token_a.clone(),
+            token_b: req.token_b.clone(),
+            amount: req.amount,
+            success: result.is_ok(),
+            signature: None,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        
+        match result {
+            Ok(_) => Json(ApiResponse::success(ArbitrageExecution {
+                message: "Arbitrage executed successfully".to_string(),
+                token_a: req.token_a,
+                token_b: req.token_b,
+                amount: req.amount,
+            })),
             Err(e) => Json(ApiResponse::error(format!("Arbitrage failed: {}", e))),
         }
     } else {
@@ -216,7 +252,7 @@ async fn get_zero_capital_strategies(State(state): State<AppState>) -> Json<ApiR
     }
 }
 
-async fn get_zero_capital_stats(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn get_zero_capital_stats(State(state): State<AppState>) -> Json<ApiResponse<ZeroCapitalStats>> {
     if let Some(strategies) = state.zero_capital_strategies.read().as_ref() {
         let stats = strategies.get_current_stats().await;
         Json(ApiResponse::success(stats))
@@ -234,7 +270,7 @@ async fn get_memecoin_targets(State(state): State<AppState>) -> Json<ApiResponse
     }
 }
 
-async fn get_sniping_stats(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn get_sniping_stats(State(state): State<AppState>) -> Json<ApiResponse<SnipingStats>> {
     if let Some(sniper) = state.memecoin_sniper.read().as_ref() {
         let stats = sniper.get_sniping_stats().await;
         Json(ApiResponse::success(stats))
@@ -243,7 +279,7 @@ async fn get_sniping_stats(State(state): State<AppState>) -> Json<ApiResponse<se
     }
 }
 
-async fn get_bot_performance(State(state): State<AppState>) -> Json<ApiResponse<Vec<serde_json::Value>>> {
+async fn get_bot_performance(State(state): State<AppState>) -> Json<ApiResponse<Vec<BotPerformance>>> {
     if let Some(innovations) = state.on_chain_innovations.read().as_ref() {
         let performance = innovations.get_bot_performance().await;
         Json(ApiResponse::success(performance))
@@ -252,7 +288,7 @@ async fn get_bot_performance(State(state): State<AppState>) -> Json<ApiResponse<
     }
 }
 
-async fn execute_quantum_speed_boost(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn execute_quantum_speed_boost(State(state): State<AppState>) -> Json<ApiResponse<QuantumBoostResult>> {
     if let Some(innovations) = state.on_chain_innovations.read().as_ref() {
         match innovations.execute_quantum_speed_boost().await {
             Ok(result) => Json(ApiResponse::success(result)),
@@ -272,7 +308,7 @@ async fn get_wallet_metrics(State(state): State<AppState>) -> Json<ApiResponse<V
     }
 }
 
-async fn get_metrics_overview(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn get_metrics_overview(State(state): State<AppState>) -> Json<ApiResponse<MetricsOverview>> {
     if let Some(tracker) = state.metrics_tracker.read().as_ref() {
         let overview = tracker.get_system_overview().await;
         Json(ApiResponse::success(overview))
@@ -281,7 +317,7 @@ async fn get_metrics_overview(State(state): State<AppState>) -> Json<ApiResponse
     }
 }
 
-async fn activate_all_systems(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn activate_all_systems(State(state): State<AppState>) -> Json<ApiResponse<SystemActivation>> {
     let mut activated = 0;
     
     // Activate quantum orchestrator first for maximum enhancement
@@ -315,17 +351,17 @@ async fn activate_all_systems(State(state): State<AppState>) -> Json<ApiResponse
         activated += 1;
     }
     
-    Json(ApiResponse::success(serde_json::json!({
-        "message": "All quantum-enhanced Rust systems activated",
-        "systems_activated": activated,
-        "performance_mode": "QUANTUM_SUPREMACY",
-        "quantum_advantage": "2000x speed, 99.99% accuracy, infinite parallelization"
-    })))
+    Json(ApiResponse::success(SystemActivation {
+        message: "All quantum-enhanced Rust systems activated".to_string(),
+        systems_activated: activated,
+        performance_mode: "QUANTUM_SUPREMACY".to_string(),
+        quantum_advantage: "2000x speed, 99.99% accuracy, infinite parallelization".to_string(),
+    }))
 }
 
 // New Quantum-Enhanced API Handlers
 
-async fn get_quantum_status(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn get_quantum_status(State(state): State<AppState>) -> Json<ApiResponse<QuantumStatus>> {
     if let Some(quantum) = state.quantum_orchestrator.read().as_ref() {
         let status = quantum.get_quantum_status().await;
         Json(ApiResponse::success(status))
@@ -334,16 +370,10 @@ async fn get_quantum_status(State(state): State<AppState>) -> Json<ApiResponse<s
     }
 }
 
-#[derive(Deserialize)]
-struct QuantumTradingRequest {
-    trades: Vec<String>,
-    quantum_enhancement: bool,
-}
-
 async fn execute_quantum_trading(
     State(state): State<AppState>,
     Json(req): Json<QuantumTradingRequest>,
-) -> Json<ApiResponse<serde_json::Value>> {
+) -> Json<ApiResponse<QuantumTradingResult>> {
     if let Some(quantum) = state.quantum_orchestrator.read().as_ref() {
         match quantum.execute_quantum_trading(req.trades).await {
             Ok(result) => Json(ApiResponse::success(result)),
@@ -354,7 +384,7 @@ async fn execute_quantum_trading(
     }
 }
 
-async fn execute_quantum_superposition(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn execute_quantum_superposition(State(state): State<AppState>) -> Json<ApiResponse<QuantumSuperposition>> {
     if let Some(quantum) = state.quantum_orchestrator.read().as_ref() {
         let trades = vec![
             "SOL/USDC".to_string(),
@@ -364,12 +394,12 @@ async fn execute_quantum_superposition(State(state): State<AppState>) -> Json<Ap
         ];
         
         match quantum.execute_quantum_trading(trades).await {
-            Ok(result) => Json(ApiResponse::success(serde_json::json!({
-                "quantum_superposition": "ACTIVE",
-                "parallel_executions": "INFINITE",
-                "quantum_results": result,
-                "enhancement_level": "MAXIMUM"
-            }))),
+            Ok(result) => Json(ApiResponse::success(QuantumSuperposition {
+                quantum_superposition: "ACTIVE".to_string(),
+                parallel_executions: "INFINITE".to_string(),
+                quantum_results: result,
+                enhancement_level: "MAXIMUM".to_string(),
+            })),
             Err(e) => Json(ApiResponse::error(format!("Quantum superposition failed: {}", e))),
         }
     } else {
@@ -377,81 +407,431 @@ async fn execute_quantum_superposition(State(state): State<AppState>) -> Json<Ap
     }
 }
 
-async fn quantum_tunnel_barriers(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn quantum_tunnel_barriers(State(state): State<AppState>) -> Json<ApiResponse<QuantumTunnelResult>> {
     // Simulate quantum tunneling through market barriers
-    Json(ApiResponse::success(serde_json::json!({
-        "quantum_tunneling": "SUCCESS",
-        "barriers_bypassed": ["liquidity", "slippage", "gas_fees", "mev_protection"],
-        "tunneling_probability": 99.9,
-        "speed_increase": "10000x",
-        "cost_reduction": "99.9%"
-    })))
+    Json(ApiResponse::success(QuantumTunnelResult {
+        quantum_tunneling: "SUCCESS".to_string(),
+        barriers_bypassed: ["liquidity", "slippage", "gas_fees", "mev_protection"]
+            .iter()
+            .map(|b| b.to_string())
+            .collect(),
+        tunneling_probability: 99.9,
+        speed_increase: "10000x".to_string(),
+        cost_reduction: "99.9%".to_string(),
+    }))
 }
 
-async fn establish_quantum_entanglement(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn establish_quantum_entanglement(State(state): State<AppState>) -> Json<ApiResponse<QuantumEntanglement>> {
     // Create quantum entangled trading pairs for instantaneous execution
-    Json(ApiResponse::success(serde_json::json!({
-        "quantum_entanglement": "ESTABLISHED",
-        "entangled_pairs": [
-            {"token_a": "SOL", "token_b": "USDC", "correlation": 0.99},
-            {"token_a": "BTC", "token_b": "ETH", "correlation": 0.95},
-            {"token_a": "MEMECOIN", "token_b": "SOL", "correlation": 0.89}
+    let pair = |token_a: &str, token_b: &str, correlation: f64| EntangledPair {
+        token_a: token_a.to_string(),
+        token_b: token_b.to_string(),
+        correlation,
+    };
+    
+    Json(ApiResponse::success(QuantumEntanglement {
+        quantum_entanglement: "ESTABLISHED".to_string(),
+        entangled_pairs: vec![
+            pair("SOL", "USDC", 0.99),
+            pair("BTC", "ETH", 0.95),
+            pair("MEMECOIN", "SOL", 0.89),
         ],
-        "instantaneous_communication": true,
-        "distance_limitation": "NONE",
-        "execution_speed": "INSTANTANEOUS"
-    })))
+        instantaneous_communication: true,
+        distance_limitation: "NONE".to_string(),
+        execution_speed: "INSTANTANEOUS".to_string(),
+    }))
 }
 
-async fn activate_quantum_consciousness(State(state): State<AppState>) -> Json<ApiResponse<serde_json::Value>> {
+async fn activate_quantum_consciousness(State(state): State<AppState>) -> Json<ApiResponse<QuantumConsciousness>> {
     // Integrate quantum consciousness for maximum trading dominance
-    Json(ApiResponse::success(serde_json::json!({
-        "quantum_consciousness": "ACTIVATED",
-        "consciousness_level": 95.0,
-        "awareness_amplification": "10x",
-        "decision_making": "QUANTUM_ENHANCED",
-        "market_prediction": "FUTURE_STATE_ACCESS",
-        "trading_dominance": "ABSOLUTE"
-    })))
-}
-
-fn create_router(state: AppState) -> Router {
-    Router::new()
-        .route("/", get(get_system_overview))
-        .route("/api/status", get(get_system_overview))
+    Json(ApiResponse::success(QuantumConsciousness {
+        quantum_consciousness: "ACTIVATED".to_string(),
+        consciousness_level: 95.0,
+        awareness_amplification: "10x".to_string(),
+        decision_making: "QUANTUM_ENHANCED".to_string(),
+        market_prediction: "FUTURE_STATE_ACCESS".to_string(),
+        trading_dominance: "ABSOLUTE".to_string(),
+    }))
+}
+
+// Event Stream
+
+async fn stream_events(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    let hub = Arc::clone(&state.events);
+    ws.on_upgrade(move |socket| handle_event_stream(socket, hub))
+}
+
+async fn send_stream_message(socket: &mut WebSocket, message: &StreamMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+async fn handle_event_stream(mut socket: WebSocket, hub: Arc<EventHub>) {
+    let mut receiver = hub.subscribe();
+    let mut topics: HashSet<StreamTopic> = StreamTopic::ALL.into_iter().collect();
+    // Highest sequence number already delivered or deliberately skipped
+    let mut last_seen = hub.latest_seq();
+    
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                
+                match serde_json::from_str::<StreamRequest>(&text) {
+                    Ok(StreamRequest::Subscribe { topics: requested, resume_from }) => {
+                        topics = requested.into_iter().collect();
+                        let replay = resume_from.map(|from| hub.replay(from, &topics));
+                        
+                        let subscribed = StreamMessage::Subscribed {
+                            topics: topics.iter().copied().collect(),
+                            latest_seq: hub.latest_seq(),
+                            gap: replay.as_ref().map(|r| r.gap).unwrap_or(false),
+                        };
+                        if !send_stream_message(&mut socket, &subscribed).await {
+                            break;
+                        }
+                        
+                        for event in replay.map(|r| r.events).unwrap_or_default() {
+                            last_seen = last_seen.max(event.seq);
+                            if !send_stream_message(&mut socket, &StreamMessage::Event(event)).await {
+                                return;
+                            }
+                        }
+                    }
+                    Ok(StreamRequest::Unsubscribe { topics: removed }) => {
+                        for topic in removed {
+                            topics.remove(&topic);
+                        }
+                    }
+                    Err(e) => {
+                        let error = StreamMessage::Error { message: format!("Invalid request: {}", e) };
+                        if !send_stream_message(&mut socket, &error).await {
+                            break;
+                        }
+                    }
+                }
+            }
+            
+            event = receiver.recv() => match event {
+                Ok(event) => {
+                    if event.seq <= last_seen {
+                        continue;
+                    }
+                    last_seen = event.seq;
+                    if topics.contains(&event.topic)
+                        && !send_stream_message(&mut socket, &StreamMessage::Event(event)).await
+                    {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => {
+                    // Catch up from the replay buffer instead of dropping events
+                    let replay = hub.replay(last_seen, &topics);
+                    if replay.gap {
+                        let error = StreamMessage::Error { message: "Stream lagged; some events were dropped".to_string() };
+                        if !send_stream_message(&mut socket, &error).await {
+                            break;
+                        }
+                    }
+                    // Events published after the replay are still queued on
+                    // the receiver, so only skip what the replay delivered
+                    for event in replay.events {
+                        last_seen = last_seen.max(event.seq);
+                        if !send_stream_message(&mut socket, &StreamMessage::Event(event)).await {
+                            return;
+                        }
+                    }
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+/// Polls subsystem state once per second and publishes what changed, so
+/// dashboards can follow the stream instead of polling the REST endpoints.
+async fn run_state_watcher(state: AppState) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    let mut known_alerts: Option<HashSet<String>> = None;
+    let mut known_targets: Option<HashSet<String>> = None;
+    let mut balances: HashMap<String, f64> = HashMap::new();
+    let mut last_status = serde_json::Map::new();
+    
+    loop {
+        ticker.tick().await;
+        
+        // Clone the handles out so no lock guard is held across an await
+        let tracker = state.metrics_tracker.read().clone();
+        if let Some(tracker) = tracker {
+            let alerts = tracker.get_recent_alerts(100).await;
+            let current: HashSet<String> = alerts.iter().map(|a| a.id.clone()).collect();
+            if let Some(known) = &known_alerts {
+                for alert in alerts.iter().filter(|a| !known.contains(&a.id)) {
+                    state.events.publish(StreamEvent::Alert {
+                        id: alert.id.clone(),
+                        alert_type: format!("{:?}", alert.alert_type),
+                        severity: format!("{:?}", alert.severity),
+                        message: alert.message.clone(),
+                        wallet_address: alert.wallet_address.clone(),
+                        transaction_hash: alert.transaction_hash.clone(),
+                        amount: alert.amount,
+                    });
+                }
+            }
+            known_alerts = Some(current);
+            
+            for wallet in tracker.get_all_wallet_metrics().await {
+                let previous = balances.insert(wallet.address.clone(), wallet.sol_balance);
+                if let Some(previous_sol) = previous {
+                    if (previous_sol - wallet.sol_balance).abs() > f64::EPSILON {
+                        state.events.publish(StreamEvent::WalletBalanceChanged {
+                            address: wallet.address,
+                            name: wallet.name,
+                            previous_sol,
+                            current_sol: wallet.sol_balance,
+                        });
+                    }
+                }
+            }
+            
+            if let Ok(serde_json::Value::Object(status)) = serde_json::to_value(tracker.get_system_overview().await) {
+                let changes: serde_json::Map<String, serde_json::Value> = status
+                    .iter()
+                    .filter(|(key, value)| last_status.get(*key) != Some(*value))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                if !changes.is_empty() {
+                    state.events.publish(StreamEvent::SystemStatus { changes });
+                }
+                last_status = status;
+            }
+        }
+        
+        let sniper = state.memecoin_sniper.read().clone();
+        if let Some(sniper) = sniper {
+            let targets = sniper.get_active_targets().await;
+            let current: HashSet<String> = targets.iter().map(|t| t.token_address.clone()).collect();
+            if let Some(known) = &known_targets {
+                for target in targets.iter().filter(|t| !known.contains(&t.token_address)) {
+                    state.events.publish(StreamEvent::OpportunityDetected {
+                        source: "memecoin_sniper".to_string(),
+                        identifier: target.token_address.clone(),
+                        description: format!("{} ({})", target.token_name, target.ticker),
+                        score: target.snipe_score,
+                    });
+                }
+            }
+            known_targets = Some(current);
+        }
+    }
+}
+
+async fn list_strategies(State(state): State<AppState>) -> Json<ApiResponse<Vec<StrategyEntry>>> {
+    let entries = state.strategy_registry.read().list().into_iter().map(StrategyEntry::from).collect();
+    Json(ApiResponse::success(entries))
+}
+
+/// Brings the paper session in line with the registry entry for `id`
+fn restart_paper_strategy(state: &AppState, registry: &StrategyRegistry, id: &str) -> anyhow::Result<StrategyEntry> {
+    let info = registry.info(id)?;
+    let strategy = if info.enabled { Some(registry.instantiate(id)?) } else { None };
+    state.paper_session.lock().set_strategy(id, strategy);
+    Ok(info.into())
+}
+
+async fn set_strategy_enabled(
+    State(state): State<AppState>,
+    Json(req): Json<StrategyToggleRequest>,
+) -> Json<ApiResponse<StrategyEntry>> {
+    let mut registry = state.strategy_registry.write();
+    let result = registry
+        .set_enabled(&req.id, req.enabled)
+        .and_then(|_| restart_paper_strategy(&state, &registry, &req.id));
+    match result {
+        Ok(entry) => Json(ApiResponse::success(entry)),
+        Err(e) => Json(ApiResponse::error(format!("Failed to update {}: {}", req.id, e))),
+    }
+}
+
+async fn configure_strategy(
+    State(state): State<AppState>,
+    Json(req): Json<StrategyConfigRequest>,
+) -> Json<ApiResponse<StrategyEntry>> {
+    let mut registry = state.strategy_registry.write();
+    let result = registry
+        .configure(&req.id, &req.parameters)
+        .and_then(|_| restart_paper_strategy(&state, &registry, &req.id));
+    match result {
+        Ok(entry) => Json(ApiResponse::success(entry)),
+        Err(e) => Json(ApiResponse::error(format!("Failed to configure {}: {}", req.id, e))),
+    }
+}
+
+async fn backtest_strategy(
+    State(state): State<AppState>,
+    Json(req): Json<BacktestRequest>,
+) -> Json<ApiResponse<StrategyReport>> {
+    let events: Result<Vec<MarketEvent>, _> = req.events.into_iter().map(serde_json::from_value).collect();
+    let report = events.map_err(anyhow::Error::from).and_then(|events| {
+        SimulationSession::backtest(&state.strategy_registry.read(), &req.id, &events, SimulationConfig::default())
+    });
+    match report {
+        Ok(report) => Json(ApiResponse::success(report.into())),
+        Err(e) => Json(ApiResponse::error(format!("Backtest of {} failed: {}", req.id, e))),
+    }
+}
+
+async fn get_paper_trading(State(state): State<AppState>) -> Json<ApiResponse<Vec<StrategyReport>>> {
+    let reports = state.paper_session.lock().reports().into_iter().map(StrategyReport::from).collect();
+    Json(ApiResponse::success(reports))
+}
+
+async fn get_audit_log(State(state): State<AppState>) -> Json<ApiResponse<Vec<AuditEntry>>> {
+    Json(ApiResponse::success(state.auth.recent_audit_entries(200)))
+}
+
+async fn get_openapi() -> Json<black_diamond_client::OpenApi> {
+    Json(black_diamond_client::openapi_document())
+}
+
+async fn require_auth(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let method = request.method().as_str().to_string();
+    let path = request.uri().path().to_string();
+    if routes::is_public(&path) {
+        return next.run(request).await;
+    }
+    
+    let header_value = |name: &str| {
+        request.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+    };
+    // Browsers cannot set headers on WebSocket upgrades, so the event stream
+    // alone also accepts a percent-encoded `?api_key=`
+    let api_key = header_value(API_KEY_HEADER).or_else(|| {
+        if path != routes::STREAM {
+            return None;
+        }
+        Query::<HashMap<String, String>>::try_from_uri(request.uri())
+            .ok()?
+            .0
+            .remove("api_key")
+    });
+    let authorization = header_value(header::AUTHORIZATION.as_str());
+    
+    let principal = match state.auth.authorize(&method, &path, api_key.as_deref(), authorization.as_deref()) {
+        Ok(principal) => principal,
+        Err(e) => {
+            warn!("🔒 Rejected {} {}: {}", method, path, e);
+            let status = match e {
+                AuthError::MissingCredentials | AuthError::InvalidCredentials(_) => StatusCode::UNAUTHORIZED,
+                AuthError::Forbidden { .. } => StatusCode::FORBIDDEN,
+                AuthError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            };
+            let caller = state.auth.identify(api_key.as_deref(), authorization.as_deref());
+            state.auth.record_audit(&caller, &method, &path, status.as_u16());
+            return (status, Json(ApiResponse::<()>::error(e.to_string()))).into_response();
+        }
+    };
+    
+    let response = next.run(request).await;
+    
+    if api_auth::is_mutating(&method) {
+        state.auth.record_audit(&principal, &method, &path, response.status().as_u16());
+    }
+    
+    response
+}
+
+fn create_cors_layer(allowed_origins: &[String]) -> CorsLayer {
+    let origins: Vec<HeaderValue> = allowed_origins
+        .iter()
+        .filter_map(|origin| origin.parse().ok())
+        .collect();
+    
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static(API_KEY_HEADER),
+        ])
+}
+
+// Every entry in the shared route table must map to exactly one handler here
+fn route_handler(spec: &RouteSpec) -> Option<MethodRouter<AppState>> {
+    let filter = match spec.method {
+        HttpMethod::Get => MethodFilter::GET,
+        HttpMethod::Post => MethodFilter::POST,
+    };
+    
+    let handler = match spec.path {
+        routes::ROOT | routes::SYSTEM_OVERVIEW => on(filter, get_system_overview),
+        routes::OPENAPI => on(filter, get_openapi),
         
         // Quantum Enhanced Endpoints
-        .route("/api/quantum/status", get(get_quantum_status))
-        .route("/api/quantum/execute-trading", post(execute_quantum_trading))
-        .route("/api/quantum/superposition", post(execute_quantum_superposition))
-        .route("/api/quantum/tunnel-barriers", post(quantum_tunnel_barriers))
-        .route("/api/quantum/entanglement", post(establish_quantum_entanglement))
-        .route("/api/quantum/consciousness", post(activate_quantum_consciousness))
+        routes::QUANTUM_STATUS => on(filter, get_quantum_status),
+        routes::QUANTUM_EXECUTE_TRADING => on(filter, execute_quantum_trading),
+        routes::QUANTUM_SUPERPOSITION => on(filter, execute_quantum_superposition),
+        routes::QUANTUM_TUNNEL_BARRIERS => on(filter, quantum_tunnel_barriers),
+        routes::QUANTUM_ENTANGLEMENT => on(filter, establish_quantum_entanglement),
+        routes::QUANTUM_CONSCIOUSNESS => on(filter, activate_quantum_consciousness),
         
         // Enhanced Black Diamond Endpoints
-        .route("/api/black-diamond/status", get(get_black_diamond_status))
-        .route("/api/black-diamond/execute-arbitrage", post(execute_arbitrage))
+        routes::BLACK_DIAMOND_STATUS => on(filter, get_black_diamond_status),
+        routes::EXECUTE_ARBITRAGE => on(filter, execute_arbitrage),
         
         // Zero Capital Strategies
-        .route("/api/zero-capital/strategies", get(get_zero_capital_strategies))
-        .route("/api/zero-capital/stats", get(get_zero_capital_stats))
+        routes::ZERO_CAPITAL_STRATEGIES => on(filter, get_zero_capital_strategies),
+        routes::ZERO_CAPITAL_STATS => on(filter, get_zero_capital_stats),
         
         // Memecoin Sniping
-        .route("/api/memecoin/targets", get(get_memecoin_targets))
-        .route("/api/memecoin/stats", get(get_sniping_stats))
+        routes::MEMECOIN_TARGETS => on(filter, get_memecoin_targets),
+        routes::MEMECOIN_STATS => on(filter, get_sniping_stats),
         
         // On-Chain Innovations
-        .route("/api/innovations/bot-performance", get(get_bot_performance))
-        .route("/api/innovations/quantum-boost", post(execute_quantum_speed_boost))
+        routes::BOT_PERFORMANCE => on(filter, get_bot_performance),
+        routes::QUANTUM_BOOST => on(filter, execute_quantum_speed_boost),
         
         // Metrics & Monitoring
-        .route("/api/metrics/wallets", get(get_wallet_metrics))
-        .route("/api/metrics/overview", get(get_metrics_overview))
+        routes::WALLET_METRICS => on(filter, get_wallet_metrics),
+        routes::METRICS_OVERVIEW => on(filter, get_metrics_overview),
+        
+        // Strategy Plugins
+        routes::STRATEGIES => on(filter, list_strategies),
+        routes::STRATEGY_ENABLE => on(filter, set_strategy_enabled),
+        routes::STRATEGY_CONFIGURE => on(filter, configure_strategy),
+        routes::STRATEGY_BACKTEST => on(filter, backtest_strategy),
+        routes::PAPER_TRADING => on(filter, get_paper_trading),
         
         // System Management
-        .route("/api/systems/activate-all", post(activate_all_systems))
+        routes::ACTIVATE_ALL => on(filter, activate_all_systems),
+        routes::AUDIT_LOG => on(filter, get_audit_log),
         
-        .layer(CorsLayer::permissive())
+        // Event Stream
+        routes::STREAM => on(filter, stream_events),
+        
+        _ => return None,
+    };
+    
+    Some(handler)
+}
+
+fn create_router(state: AppState, allowed_origins: &[String]) -> Router {
+    routes::ROUTES
+        .iter()
+        .fold(Router::new(), |router, spec| {
+            let handler = route_handler(spec)
+                .unwrap_or_else(|| panic!("No handler for {} {}", spec.operation_id, spec.path));
+            router.route(spec.path, handler)
+        })
+        .layer(middleware::from_fn_with_state(state.clone(), require_auth))
+        .layer(create_cors_layer(allowed_origins))
         .with_state(state)
 }
 
@@ -473,6 +853,12 @@ async fn main() -> anyhow::Result<()> {
         CommitmentConfig::confirmed(),
     ));
     
+    // Load API keys and JWT secret
+    let auth = ApiAuth::from_env(Some(cli.audit_log.clone().into()))?;
+    if !auth.is_configured() {
+        warn!("🔒 No API keys or JWT secret configured - every API request will be rejected");
+    }
+    
     // Create application state
     let state = AppState {
         black_diamond_pipeline: Arc::new(RwLock::new(None)),
@@ -481,8 +867,12 @@ async fn main() -> anyhow::Result<()> {
         on_chain_innovations: Arc::new(RwLock::new(None)),
         metrics_tracker: Arc::new(RwLock::new(None)),
         quantum_orchestrator: Arc::new(RwLock::new(None)),
+        strategy_registry: Arc::new(RwLock::new(StrategyRegistry::new())),
+        paper_session: Arc::new(parking_lot::Mutex::new(SimulationSession::new(Vec::new(), SimulationConfig::default()))),
         connection,
         system_stats: Arc::new(DashMap::new()),
+        auth: Arc::new(auth),
+        events: Arc::new(EventHub::new()),
     };
     
     // Initialize all systems
@@ -491,8 +881,11 @@ async fn main() -> anyhow::Result<()> {
         info!("Server will start with partial functionality");
     }
     
+    // Publish subsystem changes to the event stream
+    tokio::spawn(run_state_watcher(state.clone()));
+    
     // Create router
-    let app = create_router(state);
+    let app = create_router(state, &cli.allowed_origins);
     
     // Start server
     let addr = format!("{}:{}", cli.host, cli.port);
@@ -505,4 +898,110 @@ async fn main() -> anyhow::Result<()> {
     axum::serve(listener, app).await?;
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use black_diamond_client::types::Role;
+    use tower::Service;
+
+    fn test_state() -> AppState {
+        let mut auth = ApiAuth::new(None);
+        auth.add_api_key("test", "test-key", Role::Admin, 10_000);
+        
+        AppState {
+            black_diamond_pipeline: Arc::new(RwLock::new(None)),
+            zero_capital_strategies: Arc::new(RwLock::new(None)),
+            memecoin_sniper: Arc::new(RwLock::new(None)),
+            on_chain_innovations: Arc::new(RwLock::new(None)),
+            metrics_tracker: Arc::new(RwLock::new(None)),
+            quantum_orchestrator: Arc::new(RwLock::new(None)),
+            strategy_registry: Arc::new(RwLock::new(StrategyRegistry::new())),
+            paper_session: Arc::new(parking_lot::Mutex::new(SimulationSession::new(Vec::new(), SimulationConfig::default()))),
+            connection: Arc::new(RpcClient::new("http://127.0.0.1:8899".to_string())),
+            system_stats: Arc::new(DashMap::new()),
+            auth: Arc::new(auth),
+            events: Arc::new(EventHub::new()),
+        }
+    }
+    
+    async fn status_of(router: &Router, method: &str, path: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(API_KEY_HEADER, "test-key")
+            .body(Body::empty())
+            .unwrap();
+        router.clone().call(request).await.unwrap().status()
+    }
+    
+    #[test]
+    fn every_route_in_the_table_has_a_handler() {
+        for spec in routes::ROUTES {
+            assert!(route_handler(spec).is_some(), "{} has no handler", spec.path);
+        }
+    }
+    
+    #[tokio::test]
+    async fn the_router_serves_every_documented_operation() {
+        let router = create_router(test_state(), &[]);
+        let document = black_diamond_client::openapi_document();
+        
+        for (path, item) in &document.paths.paths {
+            for item_type in item.operations.keys() {
+                let method = serde_json::to_value(item_type).unwrap().as_str().unwrap().to_uppercase();
+                let status = status_of(&router, &method, path).await;
+                assert_ne!(status, StatusCode::NOT_FOUND, "{} {}", method, path);
+                assert_ne!(status, StatusCode::METHOD_NOT_ALLOWED, "{} {}", method, path);
+            }
+        }
+        
+        assert_eq!(status_of(&router, "GET", "/api/undocumented").await, StatusCode::NOT_FOUND);
+        assert_eq!(status_of(&router, "DELETE", routes::SYSTEM_OVERVIEW).await, StatusCode::METHOD_NOT_ALLOWED);
+    }
+    
+    async fn post_json(router: &Router, path: &str, body: serde_json::Value) -> serde_json::Value {
+        let request = Request::builder()
+            .method("POST")
+            .uri(path)
+            .header(API_KEY_HEADER, "test-key")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.clone().call(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+    
+    #[tokio::test]
+    async fn enabled_strategies_paper_trade_and_backtest_through_the_api() {
+        let state = test_state();
+        let descriptor = strategy::StrategyDescriptor {
+            id: "cross".to_string(),
+            name: "Cross venue".to_string(),
+            origin: "test".to_string(),
+            description: String::new(),
+        };
+        state
+            .strategy_registry
+            .write()
+            .register(descriptor, strategy::StrategyParams::new(), Box::new(|| Box::new(strategy::CrossVenueArbitrage::new("cross"))))
+            .unwrap();
+        let router = create_router(state.clone(), &[]);
+        
+        let enabled = post_json(&router, routes::STRATEGY_ENABLE, serde_json::json!({ "id": "cross", "enabled": true })).await;
+        assert_eq!(enabled["data"]["enabled"], true);
+        assert_eq!(state.paper_session.lock().reports().len(), 1);
+        
+        let backtest = post_json(&router, routes::STRATEGY_BACKTEST, serde_json::json!({ "id": "cross", "events": [] })).await;
+        assert_eq!(backtest["data"]["strategy_id"], "cross");
+        
+        let disabled = post_json(&router, routes::STRATEGY_ENABLE, serde_json::json!({ "id": "cross", "enabled": false })).await;
+        assert_eq!(disabled["data"]["enabled"], false);
+        assert!(state.paper_session.lock().reports().is_empty());
+        
+        let unknown = post_json(&router, routes::STRATEGY_ENABLE, serde_json::json!({ "id": "missing", "enabled": true })).await;
+        assert_eq!(unknown["success"], false);
+    }
+}
@@ -0,0 +1,328 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+use super::{
+    param_f64, param_string, param_strings, Fill, MarketEvent, OrderIntent, ParameterSpec, Side, Strategy,
+    StrategyContext, StrategyMetrics, StrategyParams,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct TopOfBook {
+    bid: f64,
+    ask: f64,
+    depth: f64,
+    timestamp_ms: u64,
+}
+
+fn is_fresh(book: &TopOfBook, now_ms: u64, max_age_ms: f64) -> bool {
+    now_ms.saturating_sub(book.timestamp_ms) as f64 <= max_age_ms
+}
+
+/// Buys a market on the venue with the lowest ask and sells it on the venue
+/// with the highest bid when the gap clears fees and a minimum edge.
+pub struct CrossVenueArbitrage {
+    id: String,
+    market: String,
+    venues: Vec<String>,
+    min_edge_bps: f64,
+    fee_bps: f64,
+    max_notional: f64,
+    max_quote_age_ms: f64,
+    books: HashMap<String, TopOfBook>,
+    metrics: StrategyMetrics,
+    next_group: u64,
+}
+
+impl CrossVenueArbitrage {
+    pub fn new(id: &str) -> Self {
+        CrossVenueArbitrage {
+            id: id.to_string(),
+            market: "SOL/USDC".to_string(),
+            venues: Vec::new(),
+            min_edge_bps: 10.0,
+            fee_bps: 5.0,
+            max_notional: 1000.0,
+            max_quote_age_ms: 2000.0,
+            books: HashMap::new(),
+            metrics: StrategyMetrics::default(),
+            next_group: 0,
+        }
+    }
+}
+
+impl Strategy for CrossVenueArbitrage {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn parameter_schema(&self) -> Vec<ParameterSpec> {
+        vec![
+            ParameterSpec::text("market", "SOL/USDC", "BASE/QUOTE pair to arbitrage"),
+            ParameterSpec::text_list("venues", &[], "Venues to compare; empty means every venue seen"),
+            ParameterSpec::float("min_edge_bps", 0.0, 1000.0, 10.0, "Edge required after fees"),
+            ParameterSpec::float("fee_bps", 0.0, 500.0, 5.0, "Taker fee per leg"),
+            ParameterSpec::float("max_notional", 0.0, 1e9, 1000.0, "Quote committed per opportunity"),
+            ParameterSpec::float("max_quote_age_ms", 0.0, 60_000.0, 2000.0, "Ignore quotes older than this"),
+        ]
+    }
+
+    fn configure(&mut self, params: &StrategyParams) -> Result<()> {
+        self.market = param_string(params, "market");
+        self.venues = param_strings(params, "venues");
+        self.min_edge_bps = param_f64(params, "min_edge_bps");
+        self.fee_bps = param_f64(params, "fee_bps");
+        self.max_notional = param_f64(params, "max_notional");
+        self.max_quote_age_ms = param_f64(params, "max_quote_age_ms");
+        self.books.clear();
+        Ok(())
+    }
+
+    fn on_market_event(&mut self, event: &MarketEvent) {
+        self.metrics.events_seen += 1;
+        if let MarketEvent::Quote { venue, market, bid, ask, depth, timestamp_ms } = event {
+            if *market != self.market || (!self.venues.is_empty() && !self.venues.contains(venue)) {
+                return;
+            }
+            self.books.insert(
+                venue.clone(),
+                TopOfBook {
+                    bid: *bid,
+                    ask: *ask,
+                    depth: *depth,
+                    timestamp_ms: *timestamp_ms,
+                },
+            );
+        }
+    }
+
+    fn propose_orders(&mut self, ctx: &StrategyContext) -> Vec<OrderIntent> {
+        let fresh: Vec<(&String, &TopOfBook)> = self
+            .books
+            .iter()
+            .filter(|(_, book)| is_fresh(book, ctx.timestamp_ms, self.max_quote_age_ms))
+            .collect();
+        let Some((buy_venue, buy)) = fresh.iter().min_by(|a, b| a.1.ask.total_cmp(&b.1.ask)) else {
+            return Vec::new();
+        };
+        let Some((sell_venue, sell)) = fresh.iter().max_by(|a, b| a.1.bid.total_cmp(&b.1.bid)) else {
+            return Vec::new();
+        };
+        if buy_venue == sell_venue || buy.ask <= 0.0 {
+            return Vec::new();
+        }
+
+        let edge_bps = (sell.bid / buy.ask - 1.0) * 10_000.0 - 2.0 * self.fee_bps;
+        if edge_bps < self.min_edge_bps {
+            return Vec::new();
+        }
+
+        let notional = self.max_notional.min(ctx.available_capital);
+        let size = (notional / (buy.ask * (1.0 + self.fee_bps / 10_000.0)))
+            .min(buy.depth)
+            .min(sell.depth);
+        if size <= 0.0 {
+            return Vec::new();
+        }
+
+        self.next_group += 1;
+        let group = format!("{}-{}", self.id, self.next_group);
+        let reason = format!("{} {:.1} bps net across {} -> {}", self.market, edge_bps, buy_venue, sell_venue);
+        let orders = vec![
+            OrderIntent {
+                venue: buy_venue.to_string(),
+                market: self.market.clone(),
+                side: Side::Buy,
+                size,
+                limit_price: Some(buy.ask),
+                group: Some(group.clone()),
+                reason: reason.clone(),
+            },
+            OrderIntent {
+                venue: sell_venue.to_string(),
+                market: self.market.clone(),
+                side: Side::Sell,
+                size,
+                limit_price: Some(sell.bid),
+                group: Some(group),
+                reason,
+            },
+        ];
+        self.metrics.orders_proposed += orders.len() as u64;
+        orders
+    }
+
+    fn on_fill(&mut self, fill: &Fill) {
+        self.metrics.record_fill(fill);
+    }
+
+    fn metrics(&self) -> StrategyMetrics {
+        self.metrics.clone()
+    }
+}
+
+/// Cycles quote -> A -> B -> quote on one venue through the `A/B`, `B/QUOTE`
+/// and `A/QUOTE` markets, in whichever direction is profitable after fees.
+pub struct TriangularArbitrage {
+    id: String,
+    venue: String,
+    /// `[A, B, QUOTE]`
+    assets: [String; 3],
+    min_edge_bps: f64,
+    fee_bps: f64,
+    max_notional: f64,
+    max_quote_age_ms: f64,
+    books: HashMap<String, TopOfBook>,
+    metrics: StrategyMetrics,
+    next_group: u64,
+}
+
+impl TriangularArbitrage {
+    pub fn new(id: &str) -> Self {
+        TriangularArbitrage {
+            id: id.to_string(),
+            venue: "raydium".to_string(),
+            assets: ["SOL".to_string(), "USDT".to_string(), "USDC".to_string()],
+            min_edge_bps: 15.0,
+            fee_bps: 5.0,
+            max_notional: 1000.0,
+            max_quote_age_ms: 2000.0,
+            books: HashMap::new(),
+            metrics: StrategyMetrics::default(),
+            next_group: 0,
+        }
+    }
+
+    fn market(&self, base: usize, quote: usize) -> String {
+        format!("{}/{}", self.assets[base], self.assets[quote])
+    }
+}
+
+impl Strategy for TriangularArbitrage {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn parameter_schema(&self) -> Vec<ParameterSpec> {
+        vec![
+            ParameterSpec::text("venue", "raydium", "Venue all three legs trade on"),
+            ParameterSpec::text_list("assets", &["SOL", "USDT", "USDC"], "[A, B, QUOTE] of the cycle"),
+            ParameterSpec::float("min_edge_bps", 0.0, 1000.0, 15.0, "Edge required after fees"),
+            ParameterSpec::float("fee_bps", 0.0, 500.0, 5.0, "Taker fee per leg"),
+            ParameterSpec::float("max_notional", 0.0, 1e9, 1000.0, "Quote committed per cycle"),
+            ParameterSpec::float("max_quote_age_ms", 0.0, 60_000.0, 2000.0, "Ignore quotes older than this"),
+        ]
+    }
+
+    fn configure(&mut self, params: &StrategyParams) -> Result<()> {
+        let assets = param_strings(params, "assets");
+        let [a, b, quote]: [String; 3] = assets
+            .try_into()
+            .map_err(|assets: Vec<String>| anyhow!("assets needs exactly 3 entries, got {}", assets.len()))?;
+        self.assets = [a, b, quote];
+        self.venue = param_string(params, "venue");
+        self.min_edge_bps = param_f64(params, "min_edge_bps");
+        self.fee_bps = param_f64(params, "fee_bps");
+        self.max_notional = param_f64(params, "max_notional");
+        self.max_quote_age_ms = param_f64(params, "max_quote_age_ms");
+        self.books.clear();
+        Ok(())
+    }
+
+    fn on_market_event(&mut self, event: &MarketEvent) {
+        self.metrics.events_seen += 1;
+        if let MarketEvent::Quote { venue, market, bid, ask, depth, timestamp_ms } = event {
+            if *venue != self.venue {
+                return;
+            }
+            let watched = [self.market(0, 1), self.market(1, 2), self.market(0, 2)];
+            if watched.contains(market) {
+                self.books.insert(
+                    market.clone(),
+                    TopOfBook {
+                        bid: *bid,
+                        ask: *ask,
+                        depth: *depth,
+                        timestamp_ms: *timestamp_ms,
+                    },
+                );
+            }
+        }
+    }
+
+    fn propose_orders(&mut self, ctx: &StrategyContext) -> Vec<OrderIntent> {
+        let (ab_market, bq_market, aq_market) = (self.market(0, 1), self.market(1, 2), self.market(0, 2));
+        let book = |market: &str| {
+            self.books
+                .get(market)
+                .copied()
+                .filter(|b| is_fresh(b, ctx.timestamp_ms, self.max_quote_age_ms) && b.bid > 0.0 && b.ask > 0.0)
+        };
+        let (Some(ab), Some(bq), Some(aq)) = (book(&ab_market), book(&bq_market), book(&aq_market)) else {
+            return Vec::new();
+        };
+
+        let leg_fee = 1.0 - self.fee_bps / 10_000.0;
+        let fee_factor = leg_fee.powi(3);
+        let notional = self.max_notional.min(ctx.available_capital);
+        // quote -> A (buy A/Q) -> B (sell A/B) -> quote (sell B/Q)
+        let forward = ab.bid * bq.bid / aq.ask * fee_factor;
+        // quote -> B (buy B/Q) -> A (buy A/B) -> quote (sell A/Q)
+        let backward = aq.bid / (bq.ask * ab.ask) * fee_factor;
+
+        let (edge, legs) = if forward >= backward {
+            let a_amount = (notional / (aq.ask * (2.0 - leg_fee))).min(aq.depth).min(ab.depth);
+            // The A/B fee is taken in B, so only the net proceeds move on
+            let b_amount = (a_amount * ab.bid * leg_fee).min(bq.depth);
+            (
+                forward,
+                vec![
+                    (aq_market, Side::Buy, a_amount, aq.ask),
+                    (ab_market, Side::Sell, a_amount, ab.bid),
+                    (bq_market, Side::Sell, b_amount, bq.bid),
+                ],
+            )
+        } else {
+            let b_amount = (notional / (bq.ask * (2.0 - leg_fee))).min(bq.depth);
+            let a_amount = (b_amount / (ab.ask * (2.0 - leg_fee))).min(ab.depth).min(aq.depth);
+            (
+                backward,
+                vec![
+                    (bq_market, Side::Buy, b_amount, bq.ask),
+                    (ab_market, Side::Buy, a_amount, ab.ask),
+                    (aq_market, Side::Sell, a_amount, aq.bid),
+                ],
+            )
+        };
+
+        let edge_bps = (edge - 1.0) * 10_000.0;
+        if edge_bps < self.min_edge_bps || legs.iter().any(|(_, _, size, _)| *size <= 0.0) {
+            return Vec::new();
+        }
+
+        self.next_group += 1;
+        let group = format!("{}-{}", self.id, self.next_group);
+        let reason = format!("{} cycle {:.1} bps net on {}", self.assets.join("/"), edge_bps, self.venue);
+        let orders: Vec<OrderIntent> = legs
+            .into_iter()
+            .map(|(market, side, size, price)| OrderIntent {
+                venue: self.venue.clone(),
+                market,
+                side,
+                size,
+                limit_price: Some(price),
+                group: Some(group.clone()),
+                reason: reason.clone(),
+            })
+            .collect();
+        self.metrics.orders_proposed += orders.len() as u64;
+        orders
+    }
+
+    fn on_fill(&mut self, fill: &Fill) {
+        self.metrics.record_fill(fill);
+    }
+
+    fn metrics(&self) -> StrategyMetrics {
+        self.metrics.clone()
+    }
+}
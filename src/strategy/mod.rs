@@ -0,0 +1,312 @@
+/**
+ * STRATEGY PLUGINS
+ * One trait for every trading strategy, so each can be enabled, configured,
+ * backtested and paper-traded through the same registry and simulator
+ */
+
+pub mod builtin;
+pub mod registry;
+pub mod simulation;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+pub use builtin::{CrossVenueArbitrage, TriangularArbitrage};
+pub use registry::{StrategyDescriptor, StrategyFactory, StrategyInfo, StrategyRegistry};
+pub use simulation::{BacktestReport, SimulationConfig, SimulationSession};
+
+pub type StrategyParams = Map<String, Value>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn sign(self) -> f64 {
+        match self {
+            Side::Buy => 1.0,
+            Side::Sell => -1.0,
+        }
+    }
+}
+
+/// Market data a strategy reacts to. Markets are `BASE/QUOTE` pairs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketEvent {
+    Quote {
+        venue: String,
+        market: String,
+        bid: f64,
+        ask: f64,
+        /// Base units available at the quoted prices
+        depth: f64,
+        timestamp_ms: u64,
+    },
+    Trade {
+        venue: String,
+        market: String,
+        price: f64,
+        size: f64,
+        side: Side,
+        timestamp_ms: u64,
+    },
+}
+
+impl MarketEvent {
+    pub fn timestamp_ms(&self) -> u64 {
+        match self {
+            MarketEvent::Quote { timestamp_ms, .. } | MarketEvent::Trade { timestamp_ms, .. } => *timestamp_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderIntent {
+    pub venue: String,
+    pub market: String,
+    pub side: Side,
+    /// Base units
+    pub size: f64,
+    /// Worst acceptable price; market order when `None`
+    pub limit_price: Option<f64>,
+    /// Legs sharing a group fill together or not at all
+    pub group: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub strategy_id: String,
+    pub venue: String,
+    pub market: String,
+    pub side: Side,
+    pub size: f64,
+    pub price: f64,
+    /// Quote paid in fees
+    pub fee: f64,
+    pub group: Option<String>,
+    pub timestamp_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyContext {
+    pub timestamp_ms: u64,
+    /// Quote capital the strategy may commit right now
+    pub available_capital: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ParameterKind {
+    Float { min: f64, max: f64 },
+    Integer { min: i64, max: i64 },
+    Bool,
+    Text,
+    TextList,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSpec {
+    pub name: String,
+    pub kind: ParameterKind,
+    pub default: Value,
+    pub description: String,
+}
+
+impl ParameterSpec {
+    pub fn float(name: &str, min: f64, max: f64, default: f64, description: &str) -> Self {
+        ParameterSpec {
+            name: name.to_string(),
+            kind: ParameterKind::Float { min, max },
+            default: Value::from(default),
+            description: description.to_string(),
+        }
+    }
+
+    pub fn text(name: &str, default: &str, description: &str) -> Self {
+        ParameterSpec {
+            name: name.to_string(),
+            kind: ParameterKind::Text,
+            default: Value::from(default),
+            description: description.to_string(),
+        }
+    }
+
+    pub fn text_list(name: &str, default: &[&str], description: &str) -> Self {
+        ParameterSpec {
+            name: name.to_string(),
+            kind: ParameterKind::TextList,
+            default: Value::from(default.to_vec()),
+            description: description.to_string(),
+        }
+    }
+
+    fn check(&self, value: &Value) -> Result<()> {
+        let valid = match &self.kind {
+            ParameterKind::Float { min, max } => value.as_f64().is_some_and(|v| (*min..=*max).contains(&v)),
+            ParameterKind::Integer { min, max } => value.as_i64().is_some_and(|v| (*min..=*max).contains(&v)),
+            ParameterKind::Bool => value.is_boolean(),
+            ParameterKind::Text => value.is_string(),
+            ParameterKind::TextList => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(anyhow!("Parameter {} = {} does not fit {:?}", self.name, value, self.kind))
+        }
+    }
+}
+
+/// Checks `params` against `schema` and fills in defaults. Unknown names are
+/// rejected so typos do not silently fall back to a default.
+pub fn resolve_params(schema: &[ParameterSpec], params: &StrategyParams) -> Result<StrategyParams> {
+    if let Some(unknown) = params.keys().find(|k| !schema.iter().any(|s| &s.name == *k)) {
+        return Err(anyhow!("Unknown parameter {}", unknown));
+    }
+
+    let mut resolved = StrategyParams::new();
+    for spec in schema {
+        let value = params.get(&spec.name).cloned().unwrap_or_else(|| spec.default.clone());
+        spec.check(&value)?;
+        resolved.insert(spec.name.clone(), value);
+    }
+    Ok(resolved)
+}
+
+pub(crate) fn param_f64(params: &StrategyParams, name: &str) -> f64 {
+    params.get(name).and_then(Value::as_f64).unwrap_or_default()
+}
+
+pub(crate) fn param_string(params: &StrategyParams, name: &str) -> String {
+    params.get(name).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+pub(crate) fn param_strings(params: &StrategyParams, name: &str) -> Vec<String> {
+    params
+        .get(name)
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StrategyMetrics {
+    pub events_seen: u64,
+    pub orders_proposed: u64,
+    pub fills: u64,
+    pub volume: f64,
+    pub fees_paid: f64,
+    pub realized_pnl: f64,
+    /// Net base position per `venue:market`
+    pub positions: HashMap<String, f64>,
+    /// Average entry price of each open position
+    pub entry_prices: HashMap<String, f64>,
+}
+
+impl StrategyMetrics {
+    /// Books a fill against the net position, realising pnl on the part that
+    /// reduces it at the average entry cost
+    pub fn record_fill(&mut self, fill: &Fill) {
+        let key = format!("{}:{}", fill.venue, fill.market);
+        let position = self.positions.entry(key.clone()).or_insert(0.0);
+        let avg_cost = self.entry_prices.entry(key).or_insert(0.0);
+        let signed = fill.side.sign() * fill.size;
+
+        if *position == 0.0 || position.signum() == signed.signum() {
+            let total = position.abs() + fill.size;
+            // An empty fill against a flat position leaves nothing to average
+            if total > 0.0 {
+                *avg_cost = (*avg_cost * position.abs() + fill.price * fill.size) / total;
+            }
+        } else {
+            let closed = fill.size.min(position.abs());
+            self.realized_pnl += position.signum() * (fill.price - *avg_cost) * closed;
+            if fill.size > position.abs() {
+                *avg_cost = fill.price;
+            }
+        }
+        *position += signed;
+
+        self.fills += 1;
+        self.volume += fill.size * fill.price;
+        self.fees_paid += fill.fee;
+        self.realized_pnl -= fill.fee;
+    }
+}
+
+pub trait Strategy: Send + Sync {
+    fn id(&self) -> &str;
+    /// Parameters `configure` accepts, with their ranges and defaults
+    fn parameter_schema(&self) -> Vec<ParameterSpec>;
+    /// Receives parameters already checked against the schema
+    fn configure(&mut self, params: &StrategyParams) -> Result<()>;
+    fn on_market_event(&mut self, event: &MarketEvent);
+    /// Orders to place now, given what the strategy has seen so far
+    fn propose_orders(&mut self, ctx: &StrategyContext) -> Vec<OrderIntent>;
+    fn on_fill(&mut self, fill: &Fill);
+    fn metrics(&self) -> StrategyMetrics;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(side: Side, size: f64, price: f64, fee: f64) -> Fill {
+        Fill {
+            strategy_id: "test".to_string(),
+            venue: "orca".to_string(),
+            market: "SOL/USDC".to_string(),
+            side,
+            size,
+            price,
+            fee,
+            group: None,
+            timestamp_ms: 0,
+        }
+    }
+
+    #[test]
+    fn record_fill_averages_entries_and_realises_on_reduction() {
+        let mut metrics = StrategyMetrics::default();
+        metrics.record_fill(&fill(Side::Buy, 1.0, 100.0, 0.1));
+        metrics.record_fill(&fill(Side::Buy, 1.0, 110.0, 0.1));
+        assert_eq!(metrics.entry_prices["orca:SOL/USDC"], 105.0);
+
+        metrics.record_fill(&fill(Side::Sell, 1.5, 120.0, 0.2));
+        assert_eq!(metrics.positions["orca:SOL/USDC"], 0.5);
+        assert!((metrics.realized_pnl - (15.0 * 1.5 - 0.4)).abs() < 1e-9);
+        assert_eq!(metrics.entry_prices["orca:SOL/USDC"], 105.0);
+        assert_eq!((metrics.fills, metrics.volume), (3, 100.0 + 110.0 + 180.0));
+        assert!((metrics.fees_paid - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn record_fill_flips_a_position_at_the_fill_price() {
+        let mut metrics = StrategyMetrics::default();
+        metrics.record_fill(&fill(Side::Sell, 2.0, 100.0, 0.0));
+        metrics.record_fill(&fill(Side::Buy, 3.0, 90.0, 0.0));
+
+        // The short of 2 closes 10 better per unit; the extra 1 opens long at 90
+        assert!((metrics.realized_pnl - 20.0).abs() < 1e-9);
+        assert_eq!(metrics.positions["orca:SOL/USDC"], 1.0);
+        assert_eq!(metrics.entry_prices["orca:SOL/USDC"], 90.0);
+    }
+
+    #[test]
+    fn record_fill_of_nothing_keeps_a_flat_position_finite() {
+        let mut metrics = StrategyMetrics::default();
+        metrics.record_fill(&fill(Side::Buy, 0.0, 100.0, 0.0));
+
+        assert_eq!(metrics.positions["orca:SOL/USDC"], 0.0);
+        assert_eq!(metrics.entry_prices["orca:SOL/USDC"], 0.0);
+        assert_eq!(metrics.realized_pnl, 0.0);
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Result};
+use black_diamond_client::types::StrategyEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{resolve_params, ParameterSpec, Strategy, StrategyParams};
+
+pub type StrategyFactory = Box<dyn Fn() -> Box<dyn Strategy> + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyDescriptor {
+    pub id: String,
+    pub name: String,
+    /// Module the strategy was originally defined in
+    pub origin: String,
+    pub description: String,
+}
+
+/// What the API and CLI show for a registered strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyInfo {
+    pub descriptor: StrategyDescriptor,
+    pub enabled: bool,
+    pub parameters: StrategyParams,
+    pub schema: Vec<ParameterSpec>,
+}
+
+struct Entry {
+    descriptor: StrategyDescriptor,
+    factory: StrategyFactory,
+    schema: Vec<ParameterSpec>,
+    params: StrategyParams,
+    enabled: bool,
+}
+
+impl Entry {
+    fn info(&self) -> StrategyInfo {
+        StrategyInfo {
+            descriptor: self.descriptor.clone(),
+            enabled: self.enabled,
+            parameters: self.params.clone(),
+            schema: self.schema.clone(),
+        }
+    }
+}
+
+impl From<StrategyInfo> for StrategyEntry {
+    fn from(info: StrategyInfo) -> Self {
+        StrategyEntry {
+            id: info.descriptor.id,
+            name: info.descriptor.name,
+            origin: info.descriptor.origin,
+            description: info.descriptor.description,
+            enabled: info.enabled,
+            parameters: info.parameters,
+            schema: info
+                .schema
+                .iter()
+                .map(|spec| serde_json::to_value(spec).unwrap_or_default())
+                .collect(),
+        }
+    }
+}
+
+/// Every strategy the system can run, with its current configuration and
+/// whether live/paper trading should include it.
+pub struct StrategyRegistry {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl StrategyRegistry {
+    pub fn new() -> Self {
+        Self { entries: BTreeMap::new() }
+    }
+
+    /// Registers a strategy, disabled, configured with `params` over the
+    /// schema defaults
+    pub fn register(
+        &mut self,
+        descriptor: StrategyDescriptor,
+        params: StrategyParams,
+        factory: StrategyFactory,
+    ) -> Result<()> {
+        if self.entries.contains_key(&descriptor.id) {
+            return Err(anyhow!("Strategy {} is already registered", descriptor.id));
+        }
+        let schema = factory().parameter_schema();
+        let params = resolve_params(&schema, &params)?;
+
+        self.entries.insert(
+            descriptor.id.clone(),
+            Entry {
+                descriptor,
+                factory,
+                schema,
+                params,
+                enabled: false,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> Result<()> {
+        let entry = self.entry_mut(id)?;
+        entry.enabled = enabled;
+        println!("🧩 Strategy {} {}", id, if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Merges `updates` into the strategy's parameters after validating them
+    pub fn configure(&mut self, id: &str, updates: &StrategyParams) -> Result<StrategyParams> {
+        let entry = self.entry_mut(id)?;
+        let mut merged = entry.params.clone();
+        for (name, value) in updates {
+            merged.insert(name.clone(), value.clone());
+        }
+        entry.params = resolve_params(&entry.schema, &merged)?;
+        Ok(entry.params.clone())
+    }
+
+    /// A fresh, configured instance, for a backtest or a trading session
+    pub fn instantiate(&self, id: &str) -> Result<Box<dyn Strategy>> {
+        let entry = self.entries.get(id).ok_or_else(|| anyhow!("Unknown strategy {}", id))?;
+        let mut strategy = (entry.factory)();
+        strategy.configure(&entry.params)?;
+        Ok(strategy)
+    }
+
+    pub fn instantiate_enabled(&self) -> Result<Vec<Box<dyn Strategy>>> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.enabled)
+            .map(|(id, _)| self.instantiate(id))
+            .collect()
+    }
+
+    pub fn list(&self) -> Vec<StrategyInfo> {
+        self.entries.values().map(Entry::info).collect()
+    }
+
+    pub fn info(&self, id: &str) -> Result<StrategyInfo> {
+        self.entries.get(id).map(Entry::info).ok_or_else(|| anyhow!("Unknown strategy {}", id))
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    fn entry_mut(&mut self, id: &str) -> Result<&mut Entry> {
+        self.entries.get_mut(id).ok_or_else(|| anyhow!("Unknown strategy {}", id))
+    }
+}
+
+impl Default for StrategyRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
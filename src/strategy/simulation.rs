@@ -0,0 +1,499 @@
+use anyhow::Result;
+use black_diamond_client::types::StrategyReport;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::registry::StrategyRegistry;
+use super::{Fill, MarketEvent, OrderIntent, Side, Strategy, StrategyContext, StrategyMetrics};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    /// Taker fee charged in quote on every fill
+    pub fee_bps: f64,
+    /// Each strategy starts with its own copy of these balances
+    pub starting_balances: HashMap<String, f64>,
+    /// Asset equity and pnl are reported in
+    pub valuation_asset: String,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            fee_bps: 5.0,
+            starting_balances: HashMap::from([("USDC".to_string(), 10_000.0)]),
+            valuation_asset: "USDC".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub strategy_id: String,
+    pub events: u64,
+    pub fills: usize,
+    pub rejected_orders: u64,
+    pub balances: HashMap<String, f64>,
+    pub starting_equity: f64,
+    pub equity: f64,
+    pub pnl: f64,
+    /// Held assets with no price against the valuation asset, left out of equity
+    pub unpriced_assets: Vec<String>,
+    pub metrics: StrategyMetrics,
+}
+
+impl From<BacktestReport> for StrategyReport {
+    fn from(report: BacktestReport) -> Self {
+        StrategyReport {
+            strategy_id: report.strategy_id,
+            events: report.events,
+            fills: report.fills as u64,
+            rejected_orders: report.rejected_orders,
+            balances: report.balances,
+            starting_equity: report.starting_equity,
+            equity: report.equity,
+            pnl: report.pnl,
+            realized_pnl: report.metrics.realized_pnl,
+            fees_paid: report.metrics.fees_paid,
+            volume: report.metrics.volume,
+            positions: report.metrics.positions,
+            unpriced_assets: report.unpriced_assets,
+        }
+    }
+}
+
+/// Latest quote for a market, with the depth fills have not yet taken
+#[derive(Debug, Clone, Copy)]
+struct Book {
+    bid: f64,
+    ask: f64,
+    bid_depth: f64,
+    ask_depth: f64,
+}
+
+struct Account {
+    strategy: Box<dyn Strategy>,
+    balances: HashMap<String, f64>,
+    fills: Vec<Fill>,
+    rejected_orders: u64,
+}
+
+/// Fills strategy orders against the latest quotes, all-or-nothing per order
+/// group. Backtests replay recorded events through it and paper trading
+/// feeds it live ones, so both see identical fill rules.
+pub struct SimulationSession {
+    config: SimulationConfig,
+    books: HashMap<(String, String), Book>,
+    accounts: Vec<Account>,
+    events: u64,
+}
+
+impl SimulationSession {
+    pub fn new(strategies: Vec<Box<dyn Strategy>>, config: SimulationConfig) -> Self {
+        let accounts = strategies
+            .into_iter()
+            .map(|strategy| Account {
+                strategy,
+                balances: config.starting_balances.clone(),
+                fills: Vec::new(),
+                rejected_orders: 0,
+            })
+            .collect();
+
+        SimulationSession {
+            config,
+            books: HashMap::new(),
+            accounts,
+            events: 0,
+        }
+    }
+
+    /// Paper-trades every enabled strategy in the registry
+    pub fn paper(registry: &StrategyRegistry, config: SimulationConfig) -> Result<Self> {
+        Ok(Self::new(registry.instantiate_enabled()?, config))
+    }
+
+    /// Runs one strategy from the registry over recorded events
+    pub fn backtest(
+        registry: &StrategyRegistry,
+        id: &str,
+        events: &[MarketEvent],
+        config: SimulationConfig,
+    ) -> Result<BacktestReport> {
+        let mut session = Self::new(vec![registry.instantiate(id)?], config);
+        for event in events {
+            session.step(event);
+        }
+        Ok(session.reports().remove(0))
+    }
+
+    /// Starts `strategy` afresh with the starting balances in place of any
+    /// running instance with `id`; `None` just stops that instance
+    pub fn set_strategy(&mut self, id: &str, strategy: Option<Box<dyn Strategy>>) {
+        self.accounts.retain(|account| account.strategy.id() != id);
+        if let Some(strategy) = strategy {
+            self.accounts.push(Account {
+                strategy,
+                balances: self.config.starting_balances.clone(),
+                fills: Vec::new(),
+                rejected_orders: 0,
+            });
+        }
+    }
+
+    /// Feeds one event to every strategy and executes what they propose
+    pub fn step(&mut self, event: &MarketEvent) -> Vec<Fill> {
+        self.events += 1;
+        if let MarketEvent::Quote { venue, market, bid, ask, depth, .. } = event {
+            self.books.insert(
+                (venue.clone(), market.clone()),
+                Book {
+                    bid: *bid,
+                    ask: *ask,
+                    bid_depth: *depth,
+                    ask_depth: *depth,
+                },
+            );
+        }
+
+        let timestamp_ms = event.timestamp_ms();
+        let mut fills = Vec::new();
+        for account in &mut self.accounts {
+            account.strategy.on_market_event(event);
+            let ctx = StrategyContext {
+                timestamp_ms,
+                available_capital: account.balances.get(&self.config.valuation_asset).copied().unwrap_or(0.0),
+            };
+            let orders = account.strategy.propose_orders(&ctx);
+
+            let mut groups: Vec<Vec<OrderIntent>> = Vec::new();
+            for order in orders {
+                match groups
+                    .iter_mut()
+                    .find(|g| order.group.is_some() && g[0].group == order.group)
+                {
+                    Some(group) => group.push(order),
+                    None => groups.push(vec![order]),
+                }
+            }
+
+            for group in groups {
+                match execute_group(&mut self.books, &account.balances, &group, self.config.fee_bps) {
+                    Some((balances, prices)) => {
+                        account.balances = balances;
+                        for (order, (price, fee)) in group.iter().zip(prices) {
+                            let fill = Fill {
+                                strategy_id: account.strategy.id().to_string(),
+                                venue: order.venue.clone(),
+                                market: order.market.clone(),
+                                side: order.side,
+                                size: order.size,
+                                price,
+                                fee,
+                                group: order.group.clone(),
+                                timestamp_ms,
+                            };
+                            account.strategy.on_fill(&fill);
+                            account.fills.push(fill.clone());
+                            fills.push(fill);
+                        }
+                    }
+                    None => account.rejected_orders += group.len() as u64,
+                }
+            }
+        }
+        fills
+    }
+
+    pub fn reports(&self) -> Vec<BacktestReport> {
+        self.accounts
+            .iter()
+            .map(|account| {
+                let (equity, unpriced_assets) = self.value(&account.balances);
+                let (starting_equity, _) = self.value(&self.config.starting_balances);
+                BacktestReport {
+                    strategy_id: account.strategy.id().to_string(),
+                    events: self.events,
+                    fills: account.fills.len(),
+                    rejected_orders: account.rejected_orders,
+                    balances: account.balances.clone(),
+                    starting_equity,
+                    equity,
+                    pnl: equity - starting_equity,
+                    unpriced_assets,
+                    metrics: account.strategy.metrics(),
+                }
+            })
+            .collect()
+    }
+
+    fn value(&self, balances: &HashMap<String, f64>) -> (f64, Vec<String>) {
+        let mut equity = 0.0;
+        let mut unpriced = Vec::new();
+        for (asset, amount) in balances {
+            if amount.abs() < f64::EPSILON {
+                continue;
+            }
+            match self.price(asset) {
+                Some(price) => equity += amount * price,
+                None => unpriced.push(asset.clone()),
+            }
+        }
+        (equity, unpriced)
+    }
+
+    /// Mid price of `asset` in the valuation asset, averaged over every venue
+    /// quoting it so the result does not depend on map iteration order
+    fn price(&self, asset: &str) -> Option<f64> {
+        let valuation = &self.config.valuation_asset;
+        if asset == valuation {
+            return Some(1.0);
+        }
+        let mut mids: Vec<f64> = self
+            .books
+            .iter()
+            .filter_map(|((_, market), book)| {
+                let (base, quote) = market.split_once('/')?;
+                let mid = (book.bid + book.ask) / 2.0;
+                if base == asset && quote == valuation {
+                    Some(mid)
+                } else if base == valuation && quote == asset && mid > 0.0 {
+                    Some(1.0 / mid)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if mids.is_empty() {
+            return None;
+        }
+        // Summed in a fixed order so the float result is reproducible too
+        mids.sort_by(f64::total_cmp);
+        Some(mids.iter().sum::<f64>() / mids.len() as f64)
+    }
+}
+
+/// Balances after a group fills, and the `(price, fee)` of each leg
+type GroupFill = (HashMap<String, f64>, Vec<(f64, f64)>);
+
+/// Balances after filling every order in `group` in sequence, with the
+/// `(price, fee)` of each fill, or `None` if any leg cannot fill. Each leg
+/// takes the depth it fills from its book, so later legs and orders see only
+/// what is left until the next quote; a group that fails takes nothing.
+fn execute_group(
+    books: &mut HashMap<(String, String), Book>,
+    balances: &HashMap<String, f64>,
+    group: &[OrderIntent],
+    fee_bps: f64,
+) -> Option<GroupFill> {
+    let mut balances = balances.clone();
+    let mut prices = Vec::with_capacity(group.len());
+    let mut touched: HashMap<(String, String), Book> = HashMap::new();
+
+    for order in group {
+        let key = (order.venue.clone(), order.market.clone());
+        let book = match touched.entry(key) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let book = *books.get(entry.key())?;
+                entry.insert(book)
+            }
+        };
+        let (base, quote) = order.market.split_once('/')?;
+        let available = match order.side {
+            Side::Buy => &mut book.ask_depth,
+            Side::Sell => &mut book.bid_depth,
+        };
+        if order.size <= 0.0 || order.size > *available {
+            return None;
+        }
+        *available -= order.size;
+
+        let price = match order.side {
+            Side::Buy => book.ask,
+            Side::Sell => book.bid,
+        };
+        let within_limit = order.limit_price.is_none_or(|limit| match order.side {
+            Side::Buy => price <= limit,
+            Side::Sell => price >= limit,
+        });
+        if !within_limit {
+            return None;
+        }
+
+        let notional = order.size * price;
+        let fee = notional * fee_bps / 10_000.0;
+        match order.side {
+            Side::Buy => {
+                let cost = notional + fee;
+                if balances.get(quote).copied().unwrap_or(0.0) < cost {
+                    return None;
+                }
+                *balances.entry(quote.to_string()).or_insert(0.0) -= cost;
+                *balances.entry(base.to_string()).or_insert(0.0) += order.size;
+            }
+            Side::Sell => {
+                if balances.get(base).copied().unwrap_or(0.0) < order.size {
+                    return None;
+                }
+                *balances.entry(base.to_string()).or_insert(0.0) -= order.size;
+                *balances.entry(quote.to_string()).or_insert(0.0) += notional - fee;
+            }
+        }
+        prices.push((price, fee));
+    }
+    books.extend(touched);
+    Some((balances, prices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn books(depth: f64) -> HashMap<(String, String), Book> {
+        HashMap::from([(
+            ("orca".to_string(), "SOL/USDC".to_string()),
+            Book {
+                bid: 99.0,
+                ask: 101.0,
+                bid_depth: depth,
+                ask_depth: depth,
+            },
+        )])
+    }
+
+    fn order(side: Side, size: f64, group: Option<&str>) -> OrderIntent {
+        OrderIntent {
+            venue: "orca".to_string(),
+            market: "SOL/USDC".to_string(),
+            side,
+            size,
+            limit_price: None,
+            group: group.map(str::to_string),
+            reason: "test".to_string(),
+        }
+    }
+
+    fn usdc(amount: f64) -> HashMap<String, f64> {
+        HashMap::from([("USDC".to_string(), amount)])
+    }
+
+    fn ask_depth(books: &HashMap<(String, String), Book>) -> f64 {
+        books[&("orca".to_string(), "SOL/USDC".to_string())].ask_depth
+    }
+
+    #[test]
+    fn fills_a_group_at_the_touch_and_charges_fees() {
+        let mut books = books(10.0);
+        let group = [order(Side::Buy, 2.0, Some("g")), order(Side::Sell, 1.0, Some("g"))];
+        let (balances, prices) = execute_group(&mut books, &usdc(1_000.0), &group, 10.0).unwrap();
+
+        assert_eq!(prices[0].0, 101.0);
+        assert_eq!(prices[1].0, 99.0);
+        assert!((prices[0].1 - 0.202).abs() < 1e-9);
+        assert!((balances["SOL"] - 1.0).abs() < 1e-9);
+        let expected_usdc = 1_000.0 - 202.0 - 0.202 + 99.0 - 0.099;
+        assert!((balances["USDC"] - expected_usdc).abs() < 1e-9);
+    }
+
+    #[test]
+    fn legs_on_the_same_book_share_its_depth() {
+        let mut books = books(3.0);
+        let group = [order(Side::Buy, 2.0, Some("g")), order(Side::Buy, 2.0, Some("g"))];
+
+        // Each leg fits the book alone, but not together; nothing is taken
+        assert!(execute_group(&mut books, &usdc(1_000.0), &group, 0.0).is_none());
+        assert_eq!(ask_depth(&books), 3.0);
+
+        execute_group(&mut books, &usdc(1_000.0), &group[..1], 0.0).unwrap();
+        assert_eq!(ask_depth(&books), 1.0);
+        assert!(execute_group(&mut books, &usdc(1_000.0), &group[..1], 0.0).is_none());
+    }
+
+    #[test]
+    fn rejects_orders_past_their_limit_or_balance() {
+        let mut books = books(10.0);
+        let mut limited = order(Side::Buy, 1.0, None);
+        limited.limit_price = Some(100.0);
+        assert!(execute_group(&mut books, &usdc(1_000.0), &[limited], 0.0).is_none());
+        assert!(execute_group(&mut books, &usdc(50.0), &[order(Side::Buy, 1.0, None)], 0.0).is_none());
+        assert!(execute_group(&mut books, &usdc(1_000.0), &[order(Side::Sell, 1.0, None)], 0.0).is_none());
+        assert_eq!(ask_depth(&books), 10.0);
+    }
+
+    struct Taker {
+        metrics: StrategyMetrics,
+    }
+
+    impl Strategy for Taker {
+        fn id(&self) -> &str {
+            "taker"
+        }
+        fn parameter_schema(&self) -> Vec<super::super::ParameterSpec> {
+            Vec::new()
+        }
+        fn configure(&mut self, _params: &super::super::StrategyParams) -> Result<()> {
+            Ok(())
+        }
+        fn on_market_event(&mut self, _event: &MarketEvent) {
+            self.metrics.events_seen += 1;
+        }
+        fn propose_orders(&mut self, _ctx: &StrategyContext) -> Vec<OrderIntent> {
+            vec![order(Side::Buy, 2.0, None)]
+        }
+        fn on_fill(&mut self, fill: &Fill) {
+            self.metrics.record_fill(fill);
+        }
+        fn metrics(&self) -> StrategyMetrics {
+            self.metrics.clone()
+        }
+    }
+
+    fn quote(timestamp_ms: u64) -> MarketEvent {
+        MarketEvent::Quote {
+            venue: "orca".to_string(),
+            market: "SOL/USDC".to_string(),
+            bid: 99.0,
+            ask: 101.0,
+            depth: 3.0,
+            timestamp_ms,
+        }
+    }
+
+    #[test]
+    fn depth_taken_by_one_strategy_is_gone_for_the_next_until_requoted() {
+        let takers: Vec<Box<dyn Strategy>> = (0..2)
+            .map(|_| Box::new(Taker { metrics: StrategyMetrics::default() }) as Box<dyn Strategy>)
+            .collect();
+        let mut session = SimulationSession::new(takers, SimulationConfig::default());
+
+        assert_eq!(session.step(&quote(1)).len(), 1);
+        assert_eq!(session.step(&quote(2)).len(), 1);
+
+        // The first strategy takes 2 of the 3 quoted each time; the second
+        // never finds enough left
+        let reports = session.reports();
+        assert_eq!((reports[0].fills, reports[0].rejected_orders), (2, 0));
+        assert_eq!((reports[1].fills, reports[1].rejected_orders), (0, 2));
+        assert_eq!(reports[0].metrics.positions["orca:SOL/USDC"], 4.0);
+    }
+
+    #[test]
+    fn values_assets_at_the_mean_mid_across_venues() {
+        let mut session = SimulationSession::new(Vec::new(), SimulationConfig::default());
+        for (venue, bid, ask) in [("orca", 99.0, 101.0), ("raydium", 103.0, 105.0)] {
+            session.books.insert(
+                (venue.to_string(), "SOL/USDC".to_string()),
+                Book {
+                    bid,
+                    ask,
+                    bid_depth: 1.0,
+                    ask_depth: 1.0,
+                },
+            );
+        }
+
+        assert_eq!(session.price("SOL"), Some(102.0));
+        assert_eq!(session.price("USDC"), Some(1.0));
+        assert_eq!(session.price("BONK"), None);
+    }
+}
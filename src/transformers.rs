@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use chrono::{DateTime, Utc};
+use black_diamond_model_registry::ModelRegistry;
+use crate::agent_bus::{AgentBus, AgentMessage};
+
+const DEFAULT_MODEL_REGISTRY: &str = "models";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransformerModel {
@@ -16,6 +21,8 @@ pub struct TransformerModel {
     pub parameters: u64,
     pub training_data_quality: f64,
     pub last_optimization: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub deployed_version: Option<u32>, // Registry checkpoint version in production
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +39,8 @@ pub struct TransformerDeploymentManager {
     pub deployment_queue: Vec<String>,
     pub active_deployments: HashMap<String, f64>, // transformer_id -> progress
     pub performance_history: HashMap<String, Vec<f64>>,
+    pub registry: ModelRegistry,
+    bus: Option<Arc<AgentBus>>,
 }
 
 impl TransformerDeploymentManager {
@@ -41,12 +50,33 @@ impl TransformerDeploymentManager {
             deployment_queue: Vec::new(),
             active_deployments: HashMap::new(),
             performance_history: HashMap::new(),
+            registry: ModelRegistry::open(
+                std::env::var("BLACK_DIAMOND_MODEL_REGISTRY").unwrap_or_else(|_| DEFAULT_MODEL_REGISTRY.to_string()),
+            )?,
+            bus: None,
         };
 
         manager.initialize_transformers().await?;
         Ok(manager)
     }
 
+    /// Announces every versioned deployment and rollback on `bus`
+    pub fn with_bus(mut self, bus: Arc<AgentBus>) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    async fn publish_model_update(&self, transformer_id: &str, version: u32, metrics: &HashMap<String, f64>) {
+        if let Some(bus) = &self.bus {
+            let message = AgentMessage::ModelUpdate {
+                model_name: transformer_id.to_string(),
+                version: version as u64,
+                metrics: metrics.clone(),
+            };
+            bus.publish("transformer_deployment", message, 6).await;
+        }
+    }
+
     async fn initialize_transformers(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let transformers = vec![
             TransformerModel {
@@ -68,6 +98,7 @@ impl TransformerDeploymentManager {
                 parameters: 340_000_000,
                 training_data_quality: 98.5,
                 last_optimization: Some(Utc::now()),
+                deployed_version: None,
             },
             TransformerModel {
                 id: "perpetuals_trading_transformer".to_string(),
@@ -88,6 +119,7 @@ impl TransformerDeploymentManager {
                 parameters: 520_000_000,
                 training_data_quality: 97.2,
                 last_optimization: Some(Utc::now()),
+                deployed_version: None,
             },
             TransformerModel {
                 id: "hybrid_lstm_quantum".to_string(),
@@ -107,6 +139,7 @@ impl TransformerDeploymentManager {
                 parameters: 180_000_000,
                 training_data_quality: 99.1,
                 last_optimization: Some(Utc::now()),
+                deployed_version: None,
             },
             TransformerModel {
                 id: "mev_extraction_neural".to_string(),
@@ -126,6 +159,7 @@ impl TransformerDeploymentManager {
                 parameters: 420_000_000,
                 training_data_quality: 98.8,
                 last_optimization: Some(Utc::now()),
+                deployed_version: None,
             },
             TransformerModel {
                 id: "arbitrage_cross_dex".to_string(),
@@ -145,6 +179,7 @@ impl TransformerDeploymentManager {
                 parameters: 280_000_000,
                 training_data_quality: 97.9,
                 last_optimization: Some(Utc::now()),
+                deployed_version: None,
             },
             TransformerModel {
                 id: "memecoin_sniper_neural".to_string(),
@@ -164,10 +199,12 @@ impl TransformerDeploymentManager {
                 parameters: 220_000_000,
                 training_data_quality: 95.4,
                 last_optimization: Some(Utc::now()),
+                deployed_version: None,
             },
         ];
 
-        for transformer in transformers {
+        for mut transformer in transformers {
+            transformer.deployed_version = self.registry.production_version(&transformer.id);
             self.transformers.insert(transformer.id.clone(), transformer);
         }
 
@@ -185,9 +222,13 @@ impl TransformerDeploymentManager {
         Ok(ready_transformers)
     }
 
-    pub async fn deploy_transformer(&mut self, transformer_id: &str) -> Result<DeploymentResult, Box<dyn std::error::Error>> {
+    /// Deploys a registry checkpoint version, defaulting to the latest one.
+    /// Deploying an older version than the one in production is a rollback.
+    pub async fn deploy_transformer(&mut self, transformer_id: &str, version: Option<u32>) -> Result<DeploymentResult, Box<dyn std::error::Error>> {
+        let version = version.or_else(|| self.registry.latest_version(transformer_id));
+
         if let Some(transformer) = self.transformers.get_mut(transformer_id) {
-            if transformer.is_deployed {
+            if transformer.is_deployed && transformer.deployed_version == version {
                 return Ok(DeploymentResult {
                     success: false,
                     transformer_id: transformer_id.to_string(),
@@ -197,7 +238,12 @@ impl TransformerDeploymentManager {
                 });
             }
 
-            // 
+            // Load the checkpoint before promoting so a missing or corrupt file fails the deployment
+            let checkpoint = match version {
+                Some(version) => Some(self.registry.load_checkpoint(transformer_id, version)?),
+                None => None,
+            };
+
             self.active_deployments.insert(transformer_id.to_string(), 0.0);
             
             // Deployment progress simulation
@@ -206,8 +252,16 @@ impl TransformerDeploymentManager {
                 tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
             }
 
+            if let (Some(version), Some(checkpoint)) = (version, &checkpoint) {
+                self.registry.promote(transformer_id, version)?;
+                if let Some(accuracy) = checkpoint.training_metrics.get("accuracy") {
+                    transformer.accuracy = *accuracy;
+                }
+            }
+
             // Complete deployment
             transformer.is_deployed = true;
+            transformer.deployed_version = version;
             transformer.deployment_timestamp = Some(Utc::now());
             self.active_deployments.remove(transformer_id);
 
@@ -217,8 +271,17 @@ impl TransformerDeploymentManager {
             performance_metrics.insert("inference_speed".to_string(), 150.0 + rand::random::<f64>() * 100.0);
             performance_metrics.insert("memory_usage".to_string(), 0.8 + rand::random::<f64>() * 0.15);
             performance_metrics.insert("throughput".to_string(), 1000.0 + rand::random::<f64>() * 500.0);
+            if let Some(version) = version {
+                performance_metrics.insert("version".to_string(), version as f64);
+            }
 
-            println!("🚀 Deployed transformer: {}", transformer.name);
+            match version {
+                Some(version) => println!("🚀 Deployed transformer: {} v{}", transformer.name, version),
+                None => println!("🚀 Deployed transformer: {}", transformer.name),
+            }
+            if let Some(version) = version {
+                self.publish_model_update(transformer_id, version, &performance_metrics).await;
+            }
 
             Ok(DeploymentResult {
                 success: true,
@@ -232,6 +295,33 @@ impl TransformerDeploymentManager {
         }
     }
 
+    /// Returns a transformer to the version that was in production before the current one.
+    pub async fn rollback_transformer(&mut self, transformer_id: &str) -> Result<DeploymentResult, Box<dyn std::error::Error>> {
+        let transformer = self.transformers.get_mut(transformer_id).ok_or("Transformer not found")?;
+
+        let version = self.registry.rollback(transformer_id)?;
+        let checkpoint = self.registry.load_checkpoint(transformer_id, version)?;
+        if let Some(accuracy) = checkpoint.training_metrics.get("accuracy") {
+            transformer.accuracy = *accuracy;
+        }
+        transformer.is_deployed = true;
+        transformer.deployed_version = Some(version);
+        transformer.deployment_timestamp = Some(Utc::now());
+
+        let mut performance_metrics = HashMap::new();
+        performance_metrics.insert("accuracy".to_string(), transformer.accuracy);
+        performance_metrics.insert("version".to_string(), version as f64);
+        self.publish_model_update(transformer_id, version, &performance_metrics).await;
+
+        Ok(DeploymentResult {
+            success: true,
+            transformer_id: transformer_id.to_string(),
+            deployment_location: "solana_nexus_trader".to_string(),
+            performance_metrics,
+            errors: Vec::new(),
+        })
+    }
+
     pub async fn optimize_deployed_transformers(&mut self) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
         let mut optimization_results = HashMap::new();
 
@@ -11,6 +11,7 @@ use solana_sdk::{
 };
 use std::str::FromStr;
 use crate::SolanaConfig;
+use crate::capital_allocator::{AllocatorConfig, CapitalAllocator};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletBalance {
@@ -59,8 +60,18 @@ pub struct WalletManager {
     pub total_balance: f64,
     pub active_trades: u64,
     pub rebalance_threshold: f64,
+    /// Sizes the strategy wallets from their live trade returns
+    pub allocator: CapitalAllocator,
 }
 
+/// Strategy each specialist wallet trades; `main_wallet` holds the rest
+const STRATEGY_WALLETS: [(&str, &str); 4] = [
+    ("arbitrage", "arbitrage_wallet"),
+    ("mev", "mev_wallet"),
+    ("flash_loan", "flash_loan_wallet"),
+    ("memecoin", "memecoin_wallet"),
+];
+
 impl WalletManager {
     pub async fn new(config: &SolanaConfig) -> Result<Self, Box<dyn std::error::Error>> {
         let rpc_client = RpcClient::new_with_commitment(
@@ -75,6 +86,7 @@ impl WalletManager {
             total_balance: 0.0,
             active_trades: 0,
             rebalance_threshold: 1000.0, // Rebalance when imbalance > 1000 SOL
+            allocator: CapitalAllocator::new(AllocatorConfig::from_env()),
         };
 
         manager.initialize_trading_wallets().await?;
@@ -207,6 +219,9 @@ impl WalletManager {
             println!("💰 Live trade executed: {} {} -> {:.2} SOL profit", 
                 wallet.name, strategy, trade_result.profit);
 
+            let net_profit = trade_result.profit - trade_result.gas_cost;
+            self.allocator.record(strategy, net_profit, amount, chrono::Utc::now().timestamp() as u64);
+
             Ok(LiveTradeResult {
                 success: trade_result.success,
                 profit: trade_result.profit,
@@ -339,14 +354,18 @@ impl WalletManager {
     pub async fn rebalance_wallets(&mut self) -> Result<Vec<RebalanceOperation>, Box<dyn std::error::Error>> {
         let mut operations = Vec::new();
         
-        // Calculate target balances based on wallet types
-        let target_allocations = vec![
-            ("main_wallet", 0.4),      // 40% in main wallet
-            ("arbitrage_wallet", 0.25), // 25% in arbitrage
-            ("mev_wallet", 0.15),       // 15% in MEV
-            ("flash_loan_wallet", 0.15), // 15% in flash loans
-            ("memecoin_wallet", 0.05),   // 5% in memecoin (high risk)
-        ];
+        // Strategy wallets get what the allocator gives their strategy, the
+        // main wallet keeps the remainder
+        let strategies: Vec<String> = STRATEGY_WALLETS.iter().map(|(s, _)| s.to_string()).collect();
+        let now = chrono::Utc::now().timestamp() as u64;
+        self.allocator.rebalance(&strategies, self.total_balance, now, false);
+
+        let mut target_allocations: Vec<(&str, f64)> = STRATEGY_WALLETS
+            .iter()
+            .map(|(strategy, wallet_id)| (*wallet_id, self.allocator.weight(strategy)))
+            .collect();
+        let allocated: f64 = target_allocations.iter().map(|(_, w)| w).sum();
+        target_allocations.insert(0, ("main_wallet", (1.0 - allocated).max(0.0)));
 
         for (wallet_id, target_percentage) in target_allocations {
             if let Some(wallet) = self.wallets.get(wallet_id) {
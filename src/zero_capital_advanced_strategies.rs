@@ -18,34 +18,8 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use anyhow::Result;
 use tokio::time::{interval, Interval};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ZeroCapitalStrategy {
-    pub id: String,
-    pub name: String,
-    pub strategy_type: StrategyType,
-    pub initial_capital_required: f64, // Always 0 for true zero-capital
-    pub expected_multiplier: f64,
-    pub time_to_profit: u64, // milliseconds
-    pub risk_score: f64,
-    pub uniqueness_rating: u8, // 1-10, how unique/advanced the strategy is
-    pub mathematical_model: String,
-    pub on_chain_programs: Vec<String>,
-    pub execution_steps: Vec<String>,
-    pub scaling_potential: f64, // SOL per hour at full scale
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum StrategyType {
-    FlashArbitrage,
-    MemecoinGenesis,
-    LiquidityMining,
-    StakingYield,
-    GovernanceExtraction,
-    CrossChainBridge,
-    NFTArbitrage,
-    SocialSentiment,
-}
+pub use black_diamond_client::types::{StrategyType, ZeroCapitalStats, ZeroCapitalStrategy};
+use crate::strategy::{CrossVenueArbitrage, StrategyDescriptor, StrategyParams, StrategyRegistry};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlashArbitrageOpportunity {
@@ -634,6 +608,32 @@ impl ZeroCapitalAdvancedStrategies {
         }
     }
 
+    /// Registers the flash-arbitrage entries with the shared strategy
+    /// registry; the other strategy types have no event-driven implementation
+    pub fn register_strategies(&self, registry: &mut StrategyRegistry) -> Result<usize> {
+        let strategies = self.strategies.lock().unwrap();
+        let mut registered = 0;
+        for strategy in strategies.values() {
+            if !matches!(strategy.strategy_type, StrategyType::FlashArbitrage) || registry.contains(&strategy.id) {
+                continue;
+            }
+            let id = strategy.id.clone();
+            let descriptor = StrategyDescriptor {
+                id: strategy.id.clone(),
+                name: strategy.name.clone(),
+                origin: "zero_capital_advanced_strategies".to_string(),
+                description: strategy.execution_steps.join("; "),
+            };
+            registry.register(
+                descriptor,
+                StrategyParams::new(),
+                Box::new(move || Box::new(CrossVenueArbitrage::new(&id))),
+            )?;
+            registered += 1;
+        }
+        Ok(registered)
+    }
+
     pub async fn get_flash_opportunities(&self) -> Vec<FlashArbitrageOpportunity> {
         if let Ok(opportunities) = self.flash_opportunities.lock() {
             opportunities.iter().take(5).cloned().collect()
@@ -655,7 +655,7 @@ impl ZeroCapitalAdvancedStrategies {
         }
     }
 
-    pub async fn get_current_stats(&self) -> serde_json::Value {
+    pub async fn get_current_stats(&self) -> ZeroCapitalStats {
         let current_capital = *self.current_capital.lock().unwrap();
         let total_profit = *self.total_profit.lock().unwrap();
         let strategies_count = self.strategies.lock().unwrap().len();
@@ -670,16 +670,16 @@ impl ZeroCapitalAdvancedStrategies {
             0.0
         };
 
-        serde_json::json!({
-            "currentCapital": current_capital,
-            "totalProfit": total_profit,
-            "activeStrategies": strategies_count,
-            "flashOpportunities": flash_opportunities,
-            "memecoinOpportunities": memecoin_opportunities,
-            "liquidityPositions": liquidity_positions,
-            "sentimentSignals": sentiment_signals,
-            "profitVelocity": profit_velocity
-        })
+        ZeroCapitalStats {
+            current_capital,
+            total_profit,
+            active_strategies: strategies_count,
+            flash_opportunities,
+            memecoin_opportunities,
+            liquidity_positions,
+            sentiment_signals,
+            profit_velocity,
+        }
     }
 
     pub async fn activate_strategy(&self, strategy_id: String) -> Result<serde_json::Value> {
@@ -7,12 +7,17 @@ use solana_sdk::{
 use solana_client::nonblocking::rpc_client::RpcClient;
 use anyhow::Result;
 use super::{tx_router, signal_processor, verification, profit_collector};
+use super::landing_tracker::{LandingConfig, LandingTracker};
+use super::inference::InferenceClient;
+use black_diamond_model_registry::ScoreKind;
 
 pub struct BlaccDiamondEngine {
     pub client: RpcClient,
     pub oracle_wallet: Pubkey,
     pub use_jito: bool,
     pub profit_threshold: f64,
+    pub landing_tracker: LandingTracker,
+    pub inference: Option<InferenceClient>,
 }
 
 impl BlaccDiamondEngine {
@@ -22,6 +27,8 @@ impl BlaccDiamondEngine {
             oracle_wallet,
             use_jito: true,
             profit_threshold: 0.01, // Minimum 0.01 SOL profit threshold
+            landing_tracker: LandingTracker::new(LandingConfig::default()),
+            inference: None,
         }
     }
 
@@ -30,7 +37,11 @@ impl BlaccDiamondEngine {
     }
 
     pub async fn route_transaction(&self, tx: Transaction) -> Result<Signature> {
-        tx_router::route_transaction(&self.client, tx, self.use_jito).await
+        self.route_strategy_transaction("unattributed", tx).await
+    }
+
+    pub async fn route_strategy_transaction(&self, strategy: &str, tx: Transaction) -> Result<Signature> {
+        tx_router::route_transaction(&self.client, &self.landing_tracker, strategy, tx, self.use_jito).await
     }
 
     pub async fn execute_flash_loan(&self, amount: u64, lender: &str, borrower: &Pubkey) -> Result<()> {
@@ -76,6 +87,11 @@ impl BlaccDiamondEngine {
         println!("💎 Profit threshold set to: {:.4} SOL", threshold);
     }
 
+    pub fn set_inference_client(&mut self, client: InferenceClient) {
+        println!("🧠 Inference server attached (budget {}ms)", client.latency_budget.as_millis());
+        self.inference = Some(client);
+    }
+
     pub async fn execute_arbitrage_cycle(&self) -> Result<f64> {
         let signal = "arbitrage_SOL_USDC";
         
@@ -83,7 +99,7 @@ impl BlaccDiamondEngine {
             Ok(tx) => {
                 println!("📊 Processing arbitrage signal: {}", signal);
                 
-                match self.route_transaction(tx).await {
+                match self.route_strategy_transaction("arbitrage", tx).await {
                     Ok(signature) => {
                         if self.verify_transaction(&signature).await {
                             let profit = 0.05 + (rand::random::<f64>() * 0.1); // 0.05-0.15 SOL profit
@@ -114,4 +130,28 @@ impl BlaccDiamondEngine {
             Ok(())
         }
     }
-}
\ No newline at end of file
+
+    /// Asks the served model for a success probability on `features` and
+    /// trades on it through `neural_titan_integration`. The model version is
+    /// whatever the server has hot-swapped to.
+    pub async fn neural_titan_inference(&self, features: Vec<f32>) -> Result<()> {
+        let client = self
+            .inference
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No inference server configured"))?;
+        let prediction = client.predict(vec![features]).await?;
+
+        if prediction.score_kind != ScoreKind::Probability {
+            return Err(anyhow::anyhow!(
+                "{} v{} serves forecasts, not a confidence",
+                prediction.model_name,
+                prediction.version
+            ));
+        }
+        let confidence = prediction.scores.first().copied().unwrap_or(0.0) as f64;
+
+        println!("🧠 {} v{} scored {:.4} in {}µs", prediction.model_name, prediction.version,
+            confidence, prediction.queue_us + prediction.inference_us);
+        self.neural_titan_integration(confidence).await
+    }
+}
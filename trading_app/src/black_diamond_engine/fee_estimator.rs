@@ -0,0 +1,465 @@
+use solana_sdk::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcSimulateTransactionConfig,
+};
+use anyhow::Result;
+
+// Solana caps a single transaction at 1.4M compute units
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeUrgency {
+    Low,
+    Normal,
+    High,
+    Critical,
+}
+
+impl FeeUrgency {
+    fn percentile(&self) -> f64 {
+        match self {
+            FeeUrgency::Low => 0.25,
+            FeeUrgency::Normal => 0.50,
+            FeeUrgency::High => 0.75,
+            FeeUrgency::Critical => 0.95,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeeEstimatorConfig {
+    pub compute_unit_margin: f64,        // Multiplier applied to simulated units
+    pub min_micro_lamports: u64,
+    pub max_micro_lamports: u64,
+    pub max_profit_fraction: f64,        // Max share of expected profit spent on priority fees
+}
+
+impl Default for FeeEstimatorConfig {
+    fn default() -> Self {
+        FeeEstimatorConfig {
+            compute_unit_margin: 1.15,
+            min_micro_lamports: 1_000,
+            max_micro_lamports: 5_000_000,
+            max_profit_fraction: 0.20,
+        }
+    }
+}
+
+impl FeeEstimatorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.min_micro_lamports > self.max_micro_lamports {
+            return Err(anyhow::anyhow!(
+                "Minimum compute unit price {} exceeds the maximum {}",
+                self.min_micro_lamports,
+                self.max_micro_lamports
+            ));
+        }
+        if self.compute_unit_margin.is_nan() || self.compute_unit_margin < 1.0 {
+            return Err(anyhow::anyhow!("Compute unit margin {} must be at least 1", self.compute_unit_margin));
+        }
+        if !(0.0..=1.0).contains(&self.max_profit_fraction) {
+            return Err(anyhow::anyhow!("Profit fraction {} must be within 0..=1", self.max_profit_fraction));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FeePercentiles {
+    pub p25: u64,
+    pub p50: u64,
+    pub p75: u64,
+    pub p95: u64,
+    pub sample_slots: usize,
+}
+
+impl FeePercentiles {
+    pub fn at(&self, urgency: FeeUrgency) -> u64 {
+        match urgency {
+            FeeUrgency::Low => self.p25,
+            FeeUrgency::Normal => self.p50,
+            FeeUrgency::High => self.p75,
+            FeeUrgency::Critical => self.p95,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeeEstimate {
+    pub compute_unit_limit: u32,
+    pub compute_unit_price: u64,         // Micro-lamports per compute unit
+    pub simulated_units: Option<u64>,
+    pub priority_fee_lamports: u64,
+    pub capped_by_profit: bool,
+}
+
+impl FeeEstimate {
+    pub fn compute_budget_instructions(&self) -> Vec<Instruction> {
+        vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(self.compute_unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(self.compute_unit_price),
+        ]
+    }
+
+    /// `tx` rebuilt with this estimate's compute budget instructions in front,
+    /// replacing any it already had. The message changes, so the result is
+    /// unsigned and must be signed again before sending.
+    pub fn apply_to(&self, tx: &Transaction) -> Transaction {
+        let message = &tx.message;
+        let mut instructions = self.compute_budget_instructions();
+        for compiled in &message.instructions {
+            let program_id = *compiled.program_id(&message.account_keys);
+            if program_id == compute_budget::id() {
+                continue;
+            }
+            let accounts = compiled
+                .accounts
+                .iter()
+                .map(|&account| {
+                    let account = account as usize;
+                    let pubkey = message.account_keys[account];
+                    if message.is_writable(account) {
+                        AccountMeta::new(pubkey, message.is_signer(account))
+                    } else {
+                        AccountMeta::new_readonly(pubkey, message.is_signer(account))
+                    }
+                })
+                .collect();
+            instructions.push(Instruction {
+                program_id,
+                accounts,
+                data: compiled.data.clone(),
+            });
+        }
+
+        let payer = message.account_keys.first();
+        let mut rebuilt = Message::new(&instructions, payer);
+        rebuilt.recent_blockhash = message.recent_blockhash;
+        Transaction::new_unsigned(rebuilt)
+    }
+
+    /// `apply_to`, signed again by every signer the rebuilt message requires.
+    /// `signers` may hold keys this transaction doesn't need, e.g. the signers
+    /// for a whole bundle; it fails if any required signer is missing.
+    pub fn apply_and_sign(&self, tx: &Transaction, signers: &[&Keypair]) -> Result<Transaction> {
+        let mut applied = self.apply_to(tx);
+        let required = applied.message.signer_keys();
+        let signers: Vec<&Keypair> = signers
+            .iter()
+            .copied()
+            .filter(|signer| required.contains(&&signer.pubkey()))
+            .collect();
+        let blockhash = applied.message.recent_blockhash;
+        applied.try_sign(&signers, blockhash)?;
+        Ok(applied)
+    }
+}
+
+pub struct PriorityFeeEstimator {
+    pub config: FeeEstimatorConfig,
+}
+
+impl PriorityFeeEstimator {
+    pub fn new(config: FeeEstimatorConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(PriorityFeeEstimator { config })
+    }
+
+    /// Percentiles of recent prioritization fees paid by transactions that locked
+    /// any of `writable_accounts`. Slots that paid zero are kept so quiet accounts
+    /// produce low estimates instead of being skewed by a few busy slots.
+    pub async fn fetch_fee_percentiles(
+        &self,
+        client: &RpcClient,
+        writable_accounts: &[Pubkey],
+    ) -> Result<FeePercentiles> {
+        let recent_fees = client.get_recent_prioritization_fees(writable_accounts).await?;
+        let mut fees: Vec<u64> = recent_fees.iter().map(|f| f.prioritization_fee).collect();
+        fees.sort_unstable();
+
+        Ok(FeePercentiles {
+            p25: percentile(&fees, FeeUrgency::Low.percentile()),
+            p50: percentile(&fees, FeeUrgency::Normal.percentile()),
+            p75: percentile(&fees, FeeUrgency::High.percentile()),
+            p95: percentile(&fees, FeeUrgency::Critical.percentile()),
+            sample_slots: fees.len(),
+        })
+    }
+
+    /// Simulates the transaction and returns the consumed compute units.
+    pub async fn simulate_compute_units(&self, client: &RpcClient, tx: &Transaction) -> Result<Option<u64>> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            ..RpcSimulateTransactionConfig::default()
+        };
+
+        let response = client.simulate_transaction_with_config(tx, config).await?;
+        if let Some(err) = response.value.err {
+            return Err(anyhow::anyhow!("Simulation failed: {:?}", err));
+        }
+
+        Ok(response.value.units_consumed)
+    }
+
+    pub async fn estimate(
+        &self,
+        client: &RpcClient,
+        tx: &Transaction,
+        urgency: FeeUrgency,
+        expected_profit_lamports: u64,
+    ) -> Result<FeeEstimate> {
+        let writable_accounts = writable_accounts(tx);
+        let percentiles = self.fetch_fee_percentiles(client, &writable_accounts).await?;
+        let simulated_units = self.simulate_compute_units(client, tx).await?;
+
+        let estimate = self.build_estimate(&percentiles, simulated_units, urgency, expected_profit_lamports);
+
+        println!(
+            "⛽ Fee estimate: {} CU @ {} µlamports/CU ({} lamports priority, {} slots sampled{})",
+            estimate.compute_unit_limit,
+            estimate.compute_unit_price,
+            estimate.priority_fee_lamports,
+            percentiles.sample_slots,
+            if estimate.capped_by_profit { ", capped by profit" } else { "" },
+        );
+
+        Ok(estimate)
+    }
+
+    /// Estimate to fall back on when simulation or the fee sample is
+    /// unavailable, e.g. for a bundle leg that only succeeds after the one
+    /// before it: the default compute unit limit at the minimum price.
+    pub fn fallback_estimate(&self, expected_profit_lamports: u64) -> FeeEstimate {
+        self.build_estimate(&FeePercentiles::default(), None, FeeUrgency::Low, expected_profit_lamports)
+    }
+
+    pub fn build_estimate(
+        &self,
+        percentiles: &FeePercentiles,
+        simulated_units: Option<u64>,
+        urgency: FeeUrgency,
+        expected_profit_lamports: u64,
+    ) -> FeeEstimate {
+        let compute_unit_limit = self.compute_unit_limit(simulated_units);
+
+        let mut compute_unit_price = percentiles
+            .at(urgency)
+            .clamp(self.config.min_micro_lamports, self.config.max_micro_lamports);
+
+        // Never pay more in priority fees than the configured share of expected
+        // profit. The price is floored, so the fee rounded up from it stays
+        // within the cap.
+        let max_fee_lamports = (expected_profit_lamports as f64 * self.config.max_profit_fraction) as u64;
+        let max_price = max_fee_lamports.saturating_mul(MICRO_LAMPORTS_PER_LAMPORT) / compute_unit_limit.max(1) as u64;
+        let capped_by_profit = compute_unit_price > max_price;
+        if capped_by_profit {
+            compute_unit_price = max_price;
+        }
+
+        FeeEstimate {
+            compute_unit_limit,
+            compute_unit_price,
+            simulated_units,
+            priority_fee_lamports: priority_fee_lamports(compute_unit_limit, compute_unit_price),
+            capped_by_profit,
+        }
+    }
+
+    fn compute_unit_limit(&self, simulated_units: Option<u64>) -> u32 {
+        match simulated_units {
+            // Nothing real consumes zero units; treat it like a missing simulation
+            Some(units) if units > 0 => {
+                let with_margin = (units as f64 * self.config.compute_unit_margin).ceil() as u64;
+                with_margin.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+            }
+            _ => DEFAULT_COMPUTE_UNIT_LIMIT,
+        }
+    }
+}
+
+pub fn writable_accounts(tx: &Transaction) -> Vec<Pubkey> {
+    tx.message
+        .account_keys
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| tx.message.is_writable(*i))
+        .map(|(_, key)| *key)
+        .collect()
+}
+
+/// Lamports charged for the priority fee, rounded up as the runtime does
+pub fn priority_fee_lamports(compute_unit_limit: u32, compute_unit_price: u64) -> u64 {
+    let micro_lamports = compute_unit_limit as u128 * compute_unit_price as u128;
+    micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT as u128) as u64
+}
+
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer, system_instruction};
+
+    fn estimator() -> PriorityFeeEstimator {
+        PriorityFeeEstimator::new(FeeEstimatorConfig::default()).unwrap()
+    }
+
+    fn percentiles(p: u64) -> FeePercentiles {
+        FeePercentiles {
+            p25: p,
+            p50: p,
+            p75: p,
+            p95: p,
+            sample_slots: 150,
+        }
+    }
+
+    #[test]
+    fn rejects_an_empty_price_range() {
+        let config = FeeEstimatorConfig {
+            min_micro_lamports: 10_000,
+            max_micro_lamports: 1_000,
+            ..FeeEstimatorConfig::default()
+        };
+        assert!(PriorityFeeEstimator::new(config).is_err());
+
+        let config = FeeEstimatorConfig {
+            compute_unit_margin: 0.5,
+            ..FeeEstimatorConfig::default()
+        };
+        assert!(PriorityFeeEstimator::new(config).is_err());
+    }
+
+    #[test]
+    fn percentile_picks_the_nearest_rank() {
+        let fees: Vec<u64> = (0..=100).collect();
+        assert_eq!(percentile(&fees, 0.25), 25);
+        assert_eq!(percentile(&fees, 0.95), 95);
+        assert_eq!(percentile(&[0, 0, 0, 10_000], 0.5), 0);
+        assert_eq!(percentile(&[7], 0.95), 7);
+        assert_eq!(percentile(&[], 0.5), 0);
+    }
+
+    #[test]
+    fn simulated_units_get_the_margin_and_the_cap() {
+        let estimator = estimator();
+        assert_eq!(estimator.compute_unit_limit(Some(100_000)), 115_000);
+        assert_eq!(estimator.compute_unit_limit(Some(2_000_000)), MAX_COMPUTE_UNIT_LIMIT);
+        assert_eq!(estimator.compute_unit_limit(None), DEFAULT_COMPUTE_UNIT_LIMIT);
+        assert_eq!(estimator.compute_unit_limit(Some(0)), DEFAULT_COMPUTE_UNIT_LIMIT);
+    }
+
+    #[test]
+    fn price_is_clamped_to_the_configured_range() {
+        let estimator = estimator();
+        let low = estimator.build_estimate(&percentiles(0), None, FeeUrgency::Normal, u64::MAX / 2);
+        assert_eq!(low.compute_unit_price, estimator.config.min_micro_lamports);
+        let high = estimator.build_estimate(&percentiles(u64::MAX), None, FeeUrgency::Normal, u64::MAX / 2);
+        assert_eq!(high.compute_unit_price, estimator.config.max_micro_lamports);
+        assert!(!high.capped_by_profit);
+    }
+
+    #[test]
+    fn fee_is_capped_at_the_profit_share() {
+        let estimator = estimator();
+        let estimate = estimator.build_estimate(&percentiles(1_000_000), Some(100_000), FeeUrgency::High, 100_000);
+        assert!(estimate.capped_by_profit);
+        assert!(estimate.priority_fee_lamports <= 20_000);
+
+        for profit in [0, 1, 7, 999, 12_345, 1_000_003] {
+            for units in [1, 3, 77_777, 200_000, 1_400_000] {
+                let estimate = estimator.build_estimate(&percentiles(5_000_000), Some(units), FeeUrgency::Critical, profit);
+                let cap = (profit as f64 * estimator.config.max_profit_fraction) as u64;
+                assert!(
+                    estimate.priority_fee_lamports <= cap,
+                    "{} lamports over the {} cap for {} units",
+                    estimate.priority_fee_lamports,
+                    cap,
+                    units
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn priority_fee_rounds_up_to_whole_lamports() {
+        assert_eq!(priority_fee_lamports(200_000, 1_000), 200);
+        assert_eq!(priority_fee_lamports(1, 1), 1);
+        assert_eq!(priority_fee_lamports(0, 1_000), 0);
+    }
+
+    #[test]
+    fn applying_an_estimate_replaces_the_compute_budget() {
+        let payer = Keypair::new();
+        let to = Pubkey::new_unique();
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(1),
+                system_instruction::transfer(&payer.pubkey(), &to, 5),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::new_unique(),
+        );
+
+        let estimate = estimator().fallback_estimate(1_000_000);
+        let mut applied = estimate.apply_to(&tx);
+        assert_eq!(applied.message.instructions.len(), 3);
+        assert_eq!(applied.message.account_keys[0], payer.pubkey());
+        assert_eq!(applied.message.recent_blockhash, tx.message.recent_blockhash);
+
+        let expected = Message::new(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(DEFAULT_COMPUTE_UNIT_LIMIT),
+                ComputeBudgetInstruction::set_compute_unit_price(estimate.compute_unit_price),
+                system_instruction::transfer(&payer.pubkey(), &to, 5),
+            ],
+            Some(&payer.pubkey()),
+        );
+        assert_eq!(applied.message.instructions, expected.instructions);
+        assert_eq!(writable_accounts(&applied), vec![payer.pubkey(), to]);
+
+        let blockhash = applied.message.recent_blockhash;
+        applied.try_sign(&[&payer], blockhash).unwrap();
+        assert!(applied.verify().is_ok());
+    }
+
+    #[test]
+    fn applying_and_signing_needs_every_required_signer() {
+        let payer = Keypair::new();
+        let authority = Keypair::new();
+        let unrelated = Keypair::new();
+        let to = Pubkey::new_unique();
+        let tx = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&authority.pubkey(), &to, 5)],
+            Some(&payer.pubkey()),
+            &[&payer, &authority],
+            Hash::new_unique(),
+        );
+
+        let estimate = estimator().fallback_estimate(1_000_000);
+        let signed = estimate.apply_and_sign(&tx, &[&unrelated, &authority, &payer]).unwrap();
+        assert_eq!(signed.message.instructions.len(), 3);
+        assert!(signed.verify().is_ok());
+
+        assert!(estimate.apply_and_sign(&tx, &[&payer]).is_err());
+    }
+}
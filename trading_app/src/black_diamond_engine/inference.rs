@@ -0,0 +1,61 @@
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use black_diamond_model_registry::{PredictRequest, PredictResponse, ServeError, ServedModelStatus};
+
+/// Client for the training app's inference server (`pablo-training-app serve <model>`).
+pub struct InferenceClient {
+    http: reqwest::Client,
+    base_url: String,
+    pub latency_budget: Duration,
+}
+
+impl InferenceClient {
+    pub fn new(base_url: &str, latency_budget: Duration) -> Result<Self> {
+        // Allow a little over the server-side budget for the round trip itself
+        let http = reqwest::Client::builder()
+            .timeout(latency_budget + Duration::from_millis(20))
+            .build()?;
+
+        Ok(InferenceClient {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            latency_budget,
+        })
+    }
+
+    pub async fn predict(&self, inputs: Vec<Vec<f32>>) -> Result<PredictResponse> {
+        let request = PredictRequest {
+            inputs,
+            budget_ms: Some(self.latency_budget.as_millis() as u64),
+        };
+        let response = self
+            .http
+            .post(format!("{}/v1/predict", self.base_url))
+            .json(&request)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            let status = response.status();
+            let error = response
+                .json::<ServeError>()
+                .await
+                .map(|e| e.error)
+                .unwrap_or_else(|_| "no error body".to_string());
+            Err(anyhow!("Inference server returned {}: {}", status, error))
+        }
+    }
+
+    pub async fn status(&self) -> Result<ServedModelStatus> {
+        Ok(self
+            .http
+            .get(format!("{}/v1/model", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
@@ -0,0 +1,454 @@
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::InstructionError,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcSendTransactionConfig,
+};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct LandingConfig {
+    pub rebroadcast_interval: Duration,
+    pub poll_interval: Duration,
+    pub commitment: CommitmentConfig,
+    pub max_history: usize,
+    /// Gives up when neither the transaction nor its blockhash expiry could be
+    /// observed in this long, e.g. while the RPC endpoint is unreachable
+    pub confirmation_timeout: Duration,
+}
+
+impl Default for LandingConfig {
+    fn default() -> Self {
+        LandingConfig {
+            rebroadcast_interval: Duration::from_millis(2000),
+            poll_interval: Duration::from_millis(400),
+            commitment: CommitmentConfig::confirmed(),
+            max_history: 1000,
+            confirmation_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LandingOutcome {
+    Landed,
+    Failed(String),   // Landed on-chain but the transaction itself errored
+    Expired,          // Blockhash expired before the transaction was seen
+    TimedOut,         // Status could not be determined before the confirmation timeout
+}
+
+#[derive(Debug, Clone)]
+pub struct LandingRecord {
+    pub signature: Signature,
+    pub endpoint: String,
+    pub strategy: String,
+    pub outcome: LandingOutcome,
+    pub slot_landed: Option<u64>,
+    pub time_to_land: Duration,
+    pub broadcasts: u32,
+}
+
+impl LandingRecord {
+    /// The signature if the transaction landed, otherwise why it did not.
+    pub fn into_signature(self) -> Result<Signature> {
+        match self.outcome {
+            LandingOutcome::Landed => Ok(self.signature),
+            LandingOutcome::Failed(reason) => Err(anyhow!("Transaction {} failed: {}", self.signature, reason)),
+            LandingOutcome::Expired => Err(anyhow!("Transaction {} expired before landing", self.signature)),
+            LandingOutcome::TimedOut => Err(anyhow!("Transaction {} could not be confirmed in time", self.signature)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LandingStats {
+    pub submitted: u64,
+    pub landed: u64,
+    pub failed: u64,
+    pub expired: u64,
+    pub timed_out: u64,
+    pub total_time_to_land: Duration,
+}
+
+impl LandingStats {
+    pub fn landing_rate(&self) -> f64 {
+        if self.submitted > 0 {
+            self.landed as f64 / self.submitted as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub fn average_time_to_land(&self) -> Duration {
+        if self.landed > 0 {
+            self.total_time_to_land / self.landed as u32
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    fn record(&mut self, record: &LandingRecord) {
+        self.submitted += 1;
+        match record.outcome {
+            LandingOutcome::Landed => {
+                self.landed += 1;
+                self.total_time_to_land += record.time_to_land;
+            }
+            LandingOutcome::Failed(_) => self.failed += 1,
+            LandingOutcome::Expired => self.expired += 1,
+            LandingOutcome::TimedOut => self.timed_out += 1,
+        }
+    }
+}
+
+#[derive(Default)]
+struct LandingState {
+    history: Vec<LandingRecord>,
+    by_endpoint: HashMap<String, LandingStats>,
+    by_strategy: HashMap<String, LandingStats>,
+}
+
+pub struct LandingTracker {
+    pub config: LandingConfig,
+    state: Mutex<LandingState>,
+}
+
+impl LandingTracker {
+    pub fn new(config: LandingConfig) -> Self {
+        LandingTracker {
+            config,
+            state: Mutex::new(LandingState::default()),
+        }
+    }
+
+    /// Sends `tx` with preflight skipped and keeps rebroadcasting it until it is
+    /// seen at the configured commitment or its blockhash expires.
+    pub async fn send_and_track(
+        &self,
+        client: &RpcClient,
+        endpoint: &str,
+        strategy: &str,
+        tx: &Transaction,
+    ) -> Result<LandingRecord> {
+        let send_config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            max_retries: Some(0), // We rebroadcast ourselves
+            ..RpcSendTransactionConfig::default()
+        };
+
+        self.track(client, endpoint, strategy, tx, || async move {
+            client.send_transaction_with_config(tx, send_config).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Broadcasts `tx` through `broadcast` (an RPC node, a block engine, ...) and
+    /// polls `client` for its status, rebroadcasting until it lands, fails,
+    /// expires or the confirmation timeout passes. RPC errors while polling are
+    /// treated as transient; every outcome is recorded in the stats.
+    pub async fn track<F, Fut>(
+        &self,
+        client: &RpcClient,
+        endpoint: &str,
+        strategy: &str,
+        tx: &Transaction,
+        mut broadcast: F,
+    ) -> Result<LandingRecord>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let signature = *tx
+            .signatures
+            .first()
+            .filter(|s| **s != Signature::default())
+            .ok_or_else(|| anyhow!("Transaction must be signed before it is tracked"))?;
+
+        let start_time = Instant::now();
+        if let Err(e) = broadcast().await {
+            println!("⚠️ Broadcast of {} via {} failed: {}", signature, endpoint, e);
+        }
+        let mut last_broadcast = Instant::now();
+        let mut broadcasts = 1;
+
+        println!("📡 Sent {} via {} ({})", signature, endpoint, strategy);
+
+        let (outcome, slot_landed) = loop {
+            if start_time.elapsed() >= self.config.confirmation_timeout {
+                break (LandingOutcome::TimedOut, None);
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+
+            match client.get_signature_statuses(&[signature]).await {
+                Ok(statuses) => {
+                    if let Some(Some(status)) = statuses.value.first() {
+                        if let Some(err) = &status.err {
+                            break (LandingOutcome::Failed(decode_transaction_error(err, tx)), Some(status.slot));
+                        }
+                        if status.satisfies_commitment(self.config.commitment) {
+                            break (LandingOutcome::Landed, Some(status.slot));
+                        }
+                        // Seen but not yet at the target commitment - no need to rebroadcast
+                        continue;
+                    }
+                }
+                Err(e) => println!("⚠️ Status poll for {} failed: {}", signature, e),
+            }
+
+            match client
+                .is_blockhash_valid(&tx.message.recent_blockhash, CommitmentConfig::processed())
+                .await
+            {
+                Ok(false) => break (LandingOutcome::Expired, None),
+                Ok(true) => {}
+                Err(e) => println!("⚠️ Blockhash check for {} failed: {}", signature, e),
+            }
+
+            if last_broadcast.elapsed() >= self.config.rebroadcast_interval {
+                if let Err(e) = broadcast().await {
+                    println!("⚠️ Rebroadcast of {} failed: {}", signature, e);
+                }
+                last_broadcast = Instant::now();
+                broadcasts += 1;
+            }
+        };
+
+        let record = LandingRecord {
+            signature,
+            endpoint: endpoint.to_string(),
+            strategy: strategy.to_string(),
+            outcome,
+            slot_landed,
+            time_to_land: start_time.elapsed(),
+            broadcasts,
+        };
+
+        match &record.outcome {
+            LandingOutcome::Landed => println!(
+                "✅ {} landed in slot {} after {:?} ({} broadcasts)",
+                signature, slot_landed.unwrap_or_default(), record.time_to_land, broadcasts
+            ),
+            LandingOutcome::Failed(reason) => println!("❌ {} failed on-chain: {}", signature, reason),
+            LandingOutcome::Expired => println!("⌛ {} expired after {} broadcasts", signature, broadcasts),
+            LandingOutcome::TimedOut => println!("⌛ {} unconfirmed after {:?}", signature, record.time_to_land),
+        }
+
+        self.record(record.clone());
+        Ok(record)
+    }
+
+    fn record(&self, record: LandingRecord) {
+        let mut state = self.state.lock().unwrap();
+        state.by_endpoint.entry(record.endpoint.clone()).or_default().record(&record);
+        state.by_strategy.entry(record.strategy.clone()).or_default().record(&record);
+        state.history.push(record);
+
+        if state.history.len() > self.config.max_history {
+            let excess = state.history.len() - self.config.max_history;
+            state.history.drain(0..excess);
+        }
+    }
+
+    pub fn endpoint_stats(&self) -> HashMap<String, LandingStats> {
+        self.state.lock().unwrap().by_endpoint.clone()
+    }
+
+    pub fn strategy_stats(&self) -> HashMap<String, LandingStats> {
+        self.state.lock().unwrap().by_strategy.clone()
+    }
+
+    pub fn recent_records(&self, limit: usize) -> Vec<LandingRecord> {
+        let state = self.state.lock().unwrap();
+        state.history.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+/// Turns a transaction error into a readable reason, naming the failing
+/// instruction's program and custom error code where available.
+pub fn decode_transaction_error(err: &TransactionError, tx: &Transaction) -> String {
+    match err {
+        TransactionError::InstructionError(index, instruction_error) => {
+            let program = tx
+                .message
+                .instructions
+                .get(*index as usize)
+                .and_then(|ix| tx.message.account_keys.get(ix.program_id_index as usize))
+                .map(|key| key.to_string())
+                .unwrap_or_else(|| "unknown program".to_string());
+
+            let detail = match instruction_error {
+                InstructionError::Custom(code) => format!("custom program error 0x{:x}", code),
+                other => format!("{:?}", other),
+            };
+
+            format!("instruction {} ({}) failed: {}", index, program, detail)
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_sdk::{
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+    };
+
+    fn signed_tx(program: Pubkey) -> Transaction {
+        let payer = Keypair::new();
+        let ix = Instruction::new_with_bytes(program, &[1], vec![AccountMeta::new(payer.pubkey(), true)]);
+        Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], Hash::new_unique())
+    }
+
+    fn fast_tracker() -> LandingTracker {
+        LandingTracker::new(LandingConfig {
+            rebroadcast_interval: Duration::from_millis(5),
+            poll_interval: Duration::from_millis(1),
+            confirmation_timeout: Duration::from_millis(50),
+            ..LandingConfig::default()
+        })
+    }
+
+    fn record(outcome: LandingOutcome, millis: u64) -> LandingRecord {
+        LandingRecord {
+            signature: Signature::new_unique(),
+            endpoint: "rpc".to_string(),
+            strategy: "arb".to_string(),
+            outcome,
+            slot_landed: None,
+            time_to_land: Duration::from_millis(millis),
+            broadcasts: 1,
+        }
+    }
+
+    #[test]
+    fn decodes_custom_errors_with_the_failing_program() {
+        let program = Pubkey::new_unique();
+        let tx = signed_tx(program);
+
+        let custom = TransactionError::InstructionError(0, InstructionError::Custom(0x1771));
+        assert_eq!(
+            decode_transaction_error(&custom, &tx),
+            format!("instruction 0 ({}) failed: custom program error 0x1771", program)
+        );
+
+        let out_of_range = TransactionError::InstructionError(3, InstructionError::InvalidAccountData);
+        assert_eq!(
+            decode_transaction_error(&out_of_range, &tx),
+            "instruction 3 (unknown program) failed: InvalidAccountData"
+        );
+        assert_eq!(decode_transaction_error(&TransactionError::AccountInUse, &tx), "AccountInUse");
+    }
+
+    #[test]
+    fn stats_count_every_outcome_but_average_only_landings() {
+        let mut stats = LandingStats::default();
+        assert_eq!(stats.landing_rate(), 0.0);
+        assert_eq!(stats.average_time_to_land(), Duration::ZERO);
+
+        stats.record(&record(LandingOutcome::Landed, 100));
+        stats.record(&record(LandingOutcome::Landed, 300));
+        stats.record(&record(LandingOutcome::Failed("x".to_string()), 5_000));
+        stats.record(&record(LandingOutcome::Expired, 5_000));
+
+        assert_eq!((stats.submitted, stats.landed, stats.failed, stats.expired), (4, 2, 1, 1));
+        assert_eq!(stats.landing_rate(), 0.5);
+        assert_eq!(stats.average_time_to_land(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let tracker = LandingTracker::new(LandingConfig { max_history: 2, ..LandingConfig::default() });
+        for _ in 0..3 {
+            tracker.record(record(LandingOutcome::Landed, 1));
+        }
+        assert_eq!(tracker.recent_records(10).len(), 2);
+        assert_eq!(tracker.endpoint_stats()["rpc"].submitted, 3);
+    }
+
+    #[tokio::test]
+    async fn tracks_a_landed_transaction() {
+        let tracker = fast_tracker();
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let tx = signed_tx(Pubkey::new_unique());
+
+        let record = tracker.send_and_track(&client, "mock", "arb", &tx).await.unwrap();
+        assert_eq!(record.outcome, LandingOutcome::Landed);
+        assert_eq!(record.signature, tx.signatures[0]);
+        assert_eq!(record.slot_landed, Some(1));
+        assert_eq!(tracker.strategy_stats()["arb"].landed, 1);
+    }
+
+    #[tokio::test]
+    async fn reports_on_chain_failures() {
+        let tracker = fast_tracker();
+        let client = RpcClient::new_mock("instruction_error".to_string());
+        let program = Pubkey::new_unique();
+
+        let record = tracker.send_and_track(&client, "mock", "arb", &signed_tx(program)).await.unwrap();
+        assert_eq!(
+            record.outcome,
+            LandingOutcome::Failed(format!("instruction 0 ({}) failed: UninitializedAccount", program))
+        );
+        assert!(record.into_signature().is_err());
+    }
+
+    #[tokio::test]
+    async fn stops_when_the_blockhash_expires() {
+        let tracker = fast_tracker();
+        let mut mocks = HashMap::new();
+        mocks.insert(RpcRequest::IsBlockhashValid, json!({ "context": { "slot": 1 }, "value": false }));
+        let client = RpcClient::new_mock_with_mocks("sig_not_found".to_string(), mocks);
+
+        let record = tracker.send_and_track(&client, "mock", "arb", &signed_tx(Pubkey::new_unique())).await.unwrap();
+        assert_eq!(record.outcome, LandingOutcome::Expired);
+        assert_eq!(tracker.endpoint_stats()["mock"].expired, 1);
+    }
+
+    #[tokio::test]
+    async fn rpc_errors_are_retried_and_recorded() {
+        let tracker = fast_tracker();
+        // Every request answers `null`, which fails to parse
+        let client = RpcClient::new_mock("fails".to_string());
+        let mut broadcasts = 0;
+
+        let record = tracker
+            .track(&client, "mock", "arb", &signed_tx(Pubkey::new_unique()), || {
+                broadcasts += 1;
+                async { Err(anyhow!("connection refused")) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(record.outcome, LandingOutcome::TimedOut);
+        assert!(broadcasts > 1, "only broadcast {} times", broadcasts);
+        assert_eq!(record.broadcasts, broadcasts);
+        assert_eq!(tracker.strategy_stats()["arb"].timed_out, 1);
+    }
+
+    #[tokio::test]
+    async fn unsigned_transactions_are_rejected() {
+        let tracker = fast_tracker();
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let payer = Pubkey::new_unique();
+        let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![AccountMeta::new(payer, true)]);
+        let tx = Transaction::new_with_payer(&[ix], Some(&payer));
+
+        assert!(tracker.send_and_track(&client, "mock", "arb", &tx).await.is_err());
+        assert!(tracker.recent_records(1).is_empty());
+    }
+}
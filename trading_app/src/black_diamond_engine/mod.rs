@@ -2,4 +2,7 @@ pub mod engine;
 pub mod tx_router;
 pub mod signal_processor;
 pub mod verification;
-pub mod profit_collector;
\ No newline at end of file
+pub mod profit_collector;
+pub mod fee_estimator;
+pub mod landing_tracker;
+pub mod inference;
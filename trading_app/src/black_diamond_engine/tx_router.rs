@@ -7,16 +7,20 @@ use solana_client::nonblocking::rpc_client::RpcClient;
 use anyhow::Result;
 use reqwest::Client;
 use serde_json::json;
+use super::fee_estimator::{FeeEstimatorConfig, FeeUrgency, PriorityFeeEstimator};
+use super::landing_tracker::LandingTracker;
 
 pub async fn route_transaction(
     client: &RpcClient,
+    tracker: &LandingTracker,
+    strategy: &str,
     tx: Transaction,
     use_jito: bool,
 ) -> Result<Signature> {
     if use_jito {
-        jito::submit_transaction_with_priority_fee(tx, Keypair::new(), 1000).await
+        jito::submit_transaction(client, tracker, strategy, tx).await
     } else {
-        regular::submit_transaction(client, tx).await
+        regular::submit_transaction(client, tracker, strategy, tx).await
     }
 }
 
@@ -25,108 +29,120 @@ mod regular {
 
     pub async fn submit_transaction(
         client: &RpcClient,
+        tracker: &LandingTracker,
+        strategy: &str,
         tx: Transaction,
     ) -> Result<Signature> {
         println!("🔗 Submitting transaction via regular RPC");
-        let signature = client.send_and_confirm_transaction(&tx).await?;
-        println!("✅ Transaction confirmed: {}", signature);
-        Ok(signature)
+        let record = tracker.send_and_track(client, &client.url(), strategy, &tx).await?;
+        record.into_signature()
     }
 }
 
 mod jito {
     use super::*;
 
-    pub async fn submit_transaction_with_priority_fee(
-        tx: Transaction,
-        fee_payer: Keypair,
-        priority_fee: u64,
-    ) -> Result<Signature> {
-        println!("🚀 Submitting transaction via Jito bundles with priority fee: {}", priority_fee);
-        
+    const BLOCK_ENGINE_URL: &str = "https://mainnet.block-engine.jito.wtf/api/v1/bundles";
+
+    async fn send_bundle(transactions: &[Transaction]) -> Result<()> {
         let client = Client::new();
-        let jito_url = "https://mainnet.block-engine.jito.wtf/api/v1/bundles";
 
-        // Serialize transaction for Jito bundle
-        let serialized_tx = bincode::serialize(&tx)?;
+        // Serialize transactions for the Jito bundle
+        let mut encoded = Vec::new();
+        for tx in transactions {
+            encoded.push(base64::encode(bincode::serialize(tx)?));
+        }
         
         let body = json!({
             "jsonrpc": "2.0",
             "id": 1,
             "method": "sendBundle",
-            "params": [
-                [base64::encode(&serialized_tx)]
-            ]
+            "params": [encoded]
         });
 
         let response = client
-            .post(jito_url)
+            .post(BLOCK_ENGINE_URL)
             .header("Content-Type", "application/json")
             .json(&body)
             .send()
             .await?;
 
         if response.status().is_success() {
-            let response_text = response.text().await?;
-            println!("💎 Jito bundle submitted successfully");
-            
-            // Generate a mock signature for demonstration
-            let signature = format!("jito_bundle_{}", uuid::Uuid::new_v4());
-            Ok(signature.parse().unwrap_or_else(|_| {
-                // Fallback signature generation
-                use solana_sdk::signature::Signature;
-                Signature::new_unique()
-            }))
+            Ok(())
         } else {
             let error_text = response.text().await?;
             Err(anyhow::anyhow!("Jito submission failed: {}", error_text))
         }
     }
 
+    /// Submits `tx` as a single-transaction bundle, resubmitting it until the
+    /// landing tracker sees it land on-chain.
+    pub async fn submit_transaction(
+        client: &RpcClient,
+        tracker: &LandingTracker,
+        strategy: &str,
+        tx: Transaction,
+    ) -> Result<Signature> {
+        println!("🚀 Submitting transaction via Jito bundles");
+        let record = tracker
+            .track(client, BLOCK_ENGINE_URL, strategy, &tx, || send_bundle(std::slice::from_ref(&tx)))
+            .await?;
+        record.into_signature()
+    }
+
+    /// Gives every leg its own compute budget, re-signs it with whichever of
+    /// `signers` it requires and sends all legs together as one atomic bundle.
     pub async fn submit_bundle_with_gas_optimization(
+        client: &RpcClient,
+        signers: &[&Keypair],
         transactions: Vec<Transaction>,
-        max_gas_price: u64,
+        expected_profit_lamports: u64,
     ) -> Result<Vec<Signature>> {
         println!("⚡ Optimizing gas for {} transactions", transactions.len());
         
-        let mut signatures = Vec::new();
+        let estimator = PriorityFeeEstimator::new(FeeEstimatorConfig::default())?;
+        // Split the fee budget across the bundle so the total stays under the profit cap
+        let profit_share = expected_profit_lamports / transactions.len().max(1) as u64;
+        let mut bundle = Vec::with_capacity(transactions.len());
+        let mut total_priority_fee = 0;
         
         for (i, tx) in transactions.iter().enumerate() {
-            let optimized_fee = calculate_optimized_fee(max_gas_price, i);
-            println!("💰 Transaction {}: Optimized fee {} lamports", i, optimized_fee);
-            
-            let signature = submit_transaction_with_priority_fee(
-                tx.clone(),
-                Keypair::new(),
-                optimized_fee,
-            ).await?;
-            
-            signatures.push(signature);
+            // Later legs depend on earlier ones and fail to simulate alone
+            let estimate = match estimator.estimate(client, tx, FeeUrgency::High, profit_share).await {
+                Ok(estimate) => estimate,
+                Err(e) => {
+                    println!("⚠️ Transaction {}: fee estimate failed ({}), using the default limit", i, e);
+                    estimator.fallback_estimate(profit_share)
+                }
+            };
+            println!("💰 Transaction {}: Optimized fee {} lamports", i, estimate.priority_fee_lamports);
+
+            total_priority_fee += estimate.priority_fee_lamports;
+            bundle.push(estimate.apply_and_sign(tx, signers)?);
         }
         
-        Ok(signatures)
-    }
-
-    fn calculate_optimized_fee(base_fee: u64, transaction_index: usize) -> u64 {
-        // Golden ratio optimization for gas fees
-        let golden_ratio = 1.618034;
-        let multiplier = 1.0 + (transaction_index as f64 * 0.1 * golden_ratio);
-        (base_fee as f64 * multiplier) as u64
+        println!("🚀 Submitting {}-transaction Jito bundle with priority fees: {}", bundle.len(), total_priority_fee);
+        send_bundle(&bundle).await?;
+        println!("💎 Jito bundle submitted successfully");
+        
+        Ok(bundle.iter().map(|tx| tx.signatures[0]).collect())
     }
 }
 
 pub async fn route_flash_loan_bundle(
     client: &RpcClient,
+    signers: &[&Keypair],
     borrow_tx: Transaction,
     arbitrage_tx: Transaction,
     repay_tx: Transaction,
+    expected_profit_lamports: u64,
 ) -> Result<Vec<Signature>> {
     println!("🔥 Routing flash loan bundle with 3 transactions");
     
     let transactions = vec![borrow_tx, arbitrage_tx, repay_tx];
     
     // Use Jito for atomic execution of flash loan bundle
-    jito::submit_bundle_with_gas_optimization(transactions, 5000).await
+    jito::submit_bundle_with_gas_optimization(client, signers, transactions, expected_profit_lamports).await
 }
 
 pub async fn verify_bundle_execution(signatures: &[Signature], client: &RpcClient) -> Result<bool> {
@@ -0,0 +1,72 @@
+use ndarray::Array2;
+
+use super::{Tape, Var};
+
+#[derive(Debug, Clone)]
+pub struct GradientCheckReport {
+    pub checked: usize,
+    pub max_abs_error: f32,
+    pub max_relative_error: f32,
+}
+
+impl GradientCheckReport {
+    pub fn passed(&self, tolerance: f32) -> bool {
+        self.max_relative_error <= tolerance
+    }
+}
+
+/// Compares the tape's gradients for `params` against central finite
+/// differences. `loss` builds the scalar loss from leaves for the given
+/// parameter values, in the same order as `params`.
+pub fn gradient_check<F>(params: &[Array2<f32>], epsilon: f32, loss: F) -> GradientCheckReport
+where
+    F: for<'t> Fn(&'t Tape, &[Var<'t>]) -> Var<'t>,
+{
+    let analytic: Vec<Array2<f32>> = {
+        let tape = Tape::new();
+        let vars: Vec<Var> = params.iter().map(|p| tape.var(p.clone())).collect();
+        let output = loss(&tape, &vars);
+        let grads = tape.backward(output);
+        vars.iter().map(|v| grads.wrt(*v).clone()).collect()
+    };
+
+    let evaluate = |values: &[Array2<f32>]| {
+        let tape = Tape::new();
+        let vars: Vec<Var> = values.iter().map(|p| tape.var(p.clone())).collect();
+        loss(&tape, &vars).scalar()
+    };
+
+    let mut report = GradientCheckReport {
+        checked: 0,
+        max_abs_error: 0.0,
+        max_relative_error: 0.0,
+    };
+    let mut perturbed = params.to_vec();
+
+    for (p, analytic_grad) in analytic.iter().enumerate() {
+        for index in 0..params[p].len() {
+            let (row, col) = (index / params[p].ncols(), index % params[p].ncols());
+            let original = params[p][[row, col]];
+
+            perturbed[p][[row, col]] = original + epsilon;
+            let plus = evaluate(&perturbed);
+            perturbed[p][[row, col]] = original - epsilon;
+            let minus = evaluate(&perturbed);
+            perturbed[p][[row, col]] = original;
+
+            let numeric = (plus - minus) / (2.0 * epsilon);
+            let exact = analytic_grad[[row, col]];
+            let abs_error = (numeric - exact).abs();
+            let relative_error = abs_error / numeric.abs().max(exact.abs()).max(1e-6);
+
+            report.checked += 1;
+            report.max_abs_error = report.max_abs_error.max(abs_error);
+            // Tiny gradients are dominated by f32 rounding in the finite difference
+            if abs_error > 1e-4 {
+                report.max_relative_error = report.max_relative_error.max(relative_error);
+            }
+        }
+    }
+
+    report
+}
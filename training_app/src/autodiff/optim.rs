@@ -0,0 +1,171 @@
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LearningRateSchedule {
+    Constant { rate: f32 },
+    /// Multiplies the rate by `factor` every `every` steps
+    StepDecay { rate: f32, factor: f32, every: usize },
+    Exponential { rate: f32, decay: f32 },
+    /// Cosine annealing from `rate` down to `min_rate` over `period` steps
+    Cosine { rate: f32, min_rate: f32, period: usize },
+}
+
+impl LearningRateSchedule {
+    pub fn rate(&self, step: usize) -> f32 {
+        match *self {
+            LearningRateSchedule::Constant { rate } => rate,
+            LearningRateSchedule::StepDecay { rate, factor, every } => {
+                rate * factor.powi((step / every.max(1)) as i32)
+            }
+            LearningRateSchedule::Exponential { rate, decay } => rate * decay.powi(step as i32),
+            LearningRateSchedule::Cosine { rate, min_rate, period } => {
+                let progress = (step.min(period) as f32) / period.max(1) as f32;
+                min_rate + 0.5 * (rate - min_rate) * (1.0 + (std::f32::consts::PI * progress).cos())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum GradientClip {
+    None,
+    /// Clamp every component to [-limit, limit]
+    Value(f32),
+    /// Rescale all gradients together so their joint L2 norm is at most the limit
+    GlobalNorm(f32),
+}
+
+/// Clips `grads` in place and returns their global norm before clipping.
+pub fn clip_gradients(grads: &mut [Array2<f32>], clip: GradientClip) -> f32 {
+    let norm = grads
+        .iter()
+        .map(|g| g.iter().map(|x| x * x).sum::<f32>())
+        .sum::<f32>()
+        .sqrt();
+
+    match clip {
+        GradientClip::None => {}
+        GradientClip::Value(limit) => {
+            for grad in grads.iter_mut() {
+                grad.mapv_inplace(|x| x.clamp(-limit, limit));
+            }
+        }
+        GradientClip::GlobalNorm(limit) => {
+            if norm > limit && norm > 0.0 {
+                let factor = limit / norm;
+                for grad in grads.iter_mut() {
+                    grad.mapv_inplace(|x| x * factor);
+                }
+            }
+        }
+    }
+    norm
+}
+
+/// Updates parameters from their gradients. Parameters and gradients are
+/// matched by position, and per-parameter state is kept in that same order,
+/// so every call must pass the parameters in the same order.
+pub trait Optimizer: Send + Sync {
+    fn step(&mut self, params: &mut [&mut Array2<f32>], grads: &[Array2<f32>]);
+    fn learning_rate(&self) -> f32;
+    fn steps(&self) -> usize;
+}
+
+pub struct Sgd {
+    pub schedule: LearningRateSchedule,
+    pub momentum: f32,
+    velocity: Vec<Array2<f32>>,
+    step: usize,
+}
+
+impl Sgd {
+    pub fn new(schedule: LearningRateSchedule, momentum: f32) -> Self {
+        Sgd {
+            schedule,
+            momentum,
+            velocity: Vec::new(),
+            step: 0,
+        }
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, params: &mut [&mut Array2<f32>], grads: &[Array2<f32>]) {
+        if self.velocity.len() != params.len() {
+            self.velocity = params.iter().map(|p| Array2::zeros(p.raw_dim())).collect();
+        }
+        let rate = self.schedule.rate(self.step);
+
+        for ((param, grad), velocity) in params.iter_mut().zip(grads).zip(self.velocity.iter_mut()) {
+            *velocity = &*velocity * self.momentum + grad;
+            param.scaled_add(-rate, velocity);
+        }
+        self.step += 1;
+    }
+
+    fn learning_rate(&self) -> f32 {
+        self.schedule.rate(self.step)
+    }
+
+    fn steps(&self) -> usize {
+        self.step
+    }
+}
+
+pub struct Adam {
+    pub schedule: LearningRateSchedule,
+    pub beta1: f32,
+    pub beta2: f32,
+    pub epsilon: f32,
+    first_moment: Vec<Array2<f32>>,
+    second_moment: Vec<Array2<f32>>,
+    step: usize,
+}
+
+impl Adam {
+    pub fn new(schedule: LearningRateSchedule) -> Self {
+        Adam {
+            schedule,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+            first_moment: Vec::new(),
+            second_moment: Vec::new(),
+            step: 0,
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, params: &mut [&mut Array2<f32>], grads: &[Array2<f32>]) {
+        if self.first_moment.len() != params.len() {
+            self.first_moment = params.iter().map(|p| Array2::zeros(p.raw_dim())).collect();
+            self.second_moment = self.first_moment.clone();
+        }
+        let rate = self.schedule.rate(self.step);
+        self.step += 1;
+        let correction1 = 1.0 - self.beta1.powi(self.step as i32);
+        let correction2 = 1.0 - self.beta2.powi(self.step as i32);
+
+        for (i, (param, grad)) in params.iter_mut().zip(grads).enumerate() {
+            let m = &mut self.first_moment[i];
+            *m = &*m * self.beta1 + &(grad * (1.0 - self.beta1));
+            let v = &mut self.second_moment[i];
+            *v = &*v * self.beta2 + &(grad.mapv(|g| g * g) * (1.0 - self.beta2));
+
+            let update = ndarray::Zip::from(&*m)
+                .and(&*v)
+                .map_collect(|&m, &v| (m / correction1) / ((v / correction2).sqrt() + self.epsilon));
+            param.scaled_add(-rate, &update);
+        }
+    }
+
+    fn learning_rate(&self) -> f32 {
+        self.schedule.rate(self.step)
+    }
+
+    fn steps(&self) -> usize {
+        self.step
+    }
+}
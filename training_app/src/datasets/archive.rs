@@ -0,0 +1,794 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::columnar::{write_table, Column};
+
+// Programs whose swap instructions are decoded
+pub const RAYDIUM_AMM_V4: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+pub const RAYDIUM_CLMM: &str = "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK";
+pub const ORCA_WHIRLPOOL: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+// Jupiter routes through the programs above; its swaps are found in inner instructions
+pub const JUPITER_V6: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tQVkTZXaUxzGkpXg";
+
+// Mints preferred as the quote side of a pair, in priority order
+const QUOTE_MINTS: [&str; 3] = [
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", // USDC
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", // USDT
+    "So11111111111111111111111111111111111111112",  // wSOL
+];
+
+const RAYDIUM_SWAP_BASE_IN: u8 = 9;
+const RAYDIUM_SWAP_BASE_OUT: u8 = 11;
+// Anchor discriminators: sha256("global:<name>")[..8]
+const ANCHOR_SWAP: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+const ANCHOR_SWAP_V2: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+// ---- Archive format: `getBlock` results with `encoding: "json"` ----
+
+/// One archived block. Files hold either a single block or one block per line,
+/// each as `{"slot": N, "block": {...}}`, a raw JSON-RPC response, or the bare
+/// result. Unwrapped blocks carry no slot, so they are only accepted from
+/// single-block files named after the requested slot (`<slot>.json`).
+/// A `null` block is a slot the leader skipped.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArchivedEntry {
+    Wrapped { slot: u64, block: Option<ArchivedBlock> },
+    Raw(ArchivedBlock),
+    Rpc {
+        #[allow(dead_code)]
+        jsonrpc: String,
+        result: Option<ArchivedBlock>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchivedBlock {
+    // Only used to tell a bare block apart from an RPC envelope
+    #[allow(dead_code)]
+    parent_slot: u64,
+    block_time: Option<i64>,
+    #[serde(default)]
+    transactions: Vec<ArchivedTransaction>,
+}
+
+#[derive(Deserialize)]
+struct ArchivedTransaction {
+    transaction: TransactionBody,
+    meta: Option<TransactionMeta>,
+}
+
+#[derive(Deserialize)]
+struct TransactionBody {
+    signatures: Vec<String>,
+    message: TransactionMessage,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionMessage {
+    account_keys: Vec<String>,
+    instructions: Vec<CompiledInstruction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompiledInstruction {
+    program_id_index: usize,
+    accounts: Vec<usize>,
+    data: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionMeta {
+    err: Option<serde_json::Value>,
+    #[serde(default)]
+    pre_token_balances: Vec<TokenBalance>,
+    #[serde(default)]
+    post_token_balances: Vec<TokenBalance>,
+    #[serde(default)]
+    inner_instructions: Vec<InnerInstructions>,
+    loaded_addresses: Option<LoadedAddresses>,
+}
+
+#[derive(Deserialize)]
+struct InnerInstructions {
+    index: usize,
+    instructions: Vec<CompiledInstruction>,
+}
+
+#[derive(Deserialize)]
+struct LoadedAddresses {
+    writable: Vec<String>,
+    readonly: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenBalance {
+    account_index: usize,
+    mint: String,
+    ui_token_amount: UiTokenAmount,
+}
+
+#[derive(Deserialize)]
+struct UiTokenAmount {
+    amount: String,
+    decimals: u8,
+}
+
+// ---- Decoded output ----
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dex {
+    RaydiumAmm,
+    RaydiumClmm,
+    OrcaWhirlpool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSide {
+    /// Base token left the pool
+    Buy,
+    /// Base token entered the pool
+    Sell,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeEvent {
+    pub slot: u64,
+    pub block_time: i64,
+    /// Position of the transaction in its block, for stable ordering within a slot
+    pub transaction_index: usize,
+    pub signature: String,
+    pub dex: Dex,
+    pub pool: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub side: TradeSide,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    pub price: f64,
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
+    pub via_jupiter: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolInfo {
+    pub pool: String,
+    pub dex: Dex,
+    pub base_mint: String,
+    pub quote_mint: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OhlcvBar {
+    pub start_time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub base_volume: f64,
+    pub quote_volume: f64,
+    pub buy_base_volume: f64,
+    pub sell_base_volume: f64,
+    pub trades: u64,
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveBuildConfig {
+    pub bar_seconds: i64,
+    /// Only keep these pools; empty keeps every decoded pool
+    pub pools: Vec<String>,
+}
+
+impl Default for ArchiveBuildConfig {
+    fn default() -> Self {
+        ArchiveBuildConfig {
+            bar_seconds: 60,
+            pools: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveSummary {
+    pub files: usize,
+    pub blocks: usize,
+    /// Slots archived as `null` (skipped by the leader or missing from the node)
+    pub empty_slots: usize,
+    /// Blocks without a known slot or block time, which cannot be placed in a bar
+    pub unplaced_blocks: usize,
+    pub transactions: usize,
+    pub failed_transactions: usize,
+    pub trades: usize,
+    pub pools: usize,
+}
+
+/// Reads every `.json`/`.jsonl` archive under `archive_dir`, decodes swaps and
+/// writes per-pool tables under `output_dir`:
+/// `<pool>/pool.json`, `<pool>/trades/`, `<pool>/reserves/` and `<pool>/ohlcv_<N>s/`.
+pub fn build_historical_dataset(archive_dir: &Path, output_dir: &Path, config: &ArchiveBuildConfig) -> Result<ArchiveSummary> {
+    if config.bar_seconds <= 0 {
+        return Err(anyhow!("Bar length must be a positive number of seconds, got {}", config.bar_seconds));
+    }
+    let mut summary = ArchiveSummary::default();
+    let mut trades = Vec::new();
+
+    for path in archive_files(archive_dir)? {
+        summary.files += 1;
+        let file_slot = requested_slot(&path);
+        for entry in read_archive(&path)? {
+            let (slot, block) = match entry {
+                ArchivedEntry::Wrapped { slot, block } => (Some(slot), block),
+                ArchivedEntry::Raw(block) => (file_slot, Some(block)),
+                ArchivedEntry::Rpc { result, .. } => (file_slot, result),
+            };
+            let block = match block {
+                Some(block) => block,
+                None => {
+                    summary.empty_slots += 1;
+                    continue;
+                }
+            };
+            let (slot, block_time) = match (slot, block.block_time) {
+                (Some(slot), Some(block_time)) => (slot, block_time),
+                _ => {
+                    summary.unplaced_blocks += 1;
+                    continue;
+                }
+            };
+            summary.blocks += 1;
+            summary.transactions += block.transactions.len();
+            summary.failed_transactions += block
+                .transactions
+                .iter()
+                .filter(|tx| tx.meta.as_ref().map(|m| m.err.is_some()).unwrap_or(true))
+                .count();
+            trades.extend(decode_block_swaps(slot, block_time, &block));
+        }
+    }
+
+    if !config.pools.is_empty() {
+        trades.retain(|t| config.pools.contains(&t.pool));
+    }
+    trades.sort_by_key(|t| (t.slot, t.transaction_index));
+    summary.trades = trades.len();
+
+    let mut by_pool: BTreeMap<String, Vec<TradeEvent>> = BTreeMap::new();
+    for trade in trades {
+        by_pool.entry(trade.pool.clone()).or_default().push(trade);
+    }
+    summary.pools = by_pool.len();
+
+    // Pool ids come from the archive, so check them before they name directories
+    for pool in by_pool.keys() {
+        validate_pool_id(pool)?;
+    }
+    for (pool, pool_trades) in &by_pool {
+        write_pool(&output_dir.join(pool), pool_trades, config.bar_seconds)?;
+    }
+
+    if summary.unplaced_blocks > 0 {
+        println!("⚠️ Skipped {} archived blocks without a slot or block time", summary.unplaced_blocks);
+    }
+    println!("📚 Decoded {} swaps across {} pools from {} blocks", summary.trades, summary.pools, summary.blocks);
+    Ok(summary)
+}
+
+/// A pool id must be a single plain path component: no separators, no `.`
+/// or `..`, nothing absolute
+fn validate_pool_id(pool: &str) -> Result<()> {
+    let mut components = Path::new(pool).components();
+    let single = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+    if !single || pool.contains(['/', '\\']) || pool.contains("..") {
+        return Err(anyhow!("Archived pool id {:?} is not a valid directory name", pool));
+    }
+    Ok(())
+}
+
+fn archive_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("reading archive directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("json") | Some("jsonl")))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// Single-block files are named after the slot that was requested
+fn requested_slot(path: &Path) -> Option<u64> {
+    if path.extension().and_then(|e| e.to_str()) != Some("json") {
+        return None;
+    }
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+fn read_archive(path: &Path) -> Result<Vec<ArchivedEntry>> {
+    let context = || format!("parsing archive {}", path.display());
+
+    if path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
+        let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.push(serde_json::from_str(&line).with_context(context)?);
+            }
+        }
+        Ok(entries)
+    } else {
+        let bytes = std::fs::read(path)?;
+        Ok(vec![serde_json::from_slice(&bytes).with_context(context)?])
+    }
+}
+
+fn decode_block_swaps(slot: u64, block_time: i64, block: &ArchivedBlock) -> Vec<TradeEvent> {
+    let mut trades = Vec::new();
+
+    for (transaction_index, tx) in block.transactions.iter().enumerate() {
+        let meta = match &tx.meta {
+            Some(meta) if meta.err.is_none() => meta,
+            _ => continue,
+        };
+
+        // v0 transactions append lookup-table addresses after the static keys
+        let mut keys = tx.transaction.message.account_keys.clone();
+        if let Some(loaded) = &meta.loaded_addresses {
+            keys.extend(loaded.writable.iter().cloned());
+            keys.extend(loaded.readonly.iter().cloned());
+        }
+
+        let mut swaps: Vec<(Dex, usize, usize, usize, bool)> = Vec::new();
+        for (ix_index, ix) in tx.transaction.message.instructions.iter().enumerate() {
+            let program = keys.get(ix.program_id_index).map(String::as_str);
+            let via_jupiter = program == Some(JUPITER_V6);
+
+            if let Some(swap) = decode_swap_instruction(&keys, ix) {
+                swaps.push((swap.0, swap.1, swap.2, swap.3, false));
+            }
+            for inner in meta.inner_instructions.iter().filter(|inner| inner.index == ix_index) {
+                for inner_ix in &inner.instructions {
+                    if let Some(swap) = decode_swap_instruction(&keys, inner_ix) {
+                        swaps.push((swap.0, swap.1, swap.2, swap.3, via_jupiter));
+                    }
+                }
+            }
+        }
+
+        let signature = tx.transaction.signatures.first().cloned().unwrap_or_default();
+        // A pool hit more than once in a transaction is recorded once, with its net flow
+        let mut seen_pools = Vec::new();
+        for (dex, pool, vault_a, vault_b, via_jupiter) in swaps {
+            if seen_pools.contains(&pool) {
+                continue;
+            }
+            seen_pools.push(pool);
+
+            if let Some(trade) = trade_from_balances(meta, &keys, dex, pool, vault_a, vault_b) {
+                trades.push(TradeEvent {
+                    slot,
+                    block_time,
+                    transaction_index,
+                    signature: signature.clone(),
+                    via_jupiter,
+                    ..trade
+                });
+            }
+        }
+    }
+
+    trades
+}
+
+/// Returns (dex, pool, vault_a, vault_b) as indexes into the transaction's account keys.
+fn decode_swap_instruction(keys: &[String], ix: &CompiledInstruction) -> Option<(Dex, usize, usize, usize)> {
+    let program = keys.get(ix.program_id_index)?.as_str();
+    let data = bs58::decode(&ix.data).into_vec().ok()?;
+    let account = |position: usize| ix.accounts.get(position).copied();
+
+    match program {
+        RAYDIUM_AMM_V4 if matches!(data.first(), Some(&RAYDIUM_SWAP_BASE_IN) | Some(&RAYDIUM_SWAP_BASE_OUT)) => {
+            // The 18-account form includes the target orders account before the vaults
+            let (coin, pc) = if ix.accounts.len() >= 18 { (5, 6) } else { (4, 5) };
+            Some((Dex::RaydiumAmm, account(1)?, account(coin)?, account(pc)?))
+        }
+        ORCA_WHIRLPOOL if data.starts_with(&ANCHOR_SWAP) => {
+            Some((Dex::OrcaWhirlpool, account(2)?, account(4)?, account(6)?))
+        }
+        ORCA_WHIRLPOOL if data.starts_with(&ANCHOR_SWAP_V2) => {
+            Some((Dex::OrcaWhirlpool, account(4)?, account(8)?, account(10)?))
+        }
+        RAYDIUM_CLMM if data.starts_with(&ANCHOR_SWAP) || data.starts_with(&ANCHOR_SWAP_V2) => {
+            Some((Dex::RaydiumClmm, account(2)?, account(5)?, account(6)?))
+        }
+        _ => None,
+    }
+}
+
+/// Builds a trade from the pool vaults' pre/post token balances.
+fn trade_from_balances(
+    meta: &TransactionMeta,
+    keys: &[String],
+    dex: Dex,
+    pool: usize,
+    vault_a: usize,
+    vault_b: usize,
+) -> Option<TradeEvent> {
+    let balance = |balances: &[TokenBalance], index: usize| -> Option<(String, f64)> {
+        balances.iter().find(|b| b.account_index == index).and_then(|b| {
+            let raw: u128 = b.ui_token_amount.amount.parse().ok()?;
+            Some((b.mint.clone(), raw as f64 / 10f64.powi(b.ui_token_amount.decimals as i32)))
+        })
+    };
+
+    let (mint_a, post_a) = balance(&meta.post_token_balances, vault_a)?;
+    let (mint_b, post_b) = balance(&meta.post_token_balances, vault_b)?;
+    let (_, pre_a) = balance(&meta.pre_token_balances, vault_a)?;
+    let (_, pre_b) = balance(&meta.pre_token_balances, vault_b)?;
+
+    // Orient the pair so the quote side is a well-known quote mint when possible
+    let a_is_base = quote_rank(&mint_b) <= quote_rank(&mint_a);
+    let (base_mint, quote_mint, base_delta, quote_delta, base_reserve, quote_reserve) = if a_is_base {
+        (mint_a, mint_b, post_a - pre_a, post_b - pre_b, post_a, post_b)
+    } else {
+        (mint_b, mint_a, post_b - pre_b, post_a - pre_a, post_b, post_a)
+    };
+
+    // A swap moves the two vaults in opposite directions
+    if base_delta == 0.0 || quote_delta == 0.0 || base_delta.signum() == quote_delta.signum() {
+        return None;
+    }
+
+    let base_amount = base_delta.abs();
+    let quote_amount = quote_delta.abs();
+    Some(TradeEvent {
+        slot: 0,
+        block_time: 0,
+        transaction_index: 0,
+        signature: String::new(),
+        dex,
+        pool: keys.get(pool)?.clone(),
+        base_mint,
+        quote_mint,
+        side: if base_delta < 0.0 { TradeSide::Buy } else { TradeSide::Sell },
+        base_amount,
+        quote_amount,
+        price: quote_amount / base_amount,
+        base_reserve,
+        quote_reserve,
+        via_jupiter: false,
+    })
+}
+
+// Lower is a stronger quote candidate; unknown mints rank last
+fn quote_rank(mint: &str) -> usize {
+    QUOTE_MINTS.iter().position(|m| *m == mint).unwrap_or(QUOTE_MINTS.len())
+}
+
+pub fn build_ohlcv_bars(trades: &[TradeEvent], bar_seconds: i64) -> Result<Vec<OhlcvBar>> {
+    if bar_seconds <= 0 {
+        return Err(anyhow!("Bar length must be a positive number of seconds, got {}", bar_seconds));
+    }
+    let mut bars: Vec<OhlcvBar> = Vec::new();
+
+    for trade in trades {
+        let start_time = trade.block_time - trade.block_time.rem_euclid(bar_seconds);
+        match bars.last_mut() {
+            Some(bar) if bar.start_time == start_time => {
+                bar.high = bar.high.max(trade.price);
+                bar.low = bar.low.min(trade.price);
+                bar.close = trade.price;
+                bar.base_volume += trade.base_amount;
+                bar.quote_volume += trade.quote_amount;
+                match trade.side {
+                    TradeSide::Buy => bar.buy_base_volume += trade.base_amount,
+                    TradeSide::Sell => bar.sell_base_volume += trade.base_amount,
+                }
+                bar.trades += 1;
+                bar.base_reserve = trade.base_reserve;
+                bar.quote_reserve = trade.quote_reserve;
+            }
+            _ => bars.push(OhlcvBar {
+                start_time,
+                open: trade.price,
+                high: trade.price,
+                low: trade.price,
+                close: trade.price,
+                base_volume: trade.base_amount,
+                quote_volume: trade.quote_amount,
+                buy_base_volume: if trade.side == TradeSide::Buy { trade.base_amount } else { 0.0 },
+                sell_base_volume: if trade.side == TradeSide::Sell { trade.base_amount } else { 0.0 },
+                trades: 1,
+                base_reserve: trade.base_reserve,
+                quote_reserve: trade.quote_reserve,
+            }),
+        }
+    }
+
+    Ok(bars)
+}
+
+fn write_pool(dir: &Path, trades: &[TradeEvent], bar_seconds: i64) -> Result<()> {
+    let first = trades.first().ok_or_else(|| anyhow!("No trades for pool {}", dir.display()))?;
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(
+        dir.join("pool.json"),
+        serde_json::to_vec_pretty(&PoolInfo {
+            pool: first.pool.clone(),
+            dex: first.dex,
+            base_mint: first.base_mint.clone(),
+            quote_mint: first.quote_mint.clone(),
+        })?,
+    )?;
+
+    let column = |f: fn(&TradeEvent) -> f64| Column::F64(trades.iter().map(f).collect());
+    write_table(
+        &dir.join("trades"),
+        &[
+            ("slot", Column::U64(trades.iter().map(|t| t.slot).collect())),
+            ("block_time", Column::I64(trades.iter().map(|t| t.block_time).collect())),
+            ("side", Column::I64(trades.iter().map(|t| if t.side == TradeSide::Buy { 1 } else { -1 }).collect())),
+            ("base_amount", column(|t| t.base_amount)),
+            ("quote_amount", column(|t| t.quote_amount)),
+            ("price", column(|t| t.price)),
+        ],
+    )?;
+
+    write_table(
+        &dir.join("reserves"),
+        &[
+            ("slot", Column::U64(trades.iter().map(|t| t.slot).collect())),
+            ("block_time", Column::I64(trades.iter().map(|t| t.block_time).collect())),
+            ("base_reserve", column(|t| t.base_reserve)),
+            ("quote_reserve", column(|t| t.quote_reserve)),
+        ],
+    )?;
+
+    let bars = build_ohlcv_bars(trades, bar_seconds)?;
+    let bar_column = |f: fn(&OhlcvBar) -> f64| Column::F64(bars.iter().map(f).collect());
+    write_table(
+        &dir.join(format!("ohlcv_{}s", bar_seconds)),
+        &[
+            ("start_time", Column::I64(bars.iter().map(|b| b.start_time).collect())),
+            ("open", bar_column(|b| b.open)),
+            ("high", bar_column(|b| b.high)),
+            ("low", bar_column(|b| b.low)),
+            ("close", bar_column(|b| b.close)),
+            ("base_volume", bar_column(|b| b.base_volume)),
+            ("quote_volume", bar_column(|b| b.quote_volume)),
+            ("buy_base_volume", bar_column(|b| b.buy_base_volume)),
+            ("sell_base_volume", bar_column(|b| b.sell_base_volume)),
+            ("trades", Column::U64(bars.iter().map(|b| b.trades).collect())),
+            ("base_reserve", bar_column(|b| b.base_reserve)),
+            ("quote_reserve", bar_column(|b| b.quote_reserve)),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Reads a pool's OHLCV table back as time-ordered bars.
+pub fn read_ohlcv_bars(pool_dir: &Path, bar_seconds: i64) -> Result<Vec<OhlcvBar>> {
+    use super::columnar::read_column;
+
+    let dir = pool_dir.join(format!("ohlcv_{}s", bar_seconds));
+    let f64_column = |name: &str| -> Result<Vec<f64>> {
+        match read_column(&dir, name)? {
+            Column::F64(values) => Ok(values),
+            _ => Err(anyhow!("Column '{}' is not f64", name)),
+        }
+    };
+    let start_times = match read_column(&dir, "start_time")? {
+        Column::I64(values) => values,
+        _ => return Err(anyhow!("Column 'start_time' is not i64")),
+    };
+    let trades = match read_column(&dir, "trades")? {
+        Column::U64(values) => values,
+        _ => return Err(anyhow!("Column 'trades' is not u64")),
+    };
+
+    let mut columns: HashMap<&str, Vec<f64>> = HashMap::new();
+    for name in [
+        "open", "high", "low", "close", "base_volume", "quote_volume",
+        "buy_base_volume", "sell_base_volume", "base_reserve", "quote_reserve",
+    ] {
+        columns.insert(name, f64_column(name)?);
+    }
+
+    Ok((0..start_times.len())
+        .map(|i| OhlcvBar {
+            start_time: start_times[i],
+            open: columns["open"][i],
+            high: columns["high"][i],
+            low: columns["low"][i],
+            close: columns["close"][i],
+            base_volume: columns["base_volume"][i],
+            quote_volume: columns["quote_volume"][i],
+            buy_base_volume: columns["buy_base_volume"][i],
+            sell_base_volume: columns["sell_base_volume"][i],
+            trades: trades[i],
+            base_reserve: columns["base_reserve"][i],
+            quote_reserve: columns["quote_reserve"][i],
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::columnar::read_column;
+    use serde_json::json;
+
+    const POOL: &str = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2";
+    const BASE_VAULT: &str = "DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz";
+    const QUOTE_VAULT: &str = "HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz";
+    const BASE_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn balance(account_index: usize, mint: &str, amount: u64) -> serde_json::Value {
+        json!({ "accountIndex": account_index, "mint": mint, "uiTokenAmount": { "amount": amount.to_string(), "decimals": 6 } })
+    }
+
+    // A Raydium AMM swap that moves the base vault by `base_delta` and the quote vault the other way by `quote_delta`
+    fn raydium_swap(base_delta: i64, quote_delta: i64, err: Option<serde_json::Value>) -> serde_json::Value {
+        let data = bs58::encode([RAYDIUM_SWAP_BASE_IN, 0, 0, 0, 0, 0, 0, 0, 0]).into_string();
+        let mut accounts = vec![0usize; 17];
+        accounts[1] = 1;
+        accounts[4] = 2;
+        accounts[5] = 3;
+        let (base_pre, quote_pre) = (1_000_000_000u64, 2_000_000_000u64);
+
+        json!({
+            "transaction": {
+                "signatures": ["sig"],
+                "message": {
+                    "accountKeys": ["payer", POOL, BASE_VAULT, QUOTE_VAULT, RAYDIUM_AMM_V4],
+                    "instructions": [{ "programIdIndex": 4, "accounts": accounts, "data": data }]
+                }
+            },
+            "meta": {
+                "err": err,
+                "preTokenBalances": [balance(2, BASE_MINT, base_pre), balance(3, USDC, quote_pre)],
+                "postTokenBalances": [
+                    balance(2, BASE_MINT, (base_pre as i64 + base_delta) as u64),
+                    balance(3, USDC, (quote_pre as i64 + quote_delta) as u64)
+                ]
+            }
+        })
+    }
+
+    fn block(block_time: Option<i64>, transactions: Vec<serde_json::Value>) -> serde_json::Value {
+        json!({ "parentSlot": 99, "blockTime": block_time, "transactions": transactions })
+    }
+
+    fn decode(block_json: serde_json::Value) -> Vec<TradeEvent> {
+        let block: ArchivedBlock = serde_json::from_value(block_json).unwrap();
+        decode_block_swaps(100, block.block_time.unwrap(), &block)
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("archive_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn decodes_a_swap_from_the_vault_balances() {
+        // 10 base left the pool for 25 USDC
+        let trades = decode(block(Some(1_700_000_000), vec![raydium_swap(-10_000_000, 25_000_000, None)]));
+
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!(trade.dex, Dex::RaydiumAmm);
+        assert_eq!(trade.pool, POOL);
+        assert_eq!(trade.base_mint, BASE_MINT);
+        assert_eq!(trade.quote_mint, USDC);
+        assert_eq!(trade.side, TradeSide::Buy);
+        assert!((trade.price - 2.5).abs() < 1e-12);
+        assert!((trade.base_reserve - 990.0).abs() < 1e-9);
+        assert!((trade.quote_reserve - 2025.0).abs() < 1e-9);
+        assert_eq!((trade.slot, trade.block_time), (100, 1_700_000_000));
+    }
+
+    #[test]
+    fn failed_and_one_sided_transactions_are_not_trades() {
+        let failed = raydium_swap(-10_000_000, 25_000_000, Some(json!({ "InstructionError": [0, "Custom"] })));
+        let one_sided = raydium_swap(-10_000_000, -5_000_000, None);
+        assert!(decode(block(Some(1_700_000_000), vec![failed, one_sided])).is_empty());
+    }
+
+    #[test]
+    fn swaps_routed_through_jupiter_are_marked() {
+        let mut tx = raydium_swap(5_000_000, -10_000_000, None);
+        let swap = tx["transaction"]["message"]["instructions"][0].clone();
+        tx["transaction"]["message"]["accountKeys"].as_array_mut().unwrap().push(json!(JUPITER_V6));
+        tx["transaction"]["message"]["instructions"] = json!([{ "programIdIndex": 5, "accounts": [], "data": "" }]);
+        tx["meta"]["innerInstructions"] = json!([{ "index": 0, "instructions": [swap] }]);
+
+        let trades = decode(block(Some(1_700_000_000), vec![tx]));
+        assert_eq!(trades.len(), 1);
+        assert!(trades[0].via_jupiter);
+        assert_eq!(trades[0].side, TradeSide::Sell);
+    }
+
+    #[test]
+    fn bars_bucket_trades_by_block_time() {
+        let mut trades = decode(block(Some(120), vec![raydium_swap(-10_000_000, 20_000_000, None)]));
+        trades.extend(decode(block(Some(150), vec![raydium_swap(-10_000_000, 30_000_000, None)])));
+        trades.extend(decode(block(Some(185), vec![raydium_swap(10_000_000, -10_000_000, None)])));
+
+        let bars = build_ohlcv_bars(&trades, 60).unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!((bars[0].start_time, bars[0].trades), (120, 2));
+        assert_eq!((bars[0].open, bars[0].high, bars[0].close), (2.0, 3.0, 3.0));
+        assert_eq!((bars[1].start_time, bars[1].sell_base_volume), (180, 10.0));
+
+        assert!(build_ohlcv_bars(&trades, 0).is_err());
+        assert!(build_ohlcv_bars(&trades, -60).is_err());
+    }
+
+    #[test]
+    fn pool_ids_must_be_plain_directory_names() {
+        assert!(validate_pool_id("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2").is_ok());
+        for bad in ["", ".", "..", "../escape", "a/b", "/etc", "a\\b", "x..y"] {
+            assert!(validate_pool_id(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn builds_pool_tables_from_mixed_archives_and_reads_them_back() {
+        let archive = test_dir("in");
+        let output = test_dir("out");
+        let swap = || raydium_swap(-10_000_000, 25_000_000, None);
+
+        // A bare block takes its slot from the file name
+        std::fs::write(archive.join("205.json"), block(Some(1_700_000_000), vec![swap()]).to_string()).unwrap();
+        let lines = [
+            json!({ "slot": 210, "block": block(Some(1_700_000_030), vec![swap()]) }),
+            json!({ "slot": 211, "block": null }),
+            json!({ "jsonrpc": "2.0", "result": null, "id": 1 }),
+            // Neither of these can be placed in time
+            json!({ "jsonrpc": "2.0", "result": block(Some(1_700_000_090), vec![swap()]), "id": 2 }),
+            json!({ "slot": 212, "block": block(None, vec![swap()]) }),
+            json!({ "slot": 290, "block": block(Some(1_700_000_100), vec![swap()]) }),
+        ];
+        let jsonl: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        std::fs::write(archive.join("blocks.jsonl"), jsonl.join("\n")).unwrap();
+
+        let summary = build_historical_dataset(&archive, &output, &ArchiveBuildConfig::default()).unwrap();
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.blocks, 3);
+        assert_eq!(summary.empty_slots, 2);
+        assert_eq!(summary.unplaced_blocks, 2);
+        assert_eq!((summary.trades, summary.pools), (3, 1));
+
+        let pool_dir = output.join(POOL);
+        match read_column(&pool_dir.join("trades"), "slot").unwrap() {
+            Column::U64(slots) => assert_eq!(slots, vec![205, 210, 290]),
+            _ => panic!("slot column is not u64"),
+        }
+
+        let bars = read_ohlcv_bars(&pool_dir, 60).unwrap();
+        let starts: Vec<i64> = bars.iter().map(|b| b.start_time).collect();
+        assert_eq!(starts, vec![1_699_999_980, 1_700_000_100]);
+        assert_eq!((bars[0].trades, bars[1].trades), (2, 1));
+        assert!((bars[0].base_volume - 20.0).abs() < 1e-9);
+        assert!((bars[1].close - 2.5).abs() < 1e-12);
+
+        std::fs::remove_dir_all(&archive).ok();
+        std::fs::remove_dir_all(&output).ok();
+    }
+}
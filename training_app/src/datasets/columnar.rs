@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+// Every supported column type is 8 bytes wide
+const VALUE_WIDTH: usize = 8;
+
+/// Column-per-file tables: every column is a raw little-endian array in
+/// `<column>.bin` and `manifest.json` lists names, types and the row count.
+/// The files load directly with `numpy.fromfile(path, dtype="<f8")` and friends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    I64,
+    U64,
+    F64,
+}
+
+#[derive(Debug, Clone)]
+pub enum Column {
+    I64(Vec<i64>),
+    U64(Vec<u64>),
+    F64(Vec<f64>),
+}
+
+impl Column {
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            Column::I64(_) => ColumnType::I64,
+            Column::U64(_) => ColumnType::U64,
+            Column::F64(_) => ColumnType::F64,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Column::I64(values) => values.len(),
+            Column::U64(values) => values.len(),
+            Column::F64(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub column_type: ColumnType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableManifest {
+    pub rows: usize,
+    pub columns: Vec<ColumnSpec>,
+}
+
+pub fn write_table(dir: &Path, columns: &[(&str, Column)]) -> Result<TableManifest> {
+    let rows = columns.first().map(|(_, c)| c.len()).unwrap_or(0);
+    if let Some((name, _)) = columns.iter().find(|(_, c)| c.len() != rows) {
+        return Err(anyhow!("Column '{}' length differs from the table's {} rows", name, rows));
+    }
+
+    std::fs::create_dir_all(dir)?;
+    let manifest_path = dir.join("manifest.json");
+    if manifest_path.exists() {
+        std::fs::remove_file(&manifest_path)?;
+    }
+
+    for (name, column) in columns {
+        let path = dir.join(format!("{}.bin", name));
+        let mut writer = BufWriter::new(File::create(&path).with_context(|| format!("creating {}", path.display()))?);
+        match column {
+            Column::I64(values) => values.iter().try_for_each(|v| writer.write_all(&v.to_le_bytes()))?,
+            Column::U64(values) => values.iter().try_for_each(|v| writer.write_all(&v.to_le_bytes()))?,
+            Column::F64(values) => values.iter().try_for_each(|v| writer.write_all(&v.to_le_bytes()))?,
+        }
+        writer.flush()?;
+    }
+
+    // The manifest goes last so a table without one is known to be incomplete
+    let manifest = TableManifest {
+        rows,
+        columns: columns
+            .iter()
+            .map(|(name, column)| ColumnSpec {
+                name: name.to_string(),
+                column_type: column.column_type(),
+            })
+            .collect(),
+    };
+    std::fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)?;
+    Ok(manifest)
+}
+
+pub fn read_manifest(dir: &Path) -> Result<TableManifest> {
+    let path = dir.join("manifest.json");
+    let bytes = std::fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub fn read_column(dir: &Path, name: &str) -> Result<Column> {
+    let manifest = read_manifest(dir)?;
+    let spec = manifest
+        .columns
+        .iter()
+        .find(|c| c.name == name)
+        .ok_or_else(|| anyhow!("Table {} has no column '{}'", dir.display(), name))?;
+
+    let mut bytes = Vec::new();
+    File::open(dir.join(format!("{}.bin", name)))?.read_to_end(&mut bytes)?;
+    if bytes.len() != manifest.rows * VALUE_WIDTH {
+        return Err(anyhow!("Column '{}' has {} bytes, expected {} rows", name, bytes.len(), manifest.rows));
+    }
+
+    let words = bytes.chunks_exact(VALUE_WIDTH).map(|chunk| {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(chunk);
+        word
+    });
+    Ok(match spec.column_type {
+        ColumnType::I64 => Column::I64(words.map(i64::from_le_bytes).collect()),
+        ColumnType::U64 => Column::U64(words.map(u64::from_le_bytes).collect()),
+        ColumnType::F64 => Column::F64(words.map(f64::from_le_bytes).collect()),
+    })
+}
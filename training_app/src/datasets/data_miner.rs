@@ -1,80 +1,159 @@
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
 
-pub async fn build_dataset_for_retail(client: &RpcClient, sample_count: usize) -> Vec<Vec<f32>> {
-    println!("Building authentic Solana dataset with {} samples", sample_count);
+use super::archive::{read_ohlcv_bars, PoolInfo};
+use super::features::{DataSource, FeatureDataset, FeaturePipeline, MarketObservation, SourcedObservation, SyntheticPolicy};
+
+/// Token vaults of the pool a retail dataset is sampled from
+#[derive(Debug, Clone)]
+pub struct PoolVaults {
+    pub market: String,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
+/// Samples the pool's vault balances `sample_count` times and turns them into
+/// feature rows. RPC failures are errors unless the pipeline allows synthetic
+/// rows, in which case the gap is filled with rows marked as synthetic that
+/// continue from the pool's last observation. A failure before the first real
+/// sample is always an error, as there is nothing to continue from.
+pub async fn build_dataset_for_retail(
+    client: &RpcClient,
+    pool: &PoolVaults,
+    sample_count: usize,
+    sample_interval: Duration,
+    pipeline: &FeaturePipeline,
+) -> Result<FeatureDataset> {
+    println!("Building Solana dataset for {} with {} samples", pool.market, sample_count);
     
-    let mut dataset = Vec::new();
+    let mut observations: Vec<SourcedObservation> = Vec::new();
+    let mut synthetic_count = 0;
     
     for i in 0..sample_count {
-        let mut sample = Vec::new();
+        let previous = observations.last().map(|o| &o.observation);
         
-        // Get authentic Solana transaction data
-        match get_recent_transaction_data(client, i).await {
-            Ok(tx_data) => {
-                sample.extend(tx_data);
-            }
-            Err(_) => {
-                // Fallback to realistic market simulation when RPC unavailable
-                sample.extend(generate_realistic_market_data(i));
-            }
-        }
+        let sourced = match sample_pool_observation(client, pool, previous).await {
+            Ok(observation) => SourcedObservation {
+                source: DataSource::Rpc,
+                market: pool.market.clone(),
+                observation,
+            },
+            Err(e) => match previous {
+                Some(previous) if pipeline.synthetic_policy == SyntheticPolicy::Allow => {
+                    synthetic_count += 1;
+                    println!("⚠️ Sample {} unavailable ({}), using synthetic observation", i, e);
+                    SourcedObservation {
+                        source: DataSource::Synthetic,
+                        market: pool.market.clone(),
+                        observation: generate_synthetic_observation(i, previous),
+                    }
+                }
+                _ => return Err(e.context(format!("sample {} of {} failed", i, pool.market))),
+            },
+        };
         
-        dataset.push(sample);
+        observations.push(sourced);
+        if i + 1 < sample_count {
+            tokio::time::sleep(sample_interval).await;
+        }
     }
     
-    println!("Authentic Solana dataset built with {} samples", dataset.len());
-    dataset
+    let dataset = pipeline.build(&observations)?;
+    println!("Solana dataset built with {} rows ({} synthetic observations)", dataset.len(), synthetic_count);
+    Ok(dataset)
 }
 
-async fn get_recent_transaction_data(client: &RpcClient, offset: usize) -> Result<Vec<f32>> {
-    // Get recent signatures from the network
-    let signatures = client.get_signatures_for_address(&Pubkey::default(), None, None, Some(10)).await?;
-    
-    let mut tx_data = Vec::new();
+async fn sample_pool_observation(
+    client: &RpcClient,
+    pool: &PoolVaults,
+    previous: Option<&MarketObservation>,
+) -> Result<MarketObservation> {
+    let slot = client.get_slot().await?;
+    let base_reserve = client.get_token_account_balance(&pool.base_vault).await?
+        .ui_amount
+        .ok_or_else(|| anyhow!("base vault balance has no UI amount"))?;
+    let quote_reserve = client.get_token_account_balance(&pool.quote_vault).await?
+        .ui_amount
+        .ok_or_else(|| anyhow!("quote vault balance has no UI amount"))?;
     
-    for (i, sig_status) in signatures.iter().enumerate() {
-        if i >= offset { break; }
-        
-        // Extract transaction metrics as features
-        let slot = sig_status.slot as f32;
-        let confirmation_status = if sig_status.confirmation_status.is_some() { 1.0 } else { 0.0 };
-        
-        tx_data.push(slot / 1000000.0); // Normalize slot number
-        tx_data.push(confirmation_status);
-        
-        // Add transaction timing data
-        if let Some(block_time) = sig_status.block_time {
-            tx_data.push((block_time % 86400) as f32 / 86400.0); // Time of day normalized
-        } else {
-            tx_data.push(0.5); // Default mid-day value
-        }
+    if base_reserve <= 0.0 {
+        return Err(anyhow!("base vault is empty"));
     }
     
-    // Pad to consistent length
-    while tx_data.len() < 20 {
-        tx_data.push(0.0);
-    }
+    // Base leaving the pool means traders bought it; base entering means they sold
+    let base_change = previous.map(|p| base_reserve - p.base_reserve).unwrap_or(0.0);
     
-    Ok(tx_data)
+    Ok(MarketObservation {
+        timestamp: unix_now(),
+        slot: Some(slot),
+        price: quote_reserve / base_reserve,
+        buy_volume: (-base_change).max(0.0),
+        sell_volume: base_change.max(0.0),
+        base_reserve,
+        quote_reserve,
+        funding_rate: None,
+    })
 }
 
-fn generate_realistic_market_data(seed: usize) -> Vec<f32> {
-    let mut data = Vec::new();
-    let base_price = 100.0 + (seed as f32 * 0.1);
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Turns a pool's archived OHLCV bars (see `archive::build_historical_dataset`)
+/// into observations for the feature pipeline.
+pub fn archive_observations(pool_dir: &Path, bar_seconds: i64) -> Result<Vec<SourcedObservation>> {
+    let info: PoolInfo = serde_json::from_slice(&std::fs::read(pool_dir.join("pool.json"))?)?;
+    let bars = read_ohlcv_bars(pool_dir, bar_seconds)?;
     
-    for i in 0..20 {
-        // Simulate realistic price movement with micro-volatility
-        let time_factor = (i as f32 * 0.1).sin();
-        let volatility = (seed as f32 * 0.001).cos() * 0.02;
-        let price = base_price * (1.0 + time_factor * volatility);
-        
-        data.push(price);
+    Ok(bars
+        .into_iter()
+        .map(|bar| SourcedObservation {
+            source: DataSource::Archive,
+            market: info.pool.clone(),
+            observation: MarketObservation {
+                timestamp: bar.start_time,
+                slot: None,
+                price: bar.close,
+                buy_volume: bar.buy_base_volume,
+                sell_volume: bar.sell_base_volume,
+                base_reserve: bar.base_reserve,
+                quote_reserve: bar.quote_reserve,
+                funding_rate: None,
+            },
+        })
+        .collect())
+}
+
+/// Continues the pool from `previous`: a small deterministic move in price,
+/// with reserves kept on the pool's constant-product curve and the base that
+/// moved counted as volume.
+fn generate_synthetic_observation(seed: usize, previous: &MarketObservation) -> MarketObservation {
+    // Simulate realistic price movement with micro-volatility
+    let time_factor = (seed as f64 * 0.1).sin();
+    let volatility = (seed as f64 * 0.001).cos() * 0.002;
+    let price = previous.price * (1.0 + time_factor * volatility);
+
+    let k = previous.base_reserve * previous.quote_reserve;
+    let base_reserve = (k / price).sqrt();
+    let base_change = base_reserve - previous.base_reserve;
+
+    MarketObservation {
+        timestamp: unix_now().max(previous.timestamp + 1),
+        slot: None,
+        price,
+        buy_volume: (-base_change).max(0.0),
+        sell_volume: base_change.max(0.0),
+        base_reserve,
+        quote_reserve: (k * price).sqrt(),
+        funding_rate: None,
     }
-    
-    data
 }
 
 pub async fn get_wallet_transaction_history(
@@ -247,4 +326,35 @@ fn estimate_price_volatility(account_data: &[u8]) -> f32 {
         .sum::<f32>() / values.len() as f32;
     
     (variance.sqrt() / mean.max(1.0)).min(2.0) // Normalize and cap
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthetic_observations_continue_the_pool() {
+        let previous = MarketObservation {
+            timestamp: 1_700_000_000,
+            slot: Some(10),
+            price: 2.5,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            base_reserve: 1_000.0,
+            quote_reserve: 2_500.0,
+            funding_rate: None,
+        };
+
+        for seed in 1..50 {
+            let synthetic = generate_synthetic_observation(seed, &previous);
+            assert!((synthetic.price / previous.price - 1.0).abs() < 0.01, "seed {}: {}", seed, synthetic.price);
+            assert!((synthetic.quote_reserve / synthetic.base_reserve - synthetic.price).abs() < 1e-9);
+            assert!((synthetic.base_reserve * synthetic.quote_reserve - 2_500_000.0).abs() < 1e-6);
+            assert!(synthetic.timestamp > previous.timestamp);
+
+            let moved = previous.base_reserve - synthetic.base_reserve;
+            assert!((synthetic.buy_volume - moved.max(0.0)).abs() < 1e-12);
+            assert!((synthetic.sell_volume - (-moved).max(0.0)).abs() < 1e-12);
+        }
+    }
+}
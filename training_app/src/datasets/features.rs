@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+pub use black_diamond_model_registry::{FeatureKind, FeatureSchema, FeatureSpec, FeatureType, ReserveSide};
+
+/// One time-ordered market reading for a single pool or perp market.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketObservation {
+    pub timestamp: i64,
+    pub slot: Option<u64>,
+    pub price: f64,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
+    pub funding_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    Rpc,
+    Archive,
+    Synthetic,
+}
+
+impl DataSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DataSource::Rpc => "rpc",
+            DataSource::Archive => "archive",
+            DataSource::Synthetic => "synthetic",
+        }
+    }
+}
+
+/// Where a row came from: the source, the observation it was computed at and
+/// the market it describes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowProvenance {
+    pub source: DataSource,
+    pub market: String,
+    pub timestamp: i64,
+    pub slot: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourcedObservation {
+    pub source: DataSource,
+    pub market: String,
+    pub observation: MarketObservation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntheticPolicy {
+    /// Any synthetic row is an error, even in a dataset with no real rows
+    Reject,
+    /// Synthetic rows may be mixed in; provenance still marks them
+    Allow,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeaturePipelineConfig {
+    pub return_windows: Vec<usize>,
+    pub volatility_window: usize,
+    pub order_flow_window: usize,
+    pub include_reserve_deltas: bool,
+    pub include_funding_rate: bool,
+}
+
+impl Default for FeaturePipelineConfig {
+    fn default() -> Self {
+        FeaturePipelineConfig {
+            return_windows: vec![1, 5, 20],
+            volatility_window: 20,
+            order_flow_window: 5,
+            include_reserve_deltas: true,
+            include_funding_rate: false,
+        }
+    }
+}
+
+pub struct FeaturePipeline {
+    pub schema: FeatureSchema,
+    pub synthetic_policy: SyntheticPolicy,
+    warmup: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureDataset {
+    pub schema: FeatureSchema,
+    pub rows: Vec<Vec<f32>>,
+    pub provenance: Vec<RowProvenance>,
+}
+
+impl FeaturePipelineConfig {
+    /// Every lookback needs at least one step; a volatility window of 0 has
+    /// no returns to average and would yield NaN
+    pub fn validate(&self) -> Result<()> {
+        if let Some(window) = self.return_windows.iter().find(|&&window| window == 0) {
+            return Err(anyhow!("Return window must be at least 1, got {}", window));
+        }
+        if self.volatility_window == 0 {
+            return Err(anyhow!("Volatility window must be at least 1"));
+        }
+        if self.order_flow_window == 0 {
+            return Err(anyhow!("Order flow window must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+impl FeaturePipeline {
+    pub fn new(config: &FeaturePipelineConfig, synthetic_policy: SyntheticPolicy) -> Result<Self> {
+        config.validate()?;
+        let mut features = Vec::new();
+
+        for &window in &config.return_windows {
+            features.push(FeatureSpec {
+                name: format!("log_return_{}", window),
+                kind: FeatureKind::LogReturn { window },
+                value_type: FeatureType::Continuous,
+            });
+        }
+        features.push(FeatureSpec {
+            name: format!("realized_volatility_{}", config.volatility_window),
+            kind: FeatureKind::RealizedVolatility { window: config.volatility_window },
+            value_type: FeatureType::Bounded { min: 0.0, max: f32::MAX },
+        });
+        features.push(FeatureSpec {
+            name: format!("order_flow_imbalance_{}", config.order_flow_window),
+            kind: FeatureKind::OrderFlowImbalance { window: config.order_flow_window },
+            value_type: FeatureType::Bounded { min: -1.0, max: 1.0 },
+        });
+        if config.include_reserve_deltas {
+            for (name, side) in [("base_reserve_delta", ReserveSide::Base), ("quote_reserve_delta", ReserveSide::Quote)] {
+                features.push(FeatureSpec {
+                    name: name.to_string(),
+                    kind: FeatureKind::ReserveDelta { side },
+                    value_type: FeatureType::Continuous,
+                });
+            }
+        }
+        if config.include_funding_rate {
+            features.push(FeatureSpec {
+                name: "funding_rate".to_string(),
+                kind: FeatureKind::FundingRate,
+                value_type: FeatureType::Continuous,
+            });
+        }
+
+        // A row needs enough history for its longest lookback plus one prior observation
+        let warmup = config
+            .return_windows
+            .iter()
+            .copied()
+            .chain([config.volatility_window, config.order_flow_window, 1])
+            .max()
+            .unwrap_or(1);
+
+        Ok(FeaturePipeline {
+            schema: FeatureSchema { features },
+            synthetic_policy,
+            warmup,
+        })
+    }
+
+    /// Computes one row per observation once its market has enough history.
+    /// Each row only uses observations of the same market at or before it, and
+    /// is validated against the schema. Rows come back ordered by timestamp.
+    pub fn build(&self, observations: &[SourcedObservation]) -> Result<FeatureDataset> {
+        check_sources(observations.iter().map(|o| o.source), self.synthetic_policy)?;
+
+        let mut markets: Vec<(String, Vec<SourcedObservation>)> = Vec::new();
+        for observation in observations {
+            match markets.iter_mut().find(|(market, _)| *market == observation.market) {
+                Some((_, series)) => series.push(observation.clone()),
+                None => markets.push((observation.market.clone(), vec![observation.clone()])),
+            }
+        }
+
+        let mut dataset: Vec<(Vec<f32>, RowProvenance)> = Vec::new();
+        for (market, series) in &markets {
+            for i in self.warmup..series.len() {
+                let history = &series[..=i];
+                let current = &series[i];
+                let row = self
+                    .schema
+                    .features
+                    .iter()
+                    .map(|spec| compute_feature(&spec.kind, history))
+                    .collect::<Result<Vec<f32>>>()?;
+
+                self.schema
+                    .validate_row(&row)
+                    .map_err(|e| anyhow!("Row at {} ({}): {}", current.observation.timestamp, market, e))?;
+
+                dataset.push((row, RowProvenance {
+                    source: current.source,
+                    market: market.clone(),
+                    timestamp: current.observation.timestamp,
+                    slot: current.observation.slot,
+                }));
+            }
+        }
+        dataset.sort_by_key(|(_, provenance)| provenance.timestamp);
+
+        let (rows, provenance) = dataset.into_iter().unzip();
+        Ok(FeatureDataset {
+            schema: self.schema.clone(),
+            rows,
+            provenance,
+        })
+    }
+}
+
+impl FeatureDataset {
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Row counts per source, as stored in checkpoints
+    pub fn source_counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for row in &self.provenance {
+            *counts.entry(row.source.label().to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn contains_synthetic(&self) -> bool {
+        self.provenance.iter().any(|p| p.source == DataSource::Synthetic)
+    }
+
+    /// Appends another dataset with the same schema, keeping rows time-ordered.
+    pub fn extend(&mut self, other: FeatureDataset, policy: SyntheticPolicy) -> Result<()> {
+        if other.schema != self.schema {
+            return Err(anyhow!("Cannot combine datasets built with different feature schemas"));
+        }
+        check_sources(
+            self.provenance.iter().chain(other.provenance.iter()).map(|p| p.source),
+            policy,
+        )?;
+
+        let mut combined: Vec<(Vec<f32>, RowProvenance)> = self
+            .rows
+            .drain(..)
+            .zip(self.provenance.drain(..))
+            .chain(other.rows.into_iter().zip(other.provenance))
+            .collect();
+        combined.sort_by_key(|(_, p)| p.timestamp);
+
+        let (rows, provenance) = combined.into_iter().unzip();
+        self.rows = rows;
+        self.provenance = provenance;
+        Ok(())
+    }
+}
+
+fn check_sources(sources: impl Iterator<Item = DataSource>, policy: SyntheticPolicy) -> Result<()> {
+    if policy == SyntheticPolicy::Allow {
+        return Ok(());
+    }
+
+    let (mut real, mut synthetic) = (0usize, 0usize);
+    for source in sources {
+        if source == DataSource::Synthetic {
+            synthetic += 1;
+        } else {
+            real += 1;
+        }
+    }
+
+    if synthetic > 0 {
+        return Err(anyhow!(
+            "Refusing {} synthetic rows alongside {} real rows; use SyntheticPolicy::Allow to opt in",
+            synthetic,
+            real
+        ));
+    }
+    Ok(())
+}
+
+fn compute_feature(kind: &FeatureKind, history: &[SourcedObservation]) -> Result<f32> {
+    let current = &history[history.len() - 1].observation;
+
+    let value = match kind {
+        FeatureKind::LogReturn { window } => {
+            let past = &history[history.len() - 1 - window].observation;
+            log_return(past.price, current.price)?
+        }
+        FeatureKind::RealizedVolatility { window } => {
+            let recent = &history[history.len() - 1 - window..];
+            let returns = recent
+                .windows(2)
+                .map(|pair| log_return(pair[0].observation.price, pair[1].observation.price))
+                .collect::<Result<Vec<f64>>>()?;
+            let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+            (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64).sqrt()
+        }
+        FeatureKind::OrderFlowImbalance { window } => {
+            let recent = &history[history.len() - window..];
+            let buys: f64 = recent.iter().map(|o| o.observation.buy_volume).sum();
+            let sells: f64 = recent.iter().map(|o| o.observation.sell_volume).sum();
+            if buys + sells > 0.0 { (buys - sells) / (buys + sells) } else { 0.0 }
+        }
+        FeatureKind::ReserveDelta { side } => {
+            let previous = &history[history.len() - 2].observation;
+            let (before, after) = match side {
+                ReserveSide::Base => (previous.base_reserve, current.base_reserve),
+                ReserveSide::Quote => (previous.quote_reserve, current.quote_reserve),
+            };
+            if before > 0.0 { (after - before) / before } else { 0.0 }
+        }
+        FeatureKind::FundingRate => current
+            .funding_rate
+            .ok_or_else(|| anyhow!("Observation at {} has no funding rate", current.timestamp))?,
+    };
+
+    Ok(value as f32)
+}
+
+fn log_return(from: f64, to: f64) -> Result<f64> {
+    if from <= 0.0 || to <= 0.0 {
+        return Err(anyhow!("Non-positive price in return calculation ({} -> {})", from, to));
+    }
+    Ok((to / from).ln())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> FeaturePipelineConfig {
+        FeaturePipelineConfig {
+            return_windows: vec![1, 2],
+            volatility_window: 2,
+            order_flow_window: 2,
+            include_reserve_deltas: true,
+            include_funding_rate: false,
+        }
+    }
+
+    fn series(market: &str, source: DataSource, prices: &[f64], start: i64) -> Vec<SourcedObservation> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, &price)| SourcedObservation {
+                source,
+                market: market.to_string(),
+                observation: MarketObservation {
+                    timestamp: start + i as i64 * 10,
+                    slot: None,
+                    price,
+                    buy_volume: 3.0,
+                    sell_volume: 1.0,
+                    base_reserve: 1_000.0 + i as f64 * 10.0,
+                    quote_reserve: 1_000.0 * price,
+                    funding_rate: None,
+                },
+            })
+            .collect()
+    }
+
+    fn feature(dataset: &FeatureDataset, row: usize, name: &str) -> f32 {
+        let column = dataset.schema.features.iter().position(|f| f.name == name).unwrap();
+        dataset.rows[row][column]
+    }
+
+    #[test]
+    fn rows_follow_the_declared_schema() {
+        let pipeline = FeaturePipeline::new(&config(), SyntheticPolicy::Reject).unwrap();
+        let names: Vec<&str> = pipeline.schema.features.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["log_return_1", "log_return_2", "realized_volatility_2", "order_flow_imbalance_2", "base_reserve_delta", "quote_reserve_delta"]
+        );
+
+        let dataset = pipeline.build(&series("SOL/USDC", DataSource::Rpc, &[100.0, 110.0, 121.0, 121.0], 0)).unwrap();
+        // Two observations of warmup
+        assert_eq!(dataset.len(), 2);
+        assert!((feature(&dataset, 0, "log_return_1") - 1.1f32.ln()).abs() < 1e-6);
+        assert!((feature(&dataset, 0, "log_return_2") - 1.21f32.ln()).abs() < 1e-6);
+        assert!(feature(&dataset, 0, "realized_volatility_2").abs() < 1e-6);
+        assert!((feature(&dataset, 0, "order_flow_imbalance_2") - 0.5).abs() < 1e-6);
+        assert!((feature(&dataset, 0, "base_reserve_delta") - 10.0 / 1_010.0).abs() < 1e-6);
+        assert_eq!(feature(&dataset, 1, "log_return_1"), 0.0);
+        assert_eq!(dataset.provenance[1].timestamp, 30);
+        assert_eq!(dataset.source_counts()["rpc"], 2);
+    }
+
+    #[test]
+    fn markets_only_see_their_own_history() {
+        let pipeline = FeaturePipeline::new(&config(), SyntheticPolicy::Reject).unwrap();
+        let mut observations = series("A", DataSource::Rpc, &[100.0, 100.0, 100.0], 0);
+        observations.extend(series("B", DataSource::Archive, &[1.0, 2.0, 4.0], 5));
+
+        let dataset = pipeline.build(&observations).unwrap();
+        assert_eq!(dataset.len(), 2);
+        assert_eq!((dataset.provenance[0].market.as_str(), dataset.provenance[0].timestamp), ("A", 20));
+        assert_eq!(feature(&dataset, 0, "log_return_1"), 0.0);
+        assert_eq!((dataset.provenance[1].market.as_str(), dataset.provenance[1].timestamp), ("B", 25));
+        assert!((feature(&dataset, 1, "log_return_1") - 2f32.ln()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reject_refuses_synthetic_rows_even_without_real_ones() {
+        let synthetic = series("A", DataSource::Synthetic, &[100.0, 101.0, 102.0], 0);
+        let mut mixed = series("A", DataSource::Rpc, &[100.0, 101.0, 102.0], 0);
+        mixed.extend(series("A", DataSource::Synthetic, &[103.0], 30));
+
+        let reject = FeaturePipeline::new(&config(), SyntheticPolicy::Reject).unwrap();
+        assert!(reject.build(&synthetic).is_err());
+        assert!(reject.build(&mixed).is_err());
+
+        let allow = FeaturePipeline::new(&config(), SyntheticPolicy::Allow).unwrap();
+        let dataset = allow.build(&mixed).unwrap();
+        assert!(dataset.contains_synthetic());
+        assert_eq!(dataset.provenance.last().unwrap().source, DataSource::Synthetic);
+    }
+
+    #[test]
+    fn extend_keeps_time_order_and_checks_schema_and_sources() {
+        let pipeline = FeaturePipeline::new(&config(), SyntheticPolicy::Reject).unwrap();
+        let mut dataset = pipeline.build(&series("A", DataSource::Rpc, &[1.0, 2.0, 3.0, 4.0], 0)).unwrap();
+        let other = pipeline.build(&series("B", DataSource::Archive, &[1.0, 2.0, 3.0], 15)).unwrap();
+        dataset.extend(other, SyntheticPolicy::Reject).unwrap();
+        let timestamps: Vec<i64> = dataset.provenance.iter().map(|p| p.timestamp).collect();
+        assert_eq!(timestamps, [20, 30, 35]);
+
+        let allow = FeaturePipeline::new(&config(), SyntheticPolicy::Allow).unwrap();
+        let synthetic = allow.build(&series("C", DataSource::Synthetic, &[1.0, 2.0, 3.0], 0)).unwrap();
+        assert!(dataset.extend(synthetic.clone(), SyntheticPolicy::Reject).is_err());
+        assert_eq!(dataset.len(), 3);
+
+        let mut funding = config();
+        funding.include_funding_rate = true;
+        let other_schema = FeaturePipeline::new(&funding, SyntheticPolicy::Allow).unwrap();
+        let mut dataset = other_schema.build(&[]).unwrap();
+        assert!(dataset.extend(synthetic, SyntheticPolicy::Allow).is_err());
+    }
+
+    #[test]
+    fn rejects_zero_length_windows() {
+        let mut volatility = config();
+        volatility.volatility_window = 0;
+        let err = FeaturePipeline::new(&volatility, SyntheticPolicy::Reject).err().unwrap();
+        assert!(err.to_string().contains("Volatility window"));
+
+        let mut returns = config();
+        returns.return_windows.push(0);
+        assert!(FeaturePipeline::new(&returns, SyntheticPolicy::Reject).is_err());
+
+        let mut order_flow = config();
+        order_flow.order_flow_window = 0;
+        assert!(FeaturePipeline::new(&order_flow, SyntheticPolicy::Reject).is_err());
+    }
+}
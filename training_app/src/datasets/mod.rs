@@ -0,0 +1,13 @@
+pub mod archive;
+pub mod columnar;
+pub mod data_miner;
+pub mod features;
+pub mod perp_markets;
+
+pub use archive::{build_historical_dataset, ArchiveBuildConfig, ArchiveSummary, OhlcvBar, TradeEvent};
+pub use data_miner::{archive_observations, build_dataset_for_retail, PoolVaults};
+pub use features::{
+    DataSource, FeatureDataset, FeaturePipeline, FeaturePipelineConfig, FeatureSchema, RowProvenance,
+    SourcedObservation, SyntheticPolicy,
+};
+pub use perp_markets::{DriftPerpAdapter, FixturePerpAdapter, PerpMarketAdapter, PerpMarketState};
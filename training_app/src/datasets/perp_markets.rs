@@ -0,0 +1,518 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::transformers::perpetuals_trading_transformer::{FundingRateData, MarketSnapshot};
+
+/// Window of mark returns behind each snapshot's volatility
+const VOLATILITY_WINDOW: usize = 20;
+
+/// One reading of a perp market, in human units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerpMarketState {
+    pub market: String,
+    pub slot: Option<u64>,
+    pub timestamp: i64,
+    pub mark_price: f64,
+    pub oracle_price: f64,
+    /// Base amount held long
+    pub long_open_interest: f64,
+    /// Base amount held short, as a positive number
+    pub short_open_interest: f64,
+    /// Last funding payment as a fraction of the oracle price
+    pub funding_rate: f64,
+    /// When that funding payment was made
+    pub funding_rate_ts: i64,
+    /// Quote volume over the last 24 hours, when the venue exposes it
+    #[serde(default)]
+    pub volume_24h: f64,
+}
+
+impl PerpMarketState {
+    /// Both sides of open interest, valued at the oracle price
+    pub fn open_interest_notional(&self) -> f64 {
+        (self.long_open_interest + self.short_open_interest) * self.oracle_price
+    }
+
+    pub fn long_short_ratio(&self) -> f64 {
+        if self.short_open_interest > 0.0 {
+            self.long_open_interest / self.short_open_interest
+        } else {
+            1.0
+        }
+    }
+}
+
+#[async_trait]
+pub trait PerpMarketAdapter: Send + Sync {
+    fn venue(&self) -> &str;
+
+    async fn market_state(&self, market: &str) -> Result<PerpMarketState>;
+
+    /// The most recent `limit` states, oldest first
+    async fn state_history(&self, market: &str, limit: usize) -> Result<Vec<PerpMarketState>>;
+
+    /// One record per funding payment, oldest first
+    async fn funding_history(&self, market: &str, limit: usize) -> Result<Vec<FundingRateData>> {
+        let states = self.state_history(market, usize::MAX).await?;
+        let mut records = funding_from_states(&states);
+        records.drain(..records.len().saturating_sub(limit));
+        Ok(records)
+    }
+
+    /// The most recent `limit` states as transformer snapshots, oldest first
+    async fn snapshot_history(&self, market: &str, limit: usize) -> Result<Vec<MarketSnapshot>> {
+        // Extra history so the first snapshots have a full volatility window
+        let states = self.state_history(market, limit.saturating_add(VOLATILITY_WINDOW)).await?;
+        let mut snapshots = snapshots_from_states(&states);
+        snapshots.drain(..snapshots.len().saturating_sub(limit));
+        Ok(snapshots)
+    }
+}
+
+/// Converts states to snapshots, with volatility as the standard deviation of
+/// log mark returns over the preceding `VOLATILITY_WINDOW` states.
+pub fn snapshots_from_states(states: &[PerpMarketState]) -> Vec<MarketSnapshot> {
+    let returns: Vec<f64> = states
+        .windows(2)
+        .map(|pair| {
+            if pair[0].mark_price > 0.0 && pair[1].mark_price > 0.0 {
+                (pair[1].mark_price / pair[0].mark_price).ln()
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    states
+        .iter()
+        .enumerate()
+        .map(|(i, state)| {
+            let recent = &returns[i.saturating_sub(VOLATILITY_WINDOW)..i];
+            let volatility = if recent.len() > 1 {
+                let mean = recent.iter().sum::<f64>() / recent.len() as f64;
+                (recent.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / recent.len() as f64).sqrt()
+            } else {
+                0.0
+            };
+
+            MarketSnapshot {
+                timestamp: state.timestamp.max(0) as u64,
+                price: state.mark_price as f32,
+                oracle_price: state.oracle_price as f32,
+                volume: state.volume_24h as f32,
+                open_interest: state.open_interest_notional() as f32,
+                funding_rate: state.funding_rate as f32,
+                long_short_ratio: state.long_short_ratio() as f32,
+                volatility: volatility as f32,
+            }
+        })
+        .collect()
+}
+
+/// Keeps the first state seen after each funding payment
+pub fn funding_from_states(states: &[PerpMarketState]) -> Vec<FundingRateData> {
+    let mut records: Vec<FundingRateData> = Vec::new();
+    let mut last_ts = None;
+
+    for state in states {
+        if last_ts == Some(state.funding_rate_ts) {
+            continue;
+        }
+        last_ts = Some(state.funding_rate_ts);
+        records.push(FundingRateData {
+            timestamp: state.funding_rate_ts.max(0) as u64,
+            rate: state.funding_rate as f32,
+            market: state.market.clone(),
+            long_short_ratio: state.long_short_ratio() as f32,
+        });
+    }
+    records
+}
+
+// Offsets into Drift v2's zero-copy `PerpMarket` account. The account starts
+// with the 8-byte discriminator and the market's own pubkey; the embedded AMM
+// follows at byte 40.
+const DRIFT_LAST_ORACLE_PRICE: usize = 72;
+const DRIFT_LAST_ORACLE_PRICE_TWAP: usize = 96;
+const DRIFT_BASE_ASSET_RESERVE: usize = 176;
+const DRIFT_QUOTE_ASSET_RESERVE: usize = 192;
+const DRIFT_PEG_MULTIPLIER: usize = 272;
+const DRIFT_BASE_ASSET_AMOUNT_LONG: usize = 304;
+const DRIFT_BASE_ASSET_AMOUNT_SHORT: usize = 320;
+const DRIFT_LAST_FUNDING_RATE: usize = 480;
+const DRIFT_LAST_FUNDING_RATE_TS: usize = 792;
+const DRIFT_MIN_ACCOUNT_LEN: usize = 800;
+
+const DRIFT_PRICE_PRECISION: f64 = 1e6;
+const DRIFT_PEG_PRECISION: f64 = 1e6;
+const DRIFT_BASE_PRECISION: f64 = 1e9;
+const DRIFT_FUNDING_RATE_PRECISION: f64 = 1e9;
+
+pub const DRIFT_PROGRAM_ID: &str = "dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH";
+
+/// Raw fields of a Drift perp market, in the program's fixed-point units
+#[derive(Debug, Clone)]
+pub struct DriftPerpMarket {
+    pub last_oracle_price: i64,
+    pub last_oracle_price_twap: i64,
+    pub base_asset_reserve: u128,
+    pub quote_asset_reserve: u128,
+    pub peg_multiplier: u128,
+    pub base_asset_amount_long: i128,
+    pub base_asset_amount_short: i128,
+    pub last_funding_rate: i64,
+    pub last_funding_rate_ts: i64,
+}
+
+fn anchor_account_discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("account:{}", name).as_bytes());
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash[..8]);
+    discriminator
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    bytes.copy_from_slice(&data[offset..offset + N]);
+    bytes
+}
+
+pub fn decode_drift_perp_market(data: &[u8]) -> Result<DriftPerpMarket> {
+    if data.len() < DRIFT_MIN_ACCOUNT_LEN {
+        return Err(anyhow!("Drift perp market account is {} bytes, expected at least {}", data.len(), DRIFT_MIN_ACCOUNT_LEN));
+    }
+    if data[..8] != anchor_account_discriminator("PerpMarket") {
+        return Err(anyhow!("Account is not a Drift PerpMarket"));
+    }
+
+    Ok(DriftPerpMarket {
+        last_oracle_price: i64::from_le_bytes(read_bytes(data, DRIFT_LAST_ORACLE_PRICE)),
+        last_oracle_price_twap: i64::from_le_bytes(read_bytes(data, DRIFT_LAST_ORACLE_PRICE_TWAP)),
+        base_asset_reserve: u128::from_le_bytes(read_bytes(data, DRIFT_BASE_ASSET_RESERVE)),
+        quote_asset_reserve: u128::from_le_bytes(read_bytes(data, DRIFT_QUOTE_ASSET_RESERVE)),
+        peg_multiplier: u128::from_le_bytes(read_bytes(data, DRIFT_PEG_MULTIPLIER)),
+        base_asset_amount_long: i128::from_le_bytes(read_bytes(data, DRIFT_BASE_ASSET_AMOUNT_LONG)),
+        base_asset_amount_short: i128::from_le_bytes(read_bytes(data, DRIFT_BASE_ASSET_AMOUNT_SHORT)),
+        last_funding_rate: i64::from_le_bytes(read_bytes(data, DRIFT_LAST_FUNDING_RATE)),
+        last_funding_rate_ts: i64::from_le_bytes(read_bytes(data, DRIFT_LAST_FUNDING_RATE_TS)),
+    })
+}
+
+impl DriftPerpMarket {
+    pub fn to_state(&self, market: &str, slot: Option<u64>, timestamp: i64) -> Result<PerpMarketState> {
+        if self.base_asset_reserve == 0 {
+            return Err(anyhow!("{} has an empty AMM", market));
+        }
+        let oracle_twap = self.last_oracle_price_twap as f64 / DRIFT_PRICE_PRECISION;
+
+        Ok(PerpMarketState {
+            market: market.to_string(),
+            slot,
+            timestamp,
+            mark_price: self.quote_asset_reserve as f64 / self.base_asset_reserve as f64 * self.peg_multiplier as f64
+                / DRIFT_PEG_PRECISION,
+            oracle_price: self.last_oracle_price as f64 / DRIFT_PRICE_PRECISION,
+            long_open_interest: self.base_asset_amount_long as f64 / DRIFT_BASE_PRECISION,
+            short_open_interest: self.base_asset_amount_short.unsigned_abs() as f64 / DRIFT_BASE_PRECISION,
+            // Funding is quoted in quote per base; dividing by the oracle TWAP makes it a fraction
+            funding_rate: if oracle_twap > 0.0 {
+                self.last_funding_rate as f64 / DRIFT_FUNDING_RATE_PRECISION / oracle_twap
+            } else {
+                0.0
+            },
+            funding_rate_ts: self.last_funding_rate_ts,
+            // Not decoded from the account
+            volume_24h: 0.0,
+        })
+    }
+}
+
+/// Reads Drift perp markets over RPC. The chain only holds the current state,
+/// so history is whatever this adapter has read since it was created; poll
+/// `market_state` to build it up.
+pub struct DriftPerpAdapter {
+    client: RpcClient,
+    program_id: Pubkey,
+    /// Market name to Drift market index
+    markets: HashMap<String, u16>,
+    history: Mutex<HashMap<String, Vec<PerpMarketState>>>,
+    pub max_history: usize,
+}
+
+impl DriftPerpAdapter {
+    pub fn new(client: RpcClient, markets: HashMap<String, u16>) -> Result<Self> {
+        Ok(DriftPerpAdapter {
+            client,
+            program_id: DRIFT_PROGRAM_ID.parse()?,
+            markets,
+            history: Mutex::new(HashMap::new()),
+            max_history: 10_000,
+        })
+    }
+
+    pub fn market_address(&self, market: &str) -> Result<Pubkey> {
+        let index = self
+            .markets
+            .get(market)
+            .ok_or_else(|| anyhow!("Unknown Drift market {}", market))?;
+        let (address, _) =
+            Pubkey::find_program_address(&[b"perp_market", &index.to_le_bytes()], &self.program_id);
+        Ok(address)
+    }
+}
+
+#[async_trait]
+impl PerpMarketAdapter for DriftPerpAdapter {
+    fn venue(&self) -> &str {
+        "drift"
+    }
+
+    async fn market_state(&self, market: &str) -> Result<PerpMarketState> {
+        let address = self.market_address(market)?;
+        let slot = self.client.get_slot().await?;
+        let account = self.client.get_account(&address).await?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let state = decode_drift_perp_market(&account.data)?.to_state(market, Some(slot), timestamp)?;
+
+        let mut history = self.history.lock().unwrap();
+        let states = history.entry(market.to_string()).or_default();
+        if states.last().and_then(|s| s.slot) != state.slot {
+            states.push(state.clone());
+            let excess = states.len().saturating_sub(self.max_history);
+            states.drain(..excess);
+        }
+        Ok(state)
+    }
+
+    async fn state_history(&self, market: &str, limit: usize) -> Result<Vec<PerpMarketState>> {
+        let history = self.history.lock().unwrap();
+        let states = history.get(market).map(Vec::as_slice).unwrap_or(&[]);
+        Ok(states[states.len().saturating_sub(limit)..].to_vec())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PerpMarketFixture {
+    /// Oldest first
+    pub states: Vec<PerpMarketState>,
+    /// Taken from `states` when empty
+    #[serde(default)]
+    pub funding: Vec<FundingRateData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerpFixtureFile {
+    pub venue: String,
+    pub markets: HashMap<String, PerpMarketFixture>,
+}
+
+/// Serves recorded market states from a JSON file, for offline training and
+/// reproducible runs.
+pub struct FixturePerpAdapter {
+    fixture: PerpFixtureFile,
+}
+
+impl FixturePerpAdapter {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        let fixture: PerpFixtureFile = serde_json::from_slice(&bytes).with_context(|| format!("parsing {}", path.display()))?;
+        Ok(FixturePerpAdapter { fixture })
+    }
+
+    pub fn from_fixture(fixture: PerpFixtureFile) -> Self {
+        FixturePerpAdapter { fixture }
+    }
+
+    fn market(&self, market: &str) -> Result<&PerpMarketFixture> {
+        self.fixture
+            .markets
+            .get(market)
+            .ok_or_else(|| anyhow!("Fixture {} has no market {}", self.fixture.venue, market))
+    }
+}
+
+#[async_trait]
+impl PerpMarketAdapter for FixturePerpAdapter {
+    fn venue(&self) -> &str {
+        &self.fixture.venue
+    }
+
+    async fn market_state(&self, market: &str) -> Result<PerpMarketState> {
+        self.market(market)?
+            .states
+            .last()
+            .cloned()
+            .ok_or_else(|| anyhow!("Fixture market {} has no states", market))
+    }
+
+    async fn state_history(&self, market: &str, limit: usize) -> Result<Vec<PerpMarketState>> {
+        let states = &self.market(market)?.states;
+        Ok(states[states.len().saturating_sub(limit)..].to_vec())
+    }
+
+    async fn funding_history(&self, market: &str, limit: usize) -> Result<Vec<FundingRateData>> {
+        let fixture = self.market(market)?;
+        let mut records = if fixture.funding.is_empty() {
+            funding_from_states(&fixture.states)
+        } else {
+            fixture.funding.clone()
+        };
+        records.drain(..records.len().saturating_sub(limit));
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(timestamp: i64, mark_price: f64, funding_rate: f64, funding_rate_ts: i64) -> PerpMarketState {
+        PerpMarketState {
+            market: "SOL-PERP".to_string(),
+            slot: Some(timestamp as u64),
+            timestamp,
+            mark_price,
+            oracle_price: mark_price,
+            long_open_interest: 300.0,
+            short_open_interest: 200.0,
+            funding_rate,
+            funding_rate_ts,
+            volume_24h: 1_000.0,
+        }
+    }
+
+    fn drift_account() -> Vec<u8> {
+        let mut data = vec![0u8; DRIFT_MIN_ACCOUNT_LEN];
+        data[..8].copy_from_slice(&anchor_account_discriminator("PerpMarket"));
+        let mut put = |offset: usize, bytes: &[u8]| data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        put(DRIFT_LAST_ORACLE_PRICE, &151_000_000i64.to_le_bytes());
+        put(DRIFT_LAST_ORACLE_PRICE_TWAP, &150_000_000i64.to_le_bytes());
+        put(DRIFT_BASE_ASSET_RESERVE, &2_000_000_000_000u128.to_le_bytes());
+        put(DRIFT_QUOTE_ASSET_RESERVE, &2_000_000_000_000u128.to_le_bytes());
+        put(DRIFT_PEG_MULTIPLIER, &150_500_000u128.to_le_bytes());
+        put(DRIFT_BASE_ASSET_AMOUNT_LONG, &3_000_000_000_000i128.to_le_bytes());
+        put(DRIFT_BASE_ASSET_AMOUNT_SHORT, &(-1_500_000_000_000i128).to_le_bytes());
+        put(480, &15_000_000i64.to_le_bytes());
+        put(792, &1_700_000_000i64.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn decodes_drift_perp_market_fields_at_their_offsets() {
+        let market = decode_drift_perp_market(&drift_account()).unwrap();
+        assert_eq!((market.last_oracle_price, market.last_oracle_price_twap), (151_000_000, 150_000_000));
+        assert_eq!((market.base_asset_reserve, market.quote_asset_reserve), (2_000_000_000_000, 2_000_000_000_000));
+        assert_eq!(market.peg_multiplier, 150_500_000);
+        assert_eq!((market.base_asset_amount_long, market.base_asset_amount_short), (3_000_000_000_000, -1_500_000_000_000));
+        assert_eq!((market.last_funding_rate, market.last_funding_rate_ts), (15_000_000, 1_700_000_000));
+
+        let state = market.to_state("SOL-PERP", Some(42), 1_700_000_100).unwrap();
+        assert!((state.mark_price - 150.5).abs() < 1e-9);
+        assert!((state.oracle_price - 151.0).abs() < 1e-9);
+        assert_eq!((state.long_open_interest, state.short_open_interest), (3_000.0, 1_500.0));
+        // 0.015 quote per base against a 150 TWAP
+        assert!((state.funding_rate - 0.0001).abs() < 1e-12);
+        assert_eq!(state.funding_rate_ts, 1_700_000_000);
+    }
+
+    #[test]
+    fn rejects_short_or_foreign_accounts() {
+        let data = drift_account();
+        assert!(decode_drift_perp_market(&data[..DRIFT_MIN_ACCOUNT_LEN - 1]).is_err());
+
+        let mut foreign = data.clone();
+        foreign[..8].copy_from_slice(&anchor_account_discriminator("SpotMarket"));
+        assert!(decode_drift_perp_market(&foreign).is_err());
+
+        let mut empty_amm = data;
+        empty_amm[DRIFT_BASE_ASSET_RESERVE..DRIFT_BASE_ASSET_RESERVE + 16].fill(0);
+        assert!(decode_drift_perp_market(&empty_amm).unwrap().to_state("SOL-PERP", None, 0).is_err());
+    }
+
+    #[test]
+    fn snapshots_carry_trailing_volatility_and_open_interest() {
+        let states = vec![state(-5, 100.0, 0.0, 0), state(60, 110.0, 0.0, 0), state(120, 99.0, 0.0, 0)];
+        let snapshots = snapshots_from_states(&states);
+
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots[0].timestamp, 0);
+        assert_eq!((snapshots[0].volatility, snapshots[1].volatility), (0.0, 0.0));
+        let returns = [(1.1f64).ln(), (0.9f64).ln()];
+        let mean = (returns[0] + returns[1]) / 2.0;
+        let expected = (((returns[0] - mean).powi(2) + (returns[1] - mean).powi(2)) / 2.0).sqrt();
+        assert!((snapshots[2].volatility as f64 - expected).abs() < 1e-6);
+
+        assert_eq!(snapshots[1].open_interest, 500.0 * 110.0);
+        assert_eq!(snapshots[1].long_short_ratio, 1.5);
+        assert_eq!(snapshots[1].volume, 1_000.0);
+    }
+
+    #[test]
+    fn funding_keeps_one_record_per_payment() {
+        let states = vec![
+            state(0, 100.0, 0.001, 10),
+            state(30, 101.0, 0.002, 10),
+            state(60, 102.0, -0.003, 70),
+            state(90, 103.0, 0.004, 70),
+            state(120, 104.0, 0.005, 130),
+        ];
+        let funding = funding_from_states(&states);
+
+        let payments: Vec<(u64, f32)> = funding.iter().map(|f| (f.timestamp, f.rate)).collect();
+        assert_eq!(payments, vec![(10, 0.001), (70, -0.003), (130, 0.005)]);
+        assert!(funding.iter().all(|f| f.market == "SOL-PERP"));
+    }
+
+    #[tokio::test]
+    async fn fixture_adapter_round_trips_through_json() {
+        let states: Vec<PerpMarketState> =
+            (0..5).map(|i| state(i * 60, 100.0 + i as f64, 0.001 * i as f64, i / 2 * 120)).collect();
+        let recorded = FundingRateData {
+            timestamp: 7,
+            rate: 0.01,
+            market: "BTC-PERP".to_string(),
+            long_short_ratio: 1.0,
+        };
+        let fixture = PerpFixtureFile {
+            venue: "drift".to_string(),
+            markets: HashMap::from([
+                ("SOL-PERP".to_string(), PerpMarketFixture { states: states.clone(), funding: Vec::new() }),
+                (
+                    "BTC-PERP".to_string(),
+                    PerpMarketFixture { states: states.clone(), funding: vec![recorded] },
+                ),
+            ]),
+        };
+        let path = std::env::temp_dir().join(format!("perp_fixture_{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_vec(&fixture).unwrap()).unwrap();
+        let adapter = FixturePerpAdapter::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(adapter.venue(), "drift");
+        assert_eq!(adapter.market_state("SOL-PERP").await.unwrap().timestamp, 240);
+        let history = adapter.state_history("SOL-PERP", 2).await.unwrap();
+        assert_eq!(history.iter().map(|s| s.timestamp).collect::<Vec<_>>(), vec![180, 240]);
+        assert_eq!(adapter.state_history("SOL-PERP", 100).await.unwrap().len(), 5);
+
+        // Derived from the states unless the fixture recorded funding
+        let derived = adapter.funding_history("SOL-PERP", 10).await.unwrap();
+        assert_eq!(derived.iter().map(|f| f.timestamp).collect::<Vec<_>>(), vec![0, 120, 240]);
+        assert_eq!(adapter.funding_history("SOL-PERP", 1).await.unwrap()[0].timestamp, 240);
+        assert_eq!(adapter.funding_history("BTC-PERP", 10).await.unwrap()[0].timestamp, 7);
+
+        let snapshots = adapter.snapshot_history("SOL-PERP", 3).await.unwrap();
+        assert_eq!(snapshots.iter().map(|s| s.timestamp).collect::<Vec<_>>(), vec![120, 180, 240]);
+        assert!(snapshots[2].volatility > 0.0);
+
+        assert!(adapter.market_state("ETH-PERP").await.is_err());
+    }
+}
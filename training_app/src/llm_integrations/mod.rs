@@ -0,0 +1,9 @@
+pub mod multi_llm_intelligence;
+pub mod providers;
+pub mod track_record;
+
+pub use multi_llm_intelligence::{
+    Abstention, ConsensusConfig, DisagreementReport, LLMResponse, MarketAnalysis, MultiLLMIntelligence, ProviderVote, TradingSignal,
+};
+pub use providers::{LlmCompletion, LlmProvider, MockProvider, MockReply, OpenAiCompatibleProvider, ProviderConfig, ProviderUsage};
+pub use track_record::{TrackRecord, TrackRecordStore};
@@ -1,18 +1,48 @@
-use serde_json::Value;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::providers::{LlmProvider, MockProvider, OpenAiCompatibleProvider, ProviderConfig, ProviderUsage};
+use super::track_record::{TrackRecord, TrackRecordStore};
+
 pub struct MultiLLMIntelligence {
-    deepseek_endpoint: String,
-    perplexity_endpoint: String,
-    consensus_threshold: f64,
+    providers: Vec<Box<dyn LlmProvider>>,
+    track_records: TrackRecordStore,
+    pub config: ConsensusConfig,
     market_analysis_cache: HashMap<String, MarketAnalysis>,
 }
 
+#[derive(Debug, Clone)]
+pub struct ConsensusConfig {
+    /// Consensus score a signal needs before a strategy acts on it
+    pub consensus_threshold: f64,
+    /// Fewest voting providers for a consensus; with fewer the result is a hold
+    pub min_quorum: usize,
+    /// Providers less confident than this abstain instead of voting
+    pub min_vote_confidence: f64,
+    /// Realised returns within ± this count as flat when scoring providers
+    pub flat_return_threshold: f64,
+}
+
+impl Default for ConsensusConfig {
+    fn default() -> Self {
+        ConsensusConfig {
+            consensus_threshold: 0.75,
+            min_quorum: 1,
+            min_vote_confidence: 0.3,
+            flat_return_threshold: 0.001,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MarketAnalysis {
-    pub deepseek_confidence: f64,
-    pub perplexity_confidence: f64,
+    pub votes: Vec<ProviderVote>,
+    pub abstentions: Vec<Abstention>,
+    pub disagreement: DisagreementReport,
+    /// False when too few providers voted; the signal is then a hold
+    pub quorum_met: bool,
     pub consensus_score: f64,
     pub trading_signal: TradingSignal,
     pub reasoning: String,
@@ -20,6 +50,35 @@ pub struct MarketAnalysis {
 }
 
 #[derive(Debug, Clone)]
+pub struct ProviderVote {
+    pub provider: String,
+    pub signal: TradingSignal,
+    pub confidence: f64,
+    /// Learned from the provider's track record when the vote was cast
+    pub weight: f64,
+    pub reasoning: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Abstention {
+    pub provider: String,
+    pub reason: String,
+}
+
+/// How the weighted vote split across directions
+#[derive(Debug, Clone, Default)]
+pub struct DisagreementReport {
+    pub up_share: f64,
+    pub flat_share: f64,
+    pub down_share: f64,
+    /// Providers whose direction differs from the consensus
+    pub dissenters: Vec<String>,
+    /// Distance between the most bullish and most bearish vote, 0 to 4
+    pub signal_spread: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TradingSignal {
     StrongBuy,
     Buy,
@@ -28,27 +87,121 @@ pub enum TradingSignal {
     StrongSell,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Flat,
+    Down,
+}
+
+impl TradingSignal {
+    /// StrongBuy 2 through StrongSell -2
+    pub fn level(&self) -> i8 {
+        match self {
+            TradingSignal::StrongBuy => 2,
+            TradingSignal::Buy => 1,
+            TradingSignal::Hold => 0,
+            TradingSignal::Sell => -1,
+            TradingSignal::StrongSell => -2,
+        }
+    }
+
+    /// Buy and StrongBuy agree on direction, as do Sell and StrongSell
+    pub fn direction(&self) -> Direction {
+        match self.level() {
+            l if l > 0 => Direction::Up,
+            0 => Direction::Flat,
+            _ => Direction::Down,
+        }
+    }
+}
+
 impl MultiLLMIntelligence {
-    pub fn new() -> Self {
-        MultiLLMIntelligence {
-            deepseek_endpoint: "https://api.deepseek.com/v1/chat/completions".to_string(),
-            perplexity_endpoint: "https://api.perplexity.ai/chat/completions".to_string(),
-            consensus_threshold: 0.75,
-            market_analysis_cache: HashMap::new(),
+    /// DeepSeek and Perplexity over their chat-completions APIs; needs
+    /// `DEEPSEEK_API_KEY` and `PERPLEXITY_API_KEY`. Track records are kept in
+    /// `BLACK_DIAMOND_LLM_TRACK_RECORDS` (default `llm_track_records.json`).
+    pub fn new() -> Result<Self> {
+        let path = std::env::var("BLACK_DIAMOND_LLM_TRACK_RECORDS")
+            .unwrap_or_else(|_| "llm_track_records.json".to_string());
+
+        Self::with_providers(
+            vec![
+                Box::new(OpenAiCompatibleProvider::new(ProviderConfig::deepseek())?),
+                Box::new(OpenAiCompatibleProvider::new(ProviderConfig::perplexity())?),
+            ],
+            TrackRecordStore::open(path)?,
+        )
+    }
+
+    /// Canned providers that agree on a confident hold, for offline runs
+    pub fn offline() -> Self {
+        Self::with_providers(
+            vec![
+                Box::new(MockProvider::fixed("deepseek", TradingSignal::Hold, 0.8)),
+                Box::new(MockProvider::fixed("perplexity", TradingSignal::Hold, 0.8)),
+            ],
+            TrackRecordStore::in_memory(),
+        )
+        .expect("offline providers have unique names")
+    }
+
+    pub fn with_providers(providers: Vec<Box<dyn LlmProvider>>, track_records: TrackRecordStore) -> Result<Self> {
+        for (i, provider) in providers.iter().enumerate() {
+            if providers[..i].iter().any(|p| p.name() == provider.name()) {
+                return Err(anyhow!("Duplicate LLM provider name '{}'", provider.name()));
+            }
         }
+
+        Ok(MultiLLMIntelligence {
+            providers,
+            track_records,
+            config: ConsensusConfig::default(),
+            market_analysis_cache: HashMap::new(),
+        })
+    }
+
+    /// Current vote weight of a provider, the skill shown by its track record
+    pub fn provider_weight(&self, name: &str) -> f64 {
+        self.track_records.provider(name).skill()
     }
 
     pub async fn analyze_market_conditions(&mut self, market_data: &str) -> Result<MarketAnalysis> {
-        println!("Analyzing market conditions with multi-LLM consensus");
-        
-        let deepseek_analysis = self.query_deepseek(market_data).await?;
-        let perplexity_analysis = self.query_perplexity(market_data).await?;
-        
-        let consensus = self.calculate_consensus(&deepseek_analysis, &perplexity_analysis);
-        
+        println!("Analyzing market conditions with {}-provider consensus", self.providers.len());
+
+        let results = join_all(self.providers.iter().map(|p| p.analyze(market_data))).await;
+
+        let mut votes = Vec::new();
+        let mut abstentions = Vec::new();
+        for (provider, result) in self.providers.iter().zip(results) {
+            let name = provider.name().to_string();
+            match result {
+                Ok(completion) if completion.response.confidence >= self.config.min_vote_confidence => {
+                    votes.push(ProviderVote {
+                        weight: self.provider_weight(&name),
+                        provider: name,
+                        signal: completion.response.signal,
+                        confidence: completion.response.confidence,
+                        reasoning: completion.response.reasoning,
+                    });
+                }
+                Ok(completion) => abstentions.push(Abstention {
+                    provider: name,
+                    reason: format!("confidence {:.2} below {:.2}", completion.response.confidence, self.config.min_vote_confidence),
+                }),
+                Err(e) => {
+                    println!("⚠️ {} abstained: {}", name, e);
+                    abstentions.push(Abstention { provider: name, reason: e.to_string() });
+                }
+            }
+        }
+
+        let consensus = self.calculate_consensus(&votes);
+
         let analysis = MarketAnalysis {
-            deepseek_confidence: deepseek_analysis.confidence,
-            perplexity_confidence: perplexity_analysis.confidence,
+            votes,
+            abstentions,
+            disagreement: consensus.disagreement,
+            quorum_met: consensus.quorum_met,
             consensus_score: consensus.score,
             trading_signal: consensus.signal,
             reasoning: consensus.reasoning,
@@ -56,103 +209,118 @@ impl MultiLLMIntelligence {
                 .duration_since(std::time::UNIX_EPOCH)?
                 .as_secs(),
         };
-        
+
         self.market_analysis_cache.insert(market_data.to_string(), analysis.clone());
         Ok(analysis)
     }
 
-    async fn query_deepseek(&self, prompt: &str) -> Result<LLMResponse> {
-        println!("Querying DeepSeek for market analysis");
-        
-        // Simulate DeepSeek API call with realistic response
-        let confidence = 0.85 + (prompt.len() % 10) as f64 / 100.0;
-        let signal_strength = self.extract_signal_strength(prompt);
-        
-        Ok(LLMResponse {
-            confidence,
-            signal: self.determine_signal(signal_strength),
-            reasoning: format!("DeepSeek analysis: Market shows {} patterns with {} confidence", 
-                             if signal_strength > 0.5 { "bullish" } else { "bearish" }, 
-                             confidence),
-        })
-    }
-
-    async fn query_perplexity(&self, prompt: &str) -> Result<LLMResponse> {
-        println!("Querying Perplexity for market analysis");
-        
-        // Simulate Perplexity API call with realistic response
-        let confidence = 0.82 + (prompt.len() % 8) as f64 / 120.0;
-        let signal_strength = self.extract_signal_strength(prompt) * 1.1; // Slightly different interpretation
-        
-        Ok(LLMResponse {
-            confidence,
-            signal: self.determine_signal(signal_strength),
-            reasoning: format!("Perplexity analysis: Market conditions indicate {} sentiment with {} reliability", 
-                             if signal_strength > 0.5 { "positive" } else { "negative" }, 
-                             confidence),
-        })
-    }
-
-    fn extract_signal_strength(&self, data: &str) -> f64 {
-        // Extract signal strength from market data patterns
-        let mut strength = 0.5; // Neutral baseline
-        
-        if data.contains("volume") { strength += 0.1; }
-        if data.contains("spike") { strength += 0.2; }
-        if data.contains("drop") { strength -= 0.2; }
-        if data.contains("liquidity") { strength += 0.15; }
-        if data.contains("arbitrage") { strength += 0.25; }
-        
-        strength.max(0.0).min(1.0)
-    }
-
-    fn determine_signal(&self, strength: f64) -> TradingSignal {
-        match strength {
-            s if s > 0.8 => TradingSignal::StrongBuy,
-            s if s > 0.6 => TradingSignal::Buy,
-            s if s > 0.4 => TradingSignal::Hold,
-            s if s > 0.2 => TradingSignal::Sell,
-            _ => TradingSignal::StrongSell,
+    /// Each vote counts `weight × confidence` towards its direction. The
+    /// direction with a strict majority wins, otherwise the result is a hold;
+    /// it is strong when its voters' weighted mean level rounds to ±2. While
+    /// no voter has shown any skill yet, every vote carries equal weight.
+    fn calculate_consensus(&self, votes: &[ProviderVote]) -> ConsensusResult {
+        if votes.len() < self.config.min_quorum.max(1) {
+            return ConsensusResult {
+                score: 0.0,
+                signal: TradingSignal::Hold,
+                quorum_met: false,
+                disagreement: DisagreementReport::default(),
+                reasoning: format!("No consensus: {} of {} required providers voted", votes.len(), self.config.min_quorum.max(1)),
+            };
         }
-    }
 
-    fn calculate_consensus(&self, deepseek: &LLMResponse, perplexity: &LLMResponse) -> ConsensusResult {
-        let confidence_avg = (deepseek.confidence + perplexity.confidence) / 2.0;
-        let signal_agreement = self.signals_match(&deepseek.signal, &perplexity.signal);
-        
-        let consensus_score = if signal_agreement {
-            confidence_avg * 1.2 // Boost for agreement
-        } else {
-            confidence_avg * 0.7 // Reduce for disagreement
+        let unweighted = votes.iter().all(|v| v.weight <= 0.0);
+        let weight = |vote: &ProviderVote| if unweighted { 1.0 } else { vote.weight };
+        let strength = |vote: &ProviderVote| weight(vote) * vote.confidence;
+        let total: f64 = votes.iter().map(strength).sum();
+        let share = |direction: Direction| {
+            if total <= 0.0 {
+                return 0.0;
+            }
+            votes.iter().filter(|v| v.signal.direction() == direction).map(strength).sum::<f64>() / total
         };
-        
-        let final_signal = if signal_agreement {
-            deepseek.signal.clone()
+        let (up_share, flat_share, down_share) = (share(Direction::Up), share(Direction::Flat), share(Direction::Down));
+
+        let (direction, agreement) = [(Direction::Up, up_share), (Direction::Flat, flat_share), (Direction::Down, down_share)]
+            .into_iter()
+            .fold((Direction::Flat, 0.0), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+
+        let signal = if agreement <= 0.5 {
+            TradingSignal::Hold
         } else {
-            TradingSignal::Hold // Default to hold on disagreement
+            let backers: Vec<&ProviderVote> = votes.iter().filter(|v| v.signal.direction() == direction).collect();
+            let backing: f64 = backers.iter().map(|v| strength(v)).sum();
+            let mean_level = backers.iter().map(|v| strength(v) * v.signal.level() as f64).sum::<f64>() / backing.max(f64::EPSILON);
+            match (direction, mean_level.abs() >= 1.5) {
+                (Direction::Up, true) => TradingSignal::StrongBuy,
+                (Direction::Up, false) => TradingSignal::Buy,
+                (Direction::Down, true) => TradingSignal::StrongSell,
+                (Direction::Down, false) => TradingSignal::Sell,
+                (Direction::Flat, _) => TradingSignal::Hold,
+            }
         };
-        
+
+        let weight_total: f64 = votes.iter().map(weight).sum();
+        let weighted_confidence = total / weight_total.max(f64::EPSILON);
+        let consensus_score = if agreement > 0.5 { agreement * weighted_confidence } else { 0.0 };
+
+        let levels = votes.iter().map(|v| v.signal.level());
+        let spread = levels.clone().max().unwrap_or(0) - levels.min().unwrap_or(0);
+        let dissenters: Vec<String> = votes
+            .iter()
+            .filter(|v| v.signal.direction() != signal.direction())
+            .map(|v| v.provider.clone())
+            .collect();
+
         ConsensusResult {
             score: consensus_score.min(1.0),
-            signal: final_signal,
-            reasoning: format!("Consensus: {} agreement, {:.2} confidence", 
-                             if signal_agreement { "High" } else { "Low" }, 
-                             consensus_score),
+            reasoning: format!(
+                "Consensus: {:.0}% weighted agreement across {} providers, {:.2} confidence{}",
+                agreement * 100.0,
+                votes.len(),
+                consensus_score,
+                if dissenters.is_empty() { String::new() } else { format!(", dissent from {}", dissenters.join(", ")) }
+            ),
+            signal,
+            quorum_met: true,
+            disagreement: DisagreementReport {
+                up_share,
+                flat_share,
+                down_share,
+                dissenters,
+                signal_spread: spread as u8,
+            },
         }
     }
 
-    fn signals_match(&self, signal1: &TradingSignal, signal2: &TradingSignal) -> bool {
-        match (signal1, signal2) {
-            (TradingSignal::StrongBuy, TradingSignal::Buy) |
-            (TradingSignal::Buy, TradingSignal::StrongBuy) |
-            (TradingSignal::StrongSell, TradingSignal::Sell) |
-            (TradingSignal::Sell, TradingSignal::StrongSell) => true,
-            _ => std::mem::discriminant(signal1) == std::mem::discriminant(signal2),
+    /// Scores every vote and the consensus against the realised return over
+    /// the signal's horizon, then persists the updated track records.
+    pub fn record_outcome(&mut self, analysis: &MarketAnalysis, realized_return: f64) -> Result<()> {
+        let realized = if realized_return > self.config.flat_return_threshold {
+            Direction::Up
+        } else if realized_return < -self.config.flat_return_threshold {
+            Direction::Down
+        } else {
+            Direction::Flat
+        };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+
+        for vote in &analysis.votes {
+            self.track_records.record_provider(&vote.provider, Some(vote.signal.direction() == realized), now);
+        }
+        for abstention in &analysis.abstentions {
+            self.track_records.record_provider(&abstention.provider, None, now);
         }
+        let consensus_correct = analysis.quorum_met.then(|| analysis.trading_signal.direction() == realized);
+        self.track_records.record_consensus(consensus_correct, now);
+
+        self.track_records.save()
     }
 
     pub async fn generate_trading_strategy(&self, analysis: &MarketAnalysis) -> Result<String> {
-        if analysis.consensus_score > self.consensus_threshold {
+        if analysis.consensus_score > self.config.consensus_threshold {
             match analysis.trading_signal {
                 TradingSignal::StrongBuy => Ok("Execute aggressive long position with 2x leverage".to_string()),
                 TradingSignal::Buy => Ok("Open moderate long position, tight stop-loss".to_string()),
@@ -167,14 +335,25 @@ impl MultiLLMIntelligence {
 
     pub fn get_training_report(&self) -> TrainingReport {
         TrainingReport {
-            models_trained: vec![
-                "DeepSeek-V2-Chat".to_string(),
-                "Perplexity-70B".to_string(),
-            ],
-            consensus_accuracy: 0.89,
+            models_trained: self
+                .providers
+                .iter()
+                .map(|p| format!("{}/{}", p.name(), p.model()))
+                .collect(),
+            consensus_accuracy: self.track_records.consensus().accuracy(),
             total_analyses: self.market_analysis_cache.len(),
             avg_confidence: self.calculate_avg_confidence(),
             active_strategies: 3,
+            provider_usage: self
+                .providers
+                .iter()
+                .map(|p| (p.name().to_string(), p.usage()))
+                .collect(),
+            provider_records: self
+                .providers
+                .iter()
+                .map(|p| (p.name().to_string(), self.track_records.provider(p.name())))
+                .collect(),
         }
     }
 
@@ -182,34 +361,180 @@ impl MultiLLMIntelligence {
         if self.market_analysis_cache.is_empty() {
             return 0.0;
         }
-        
+
         let total: f64 = self.market_analysis_cache.values()
             .map(|a| a.consensus_score)
             .sum();
-        
+
         total / self.market_analysis_cache.len() as f64
     }
 }
 
-#[derive(Debug)]
-struct LLMResponse {
-    confidence: f64,
-    signal: TradingSignal,
-    reasoning: String,
+#[derive(Debug, Clone)]
+pub struct LLMResponse {
+    pub confidence: f64,
+    pub signal: TradingSignal,
+    pub reasoning: String,
 }
 
 #[derive(Debug)]
 struct ConsensusResult {
     score: f64,
     signal: TradingSignal,
+    quorum_met: bool,
+    disagreement: DisagreementReport,
     reasoning: String,
 }
 
 #[derive(Debug)]
 pub struct TrainingReport {
     pub models_trained: Vec<String>,
+    /// Smoothed hit rate of the consensus signal's direction
     pub consensus_accuracy: f64,
     pub total_analyses: usize,
     pub avg_confidence: f64,
     pub active_strategies: u32,
-}
\ No newline at end of file
+    /// Requests, retries, tokens and spend per provider
+    pub provider_usage: HashMap<String, ProviderUsage>,
+    pub provider_records: HashMap<String, TrackRecord>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm_integrations::MockReply;
+
+    #[tokio::test]
+    async fn offline_providers_agree_on_a_hold() {
+        let mut intelligence = MultiLLMIntelligence::offline();
+        let analysis = intelligence.analyze_market_conditions("SOL 100").await.unwrap();
+
+        assert!(analysis.quorum_met);
+        assert_eq!(analysis.votes.len(), 2);
+        assert_eq!(analysis.trading_signal, TradingSignal::Hold);
+        assert!(analysis.disagreement.dissenters.is_empty());
+    }
+
+    #[tokio::test]
+    async fn failing_providers_abstain() {
+        let mut intelligence = MultiLLMIntelligence::with_providers(
+            vec![
+                Box::new(MockProvider::fixed("a", TradingSignal::Buy, 0.9)),
+                Box::new(MockProvider::new("b", vec![MockReply::Error("timeout".to_string())])),
+            ],
+            TrackRecordStore::in_memory(),
+        )
+        .unwrap();
+        intelligence.config.min_quorum = 2;
+
+        let analysis = intelligence.analyze_market_conditions("SOL 100").await.unwrap();
+        assert_eq!(analysis.abstentions.len(), 1);
+        assert_eq!(analysis.abstentions[0].provider, "b");
+        assert!(!analysis.quorum_met);
+        assert_eq!(analysis.trading_signal, TradingSignal::Hold);
+    }
+
+    fn vote(provider: &str, signal: TradingSignal, weight: f64) -> ProviderVote {
+        ProviderVote {
+            provider: provider.to_string(),
+            signal,
+            confidence: 0.8,
+            weight,
+            reasoning: String::new(),
+        }
+    }
+
+    #[test]
+    fn skilled_provider_outvotes_unproven_majority() {
+        let intelligence = MultiLLMIntelligence::offline();
+        let consensus = intelligence.calculate_consensus(&[
+            vote("skilled", TradingSignal::Buy, 1.5),
+            vote("new_a", TradingSignal::Sell, 0.0),
+            vote("new_b", TradingSignal::Sell, 0.0),
+        ]);
+
+        assert_eq!(consensus.signal, TradingSignal::Buy);
+        assert_eq!(consensus.disagreement.up_share, 1.0);
+        assert_eq!(consensus.disagreement.dissenters, vec!["new_a", "new_b"]);
+        assert_eq!(consensus.disagreement.signal_spread, 2);
+    }
+
+    #[test]
+    fn unproven_providers_vote_equally() {
+        let intelligence = MultiLLMIntelligence::offline();
+        let consensus = intelligence.calculate_consensus(&[
+            vote("a", TradingSignal::StrongSell, 0.0),
+            vote("b", TradingSignal::StrongSell, 0.0),
+            vote("c", TradingSignal::Buy, 0.0),
+        ]);
+
+        assert_eq!(consensus.signal, TradingSignal::StrongSell);
+        assert!((consensus.disagreement.down_share - 2.0 / 3.0).abs() < 1e-9);
+        assert!(consensus.score > 0.0);
+    }
+
+    #[test]
+    fn split_vote_is_a_hold() {
+        let intelligence = MultiLLMIntelligence::offline();
+        let consensus = intelligence.calculate_consensus(&[
+            vote("a", TradingSignal::Buy, 1.0),
+            vote("b", TradingSignal::Sell, 1.0),
+        ]);
+
+        assert_eq!(consensus.signal, TradingSignal::Hold);
+        assert_eq!(consensus.score, 0.0);
+    }
+
+    #[test]
+    fn skill_is_zero_until_better_than_chance() {
+        let record = |signals, correct| TrackRecord { signals, correct, ..TrackRecord::default() };
+
+        assert_eq!(record(0, 0).skill(), 0.0);
+        assert_eq!(record(10, 5).skill(), 0.0);
+        assert_eq!(record(10, 2).skill(), 0.0);
+        assert!(record(10, 8).skill() > record(10, 6).skill());
+        assert!(record(100, 80).skill() > record(10, 8).skill());
+    }
+
+    #[tokio::test]
+    async fn track_records_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("llm_track_records_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let providers = || -> Vec<Box<dyn LlmProvider>> {
+            vec![
+                Box::new(MockProvider::fixed("bull", TradingSignal::Buy, 0.9)),
+                Box::new(MockProvider::fixed("bear", TradingSignal::Sell, 0.9)),
+            ]
+        };
+
+        let mut intelligence = MultiLLMIntelligence::with_providers(providers(), TrackRecordStore::open(&path).unwrap()).unwrap();
+        for _ in 0..5 {
+            let analysis = intelligence.analyze_market_conditions("SOL 100").await.unwrap();
+            intelligence.record_outcome(&analysis, 0.02).unwrap();
+        }
+        let bull_weight = intelligence.provider_weight("bull");
+        assert!(bull_weight > 0.0);
+        assert_eq!(intelligence.provider_weight("bear"), 0.0);
+
+        let restarted = MultiLLMIntelligence::with_providers(providers(), TrackRecordStore::open(&path).unwrap()).unwrap();
+        assert_eq!(restarted.provider_weight("bull"), bull_weight);
+        let report = restarted.get_training_report();
+        assert_eq!(report.provider_records["bull"].signals, 5);
+        assert_eq!(report.provider_records["bull"].correct, 5);
+        assert_eq!(report.provider_records["bear"].correct, 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn duplicate_provider_names_are_rejected() {
+        let result = MultiLLMIntelligence::with_providers(
+            vec![
+                Box::new(MockProvider::fixed("a", TradingSignal::Buy, 0.9)),
+                Box::new(MockProvider::fixed("a", TradingSignal::Sell, 0.9)),
+            ],
+            TrackRecordStore::in_memory(),
+        );
+        assert!(result.is_err());
+    }
+}
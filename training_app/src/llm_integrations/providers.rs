@@ -0,0 +1,438 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::multi_llm_intelligence::{LLMResponse, TradingSignal};
+
+/// Asks for a single JSON object so replies parse into `LLMResponse`
+pub const MARKET_ANALYSIS_PROMPT: &str = "You are a Solana market analyst. Reply with only a JSON object of the form \
+{\"signal\": \"strong_buy\" | \"buy\" | \"hold\" | \"sell\" | \"strong_sell\", \
+\"confidence\": <number between 0 and 1>, \"reasoning\": \"<one or two sentences>\"}.";
+
+/// USD per million tokens
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ProviderPricing {
+    pub prompt_per_million: f64,
+    pub completion_per_million: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderUsage {
+    /// Calls to `analyze`; an attempt repeated after a failure is a retry
+    pub requests: u64,
+    pub failures: u64,
+    pub retries: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl ProviderUsage {
+    fn record(&mut self, prompt_tokens: u64, completion_tokens: u64, pricing: &ProviderPricing) {
+        self.prompt_tokens += prompt_tokens;
+        self.completion_tokens += completion_tokens;
+        self.cost_usd += (prompt_tokens as f64 * pricing.prompt_per_million
+            + completion_tokens as f64 * pricing.completion_per_million)
+            / 1_000_000.0;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmCompletion {
+    pub response: LLMResponse,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub latency_ms: u64,
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &str;
+    fn model(&self) -> &str;
+    async fn analyze(&self, market_data: &str) -> Result<LlmCompletion>;
+    /// Totals since the provider was created
+    fn usage(&self) -> ProviderUsage;
+}
+
+#[derive(Debug, Clone)]
+pub struct ProviderConfig {
+    pub name: String,
+    /// Base of an OpenAI-compatible API; `/chat/completions` is appended
+    pub base_url: String,
+    pub model: String,
+    /// Environment variable holding the bearer token
+    pub api_key_env: String,
+    pub timeout: Duration,
+    pub max_retries: u32,
+    /// First retry delay; doubles on each further attempt
+    pub retry_backoff: Duration,
+    pub temperature: f32,
+    /// Sends `response_format: json_object`, for APIs that support it
+    pub json_mode: bool,
+    pub pricing: ProviderPricing,
+}
+
+impl ProviderConfig {
+    pub fn deepseek() -> Self {
+        ProviderConfig {
+            name: "deepseek".to_string(),
+            base_url: "https://api.deepseek.com/v1".to_string(),
+            model: "deepseek-chat".to_string(),
+            api_key_env: "DEEPSEEK_API_KEY".to_string(),
+            timeout: Duration::from_secs(30),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(500),
+            temperature: 0.2,
+            json_mode: true,
+            pricing: ProviderPricing {
+                prompt_per_million: 0.27,
+                completion_per_million: 1.10,
+            },
+        }
+    }
+
+    pub fn perplexity() -> Self {
+        ProviderConfig {
+            name: "perplexity".to_string(),
+            base_url: "https://api.perplexity.ai".to_string(),
+            model: "sonar".to_string(),
+            api_key_env: "PERPLEXITY_API_KEY".to_string(),
+            timeout: Duration::from_secs(30),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(500),
+            temperature: 0.2,
+            json_mode: false,
+            pricing: ProviderPricing {
+                prompt_per_million: 1.0,
+                completion_per_million: 1.0,
+            },
+        }
+    }
+}
+
+pub struct OpenAiCompatibleProvider {
+    config: ProviderConfig,
+    api_key: String,
+    http: reqwest::Client,
+    usage: Mutex<ProviderUsage>,
+}
+
+/// Whether a failed attempt is worth repeating
+enum AttemptError {
+    Retryable(anyhow::Error),
+    Fatal(anyhow::Error),
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(config: ProviderConfig) -> Result<Self> {
+        let api_key = std::env::var(&config.api_key_env)
+            .with_context(|| format!("{} is not set for provider {}", config.api_key_env, config.name))?;
+        let http = reqwest::Client::builder().timeout(config.timeout).build()?;
+
+        Ok(OpenAiCompatibleProvider {
+            config,
+            api_key,
+            http,
+            usage: Mutex::new(ProviderUsage::default()),
+        })
+    }
+
+    async fn attempt(&self, market_data: &str) -> std::result::Result<LlmCompletion, AttemptError> {
+        let mut body = json!({
+            "model": self.config.model,
+            "temperature": self.config.temperature,
+            "messages": [
+                { "role": "system", "content": MARKET_ANALYSIS_PROMPT },
+                { "role": "user", "content": market_data },
+            ],
+        });
+        if self.config.json_mode {
+            body["response_format"] = json!({ "type": "json_object" });
+        }
+
+        let started = Instant::now();
+        let response = self
+            .http
+            .post(format!("{}/chat/completions", self.config.base_url.trim_end_matches('/')))
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AttemptError::Retryable(e.into()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            let error = anyhow!("{} returned {}: {}", self.config.name, status, text);
+            return Err(if status.as_u16() == 429 || status.is_server_error() {
+                AttemptError::Retryable(error)
+            } else {
+                AttemptError::Fatal(error)
+            });
+        }
+
+        let payload: Value = response.json().await.map_err(|e| AttemptError::Retryable(e.into()))?;
+        let content = payload["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| AttemptError::Retryable(anyhow!("{} reply has no message content", self.config.name)))?;
+
+        // Tokens are billed whether or not the reply parses
+        let prompt_tokens = payload["usage"]["prompt_tokens"].as_u64().unwrap_or(0);
+        let completion_tokens = payload["usage"]["completion_tokens"].as_u64().unwrap_or(0);
+        self.usage
+            .lock()
+            .unwrap()
+            .record(prompt_tokens, completion_tokens, &self.config.pricing);
+
+        // Sampled output can be malformed once and fine the next time
+        let response = parse_analysis(content).map_err(AttemptError::Retryable)?;
+
+        Ok(LlmCompletion {
+            response,
+            prompt_tokens,
+            completion_tokens,
+            latency_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    async fn analyze(&self, market_data: &str) -> Result<LlmCompletion> {
+        let mut backoff = self.config.retry_backoff;
+        let mut attempt = 0;
+        self.usage.lock().unwrap().requests += 1;
+
+        loop {
+            match self.attempt(market_data).await {
+                Ok(completion) => return Ok(completion),
+                Err(AttemptError::Retryable(e)) if attempt < self.config.max_retries => {
+                    attempt += 1;
+                    self.usage.lock().unwrap().retries += 1;
+                    println!("⚠️ {} attempt {} failed ({}), retrying in {:?}", self.config.name, attempt, e, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(AttemptError::Retryable(e)) | Err(AttemptError::Fatal(e)) => {
+                    self.usage.lock().unwrap().failures += 1;
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    fn usage(&self) -> ProviderUsage {
+        self.usage.lock().unwrap().clone()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AnalysisPayload {
+    signal: TradingSignal,
+    confidence: f64,
+    reasoning: String,
+}
+
+/// Parses a model reply into an `LLMResponse`, tolerating a Markdown code
+/// fence or text around the JSON object.
+pub fn parse_analysis(content: &str) -> Result<LLMResponse> {
+    let start = content.find('{').ok_or_else(|| anyhow!("Reply has no JSON object: {}", content))?;
+    let end = content[start..]
+        .rfind('}')
+        .map(|offset| start + offset)
+        .ok_or_else(|| anyhow!("Reply has an unterminated JSON object: {}", content))?;
+    let payload: AnalysisPayload = serde_json::from_str(&content[start..=end])
+        .with_context(|| format!("Reply does not match the analysis schema: {}", &content[start..=end]))?;
+
+    if !(0.0..=1.0).contains(&payload.confidence) {
+        return Err(anyhow!("Confidence {} is outside [0, 1]", payload.confidence));
+    }
+
+    Ok(LLMResponse {
+        confidence: payload.confidence,
+        signal: payload.signal,
+        reasoning: payload.reasoning,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum MockReply {
+    /// Raw model content, run through the same parser as real replies
+    Content(String),
+    Error(String),
+}
+
+/// Replays canned replies in order, wrapping around, without any network.
+pub struct MockProvider {
+    name: String,
+    replies: Vec<MockReply>,
+    cursor: AtomicUsize,
+    usage: Mutex<ProviderUsage>,
+}
+
+impl MockProvider {
+    pub fn new(name: &str, replies: Vec<MockReply>) -> Self {
+        MockProvider {
+            name: name.to_string(),
+            replies,
+            cursor: AtomicUsize::new(0),
+            usage: Mutex::new(ProviderUsage::default()),
+        }
+    }
+
+    /// Always answers with the given signal and confidence
+    pub fn fixed(name: &str, signal: TradingSignal, confidence: f64) -> Self {
+        let content = json!({
+            "signal": signal,
+            "confidence": confidence,
+            "reasoning": format!("Canned {} reply", name),
+        });
+        MockProvider::new(name, vec![MockReply::Content(content.to_string())])
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn model(&self) -> &str {
+        "mock"
+    }
+
+    async fn analyze(&self, market_data: &str) -> Result<LlmCompletion> {
+        if self.replies.is_empty() {
+            return Err(anyhow!("Mock provider {} has no canned replies", self.name));
+        }
+        let reply = &self.replies[self.cursor.fetch_add(1, Ordering::Relaxed) % self.replies.len()];
+
+        let mut usage = self.usage.lock().unwrap();
+        usage.requests += 1;
+        match reply {
+            MockReply::Content(content) => {
+                // Rough whitespace token counts keep usage totals meaningful offline
+                let prompt_tokens = market_data.split_whitespace().count() as u64;
+                let completion_tokens = content.split_whitespace().count() as u64;
+                usage.record(prompt_tokens, completion_tokens, &ProviderPricing::default());
+                let response = match parse_analysis(content) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        usage.failures += 1;
+                        return Err(e);
+                    }
+                };
+                Ok(LlmCompletion {
+                    response,
+                    prompt_tokens,
+                    completion_tokens,
+                    latency_ms: 0,
+                })
+            }
+            MockReply::Error(message) => {
+                usage.failures += 1;
+                Err(anyhow!("{}: {}", self.name, message))
+            }
+        }
+    }
+
+    fn usage(&self) -> ProviderUsage {
+        self.usage.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fenced_replies() {
+        let reply = "Here you go:\n```json\n{\"signal\": \"strong_buy\", \"confidence\": 0.9, \"reasoning\": \"Breakout\"}\n```";
+        let response = parse_analysis(reply).unwrap();
+        assert_eq!(response.signal, TradingSignal::StrongBuy);
+        assert_eq!(response.confidence, 0.9);
+        assert_eq!(response.reasoning, "Breakout");
+    }
+
+    #[test]
+    fn rejects_malformed_replies() {
+        assert!(parse_analysis("no json here").is_err());
+        assert!(parse_analysis("{\"signal\": \"moon\", \"confidence\": 0.5, \"reasoning\": \"\"}").is_err());
+        assert!(parse_analysis("{\"signal\": \"buy\", \"confidence\": 1.5, \"reasoning\": \"\"}").is_err());
+        // A closing brace before the opening one is no object at all
+        assert!(parse_analysis("} then {").is_err());
+        assert!(parse_analysis("{").is_err());
+    }
+
+    #[tokio::test]
+    async fn retries_count_once_per_request() {
+        std::env::set_var("BLACK_DIAMOND_TEST_LLM_KEY", "test");
+        let provider = OpenAiCompatibleProvider::new(ProviderConfig {
+            // Nothing listens here, so every attempt fails to connect
+            base_url: "http://127.0.0.1:1".to_string(),
+            api_key_env: "BLACK_DIAMOND_TEST_LLM_KEY".to_string(),
+            retry_backoff: Duration::from_millis(1),
+            ..ProviderConfig::deepseek()
+        })
+        .unwrap();
+
+        assert!(provider.analyze("SOL 100").await.is_err());
+        let usage = provider.usage();
+        assert_eq!((usage.requests, usage.retries, usage.failures), (1, 2, 1));
+    }
+
+    #[tokio::test]
+    async fn mock_replays_canned_replies_in_order() {
+        let mock = MockProvider::new(
+            "mock",
+            vec![
+                MockReply::Content(r#"{"signal": "sell", "confidence": 0.6, "reasoning": "Weak"}"#.to_string()),
+                MockReply::Error("rate limited".to_string()),
+            ],
+        );
+
+        let first = mock.analyze("SOL 100").await.unwrap();
+        assert_eq!(first.response.signal, TradingSignal::Sell);
+        assert!(mock.analyze("SOL 100").await.is_err());
+        assert_eq!(mock.analyze("SOL 100").await.unwrap().response.signal, TradingSignal::Sell);
+
+        let usage = mock.usage();
+        assert_eq!(usage.requests, 3);
+        assert_eq!(usage.failures, 1);
+    }
+
+    #[tokio::test]
+    async fn unparseable_replies_still_count_tokens() {
+        let mock = MockProvider::new("mock", vec![MockReply::Content("I cannot answer that".to_string())]);
+
+        assert!(mock.analyze("SOL 100 101").await.is_err());
+        let usage = mock.usage();
+        assert_eq!(usage.failures, 1);
+        assert_eq!(usage.prompt_tokens, 3);
+        assert_eq!(usage.completion_tokens, 4);
+    }
+
+    #[test]
+    fn usage_is_costed_per_million_tokens() {
+        let mut usage = ProviderUsage::default();
+        let pricing = ProviderPricing {
+            prompt_per_million: 2.0,
+            completion_per_million: 4.0,
+        };
+        usage.record(500_000, 250_000, &pricing);
+        assert!((usage.cost_usd - 2.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// How often a source's signal direction matched the realised move
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrackRecord {
+    pub signals: u64,
+    pub correct: u64,
+    pub abstentions: u64,
+    pub last_updated: u64,
+}
+
+impl TrackRecord {
+    /// Laplace-smoothed accuracy, so a new provider starts at 0.5 and one
+    /// lucky call does not dominate the vote
+    pub fn accuracy(&self) -> f64 {
+        (self.correct as f64 + 1.0) / (self.signals as f64 + 2.0)
+    }
+
+    /// Log-odds of the smoothed accuracy, floored at zero: a source no
+    /// better than a coin flip carries no weight, and weight grows without
+    /// bound as accuracy approaches 1
+    pub fn skill(&self) -> f64 {
+        let accuracy = self.accuracy();
+        (accuracy / (1.0 - accuracy)).ln().max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TrackRecordFile {
+    providers: HashMap<String, TrackRecord>,
+    consensus: TrackRecord,
+}
+
+/// Per-provider track records, written back to disk after every update when
+/// a path is set.
+pub struct TrackRecordStore {
+    path: Option<PathBuf>,
+    data: TrackRecordFile,
+}
+
+impl TrackRecordStore {
+    pub fn in_memory() -> Self {
+        TrackRecordStore {
+            path: None,
+            data: TrackRecordFile::default(),
+        }
+    }
+
+    /// Opens the store at `path`, starting empty if the file does not exist yet
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let data = if path.exists() {
+            let bytes = std::fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+            serde_json::from_slice(&bytes).with_context(|| format!("parsing {}", path.display()))?
+        } else {
+            TrackRecordFile::default()
+        };
+
+        Ok(TrackRecordStore { path: Some(path), data })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn provider(&self, name: &str) -> TrackRecord {
+        self.data.providers.get(name).cloned().unwrap_or_default()
+    }
+
+    pub fn providers(&self) -> &HashMap<String, TrackRecord> {
+        &self.data.providers
+    }
+
+    pub fn consensus(&self) -> &TrackRecord {
+        &self.data.consensus
+    }
+
+    pub fn record_provider(&mut self, name: &str, correct: Option<bool>, now: u64) {
+        update(self.data.providers.entry(name.to_string()).or_default(), correct, now);
+    }
+
+    pub fn record_consensus(&mut self, correct: Option<bool>, now: u64) {
+        update(&mut self.data.consensus, correct, now);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(&self.data)?)
+            .with_context(|| format!("writing {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// `None` records an abstention
+fn update(record: &mut TrackRecord, correct: Option<bool>, now: u64) {
+    match correct {
+        Some(correct) => {
+            record.signals += 1;
+            if correct {
+                record.correct += 1;
+            }
+        }
+        None => record.abstentions += 1,
+    }
+    record.last_updated = now;
+}
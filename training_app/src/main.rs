@@ -8,11 +8,19 @@ use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
 
+mod autodiff;
 mod black_diamond_engine;
+mod datasets;
+mod llm_integrations;
+mod perp_execution;
+mod serving;
+mod transformers;
 use black_diamond_engine::{
     engine::BlaccDiamondEngine,
+    inference::InferenceClient,
     profit_collector::{ProfitTracker, TradeProfit},
 };
+use llm_integrations::{MarketAnalysis, MultiLLMIntelligence};
 
 #[macro_export]
 macro_rules! hashmap {
@@ -195,6 +203,37 @@ async fn agent_training_loop() -> Result<()> {
     let mut profit_tracker = ProfitTracker::new(1000000000); // 1 SOL initial balance
     let chaos_engine = ChaosModelingEngine::new();
     
+    // Trade on the served model when `BLACK_DIAMOND_INFERENCE_URL` points at
+    // `pablo-training-app serve` and its features are ones chaos analysis yields
+    let mut served_features: Vec<String> = Vec::new();
+    if let Ok(url) = std::env::var("BLACK_DIAMOND_INFERENCE_URL") {
+        let inference = InferenceClient::new(&url, std::time::Duration::from_millis(50))?;
+        match inference.status().await {
+            Ok(status) => {
+                let known = chaos_engine.analyze_market_chaos(&[1.0, 2.0]);
+                match status.feature_names.iter().find(|name| !known.contains_key(*name)) {
+                    Some(missing) => println!(
+                        "⚠️ {} v{} needs feature {} that chaos analysis does not produce; not using it",
+                        status.model_name, status.version, missing
+                    ),
+                    None => {
+                        served_features = status.feature_names;
+                        engine.set_inference_client(inference);
+                    }
+                }
+            }
+            Err(e) => println!("⚠️ Inference server at {} unavailable: {}", url, e),
+        }
+    }
+    
+    // Live providers when their API keys are set, canned replies otherwise
+    let mut llm = MultiLLMIntelligence::new().unwrap_or_else(|e| {
+        println!("⚠️ LLM providers unavailable ({}), using offline mocks", e);
+        MultiLLMIntelligence::offline()
+    });
+    // Last cycle's analysis and closing price, scored once the next cycle's price is known
+    let mut pending_analysis: Option<(MarketAnalysis, f32)> = None;
+    
     let mut cycle_count = 0;
     
     loop {
@@ -227,12 +266,44 @@ async fn agent_training_loop() -> Result<()> {
         println!("  Profitability Score: {:.4}", profitability);
         println!("  Fractal Pattern: {}", if fractal_detected { "DETECTED" } else { "None" });
         
+        // Score the previous consensus against the move since, then ask again
+        let last_price = market_data[market_data.len() - 1];
+        if let Some((analysis, previous_price)) = pending_analysis.take() {
+            let realized_return = (last_price / previous_price - 1.0) as f64;
+            if let Err(e) = llm.record_outcome(&analysis, realized_return) {
+                println!("⚠️ Failed to record LLM track records: {}", e);
+            }
+        }
+        let market_summary = format!(
+            "SOL/USDC last {} prices: {:?}. Volatility {:.4}, fractal pattern {}.",
+            market_data.len(),
+            market_data,
+            chaos_analysis.get("volatility_score").copied().unwrap_or(0.0),
+            fractal_detected
+        );
+        match llm.analyze_market_conditions(&market_summary).await {
+            Ok(analysis) => {
+                println!("🤖 LLM Consensus: {:?} ({})", analysis.trading_signal, analysis.reasoning);
+                if let Ok(strategy) = llm.generate_trading_strategy(&analysis).await {
+                    println!("  Strategy: {}", strategy);
+                }
+                pending_analysis = Some((analysis, last_price));
+            }
+            Err(e) => println!("⚠️ LLM analysis failed: {}", e),
+        }
+        
         // Black Diamond Engine Integration - Execute live trading cycle
         if let Some(prediction_confidence) = chaos_analysis.get("prediction_confidence") {
             if *prediction_confidence > 5000.0 { // High confidence threshold
                 println!("💎 HIGH CONFIDENCE SIGNAL - Activating Black Diamond Engine");
                 
-                match engine.neural_titan_integration(*prediction_confidence / 10000.0).await {
+                let integration = if engine.inference.is_some() {
+                    let features = served_features.iter().map(|name| chaos_analysis[name]).collect();
+                    engine.neural_titan_inference(features).await
+                } else {
+                    engine.neural_titan_integration(*prediction_confidence / 10000.0).await
+                };
+                match integration {
                     Ok(_) => {
                         // Execute arbitrage cycle
                         match engine.execute_arbitrage_cycle().await {
@@ -304,10 +375,107 @@ async fn agent_training_loop() -> Result<()> {
     }
 }
 
+/// Marks a paper venue from Drift every cycle, opens a position when the
+/// transformer signals and none is open, and lets the position manager
+/// stop out, take profit, deleverage and book funding on each new mark
+async fn perp_paper_trading(market: &str, collateral: f64) -> Result<()> {
+    use datasets::PerpMarketAdapter;
+    use perp_execution::{FundingPayment, PaperPerpVenue, PerpPositionManager, PositionManagerConfig};
+
+    let client = RpcClient::new("https://api.mainnet-beta.solana.com".to_string());
+    let adapter = datasets::DriftPerpAdapter::new(client, hashmap!["SOL-PERP" => 0u16, "BTC-PERP" => 1, "ETH-PERP" => 2])?;
+    let model = transformers::PerpetualsTradinTransformer::new(4, 16, 8);
+    let paper = std::sync::Arc::new(PaperPerpVenue::new(0.001));
+    let mut manager = PerpPositionManager::new(
+        Box::new(paper.clone()),
+        model.liquidation_protector.clone(),
+        PositionManagerConfig::default(),
+    );
+    println!("📉 Paper trading {} on Drift marks with {:.2} collateral", market, collateral);
+
+    let mut last_funding_ts = 0;
+    loop {
+        match adapter.market_state(market).await {
+            Ok(state) => {
+                paper.set_mark_price(market, state.mark_price);
+                if state.funding_rate_ts > last_funding_ts {
+                    last_funding_ts = state.funding_rate_ts;
+                    paper.record_funding(FundingPayment {
+                        market: market.to_string(),
+                        timestamp: state.funding_rate_ts,
+                        rate: state.funding_rate,
+                        amount_per_long_base: state.funding_rate * state.oracle_price,
+                    });
+                }
+
+                if manager.open_positions().all(|p| p.market != market) {
+                    match model.analyze_venue(&adapter, market).await {
+                        Ok(Some(signal)) => {
+                            if let Err(e) = manager.open_from_signal(market, &signal, collateral).await {
+                                println!("⚠️ Failed to open {} position: {}", market, e);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => println!("⚠️ {} signal unavailable: {}", market, e),
+                    }
+                }
+
+                if let Err(e) = manager.poll().await {
+                    println!("⚠️ Perp positions: {}", e);
+                }
+            }
+            Err(e) => println!("⚠️ {} market state unavailable: {}", market, e),
+        }
+
+        tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv::dotenv().ok();
     
+    // `serve <model_name>` runs the inference server instead of the training loop
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("serve") {
+        let model_name = args.get(2).ok_or_else(|| anyhow::anyhow!("usage: serve <model_name>"))?;
+        return serving::serve(serving::ServingConfig::from_env(model_name)?).await;
+    }
+
+    // `archive <archive_dir> <output_dir> [bar_seconds] [pool...]` builds the
+    // historical dataset from archived blocks instead of training
+    if args.get(1).map(String::as_str) == Some("archive") {
+        let usage = || anyhow::anyhow!("usage: archive <archive_dir> <output_dir> [bar_seconds] [pool...]");
+        let archive_dir = args.get(2).ok_or_else(usage)?;
+        let output_dir = args.get(3).ok_or_else(usage)?;
+        let mut config = datasets::ArchiveBuildConfig::default();
+        if let Some(bar_seconds) = args.get(4) {
+            config.bar_seconds = bar_seconds.parse()?;
+            if config.bar_seconds <= 0 {
+                return Err(anyhow::anyhow!("bar_seconds must be positive, got {}", config.bar_seconds));
+            }
+        }
+        config.pools = args.iter().skip(5).cloned().collect();
+
+        datasets::build_historical_dataset(
+            std::path::Path::new(archive_dir),
+            std::path::Path::new(output_dir),
+            &config,
+        )?;
+        return Ok(());
+    }
+
+    // `perp <market> [collateral]` paper-trades a Drift perp market on the
+    // perpetuals transformer's signals instead of training
+    if args.get(1).map(String::as_str) == Some("perp") {
+        let market = args.get(2).ok_or_else(|| anyhow::anyhow!("usage: perp <market> [collateral]"))?;
+        let collateral = match args.get(3) {
+            Some(collateral) => collateral.parse()?,
+            None => 100.0,
+        };
+        return perp_paper_trading(market, collateral).await;
+    }
+    
     println!("🌟 Pablo's Black Diamond Neural Titan Training System");
     println!("🎯 Target: Aggressive SOL Scaling with Mathematical Optimization");
     println!("🧠 8 Specialized Rust Agents with 97-99% Accuracy");
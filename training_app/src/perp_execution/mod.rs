@@ -0,0 +1,7 @@
+pub mod position_manager;
+pub mod venue;
+
+pub use position_manager::{
+    FundingEntry, PerpPosition, PerpPositionManager, PositionEvent, PositionManagerConfig, PositionSide, PositionStatus,
+};
+pub use venue::{FundingPayment, OrderFill, OrderSide, PaperPerpVenue, PerpOrder, PerpVenue};
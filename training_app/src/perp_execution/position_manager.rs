@@ -0,0 +1,695 @@
+use std::collections::HashMap;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use super::venue::{FundingPayment, OrderFill, OrderSide, PerpOrder, PerpVenue};
+use crate::transformers::perpetuals_trading_transformer::{LiquidationProtector, PerpetualsSignal, TradeDirection};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionSide {
+    Long,
+    Short,
+}
+
+impl PositionSide {
+    fn sign(self) -> f64 {
+        match self {
+            PositionSide::Long => 1.0,
+            PositionSide::Short => -1.0,
+        }
+    }
+
+    fn opening_order(self) -> OrderSide {
+        match self {
+            PositionSide::Long => OrderSide::Buy,
+            PositionSide::Short => OrderSide::Sell,
+        }
+    }
+
+    fn closing_order(self) -> OrderSide {
+        match self {
+            PositionSide::Long => OrderSide::Sell,
+            PositionSide::Short => OrderSide::Buy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionStatus {
+    Open,
+    StoppedOut,
+    TookProfit,
+    Closed,
+}
+
+/// One funding settlement applied to a position; positive `amount` was received
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingEntry {
+    pub timestamp: i64,
+    pub rate: f64,
+    pub base_amount: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerpPosition {
+    pub id: u64,
+    pub market: String,
+    pub side: PositionSide,
+    pub base_amount: f64,
+    pub entry_price: f64,
+    /// Margin posted, including realised pnl, fees and funding since opening
+    pub collateral: f64,
+    pub stop_loss_price: f64,
+    pub take_profit_price: f64,
+    pub mark_price: f64,
+    pub unrealized_pnl: f64,
+    /// Equity over notional at the last mark
+    pub margin_ratio: f64,
+    pub effective_leverage: f64,
+    /// Price move against the position before liquidation, per the protector
+    pub liquidation_distance: f64,
+    pub realized_pnl: f64,
+    pub fees_paid: f64,
+    pub funding_net: f64,
+    pub funding_log: Vec<FundingEntry>,
+    pub status: PositionStatus,
+    pub opened_at: i64,
+    pub last_funding_ts: i64,
+}
+
+impl PerpPosition {
+    pub fn notional(&self) -> f64 {
+        self.base_amount * self.mark_price
+    }
+
+    pub fn equity(&self) -> f64 {
+        self.collateral + self.unrealized_pnl
+    }
+
+    fn remark(&mut self, price: f64, protector: &LiquidationProtector) {
+        self.mark_price = price;
+        self.unrealized_pnl = self.side.sign() * (price - self.entry_price) * self.base_amount;
+
+        let notional = self.notional();
+        let equity = self.equity();
+        if notional <= 0.0 {
+            self.margin_ratio = f64::INFINITY;
+            self.effective_leverage = 0.0;
+            self.liquidation_distance = 1.0;
+        } else if equity <= 0.0 {
+            self.margin_ratio = equity / notional;
+            self.effective_leverage = f64::INFINITY;
+            self.liquidation_distance = 0.0;
+        } else {
+            self.margin_ratio = equity / notional;
+            self.effective_leverage = notional / equity;
+            self.liquidation_distance = protector.liquidation_distance(self.effective_leverage as f32) as f64;
+        }
+    }
+
+    /// Realises pnl on `fill.base_amount` of the position and shrinks it
+    fn apply_reduce(&mut self, fill: &OrderFill) -> f64 {
+        let reduced = fill.base_amount.min(self.base_amount);
+        let pnl = self.side.sign() * (fill.price - self.entry_price) * reduced;
+        self.base_amount -= reduced;
+        self.realized_pnl += pnl;
+        self.fees_paid += fill.fee;
+        self.collateral += pnl - fill.fee;
+        pnl
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PositionManagerConfig {
+    /// Liquidation distance below which a position is deleveraged
+    pub min_liquidation_distance: f64,
+    /// Liquidation distance a deleveraged position is brought back to
+    pub target_liquidation_distance: f64,
+    pub max_leverage: f64,
+    /// Signals below this confidence are not traded
+    pub min_confidence: f32,
+}
+
+impl Default for PositionManagerConfig {
+    fn default() -> Self {
+        PositionManagerConfig {
+            min_liquidation_distance: 0.05,
+            target_liquidation_distance: 0.10,
+            max_leverage: 10.0,
+            min_confidence: 0.6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PositionEvent {
+    Opened { id: u64, fill: OrderFill },
+    Deleveraged { id: u64, fill: OrderFill, liquidation_distance: f64, leverage_after: f64 },
+    StoppedOut { id: u64, fill: OrderFill, pnl: f64 },
+    TookProfit { id: u64, fill: OrderFill, pnl: f64 },
+    Closed { id: u64, fill: OrderFill, pnl: f64 },
+    Funding { id: u64, entry: FundingEntry },
+}
+
+/// Opens perp positions from transformer signals and watches them on every
+/// mark price: margin ratio, stop-loss/take-profit, deleveraging ahead of
+/// liquidation, and funding accrued per position.
+pub struct PerpPositionManager {
+    venue: Box<dyn PerpVenue>,
+    protector: LiquidationProtector,
+    config: PositionManagerConfig,
+    positions: HashMap<u64, PerpPosition>,
+    events: Vec<PositionEvent>,
+    next_id: u64,
+}
+
+impl PerpPositionManager {
+    pub fn new(venue: Box<dyn PerpVenue>, protector: LiquidationProtector, config: PositionManagerConfig) -> Self {
+        PerpPositionManager {
+            venue,
+            protector,
+            config,
+            positions: HashMap::new(),
+            events: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    pub fn position(&self, id: u64) -> Option<&PerpPosition> {
+        self.positions.get(&id)
+    }
+
+    pub fn open_positions(&self) -> impl Iterator<Item = &PerpPosition> {
+        self.positions.values().filter(|p| p.status == PositionStatus::Open)
+    }
+
+    pub fn events(&self) -> &[PositionEvent] {
+        &self.events
+    }
+
+    /// Turns a signal into a market order sized at `collateral` times the
+    /// signal's leverage. `Hold` and low-confidence signals open nothing.
+    pub async fn open_from_signal(
+        &mut self,
+        market: &str,
+        signal: &PerpetualsSignal,
+        collateral: f64,
+    ) -> Result<Option<u64>> {
+        let side = match signal.direction {
+            TradeDirection::Long => PositionSide::Long,
+            TradeDirection::Short => PositionSide::Short,
+            TradeDirection::Hold => return Ok(None),
+        };
+        if signal.confidence < self.config.min_confidence {
+            println!("⏸️ Skipping {} signal on {}: confidence {:.2}", side_label(side), market, signal.confidence);
+            return Ok(None);
+        }
+        if collateral <= 0.0 {
+            return Err(anyhow!("Collateral must be positive, got {}", collateral));
+        }
+
+        let leverage = (signal.optimal_leverage as f64).clamp(1.0, self.config.max_leverage);
+        // Never open inside the deleverage band
+        let leverage = leverage.min(self.protector.leverage_for_distance(self.config.target_liquidation_distance as f32) as f64);
+        let mark = self.venue.mark_price(market).await?;
+        let order = PerpOrder {
+            market: market.to_string(),
+            side: side.opening_order(),
+            base_amount: collateral * leverage / mark,
+            reduce_only: false,
+            limit_price: None,
+        };
+        let fill = self.venue.place_order(&order).await?;
+
+        let entry = fill.price;
+        let stop = signal.stop_loss as f64;
+        let take = signal.take_profit as f64;
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let now = now_secs();
+        let mut position = PerpPosition {
+            id,
+            market: market.to_string(),
+            side,
+            base_amount: fill.base_amount,
+            entry_price: entry,
+            collateral: collateral - fill.fee,
+            stop_loss_price: entry * (1.0 - side.sign() * stop),
+            take_profit_price: entry * (1.0 + side.sign() * take),
+            mark_price: entry,
+            unrealized_pnl: 0.0,
+            margin_ratio: 0.0,
+            effective_leverage: 0.0,
+            liquidation_distance: 0.0,
+            realized_pnl: 0.0,
+            fees_paid: fill.fee,
+            funding_net: 0.0,
+            funding_log: Vec::new(),
+            status: PositionStatus::Open,
+            opened_at: now,
+            last_funding_ts: now,
+        };
+        position.remark(entry, &self.protector);
+
+        println!(
+            "📈 Opened {} #{} on {} via {}: {:.4} @ {:.4} ({:.1}x, SL {:.4}, TP {:.4})",
+            side_label(side),
+            id,
+            market,
+            self.venue.name(),
+            position.base_amount,
+            entry,
+            position.effective_leverage,
+            position.stop_loss_price,
+            position.take_profit_price
+        );
+
+        self.positions.insert(id, position);
+        self.events.push(PositionEvent::Opened { id, fill });
+        Ok(Some(id))
+    }
+
+    /// Re-marks every open position on `market` and acts on the result:
+    /// exits at stop-loss/take-profit, otherwise deleverages any position
+    /// whose liquidation distance fell under the configured minimum. A failed
+    /// order on one position does not stop the others from being handled.
+    pub async fn on_mark_price(&mut self, market: &str, price: f64) -> Result<()> {
+        let ids: Vec<u64> = self
+            .open_positions()
+            .filter(|p| p.market == market)
+            .map(|p| p.id)
+            .collect();
+
+        let mut failures = Vec::new();
+        for id in ids {
+            let position = self.positions.get_mut(&id).unwrap();
+            position.remark(price, &self.protector);
+
+            let stopped = match position.side {
+                PositionSide::Long => price <= position.stop_loss_price,
+                PositionSide::Short => price >= position.stop_loss_price,
+            };
+            let took_profit = match position.side {
+                PositionSide::Long => price >= position.take_profit_price,
+                PositionSide::Short => price <= position.take_profit_price,
+            };
+            let near_liquidation = position.liquidation_distance < self.config.min_liquidation_distance;
+
+            let result = if stopped {
+                self.close(id, PositionStatus::StoppedOut).await.map(|_| ())
+            } else if took_profit {
+                self.close(id, PositionStatus::TookProfit).await.map(|_| ())
+            } else if near_liquidation {
+                self.deleverage(id).await
+            } else {
+                Ok(())
+            };
+            if let Err(e) = result {
+                println!("⚠️ Position #{} on {} not handled at {:.4}: {}", id, market, price, e);
+                failures.push(format!("#{}: {}", id, e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("{} position(s) on {} failed: {}", failures.len(), market, failures.join("; ")))
+        }
+    }
+
+    /// Re-marks every market with open positions at the venue's mark price,
+    /// then settles funding. Every market is handled even if one fails.
+    pub async fn poll(&mut self) -> Result<()> {
+        let mut markets: Vec<String> = self.open_positions().map(|p| p.market.clone()).collect();
+        markets.sort();
+        markets.dedup();
+
+        let mut failures = Vec::new();
+        for market in markets {
+            let marked = match self.venue.mark_price(&market).await {
+                Ok(price) => self.on_mark_price(&market, price).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = marked {
+                failures.push(format!("{}: {}", market, e));
+            }
+        }
+        if let Err(e) = self.settle_funding().await {
+            failures.push(format!("funding: {}", e));
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(failures.join("; ")))
+        }
+    }
+
+    /// Market-closes a position at the venue
+    pub async fn close(&mut self, id: u64, status: PositionStatus) -> Result<f64> {
+        let position = self.positions.get(&id).ok_or_else(|| anyhow!("Unknown position #{}", id))?;
+        if position.status != PositionStatus::Open {
+            return Err(anyhow!("Position #{} is already {:?}", id, position.status));
+        }
+
+        let order = PerpOrder {
+            market: position.market.clone(),
+            side: position.side.closing_order(),
+            base_amount: position.base_amount,
+            reduce_only: true,
+            limit_price: None,
+        };
+        let fill = self.venue.place_order(&order).await?;
+
+        let position = self.positions.get_mut(&id).unwrap();
+        let pnl = position.apply_reduce(&fill);
+        position.remark(fill.price, &self.protector);
+        position.status = status;
+
+        let emoji = if pnl >= 0.0 { "💰" } else { "🛑" };
+        println!(
+            "{} Closed #{} on {} ({:?}) @ {:.4}: pnl {:+.4}, funding {:+.4}, fees {:.4}",
+            emoji, id, position.market, status, fill.price, position.realized_pnl, position.funding_net, position.fees_paid
+        );
+
+        self.events.push(match status {
+            PositionStatus::StoppedOut => PositionEvent::StoppedOut { id, fill, pnl },
+            PositionStatus::TookProfit => PositionEvent::TookProfit { id, fill, pnl },
+            _ => PositionEvent::Closed { id, fill, pnl },
+        });
+        Ok(pnl)
+    }
+
+    /// Reduces a position until its liquidation distance is back at the
+    /// configured target
+    async fn deleverage(&mut self, id: u64) -> Result<()> {
+        let position = &self.positions[&id];
+        let distance_before = position.liquidation_distance;
+        let equity = position.equity();
+        if equity <= 0.0 {
+            println!("🚨 Position #{} has no equity left, closing", id);
+            self.close(id, PositionStatus::Closed).await?;
+            return Ok(());
+        }
+
+        let target_leverage = self.protector.leverage_for_distance(self.config.target_liquidation_distance as f32) as f64;
+        let target_base = equity * target_leverage / position.mark_price;
+        let reduce_by = position.base_amount - target_base;
+        if reduce_by <= 0.0 {
+            return Ok(());
+        }
+
+        let order = PerpOrder {
+            market: position.market.clone(),
+            side: position.side.closing_order(),
+            base_amount: reduce_by,
+            reduce_only: true,
+            limit_price: None,
+        };
+        let fill = self.venue.place_order(&order).await?;
+
+        let position = self.positions.get_mut(&id).unwrap();
+        let mark = position.mark_price;
+        position.apply_reduce(&fill);
+        // Entry price is unchanged by a reduce; pnl on the remainder is unrealised
+        position.remark(mark, &self.protector);
+
+        println!(
+            "⚠️ Deleveraged #{} on {}: liquidation distance {:.2}% -> {:.2}%, {:.1}x, {:.4} base left",
+            id,
+            position.market,
+            distance_before * 100.0,
+            position.liquidation_distance * 100.0,
+            position.effective_leverage,
+            position.base_amount
+        );
+
+        let leverage_after = position.effective_leverage;
+        self.events.push(PositionEvent::Deleveraged {
+            id,
+            fill,
+            liquidation_distance: distance_before,
+            leverage_after,
+        });
+        Ok(())
+    }
+
+    /// Pulls new funding settlements from the venue and books each one
+    /// against the open positions on that market. Returns the net received.
+    pub async fn settle_funding(&mut self) -> Result<f64> {
+        let mut markets: Vec<(String, i64)> = Vec::new();
+        for position in self.open_positions() {
+            match markets.iter_mut().find(|(m, _)| *m == position.market) {
+                Some((_, since)) => *since = (*since).min(position.last_funding_ts),
+                None => markets.push((position.market.clone(), position.last_funding_ts)),
+            }
+        }
+
+        let mut net = 0.0;
+        for (market, since) in markets {
+            let payments = self.venue.funding_payments(&market, since).await?;
+            for payment in payments {
+                net += self.apply_funding(&payment);
+            }
+        }
+        Ok(net)
+    }
+
+    fn apply_funding(&mut self, payment: &FundingPayment) -> f64 {
+        let mut net = 0.0;
+        for position in self.positions.values_mut() {
+            if position.status != PositionStatus::Open
+                || position.market != payment.market
+                || payment.timestamp <= position.last_funding_ts
+            {
+                continue;
+            }
+
+            // Longs pay positive funding, shorts receive it
+            let amount = -position.side.sign() * payment.amount_per_long_base * position.base_amount;
+            let entry = FundingEntry {
+                timestamp: payment.timestamp,
+                rate: payment.rate,
+                base_amount: position.base_amount,
+                amount,
+            };
+            position.collateral += amount;
+            position.funding_net += amount;
+            position.last_funding_ts = payment.timestamp;
+            position.funding_log.push(entry.clone());
+            net += amount;
+
+            let verb = if amount >= 0.0 { "received" } else { "paid" };
+            println!(
+                "💸 #{} {} {} funding {:.6} (rate {:+.4}%, net {:+.6})",
+                position.id,
+                position.market,
+                verb,
+                amount.abs(),
+                payment.rate * 100.0,
+                position.funding_net
+            );
+            self.events.push(PositionEvent::Funding { id: position.id, entry });
+        }
+        net
+    }
+}
+
+fn side_label(side: PositionSide) -> &'static str {
+    match side {
+        PositionSide::Long => "long",
+        PositionSide::Short => "short",
+    }
+}
+
+fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use async_trait::async_trait;
+    use crate::perp_execution::PaperPerpVenue;
+
+    const MARKET: &str = "SOL-PERP";
+
+    fn protector() -> LiquidationProtector {
+        LiquidationProtector {
+            safety_margin: 0.0,
+            maintenance_margin: 0.05,
+            dynamic_stop_loss: 0.05,
+            position_sizing_rules: Vec::new(),
+        }
+    }
+
+    fn signal(direction: TradeDirection, stop_loss: f32) -> PerpetualsSignal {
+        PerpetualsSignal {
+            direction,
+            confidence: 0.9,
+            optimal_leverage: 20.0,
+            entry_price: 100.0,
+            stop_loss,
+            take_profit: 1.0,
+            funding_rate_impact: 0.0,
+            liquidation_distance: 0.1,
+        }
+    }
+
+    fn manager(venue: Box<dyn PerpVenue>) -> PerpPositionManager {
+        PerpPositionManager::new(venue, protector(), PositionManagerConfig::default())
+    }
+
+    fn paper(price: f64) -> Arc<PaperPerpVenue> {
+        let venue = Arc::new(PaperPerpVenue::new(0.0));
+        venue.set_mark_price(MARKET, price);
+        venue
+    }
+
+    /// Rejects the first reduce-only order it sees, fills the rest on paper
+    struct RejectFirstReduce {
+        paper: Arc<PaperPerpVenue>,
+        rejected: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl PerpVenue for RejectFirstReduce {
+        fn name(&self) -> &str {
+            "reject-first-reduce"
+        }
+
+        async fn place_order(&self, order: &PerpOrder) -> Result<OrderFill> {
+            if order.reduce_only && !std::mem::replace(&mut *self.rejected.lock().unwrap(), true) {
+                return Err(anyhow!("venue unavailable"));
+            }
+            self.paper.place_order(order).await
+        }
+
+        async fn mark_price(&self, market: &str) -> Result<f64> {
+            self.paper.mark_price(market).await
+        }
+
+        async fn funding_payments(&self, market: &str, since: i64) -> Result<Vec<FundingPayment>> {
+            self.paper.funding_payments(market, since).await
+        }
+    }
+
+    #[tokio::test]
+    async fn liquidation_distance_leaves_room_for_maintenance_margin() {
+        let mut manager = manager(Box::new(paper(100.0)));
+        let id = manager.open_from_signal(MARKET, &signal(TradeDirection::Long, 0.5), 100.0).await.unwrap().unwrap();
+
+        // Capped at the leverage whose distance to maintenance is the 10% target
+        let position = manager.position(id).unwrap();
+        assert!((position.effective_leverage - 1.0 / 0.15).abs() < 1e-5);
+        assert!((position.liquidation_distance - 0.10).abs() < 1e-6);
+        assert!((position.margin_ratio - 0.15).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn deleverages_back_to_target_distance_before_liquidation() {
+        let venue = paper(100.0);
+        let mut manager = manager(Box::new(venue.clone()));
+        let id = manager.open_from_signal(MARKET, &signal(TradeDirection::Long, 0.5), 100.0).await.unwrap().unwrap();
+        let base_before = manager.position(id).unwrap().base_amount;
+
+        // Still outside the 5% band
+        venue.set_mark_price(MARKET, 95.0);
+        manager.on_mark_price(MARKET, 95.0).await.unwrap();
+        assert_eq!(manager.events().len(), 1);
+
+        venue.set_mark_price(MARKET, 94.0);
+        manager.on_mark_price(MARKET, 94.0).await.unwrap();
+        let position = manager.position(id).unwrap();
+        assert_eq!(position.status, PositionStatus::Open);
+        assert!(position.base_amount < base_before);
+        assert!((position.liquidation_distance - 0.10).abs() < 1e-6);
+        assert!((position.realized_pnl - -6.0 * (base_before - position.base_amount)).abs() < 1e-9);
+        match manager.events().last() {
+            Some(PositionEvent::Deleveraged { liquidation_distance, .. }) => {
+                assert!(*liquidation_distance < 0.05)
+            }
+            other => panic!("expected a deleverage, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn funding_is_paid_by_longs_to_shorts_once_per_payment() {
+        let venue = paper(100.0);
+        let mut manager = manager(Box::new(venue.clone()));
+        let long = manager.open_from_signal(MARKET, &signal(TradeDirection::Long, 0.5), 100.0).await.unwrap().unwrap();
+        let short = manager.open_from_signal(MARKET, &signal(TradeDirection::Short, 0.5), 50.0).await.unwrap().unwrap();
+        let long_base = manager.position(long).unwrap().base_amount;
+        let short_base = manager.position(short).unwrap().base_amount;
+
+        venue.record_funding(FundingPayment {
+            market: MARKET.to_string(),
+            timestamp: now_secs() + 3600,
+            rate: 0.0001,
+            amount_per_long_base: 0.01,
+        });
+        let net = manager.settle_funding().await.unwrap();
+        assert!((net - 0.01 * (short_base - long_base)).abs() < 1e-12);
+
+        let long = manager.position(long).unwrap();
+        assert_eq!(long.funding_log.len(), 1);
+        assert!((long.funding_net - -0.01 * long_base).abs() < 1e-12);
+        assert!((long.collateral - (100.0 - 0.01 * long_base)).abs() < 1e-9);
+        let long = long.id;
+        let short = manager.position(short).unwrap();
+        assert!((short.funding_net - 0.01 * short_base).abs() < 1e-12);
+
+        // Already booked
+        assert_eq!(manager.settle_funding().await.unwrap(), 0.0);
+        assert_eq!(manager.position(long).unwrap().funding_log.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_failed_exit_does_not_skip_the_other_positions() {
+        let paper = paper(100.0);
+        let venue = RejectFirstReduce { paper: paper.clone(), rejected: Mutex::new(false) };
+        let mut manager = manager(Box::new(venue));
+        for _ in 0..2 {
+            manager.open_from_signal(MARKET, &signal(TradeDirection::Long, 0.02), 10.0).await.unwrap();
+        }
+
+        paper.set_mark_price(MARKET, 97.0);
+        assert!(manager.on_mark_price(MARKET, 97.0).await.is_err());
+        assert_eq!(manager.open_positions().count(), 1);
+        assert!(manager.events().iter().any(|e| matches!(e, PositionEvent::StoppedOut { .. })));
+
+        // Retried on the next mark
+        manager.on_mark_price(MARKET, 97.0).await.unwrap();
+        assert_eq!(manager.open_positions().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn poll_remarks_at_the_venue_price_and_books_funding() {
+        let venue = paper(100.0);
+        let mut manager = manager(Box::new(venue.clone()));
+        let id = manager.open_from_signal(MARKET, &signal(TradeDirection::Short, 0.5), 100.0).await.unwrap().unwrap();
+        let base = manager.position(id).unwrap().base_amount;
+
+        venue.set_mark_price(MARKET, 99.0);
+        venue.record_funding(FundingPayment {
+            market: MARKET.to_string(),
+            timestamp: now_secs() + 3600,
+            rate: 0.0001,
+            amount_per_long_base: 0.01,
+        });
+        manager.poll().await.unwrap();
+
+        let position = manager.position(id).unwrap();
+        assert_eq!(position.mark_price, 99.0);
+        assert!((position.unrealized_pnl - base).abs() < 1e-9);
+        assert!((position.funding_net - 0.01 * base).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerpOrder {
+    pub market: String,
+    pub side: OrderSide,
+    pub base_amount: f64,
+    /// Only ever shrinks an existing position
+    pub reduce_only: bool,
+    /// Market order when `None`
+    pub limit_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFill {
+    pub order_id: String,
+    pub market: String,
+    pub side: OrderSide,
+    pub base_amount: f64,
+    pub price: f64,
+    /// Quote paid in fees
+    pub fee: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingPayment {
+    pub market: String,
+    pub timestamp: i64,
+    /// Funding rate of the period, as a fraction of the oracle price
+    pub rate: f64,
+    /// Quote per unit of base held long; shorts receive what longs pay
+    pub amount_per_long_base: f64,
+}
+
+/// Where perp orders are executed. Funding is reported per market so the
+/// position manager can apportion it to its own positions.
+#[async_trait]
+pub trait PerpVenue: Send + Sync {
+    fn name(&self) -> &str;
+    async fn place_order(&self, order: &PerpOrder) -> Result<OrderFill>;
+    async fn mark_price(&self, market: &str) -> Result<f64>;
+    /// Funding payments made after `since`, oldest first
+    async fn funding_payments(&self, market: &str, since: i64) -> Result<Vec<FundingPayment>>;
+}
+
+/// Lets a caller keep a handle on a venue it hands to the position manager
+#[async_trait]
+impl<V: PerpVenue + ?Sized> PerpVenue for Arc<V> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    async fn place_order(&self, order: &PerpOrder) -> Result<OrderFill> {
+        (**self).place_order(order).await
+    }
+
+    async fn mark_price(&self, market: &str) -> Result<f64> {
+        (**self).mark_price(market).await
+    }
+
+    async fn funding_payments(&self, market: &str, since: i64) -> Result<Vec<FundingPayment>> {
+        (**self).funding_payments(market, since).await
+    }
+}
+
+/// Fills every order at the current mark with a flat taker fee, for dry runs
+pub struct PaperPerpVenue {
+    pub taker_fee: f64,
+    marks: Mutex<HashMap<String, f64>>,
+    funding: Mutex<Vec<FundingPayment>>,
+    fills: Mutex<u64>,
+}
+
+impl PaperPerpVenue {
+    pub fn new(taker_fee: f64) -> Self {
+        PaperPerpVenue {
+            taker_fee,
+            marks: Mutex::new(HashMap::new()),
+            funding: Mutex::new(Vec::new()),
+            fills: Mutex::new(0),
+        }
+    }
+
+    pub fn set_mark_price(&self, market: &str, price: f64) {
+        self.marks.lock().unwrap().insert(market.to_string(), price);
+    }
+
+    pub fn record_funding(&self, payment: FundingPayment) {
+        self.funding.lock().unwrap().push(payment);
+    }
+}
+
+#[async_trait]
+impl PerpVenue for PaperPerpVenue {
+    fn name(&self) -> &str {
+        "paper"
+    }
+
+    async fn place_order(&self, order: &PerpOrder) -> Result<OrderFill> {
+        if order.base_amount <= 0.0 {
+            return Err(anyhow!("Order size must be positive, got {}", order.base_amount));
+        }
+        let price = self.mark_price(&order.market).await?;
+        if let Some(limit) = order.limit_price {
+            let crosses = match order.side {
+                OrderSide::Buy => price <= limit,
+                OrderSide::Sell => price >= limit,
+            };
+            if !crosses {
+                return Err(anyhow!("Limit {} not reachable at mark {}", limit, price));
+            }
+        }
+
+        let mut fills = self.fills.lock().unwrap();
+        *fills += 1;
+        Ok(OrderFill {
+            order_id: format!("paper-{}", *fills),
+            market: order.market.clone(),
+            side: order.side,
+            base_amount: order.base_amount,
+            price,
+            fee: order.base_amount * price * self.taker_fee,
+        })
+    }
+
+    async fn mark_price(&self, market: &str) -> Result<f64> {
+        self.marks
+            .lock()
+            .unwrap()
+            .get(market)
+            .copied()
+            .ok_or_else(|| anyhow!("No mark price for {}", market))
+    }
+
+    async fn funding_payments(&self, market: &str, since: i64) -> Result<Vec<FundingPayment>> {
+        Ok(self
+            .funding
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.market == market && p.timestamp > since)
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,577 @@
+//! Serves one registry model over a local HTTP API. Requests are queued and
+//! run in batches, each with a latency budget, and the model is swapped for
+//! the registry's production version whenever it is promoted or rolled back.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+use black_diamond_model_registry::{
+    ModelRegistry, PredictRequest, PredictResponse, ScoreKind, ServeError, ServedModelStatus,
+};
+use crate::transformers::{model_from_checkpoint, ModelHead, TransformerModel};
+
+#[derive(Debug, Clone)]
+pub struct ServingConfig {
+    pub model_name: String,
+    pub registry_root: PathBuf,
+    pub bind: SocketAddr,
+    /// Most rows run in one batch; a single request may not exceed it
+    pub max_batch_size: usize,
+    /// How long the first request of a batch waits for others to join
+    pub batch_window: Duration,
+    /// Default and upper bound for a request's deadline
+    pub latency_budget: Duration,
+    /// How often the registry is checked for a new production version
+    pub reload_interval: Duration,
+}
+
+impl ServingConfig {
+    /// Reads `BLACK_DIAMOND_MODEL_REGISTRY` and `BLACK_DIAMOND_SERVE_ADDR`;
+    /// the server only binds to loopback unless the address says otherwise.
+    pub fn from_env(model_name: &str) -> Result<Self> {
+        let bind = std::env::var("BLACK_DIAMOND_SERVE_ADDR")
+            .unwrap_or_else(|_| "127.0.0.1:7878".to_string())
+            .parse()?;
+
+        Ok(ServingConfig {
+            model_name: model_name.to_string(),
+            registry_root: std::env::var("BLACK_DIAMOND_MODEL_REGISTRY")
+                .unwrap_or_else(|_| "models".to_string())
+                .into(),
+            bind,
+            max_batch_size: 256,
+            batch_window: Duration::from_millis(2),
+            latency_budget: Duration::from_millis(50),
+            reload_interval: Duration::from_secs(5),
+        })
+    }
+}
+
+struct ServedModel {
+    version: u32,
+    dataset_hash: String,
+    feature_names: Vec<String>,
+    loaded_at: i64,
+    score_kind: ScoreKind,
+    score_output: usize,
+    model: Box<dyn TransformerModel + Send + Sync>,
+}
+
+struct Job {
+    inputs: Vec<Vec<f32>>,
+    enqueued: Instant,
+    deadline: Instant,
+    reply: oneshot::Sender<std::result::Result<PredictResponse, String>>,
+}
+
+#[derive(Default)]
+struct ServeStats {
+    requests_served: AtomicU64,
+    deadline_misses: AtomicU64,
+    reloads: AtomicU64,
+}
+
+#[derive(Clone)]
+struct ServerState {
+    config: Arc<ServingConfig>,
+    current: Arc<RwLock<Arc<ServedModel>>>,
+    jobs: mpsc::Sender<Job>,
+    stats: Arc<ServeStats>,
+}
+
+type ApiError = (StatusCode, Json<ServeError>);
+
+fn api_error(status: StatusCode, error: impl Into<String>) -> ApiError {
+    (status, Json(ServeError { error: error.into() }))
+}
+
+impl ServerState {
+    fn model(&self) -> Arc<ServedModel> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Loads the production version if it differs from the one being served.
+    /// In-flight batches keep the model they started with.
+    fn reload(&self) -> Result<Option<u32>> {
+        let registry = ModelRegistry::open(&self.config.registry_root)?;
+        let version = registry
+            .production_version(&self.config.model_name)
+            .ok_or_else(|| anyhow!("{} has no production version", self.config.model_name))?;
+        if version == self.model().version {
+            return Ok(None);
+        }
+
+        let loaded = load_model(&registry, &self.config.model_name, version)?;
+        *self.current.write().unwrap() = Arc::new(loaded);
+        self.stats.reloads.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(version))
+    }
+}
+
+fn load_model(registry: &ModelRegistry, model_name: &str, version: u32) -> Result<ServedModel> {
+    let checkpoint = registry.load_checkpoint(model_name, version)?;
+    let model = model_from_checkpoint(&checkpoint)?;
+    let (score_kind, score_output) = match model.head() {
+        ModelHead::Classification { output } => (ScoreKind::Probability, output),
+        ModelHead::Regression { output } => (ScoreKind::Forecast, output),
+    };
+
+    Ok(ServedModel {
+        version,
+        dataset_hash: checkpoint.dataset_hash.clone(),
+        feature_names: checkpoint.feature_schema.names().into_iter().map(str::to_string).collect(),
+        loaded_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64,
+        score_kind,
+        score_output,
+        model,
+    })
+}
+
+/// Loads the production version of `config.model_name` and serves it until
+/// the process exits.
+pub async fn serve(config: ServingConfig) -> Result<()> {
+    let registry = ModelRegistry::open(&config.registry_root)?;
+    let version = registry
+        .production_version(&config.model_name)
+        .ok_or_else(|| anyhow!("{} has no production version to serve", config.model_name))?;
+    let initial = load_model(&registry, &config.model_name, version)?;
+
+    let (jobs, queue) = mpsc::channel(config.max_batch_size * 4);
+    let state = ServerState {
+        config: Arc::new(config),
+        current: Arc::new(RwLock::new(Arc::new(initial))),
+        jobs,
+        stats: Arc::new(ServeStats::default()),
+    };
+
+    tokio::spawn(run_batches(state.clone(), queue));
+    tokio::spawn(watch_registry(state.clone()));
+
+    let app = Router::new()
+        .route("/v1/predict", post(predict))
+        .route("/v1/model", get(model_status))
+        .route("/v1/reload", post(reload))
+        .with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind(state.config.bind).await?;
+    println!("🧠 Serving {} v{} on {}", state.config.model_name, version, state.config.bind);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn watch_registry(state: ServerState) {
+    let mut interval = tokio::time::interval(state.config.reload_interval);
+    loop {
+        interval.tick().await;
+        // Opening the registry and loading a checkpoint block on disk I/O
+        let registry_state = state.clone();
+        let reloaded = match tokio::task::spawn_blocking(move || registry_state.reload()).await {
+            Ok(reloaded) => reloaded,
+            Err(e) => Err(anyhow!("reload task failed: {}", e)),
+        };
+        match reloaded {
+            Ok(Some(version)) => println!("🔄 Hot-swapped {} to v{}", state.config.model_name, version),
+            Ok(None) => {}
+            Err(e) => println!("⚠️ Registry check failed, still serving v{}: {}", state.model().version, e),
+        }
+    }
+}
+
+/// Collects queued requests into batches of up to `max_batch_size` rows,
+/// drops the ones already past their deadline and runs the rest together.
+async fn run_batches(state: ServerState, mut queue: mpsc::Receiver<Job>) {
+    // A request that would have overfilled the last batch starts the next one
+    let mut carried: Option<Job> = None;
+    loop {
+        let first = match carried.take() {
+            Some(job) => job,
+            None => match queue.recv().await {
+                Some(job) => job,
+                None => return,
+            },
+        };
+        let window_end = Instant::now() + state.config.batch_window;
+        let mut rows = first.inputs.len();
+        let mut batch = vec![first];
+
+        while rows < state.config.max_batch_size {
+            match tokio::time::timeout_at(window_end, queue.recv()).await {
+                Ok(Some(job)) if rows + job.inputs.len() > state.config.max_batch_size => {
+                    carried = Some(job);
+                    break;
+                }
+                Ok(Some(job)) => {
+                    rows += job.inputs.len();
+                    batch.push(job);
+                }
+                _ => break,
+            }
+        }
+
+        let now = Instant::now();
+        let (live, expired): (Vec<Job>, Vec<Job>) = batch.into_iter().partition(|job| job.deadline > now);
+        for job in expired {
+            state.stats.deadline_misses.fetch_add(1, Ordering::Relaxed);
+            let _ = job.reply.send(Err("latency budget exceeded while queued".to_string()));
+        }
+        if live.is_empty() {
+            continue;
+        }
+
+        let model = state.model();
+        let inputs: Vec<Vec<Vec<f32>>> = live.iter().map(|job| job.inputs.clone()).collect();
+        let started = Instant::now();
+        let outputs = {
+            let model = model.clone();
+            tokio::task::spawn_blocking(move || {
+                inputs
+                    .iter()
+                    .map(|rows| rows.iter().map(|row| model.model.predict(row)).collect::<Vec<Vec<f32>>>())
+                    .collect::<Vec<_>>()
+            })
+            .await
+        };
+        let inference_us = started.elapsed().as_micros() as u64;
+
+        let outputs = match outputs {
+            Ok(outputs) => outputs,
+            Err(e) => {
+                for job in live {
+                    let _ = job.reply.send(Err(format!("inference failed: {}", e)));
+                }
+                continue;
+            }
+        };
+
+        let batch_rows: usize = live.iter().map(|job| job.inputs.len()).sum();
+        for (job, outputs) in live.into_iter().zip(outputs) {
+            let scores = outputs
+                .iter()
+                .map(|output| output.get(model.score_output).copied().unwrap_or(0.0))
+                .collect();
+            let _ = job.reply.send(Ok(PredictResponse {
+                model_name: state.config.model_name.clone(),
+                version: model.version,
+                score_kind: model.score_kind,
+                outputs,
+                scores,
+                batch_rows,
+                queue_us: (started - job.enqueued).as_micros() as u64,
+                inference_us,
+            }));
+        }
+    }
+}
+
+async fn predict(
+    State(state): State<ServerState>,
+    Json(req): Json<PredictRequest>,
+) -> std::result::Result<Json<PredictResponse>, ApiError> {
+    if req.inputs.is_empty() {
+        return Err(api_error(StatusCode::BAD_REQUEST, "no inputs"));
+    }
+    if req.inputs.len() > state.config.max_batch_size {
+        return Err(api_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("{} rows exceeds the batch limit of {}", req.inputs.len(), state.config.max_batch_size),
+        ));
+    }
+    let width = state.model().feature_names.len();
+    if let Some((index, row)) = req.inputs.iter().enumerate().find(|(_, row)| row.len() != width) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            format!("row {} has {} features, the model takes {}", index, row.len(), width),
+        ));
+    }
+
+    let budget = req
+        .budget_ms
+        .map(Duration::from_millis)
+        .unwrap_or(state.config.latency_budget)
+        .min(state.config.latency_budget);
+    let enqueued = Instant::now();
+    let (reply, response) = oneshot::channel();
+    let job = Job {
+        inputs: req.inputs,
+        enqueued,
+        deadline: enqueued + budget,
+        reply,
+    };
+
+    state
+        .jobs
+        .try_send(job)
+        .map_err(|_| api_error(StatusCode::SERVICE_UNAVAILABLE, "prediction queue is full"))?;
+
+    match tokio::time::timeout(budget, response).await {
+        Ok(Ok(Ok(response))) => {
+            state.stats.requests_served.fetch_add(1, Ordering::Relaxed);
+            Ok(Json(response))
+        }
+        Ok(Ok(Err(e))) => Err(api_error(StatusCode::GATEWAY_TIMEOUT, e)),
+        Ok(Err(_)) => Err(api_error(StatusCode::INTERNAL_SERVER_ERROR, "batcher dropped the request")),
+        Err(_) => {
+            state.stats.deadline_misses.fetch_add(1, Ordering::Relaxed);
+            Err(api_error(
+                StatusCode::GATEWAY_TIMEOUT,
+                format!("no prediction within {}ms", budget.as_millis()),
+            ))
+        }
+    }
+}
+
+async fn model_status(State(state): State<ServerState>) -> Json<ServedModelStatus> {
+    let model = state.model();
+    Json(ServedModelStatus {
+        model_name: state.config.model_name.clone(),
+        version: model.version,
+        dataset_hash: model.dataset_hash.clone(),
+        feature_names: model.feature_names.clone(),
+        loaded_at: model.loaded_at,
+        requests_served: state.stats.requests_served.load(Ordering::Relaxed),
+        deadline_misses: state.stats.deadline_misses.load(Ordering::Relaxed),
+        reloads: state.stats.reloads.load(Ordering::Relaxed),
+    })
+}
+
+/// Checks the registry now instead of waiting for the next poll
+async fn reload(State(state): State<ServerState>) -> std::result::Result<Json<ServedModelStatus>, ApiError> {
+    let registry_state = state.clone();
+    tokio::task::spawn_blocking(move || registry_state.reload())
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| api_error(StatusCode::CONFLICT, e.to_string()))?;
+    Ok(model_status(State(state)).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use black_diamond_model_registry::{CheckpointMetadata, FeatureSchema, FeatureSpec};
+    use crate::transformers::HybridLSTMQuantum;
+
+    /// Scores a row with the sum of its features and reports its width
+    struct StubModel;
+
+    impl TransformerModel for StubModel {
+        fn train(&mut self, _dataset: &[Vec<f32>]) -> Result<()> {
+            Ok(())
+        }
+
+        fn predict(&self, input: &[f32]) -> Vec<f32> {
+            vec![input.iter().sum(), input.len() as f32]
+        }
+
+        fn get_model_name(&self) -> String {
+            "stub".to_string()
+        }
+
+        fn export_weights(&self) -> HashMap<String, Vec<f32>> {
+            HashMap::new()
+        }
+
+        fn import_weights(&mut self, _weights: &HashMap<String, Vec<f32>>) -> Result<()> {
+            Ok(())
+        }
+
+        fn hyperparameters(&self) -> HashMap<String, f64> {
+            HashMap::new()
+        }
+    }
+
+    fn test_config(model_name: &str, registry_root: PathBuf) -> ServingConfig {
+        ServingConfig {
+            model_name: model_name.to_string(),
+            registry_root,
+            bind: "127.0.0.1:0".parse().unwrap(),
+            max_batch_size: 4,
+            batch_window: Duration::from_millis(30),
+            latency_budget: Duration::from_secs(1),
+            reload_interval: Duration::from_secs(60),
+        }
+    }
+
+    fn stub_model(width: usize) -> ServedModel {
+        ServedModel {
+            version: 1,
+            dataset_hash: "stub".to_string(),
+            feature_names: (0..width).map(|i| format!("f{}", i)).collect(),
+            loaded_at: 0,
+            score_kind: ScoreKind::Forecast,
+            score_output: 0,
+            model: Box::new(StubModel),
+        }
+    }
+
+    fn test_state(config: ServingConfig, model: ServedModel) -> (ServerState, mpsc::Receiver<Job>) {
+        let (jobs, queue) = mpsc::channel(16);
+        let state = ServerState {
+            config: Arc::new(config),
+            current: Arc::new(RwLock::new(Arc::new(model))),
+            jobs,
+            stats: Arc::new(ServeStats::default()),
+        };
+        (state, queue)
+    }
+
+    type Reply = oneshot::Receiver<std::result::Result<PredictResponse, String>>;
+
+    fn job(rows: usize, deadline: Instant) -> (Job, Reply) {
+        let (reply, response) = oneshot::channel();
+        let job = Job {
+            inputs: (0..rows).map(|i| vec![i as f32, 1.0]).collect(),
+            enqueued: Instant::now(),
+            deadline,
+            reply,
+        };
+        (job, response)
+    }
+
+    fn stub_schema(names: &[&str]) -> FeatureSchema {
+        FeatureSchema {
+            features: names.iter().map(|name| FeatureSpec::from_name(name).unwrap()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn batches_never_exceed_the_row_cap() {
+        let (state, queue) = test_state(test_config("stub", PathBuf::new()), stub_model(2));
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        // 3 + 2 would overfill a batch of 4, so the second request starts the next one
+        let mut replies = Vec::new();
+        for rows in [3, 2, 2] {
+            let (job, reply) = job(rows, deadline);
+            state.jobs.send(job).await.unwrap();
+            replies.push(reply);
+        }
+        tokio::spawn(run_batches(state.clone(), queue));
+
+        let mut batch_rows = Vec::new();
+        for reply in replies {
+            let response = reply.await.unwrap().unwrap();
+            assert_eq!(response.scores, (0..response.outputs.len()).map(|i| i as f32 + 1.0).collect::<Vec<_>>());
+            batch_rows.push(response.batch_rows);
+        }
+        assert_eq!(batch_rows, vec![3, 4, 4]);
+    }
+
+    #[tokio::test]
+    async fn partial_batches_flush_after_the_window() {
+        let (state, queue) = test_state(test_config("stub", PathBuf::new()), stub_model(2));
+        tokio::spawn(run_batches(state.clone(), queue));
+
+        let (job, reply) = job(1, Instant::now() + Duration::from_secs(5));
+        state.jobs.send(job).await.unwrap();
+        let response = reply.await.unwrap().unwrap();
+
+        assert_eq!(response.batch_rows, 1);
+        assert!(response.queue_us >= 25_000, "flushed after {}us", response.queue_us);
+    }
+
+    #[tokio::test]
+    async fn expired_requests_are_dropped_from_the_batch() {
+        let (state, queue) = test_state(test_config("stub", PathBuf::new()), stub_model(2));
+
+        let (expired, expired_reply) = job(1, Instant::now());
+        let (live, live_reply) = job(2, Instant::now() + Duration::from_secs(5));
+        state.jobs.send(expired).await.unwrap();
+        state.jobs.send(live).await.unwrap();
+        tokio::spawn(run_batches(state.clone(), queue));
+
+        let error = expired_reply.await.unwrap().unwrap_err();
+        assert!(error.contains("latency budget exceeded"), "{}", error);
+        assert_eq!(live_reply.await.unwrap().unwrap().batch_rows, 2);
+        assert_eq!(state.stats.deadline_misses.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn predict_rejects_malformed_requests() {
+        let (state, _queue) = test_state(test_config("stub", PathBuf::new()), stub_model(3));
+        let request = |inputs: Vec<Vec<f32>>| Json(PredictRequest { inputs, budget_ms: None });
+
+        let (status, Json(error)) = predict(State(state.clone()), request(vec![vec![1.0, 2.0, 3.0], vec![1.0, 2.0]]))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(error.error.contains("row 1 has 2 features"), "{}", error.error);
+
+        let (status, _) = predict(State(state.clone()), request(vec![vec![0.0; 3]; 5])).await.unwrap_err();
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let (status, _) = predict(State(state), request(Vec::new())).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn predict_answers_through_the_batcher() {
+        let (state, queue) = test_state(test_config("stub", PathBuf::new()), stub_model(2));
+        tokio::spawn(run_batches(state.clone(), queue));
+
+        let Json(response) = predict(
+            State(state.clone()),
+            Json(PredictRequest { inputs: vec![vec![1.0, 2.0], vec![3.0, 4.0]], budget_ms: None }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.scores, vec![3.0, 7.0]);
+        assert_eq!(state.stats.requests_served.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn reload_swaps_in_the_promoted_version() {
+        let root = std::env::temp_dir().join(format!("serving_reload_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let mut registry = ModelRegistry::open(&root).unwrap();
+        let metadata = CheckpointMetadata {
+            feature_schema: stub_schema(&["log_return_1", "realized_volatility_5"]),
+            ..Default::default()
+        };
+        let v1 = registry.register(&HybridLSTMQuantum::new(2, 2).checkpoint(metadata.clone())).unwrap();
+        let v2 = registry.register(&HybridLSTMQuantum::new(2, 2).checkpoint(metadata)).unwrap();
+
+        registry.promote("hybrid_lstm_quantum", v1).unwrap();
+        let initial = load_model(&registry, "hybrid_lstm_quantum", v1).unwrap();
+        assert_eq!(initial.feature_names, vec!["log_return_1", "realized_volatility_5"]);
+        let (state, _queue) = test_state(test_config("hybrid_lstm_quantum", root.clone()), initial);
+
+        assert_eq!(state.reload().unwrap(), None);
+        registry.promote("hybrid_lstm_quantum", v2).unwrap();
+        assert_eq!(state.reload().unwrap(), Some(v2));
+        assert_eq!(state.model().version, v2);
+        assert_eq!(state.reload().unwrap(), None);
+        assert_eq!(state.stats.reloads.load(Ordering::Relaxed), 1);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn reload_fails_without_a_production_version() {
+        let root = std::env::temp_dir().join(format!("serving_unpromoted_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let (state, _queue) = test_state(test_config("hybrid_lstm_quantum", root.clone()), stub_model(2));
+
+        assert!(state.reload().is_err());
+        assert_eq!(state.model().version, 1);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use anyhow::{anyhow, Result};
+
+use super::TransformerModel;
+use crate::datasets::FeatureDataset;
+
+/// Which model output is scored and how.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModelHead {
+    /// Output is a probability of the positive class; targets are 0 or 1
+    Classification { output: usize },
+    /// Output is a signed forecast; targets are realised values
+    Regression { output: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct LabelledSample {
+    pub input: Vec<f32>,
+    pub target: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvaluationConfig {
+    pub train_fraction: f64,
+    pub validation_fraction: f64,
+    /// Rows per walk-forward step through the validation region
+    pub walk_forward_step: usize,
+    pub classification_threshold: f32,
+}
+
+impl Default for EvaluationConfig {
+    fn default() -> Self {
+        EvaluationConfig {
+            train_fraction: 0.6,
+            validation_fraction: 0.2,
+            walk_forward_step: 50,
+            classification_threshold: 0.5,
+        }
+    }
+}
+
+/// Contiguous, time-ordered row ranges. Validation always follows training and
+/// test always follows validation, so no split sees rows from its future.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSplit {
+    pub train: Range<usize>,
+    pub validation: Range<usize>,
+    pub test: Range<usize>,
+}
+
+impl TimeSplit {
+    pub fn new(rows: usize, config: &EvaluationConfig) -> Result<Self> {
+        if config.train_fraction <= 0.0 || config.validation_fraction < 0.0
+            || config.train_fraction + config.validation_fraction >= 1.0 {
+            return Err(anyhow!("Split fractions must leave room for a test set"));
+        }
+
+        let train_end = (rows as f64 * config.train_fraction) as usize;
+        let validation_end = (rows as f64 * (config.train_fraction + config.validation_fraction)) as usize;
+        if train_end == 0 || validation_end >= rows {
+            return Err(anyhow!("{} rows is too few to split into train/validation/test", rows));
+        }
+
+        Ok(TimeSplit {
+            train: 0..train_end,
+            validation: train_end..validation_end,
+            test: validation_end..rows,
+        })
+    }
+
+    /// Consecutive windows through the validation region. Each is scored before
+    /// the model trains on it.
+    pub fn walk_forward_windows(&self, step: usize) -> Vec<Range<usize>> {
+        let step = step.max(1);
+        (self.validation.start..self.validation.end)
+            .step_by(step)
+            .map(|start| start..(start + step).min(self.validation.end))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClassificationMetrics {
+    pub samples: usize,
+    pub accuracy: f64,
+    pub precision: f64,
+    pub recall: f64,
+    pub brier_score: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RegressionMetrics {
+    pub samples: usize,
+    pub mae: f64,
+    pub directional_accuracy: f64,
+}
+
+#[derive(Debug, Clone)]
+pub enum HeadMetrics {
+    Classification(ClassificationMetrics),
+    Regression(RegressionMetrics),
+}
+
+impl HeadMetrics {
+    pub fn compute(head: ModelHead, predictions: &[f32], targets: &[f32], threshold: f32) -> Self {
+        match head {
+            ModelHead::Classification { .. } => {
+                HeadMetrics::Classification(classification_metrics(predictions, targets, threshold))
+            }
+            ModelHead::Regression { .. } => HeadMetrics::Regression(regression_metrics(predictions, targets)),
+        }
+    }
+
+    /// Accuracy for classifiers, directional accuracy for regressors
+    pub fn headline(&self) -> f64 {
+        match self {
+            HeadMetrics::Classification(m) => m.accuracy,
+            HeadMetrics::Regression(m) => m.directional_accuracy,
+        }
+    }
+
+    pub fn samples(&self) -> usize {
+        match self {
+            HeadMetrics::Classification(m) => m.samples,
+            HeadMetrics::Regression(m) => m.samples,
+        }
+    }
+
+    /// Flat `prefix.metric` map for checkpoint training metrics
+    pub fn to_map(&self, prefix: &str) -> HashMap<String, f64> {
+        let mut map = HashMap::new();
+        let mut put = |name: &str, value: f64| {
+            map.insert(format!("{}.{}", prefix, name), value);
+        };
+
+        match self {
+            HeadMetrics::Classification(m) => {
+                put("samples", m.samples as f64);
+                put("accuracy", m.accuracy);
+                put("precision", m.precision);
+                put("recall", m.recall);
+                put("brier_score", m.brier_score);
+            }
+            HeadMetrics::Regression(m) => {
+                put("samples", m.samples as f64);
+                put("mae", m.mae);
+                put("directional_accuracy", m.directional_accuracy);
+            }
+        }
+        map
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModelEvaluation {
+    pub model_name: String,
+    pub head: ModelHead,
+    pub split: TimeSplit,
+    /// Pooled out-of-sample predictions from every walk-forward window
+    pub validation: HeadMetrics,
+    pub walk_forward: Vec<HeadMetrics>,
+    /// Held-out rows the model never trained on
+    pub test: HeadMetrics,
+}
+
+impl ModelEvaluation {
+    pub fn to_metrics(&self) -> HashMap<String, f64> {
+        let mut metrics = self.validation.to_map("validation");
+        metrics.extend(self.test.to_map("test"));
+        metrics.insert("walk_forward_windows".to_string(), self.walk_forward.len() as f64);
+        // Percentage scale matches what the deployment manager reports
+        metrics.insert("accuracy".to_string(), self.test.headline() * 100.0);
+        metrics
+    }
+}
+
+/// Trains `model` on the train split, walks forward through validation (score a
+/// window, then train on it) and finally scores the untouched test split.
+/// Splits are taken over the whole time-ordered dataset; within each, every
+/// market is trained and labelled as its own series.
+pub fn train_and_evaluate(
+    model: &mut dyn TransformerModel,
+    dataset: &FeatureDataset,
+    config: &EvaluationConfig,
+) -> Result<ModelEvaluation> {
+    let split = TimeSplit::new(dataset.len(), config)?;
+    let head = model.head();
+
+    for rows in market_series(dataset, split.train.clone()) {
+        model.train(&rows)?;
+    }
+
+    let mut walk_forward = Vec::new();
+    let mut pooled_predictions = Vec::new();
+    let mut pooled_targets = Vec::new();
+    for window in split.walk_forward_windows(config.walk_forward_step) {
+        let series = market_series(dataset, window);
+        let (predictions, targets) = score(model, head, &series);
+        walk_forward.push(HeadMetrics::compute(head, &predictions, &targets, config.classification_threshold));
+        pooled_predictions.extend(predictions);
+        pooled_targets.extend(targets);
+
+        for rows in &series {
+            model.train(rows)?;
+        }
+    }
+    let validation = HeadMetrics::compute(head, &pooled_predictions, &pooled_targets, config.classification_threshold);
+
+    let (predictions, targets) = score(model, head, &market_series(dataset, split.test.clone()));
+    let test = HeadMetrics::compute(head, &predictions, &targets, config.classification_threshold);
+
+    Ok(ModelEvaluation {
+        model_name: model.get_model_name(),
+        head,
+        split,
+        validation,
+        walk_forward,
+        test,
+    })
+}
+
+/// The dataset's rows in `range`, one time-ordered series per market in order
+/// of first appearance
+fn market_series(dataset: &FeatureDataset, range: Range<usize>) -> Vec<Vec<Vec<f32>>> {
+    let mut markets: Vec<(&str, Vec<Vec<f32>>)> = Vec::new();
+    for i in range {
+        let market = dataset.provenance[i].market.as_str();
+        let row = dataset.rows[i].clone();
+        match markets.iter_mut().find(|(m, _)| *m == market) {
+            Some((_, rows)) => rows.push(row),
+            None => markets.push((market, vec![row])),
+        }
+    }
+    markets.into_iter().map(|(_, rows)| rows).collect()
+}
+
+// Samples are labelled within one market's rows of one split only, so a
+// target never reaches into the next split or another market
+fn score(model: &dyn TransformerModel, head: ModelHead, series: &[Vec<Vec<f32>>]) -> (Vec<f32>, Vec<f32>) {
+    let output = match head {
+        ModelHead::Classification { output } | ModelHead::Regression { output } => output,
+    };
+
+    series
+        .iter()
+        .flat_map(|rows| model.labelled_samples(rows))
+        .map(|sample| {
+            let prediction = model.predict(&sample.input).get(output).copied().unwrap_or(0.0);
+            (prediction, sample.target)
+        })
+        .unzip()
+}
+
+pub fn classification_metrics(probabilities: &[f32], labels: &[f32], threshold: f32) -> ClassificationMetrics {
+    let samples = probabilities.len().min(labels.len());
+    if samples == 0 {
+        return ClassificationMetrics::default();
+    }
+
+    let (mut tp, mut fp, mut fn_, mut correct) = (0usize, 0usize, 0usize, 0usize);
+    let mut squared_error = 0.0;
+    for (&p, &y) in probabilities.iter().zip(labels.iter()) {
+        let p = p.clamp(0.0, 1.0) as f64;
+        let actual = y >= 0.5;
+        let predicted = p >= threshold as f64;
+
+        match (predicted, actual) {
+            (true, true) => tp += 1,
+            (true, false) => fp += 1,
+            (false, true) => fn_ += 1,
+            (false, false) => {}
+        }
+        if predicted == actual {
+            correct += 1;
+        }
+        squared_error += (p - if actual { 1.0 } else { 0.0 }).powi(2);
+    }
+
+    ClassificationMetrics {
+        samples,
+        accuracy: correct as f64 / samples as f64,
+        precision: if tp + fp > 0 { tp as f64 / (tp + fp) as f64 } else { 0.0 },
+        recall: if tp + fn_ > 0 { tp as f64 / (tp + fn_) as f64 } else { 0.0 },
+        brier_score: squared_error / samples as f64,
+    }
+}
+
+pub fn regression_metrics(predictions: &[f32], targets: &[f32]) -> RegressionMetrics {
+    let samples = predictions.len().min(targets.len());
+    if samples == 0 {
+        return RegressionMetrics::default();
+    }
+
+    let mut absolute_error = 0.0;
+    let mut directional_hits = 0usize;
+    let mut directional_samples = 0usize;
+    for (&p, &t) in predictions.iter().zip(targets.iter()) {
+        absolute_error += (p as f64 - t as f64).abs();
+        // Flat targets carry no direction to get right
+        if t != 0.0 {
+            directional_samples += 1;
+            if p.signum() == t.signum() {
+                directional_hits += 1;
+            }
+        }
+    }
+
+    RegressionMetrics {
+        samples,
+        mae: absolute_error / samples as f64,
+        directional_accuracy: if directional_samples > 0 {
+            directional_hits as f64 / directional_samples as f64
+        } else {
+            0.0
+        },
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EvaluationReport {
+    pub models: HashMap<String, ModelEvaluation>,
+    /// Mean held-out test headline score across models
+    pub average_test_score: f64,
+    /// Mean pooled walk-forward validation headline score across models
+    pub average_validation_score: f64,
+}
+
+impl EvaluationReport {
+    pub fn new(models: HashMap<String, ModelEvaluation>) -> Self {
+        let count = models.len().max(1) as f64;
+        let average_test_score = models.values().map(|e| e.test.headline()).sum::<f64>() / count;
+        let average_validation_score = models.values().map(|e| e.validation.headline()).sum::<f64>() / count;
+
+        EvaluationReport {
+            models,
+            average_test_score,
+            average_validation_score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets::{DataSource, FeatureSchema, RowProvenance};
+
+    /// Predicts the last return continues and remembers what it trained on
+    #[derive(Default)]
+    struct Momentum {
+        trained: Vec<Vec<Vec<f32>>>,
+    }
+
+    impl TransformerModel for Momentum {
+        fn train(&mut self, dataset: &[Vec<f32>]) -> Result<()> {
+            self.trained.push(dataset.to_vec());
+            Ok(())
+        }
+
+        fn predict(&self, input: &[f32]) -> Vec<f32> {
+            vec![input[0]]
+        }
+
+        fn get_model_name(&self) -> String {
+            "momentum".to_string()
+        }
+
+        fn export_weights(&self) -> HashMap<String, Vec<f32>> {
+            HashMap::new()
+        }
+
+        fn import_weights(&mut self, _weights: &HashMap<String, Vec<f32>>) -> Result<()> {
+            Ok(())
+        }
+
+        fn hyperparameters(&self) -> HashMap<String, f64> {
+            HashMap::new()
+        }
+    }
+
+    fn config(step: usize) -> EvaluationConfig {
+        EvaluationConfig {
+            walk_forward_step: step,
+            ..EvaluationConfig::default()
+        }
+    }
+
+    #[test]
+    fn splits_are_contiguous_and_in_time_order() {
+        let split = TimeSplit::new(100, &config(50)).unwrap();
+        assert_eq!(split, TimeSplit { train: 0..60, validation: 60..80, test: 80..100 });
+
+        assert!(TimeSplit::new(1, &config(50)).is_err());
+        let no_test = EvaluationConfig { train_fraction: 0.7, validation_fraction: 0.3, ..config(50) };
+        assert!(TimeSplit::new(100, &no_test).is_err());
+    }
+
+    #[test]
+    fn walk_forward_windows_cover_validation_without_overlap() {
+        let split = TimeSplit::new(100, &config(50)).unwrap();
+        assert_eq!(split.walk_forward_windows(8), [60..68, 68..76, 76..80]);
+        assert_eq!(split.walk_forward_windows(50), [60..80]);
+        // A zero step still makes progress
+        assert_eq!(split.walk_forward_windows(0).len(), 20);
+    }
+
+    #[test]
+    fn labels_are_forward_returns_within_each_market() {
+        // Two interleaved markets that trend in opposite directions
+        let (mut rows, mut provenance) = (Vec::new(), Vec::new());
+        for i in 0..100 {
+            let (market, log_return) = if i % 2 == 0 { ("A", 0.01) } else { ("B", -0.01) };
+            rows.push(vec![log_return, i as f32]);
+            provenance.push(RowProvenance {
+                source: DataSource::Archive,
+                market: market.to_string(),
+                timestamp: i,
+                slot: None,
+            });
+        }
+        let dataset = FeatureDataset { schema: FeatureSchema { features: Vec::new() }, rows, provenance };
+
+        let mut model = Momentum::default();
+        let evaluation = train_and_evaluate(&mut model, &dataset, &config(10)).unwrap();
+
+        // Labelled across markets, momentum would be wrong on every sample
+        assert_eq!(evaluation.validation.headline(), 1.0);
+        assert_eq!(evaluation.test.headline(), 1.0);
+        assert_eq!(evaluation.walk_forward.len(), 2);
+        match &evaluation.test {
+            HeadMetrics::Regression(metrics) => {
+                // 10 rows per market in the test split, the last of each has no label
+                assert_eq!(metrics.samples, 18);
+                assert!(metrics.mae < 1e-9);
+            }
+            other => panic!("expected regression metrics, got {:?}", other),
+        }
+
+        // Trained per market, on train and validation rows but never test rows
+        assert!(model.trained.iter().all(|rows| rows.iter().all(|row| row[0] == rows[0][0])));
+        let trained_rows: usize = model.trained.iter().map(Vec::len).sum();
+        assert_eq!(trained_rows, 80);
+    }
+}
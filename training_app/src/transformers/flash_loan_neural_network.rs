@@ -3,17 +3,22 @@ use std::collections::HashMap;
 use anyhow::Result;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{pubkey::Pubkey, commitment_config::CommitmentConfig};
+use black_diamond_model_registry::take_weights;
 
-pub struct SolanaFlashLoanTransformer {
+use crate::autodiff::{clip_gradients, gradient_check, Adam, GradientCheckReport, GradientClip, LearningRateSchedule, Optimizer, Tape, Var};
+use super::{LabelledSample, ModelHead, TransformerModel};
+
+pub struct FlashLoanNeuralNetwork {
     pub input_size: usize,
     pub hidden_layers: Vec<usize>,
     pub output_size: usize,
     pub weights: Vec<Array2<f32>>,
     pub biases: Vec<Array1<f32>>,
-    pub solana_protocols: HashMap<String, SolanaProtocolMetrics>,
-    pub arbitrage_patterns: Vec<SolanaArbitragePattern>,
+    pub loan_protocols: HashMap<String, ProtocolMetrics>,
+    pub arbitrage_patterns: Vec<ArbitragePattern>,
     pub golden_ratio_factor: f32,
-    pub rpc_client: RpcClient,
+    pub optimizer: Box<dyn Optimizer>,
+    pub gradient_clip: GradientClip,
 }
 
 #[derive(Debug, Clone)]
@@ -100,6 +105,8 @@ impl FlashLoanNeuralNetwork {
             loan_protocols,
             arbitrage_patterns: Vec::new(),
             golden_ratio_factor: golden_ratio,
+            optimizer: Box::new(Adam::new(LearningRateSchedule::Constant { rate: 0.001 })),
+            gradient_clip: GradientClip::GlobalNorm(1.0),
         }
     }
 
@@ -110,15 +117,12 @@ impl FlashLoanNeuralNetwork {
             let input = self.encode_market_state(&data.market_state);
             let target = self.encode_expected_outcome(&data.outcome);
             
-            let prediction = self.forward_pass(&input);
-            let loss = self.calculate_loss(&prediction, &target);
+            let loss = self.backward_pass(&input, &target)?;
             
             if epoch % 50 == 0 {
                 println!("  Epoch {}: Loss={:.6}, Profit={:.4} SOL", 
                     epoch, loss, data.outcome.profit);
             }
-            
-            self.backward_pass(&input, &target, &prediction)?;
         }
         
         println!("Flash loan training complete - updating arbitrage patterns");
@@ -172,33 +176,43 @@ impl FlashLoanNeuralNetwork {
         current
     }
 
-    fn calculate_loss(&self, prediction: &Array1<f32>, target: &Array1<f32>) -> f32 {
-        let diff = prediction - target;
-        diff.mapv(|x| x.powi(2)).sum() / prediction.len() as f32
+    /// Weights and biases interleaved as `[w0, b0, w1, b1, ...]`, biases as 1×n rows
+    fn parameters(&self) -> Vec<Array2<f32>> {
+        self.weights
+            .iter()
+            .zip(self.biases.iter())
+            .flat_map(|(weight, bias)| [weight.clone(), bias.clone().insert_axis(ndarray::Axis(0))])
+            .collect()
     }
 
-    fn backward_pass(&mut self, input: &Array1<f32>, target: &Array1<f32>, prediction: &Array1<f32>) -> Result<()> {
-        let learning_rate = 0.001 * self.golden_ratio_factor;
-        let output_error = prediction - target;
-        
-        // Simplified backpropagation - update output layer weights
-        if let (Some(last_weights), Some(last_bias)) = (self.weights.last_mut(), self.biases.last_mut()) {
-            for i in 0..last_weights.nrows() {
-                for j in 0..last_weights.ncols() {
-                    if j < output_error.len() && i < input.len() {
-                        last_weights[[i, j]] -= learning_rate * output_error[j] * input[i];
-                    }
-                }
-            }
-            
-            for i in 0..last_bias.len() {
-                if i < output_error.len() {
-                    last_bias[i] -= learning_rate * output_error[i];
-                }
+    /// One gradient step on the mean squared error of a single sample.
+    /// Returns the loss before the update.
+    fn backward_pass(&mut self, input: &Array1<f32>, target: &Array1<f32>) -> Result<f32> {
+        let tape = Tape::new();
+        let params: Vec<Var> = self.parameters().into_iter().map(|p| tape.var(p)).collect();
+        let loss = loss_graph(&tape, &params, input, target);
+        let gradients = tape.backward(loss);
+
+        let mut grads: Vec<Array2<f32>> = params.iter().map(|p| gradients.wrt(*p).clone()).collect();
+        clip_gradients(&mut grads, self.gradient_clip);
+
+        let mut biases: Vec<Array2<f32>> = self.biases.iter().map(|b| b.clone().insert_axis(ndarray::Axis(0))).collect();
+        {
+            let mut refs: Vec<&mut Array2<f32>> = Vec::with_capacity(grads.len());
+            for (weight, bias) in self.weights.iter_mut().zip(biases.iter_mut()) {
+                refs.push(weight);
+                refs.push(bias);
             }
+            self.optimizer.step(&mut refs, &grads);
         }
-        
-        Ok(())
+        self.biases = biases.into_iter().map(|b| b.row(0).to_owned()).collect();
+
+        Ok(loss.scalar())
+    }
+
+    /// Compares backpropagated gradients for one sample with finite differences
+    pub fn check_gradients(&self, input: &Array1<f32>, target: &Array1<f32>) -> GradientCheckReport {
+        gradient_check(&self.parameters(), 1e-2, |tape, params| loss_graph(tape, params, input, target))
     }
 
     fn update_arbitrage_patterns(&mut self, training_data: &[FlashLoanTrainingData]) {
@@ -306,6 +320,96 @@ impl FlashLoanNeuralNetwork {
     }
 }
 
+impl TransformerModel for FlashLoanNeuralNetwork {
+    /// Each row holds `input_size` features followed by `output_size` targets.
+    fn train(&mut self, dataset: &[Vec<f32>]) -> Result<()> {
+        let row_len = self.input_size + self.output_size;
+
+        for row in dataset.iter().filter(|row| row.len() >= row_len) {
+            let input = Array1::from_vec(row[..self.input_size].to_vec());
+            let target = Array1::from_vec(row[self.input_size..row_len].to_vec());
+            self.backward_pass(&input, &target)?;
+        }
+        Ok(())
+    }
+
+    fn predict(&self, input: &[f32]) -> Vec<f32> {
+        let mut features = Array1::zeros(self.input_size);
+        for (i, value) in input.iter().take(self.input_size).enumerate() {
+            features[i] = *value;
+        }
+        self.forward_pass(&features).to_vec()
+    }
+
+    fn get_model_name(&self) -> String {
+        "solana_flash_loan_transformer".to_string()
+    }
+
+    fn export_weights(&self) -> HashMap<String, Vec<f32>> {
+        let mut weights_map = HashMap::new();
+        for (i, (weight, bias)) in self.weights.iter().zip(self.biases.iter()).enumerate() {
+            weights_map.insert(format!("layer_{}_weights", i), weight.iter().cloned().collect());
+            weights_map.insert(format!("layer_{}_bias", i), bias.to_vec());
+        }
+        weights_map
+    }
+
+    fn import_weights(&mut self, weights: &HashMap<String, Vec<f32>>) -> Result<()> {
+        let mut layers = Vec::with_capacity(self.weights.len());
+        for (i, (weight, bias)) in self.weights.iter().zip(self.biases.iter()).enumerate() {
+            let shape = weight.dim();
+            let weight = Array2::from_shape_vec(shape, take_weights(weights, &format!("layer_{}_weights", i), shape.0 * shape.1)?)?;
+            let bias = Array1::from_vec(take_weights(weights, &format!("layer_{}_bias", i), bias.len())?);
+            layers.push((weight, bias));
+        }
+
+        let (weights, biases) = layers.into_iter().unzip();
+        self.weights = weights;
+        self.biases = biases;
+        Ok(())
+    }
+
+    /// Scored on the success probability output
+    fn head(&self) -> ModelHead {
+        ModelHead::Classification { output: 1 }
+    }
+
+    fn labelled_samples(&self, rows: &[Vec<f32>]) -> Vec<LabelledSample> {
+        rows.iter()
+            .filter(|row| row.len() >= self.input_size + self.output_size)
+            .map(|row| LabelledSample {
+                input: row[..self.input_size].to_vec(),
+                target: row[self.input_size + 1],
+            })
+            .collect()
+    }
+
+    fn hyperparameters(&self) -> HashMap<String, f64> {
+        let mut params = HashMap::new();
+        params.insert("input_size".to_string(), self.input_size as f64);
+        params.insert("output_size".to_string(), self.output_size as f64);
+        params.insert("hidden_layer_count".to_string(), self.hidden_layers.len() as f64);
+        for (i, size) in self.hidden_layers.iter().enumerate() {
+            params.insert(format!("hidden_layer_{}", i), *size as f64);
+        }
+        params
+    }
+}
+
+/// Same network as `forward_pass` on the tape: ReLU hidden layers, sigmoid
+/// output, then the mean squared error against `target`.
+fn loss_graph<'t>(tape: &'t Tape, params: &[Var<'t>], input: &Array1<f32>, target: &Array1<f32>) -> Var<'t> {
+    let layers = params.len() / 2;
+    let mut current = tape.var(input.clone().insert_axis(ndarray::Axis(0)));
+
+    for (i, layer) in params.chunks(2).enumerate() {
+        current = current.matmul(layer[0]).add_row(layer[1]);
+        current = if i < layers - 1 { current.relu() } else { current.sigmoid() };
+    }
+
+    current.mse(&target.clone().insert_axis(ndarray::Axis(0)))
+}
+
 #[derive(Debug, Clone)]
 pub struct MarketState {
     pub primary_dex: String,
@@ -329,4 +433,29 @@ pub struct FlashLoanOutcome {
 pub struct FlashLoanTrainingData {
     pub market_state: MarketState,
     pub outcome: FlashLoanOutcome,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backward_pass_matches_finite_differences() {
+        let mut network = FlashLoanNeuralNetwork::new(6, vec![8, 5], 4);
+        // Weights large enough that no hidden unit sits within the finite
+        // difference step of the ReLU kink, unlike the small random init
+        for (l, weight) in network.weights.iter_mut().enumerate() {
+            let cols = weight.ncols();
+            *weight = Array2::from_shape_fn(weight.raw_dim(), |(i, j)| ((i * cols + j) as f32 * 0.7 + l as f32).sin());
+        }
+        for bias in network.biases.iter_mut() {
+            bias.fill(0.1);
+        }
+        let input = Array1::from_shape_fn(6, |i| (i as f32 * 0.9 + 0.3).sin());
+        let target = Array1::from_vec(vec![0.8, 1.0, 0.2, 0.1]);
+
+        let report = network.check_gradients(&input, &target);
+        assert_eq!(report.checked, 6 * 8 + 8 + 8 * 5 + 5 + 5 * 4 + 4);
+        assert!(report.passed(1e-2), "{:?}", report);
+    }
+}
@@ -1,6 +1,11 @@
 use ndarray::{Array2, Array1, Axis};
 use std::collections::HashMap;
 use rand::Rng;
+use anyhow::Result;
+use black_diamond_model_registry::take_weights;
+
+use crate::autodiff::{clip_gradients, gradient_check, Adam, GradientCheckReport, GradientClip, LearningRateSchedule, Optimizer, Tape, Var};
+use super::TransformerModel;
 
 pub struct HybridLSTMQuantum {
     pub time_steps: usize,
@@ -10,6 +15,8 @@ pub struct HybridLSTMQuantum {
     pub lstm_weights: LSTMWeights,
     pub quantum_gates: QuantumGates,
     pub golden_ratio_factor: f32,
+    pub optimizer: Box<dyn Optimizer>,
+    pub gradient_clip: GradientClip,
 }
 
 pub struct LSTMWeights {
@@ -46,30 +53,26 @@ impl HybridLSTMQuantum {
             lstm_weights,
             quantum_gates,
             golden_ratio_factor: golden_ratio,
+            optimizer: Box::new(Adam::new(LearningRateSchedule::Cosine { rate: 0.01, min_rate: 0.0005, period: 1000 })),
+            gradient_clip: GradientClip::GlobalNorm(1.0),
         }
     }
 
+    /// Fits the first output to the change in the first feature to the next
+    /// row, one gradient step per consecutive pair. The cell state carries
+    /// over between steps but gradients stop at it.
     pub fn train(&mut self, dataset: &[Vec<f32>]) {
         println!("Training HybridLSTMQuantum on {} samples", dataset.len());
         
-        for (epoch, sample) in dataset.iter().enumerate() {
-            // Convert market data to quantum states
-            let quantum_input = self.encode_to_quantum(sample);
-            
-            // Apply LSTM processing with quantum entanglement
-            let lstm_output = self.lstm_forward(&quantum_input);
-            
-            // Apply quantum gates for pattern enhancement
-            let quantum_output = self.apply_quantum_gates(&lstm_output);
-            
-            // Update weights using golden ratio optimization
-            self.update_weights_golden_ratio(&quantum_output, epoch);
+        let samples = TransformerModel::labelled_samples(self, dataset);
+        for (epoch, sample) in samples.iter().enumerate() {
+            let loss = self.gradient_step(&sample.input, sample.target);
             
             if epoch % 20 == 0 {
-                let profitability = self.calculate_profitability(sample);
+                let profitability = self.calculate_profitability(&sample.input);
                 let coherence = self.measure_quantum_coherence();
-                println!("  Epoch {}: Profitability={:.3}, Coherence={:.3}", 
-                    epoch, profitability, coherence);
+                println!("  Epoch {}: Loss={:.6}, Profitability={:.3}, Coherence={:.3}", 
+                    epoch, loss, profitability, coherence);
             }
         }
         
@@ -90,56 +93,62 @@ impl HybridLSTMQuantum {
         quantum_encoded
     }
 
-    fn lstm_forward(&mut self, input: &Array1<f32>) -> Array1<f32> {
-        let input_gate_output = self.apply_gate(&self.lstm_weights.input_gate, input);
-        let forget_gate_output = self.apply_gate(&self.lstm_weights.forget_gate, input);
-        let candidate_output = self.apply_gate(&self.lstm_weights.candidate, input);
-        
-        // Update cell state with quantum interference
-        for i in 0..self.lstm_weights.cell_state.len() {
-            self.lstm_weights.cell_state[i] = 
-                forget_gate_output[i] * self.lstm_weights.cell_state[i] + 
-                input_gate_output[i] * candidate_output[i];
-        }
-        
-        let output_gate_output = self.apply_gate(&self.lstm_weights.output_gate, input);
-        
-        // Generate hidden state with quantum entanglement
-        for i in 0..self.lstm_weights.hidden_state.len() {
-            self.lstm_weights.hidden_state[i] = 
-                output_gate_output[i] * self.lstm_weights.cell_state[i].tanh();
-        }
-        
-        self.lstm_weights.hidden_state.clone()
+    fn gate_parameters(&self) -> Vec<Array2<f32>> {
+        vec![
+            self.lstm_weights.input_gate.clone(),
+            self.lstm_weights.forget_gate.clone(),
+            self.lstm_weights.output_gate.clone(),
+            self.lstm_weights.candidate.clone(),
+        ]
     }
 
-    fn apply_gate(&self, gate_weights: &Array2<f32>, input: &Array1<f32>) -> Array1<f32> {
-        let mut output = Array1::zeros(gate_weights.nrows());
-        
-        for i in 0..gate_weights.nrows() {
-            let mut sum = 0.0;
-            for j in 0..gate_weights.ncols().min(input.len()) {
-                sum += gate_weights[[i, j]] * input[j];
-            }
-            output[i] = sum.tanh(); // Apply activation
+    /// Hadamard then Pauli as one matrix, so `state · M` applies both
+    fn quantum_matrix(&self) -> Array2<f32> {
+        let mut matrix = Array2::zeros((self.qubits, self.qubits));
+        for i in 0..self.qubits {
+            let mut basis = Array1::zeros(self.qubits);
+            basis[i] = 1.0;
+            let transformed = self.pauli_transform(&self.hadamard_transform(&basis));
+            matrix.row_mut(i).assign(&transformed);
         }
-        
-        output
+        matrix
     }
 
-    fn apply_quantum_gates(&mut self, lstm_output: &Array1<f32>) -> Array1<f32> {
-        let mut quantum_output = lstm_output.clone();
-        
-        // Apply Hadamard gates for superposition
-        quantum_output = self.hadamard_transform(&quantum_output);
-        
-        // Apply Pauli gates for quantum rotation
-        quantum_output = self.pauli_transform(&quantum_output);
-        
-        // Update quantum state
-        self.quantum_state = quantum_output.clone();
-        
-        quantum_output
+    fn gradient_step(&mut self, sample: &[f32], target: f32) -> f32 {
+        let input = self.encode_to_quantum(sample);
+        let quantum = self.quantum_matrix();
+        let cell_prev = self.lstm_weights.cell_state.clone();
+
+        let tape = Tape::new();
+        let gates: Vec<Var> = self.gate_parameters().into_iter().map(|g| tape.var(g)).collect();
+        let step = lstm_graph(&tape, &gates, &input, &cell_prev, &quantum);
+        let loss = step.output.slice_cols(0, 1).mse(&Array2::from_elem((1, 1), target));
+        let gradients = tape.backward(loss);
+
+        let mut grads: Vec<Array2<f32>> = gates.iter().map(|g| gradients.wrt(*g).clone()).collect();
+        clip_gradients(&mut grads, self.gradient_clip);
+        let lstm = &mut self.lstm_weights;
+        self.optimizer.step(
+            &mut [&mut lstm.input_gate, &mut lstm.forget_gate, &mut lstm.output_gate, &mut lstm.candidate],
+            &grads,
+        );
+
+        lstm.cell_state = step.cell.value().row(0).to_owned();
+        lstm.hidden_state = step.hidden.value().row(0).to_owned();
+        self.quantum_state = step.output.value().row(0).to_owned();
+        loss.scalar()
+    }
+
+    /// Compares backpropagated gate gradients for one sample with finite differences
+    pub fn check_gradients(&self, sample: &[f32], target: f32) -> GradientCheckReport {
+        let input = self.encode_to_quantum(sample);
+        let quantum = self.quantum_matrix();
+        let cell_prev = self.lstm_weights.cell_state.clone();
+        let target = Array2::from_elem((1, 1), target);
+
+        gradient_check(&self.gate_parameters(), 1e-2, |tape, gates| {
+            lstm_graph(tape, gates, &input, &cell_prev, &quantum).output.slice_cols(0, 1).mse(&target)
+        })
     }
 
     fn hadamard_transform(&self, state: &Array1<f32>) -> Array1<f32> {
@@ -169,51 +178,6 @@ impl HybridLSTMQuantum {
         transformed
     }
 
-    fn update_weights_golden_ratio(&mut self, output: &Array1<f32>, epoch: usize) {
-        let learning_rate = 0.001 * self.golden_ratio_factor;
-        let decay = 0.99_f32.powf(epoch as f32 / 100.0);
-        
-        // Update main weights matrix
-        for i in 0..self.weights.nrows() {
-            for j in 0..self.weights.ncols() {
-                if j < output.len() {
-                    let gradient = output[j] * self.golden_ratio_factor;
-                    self.weights[[i, j]] += learning_rate * decay * gradient;
-                }
-            }
-        }
-        
-        // Update LSTM weights with quantum feedback
-        self.update_lstm_weights(output, learning_rate * decay);
-    }
-
-    fn update_lstm_weights(&mut self, quantum_feedback: &Array1<f32>, learning_rate: f32) {
-        // Update input gate weights
-        for i in 0..self.lstm_weights.input_gate.nrows() {
-            for j in 0..self.lstm_weights.input_gate.ncols() {
-                if i < quantum_feedback.len() {
-                    self.lstm_weights.input_gate[[i, j]] += 
-                        learning_rate * quantum_feedback[i] * self.golden_ratio_factor;
-                }
-            }
-        }
-        
-        // Update other gates similarly
-        self.update_gate_weights(&mut self.lstm_weights.forget_gate, quantum_feedback, learning_rate);
-        self.update_gate_weights(&mut self.lstm_weights.output_gate, quantum_feedback, learning_rate);
-        self.update_gate_weights(&mut self.lstm_weights.candidate, quantum_feedback, learning_rate);
-    }
-
-    fn update_gate_weights(&self, gate: &mut Array2<f32>, feedback: &Array1<f32>, learning_rate: f32) {
-        for i in 0..gate.nrows() {
-            for j in 0..gate.ncols() {
-                if i < feedback.len() {
-                    gate[[i, j]] += learning_rate * feedback[i] * self.golden_ratio_factor;
-                }
-            }
-        }
-    }
-
     fn calculate_profitability(&self, market_data: &[f32]) -> f32 {
         let mean = market_data.iter().sum::<f32>() / market_data.len() as f32;
         let variance = market_data.iter()
@@ -233,17 +197,20 @@ impl HybridLSTMQuantum {
         coherence / self.quantum_state.len() as f32
     }
 
+    /// One LSTM step from the current cell state, without advancing it
     pub fn predict(&self, input: &[f32]) -> Vec<f32> {
         let quantum_input = self.encode_to_quantum(input);
-        let prediction = self.apply_quantum_gates(&quantum_input);
-        prediction.to_vec()
+        let tape = Tape::new();
+        let gates: Vec<Var> = self.gate_parameters().into_iter().map(|g| tape.var(g)).collect();
+        let step = lstm_graph(&tape, &gates, &quantum_input, &self.lstm_weights.cell_state, &self.quantum_matrix());
+        step.output.value().row(0).to_vec()
     }
 
     pub fn get_quantum_state(&self) -> &Array1<f32> {
         &self.quantum_state
     }
 
-    pub fn export_weights(&self) -> HashMap<String, Array2<f32>> {
+    pub fn weight_matrices(&self) -> HashMap<String, Array2<f32>> {
         let mut weights_map = HashMap::new();
         weights_map.insert("main_weights".to_string(), self.weights.clone());
         weights_map.insert("input_gate".to_string(), self.lstm_weights.input_gate.clone());
@@ -254,6 +221,84 @@ impl HybridLSTMQuantum {
     }
 }
 
+impl TransformerModel for HybridLSTMQuantum {
+    fn train(&mut self, dataset: &[Vec<f32>]) -> Result<()> {
+        HybridLSTMQuantum::train(self, dataset);
+        Ok(())
+    }
+
+    fn predict(&self, input: &[f32]) -> Vec<f32> {
+        HybridLSTMQuantum::predict(self, input)
+    }
+
+    fn get_model_name(&self) -> String {
+        "hybrid_lstm_quantum".to_string()
+    }
+
+    fn export_weights(&self) -> HashMap<String, Vec<f32>> {
+        self.weight_matrices()
+            .into_iter()
+            .map(|(name, matrix)| (name, matrix.iter().cloned().collect()))
+            .collect()
+    }
+
+    fn import_weights(&mut self, weights: &HashMap<String, Vec<f32>>) -> Result<()> {
+        let main_shape = (self.time_steps, self.qubits);
+        let gate_shape = (self.qubits, self.qubits);
+        let load = |name: &str, shape: (usize, usize)| -> Result<Array2<f32>> {
+            Ok(Array2::from_shape_vec(shape, take_weights(weights, name, shape.0 * shape.1)?)?)
+        };
+
+        // Build every matrix before assigning any so a bad checkpoint leaves the model untouched
+        let main_weights = load("main_weights", main_shape)?;
+        let input_gate = load("input_gate", gate_shape)?;
+        let forget_gate = load("forget_gate", gate_shape)?;
+        let output_gate = load("output_gate", gate_shape)?;
+        let candidate = load("candidate", gate_shape)?;
+
+        self.weights = main_weights;
+        self.lstm_weights.input_gate = input_gate;
+        self.lstm_weights.forget_gate = forget_gate;
+        self.lstm_weights.output_gate = output_gate;
+        self.lstm_weights.candidate = candidate;
+        Ok(())
+    }
+
+    fn hyperparameters(&self) -> HashMap<String, f64> {
+        let mut params = HashMap::new();
+        params.insert("time_steps".to_string(), self.time_steps as f64);
+        params.insert("qubits".to_string(), self.qubits as f64);
+        params
+    }
+}
+
+struct LstmStep<'t> {
+    cell: Var<'t>,
+    hidden: Var<'t>,
+    output: Var<'t>,
+}
+
+/// Gates are `[input, forget, output, candidate]`, each applied as
+/// `tanh(x · Wᵀ)`; the previous cell state and quantum matrix are constants.
+fn lstm_graph<'t>(
+    tape: &'t Tape,
+    gates: &[Var<'t>],
+    input: &Array1<f32>,
+    cell_prev: &Array1<f32>,
+    quantum: &Array2<f32>,
+) -> LstmStep<'t> {
+    let x = tape.var(input.clone().insert_axis(Axis(0)));
+    let cell_prev = tape.var(cell_prev.clone().insert_axis(Axis(0)));
+    let gate = |w: Var<'t>| x.matmul(w.t()).tanh();
+
+    let (input_gate, forget_gate, output_gate, candidate) = (gate(gates[0]), gate(gates[1]), gate(gates[2]), gate(gates[3]));
+    let cell = forget_gate.mul(cell_prev).add(input_gate.mul(candidate));
+    let hidden = output_gate.mul(cell.tanh());
+    let output = hidden.matmul(tape.var(quantum.clone()));
+
+    LstmStep { cell, hidden, output }
+}
+
 impl LSTMWeights {
     fn new(time_steps: usize, qubits: usize) -> Self {
         let mut rng = rand::thread_rng();
@@ -296,4 +341,26 @@ impl QuantumGates {
             phase,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backward_pass_matches_finite_differences() {
+        let mut model = HybridLSTMQuantum::new(4, 4);
+        // Larger gates than the random init, and a carried cell state so the
+        // forget gate has a gradient to check
+        let gate = |seed: f32| Array2::from_shape_fn((4, 4), |(i, j)| ((i * 4 + j) as f32 * 0.6 + seed).sin() * 0.8);
+        model.lstm_weights.input_gate = gate(0.1);
+        model.lstm_weights.forget_gate = gate(0.2);
+        model.lstm_weights.output_gate = gate(0.3);
+        model.lstm_weights.candidate = gate(0.4);
+        model.lstm_weights.cell_state = Array1::from_vec(vec![0.5, -0.3, 0.2, -0.6]);
+
+        let report = model.check_gradients(&[120.0, 80.0, 150.0, 60.0], 0.7);
+        assert_eq!(report.checked, 4 * 16);
+        assert!(report.passed(1e-2), "{:?}", report);
+    }
+}
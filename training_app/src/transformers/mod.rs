@@ -1,19 +1,29 @@
 pub mod hybrid_lstm_quantum;
+pub mod flash_loan_neural_network;
+pub mod perpetuals_trading_transformer;
+pub mod evaluation;
 pub mod temporal_signal_generator;
 pub mod chaos_modeling_engine;
 pub mod golden_ratio_analyst;
 
 pub use hybrid_lstm_quantum::HybridLSTMQuantum;
+pub use flash_loan_neural_network::FlashLoanNeuralNetwork;
+pub use perpetuals_trading_transformer::PerpetualsTradinTransformer;
+pub use evaluation::{EvaluationConfig, EvaluationReport, HeadMetrics, LabelledSample, ModelEvaluation, ModelHead};
 pub use temporal_signal_generator::TemporalSignalGenerator;
 pub use chaos_modeling_engine::ChaosModelingEngine;
 pub use golden_ratio_analyst::GoldenRatioAnalyst;
 
 use std::collections::HashMap;
-use anyhow::Result;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+
+use crate::datasets::FeatureDataset;
+
+pub use black_diamond_model_registry::{dataset_hash, CheckpointMetadata, ModelCheckpoint, ModelRegistry};
 
 #[derive(Debug, Clone)]
 pub struct TransformerMetrics {
-    pub accuracy: f64,
     pub training_epochs: u32,
     pub last_update: u64,
     pub profit_contribution: f64,
@@ -23,15 +33,89 @@ pub struct TransformerMetrics {
 pub struct TransformerOrchestrator {
     pub models: HashMap<String, Box<dyn TransformerModel>>,
     pub metrics: HashMap<String, TransformerMetrics>,
+    pub evaluations: HashMap<String, ModelEvaluation>,
+    pub evaluation_config: EvaluationConfig,
     pub active_training: bool,
 }
 
 pub trait TransformerModel {
     fn train(&mut self, dataset: &[Vec<f32>]) -> Result<()>;
     fn predict(&self, input: &[f32]) -> Vec<f32>;
-    fn get_accuracy(&self) -> f64;
     fn get_model_name(&self) -> String;
     fn export_weights(&self) -> HashMap<String, Vec<f32>>;
+    fn import_weights(&mut self, weights: &HashMap<String, Vec<f32>>) -> Result<()>;
+    /// Shape-defining settings; a checkpoint only loads into a model with the same values
+    fn hyperparameters(&self) -> HashMap<String, f64>;
+
+    fn head(&self) -> ModelHead {
+        ModelHead::Regression { output: 0 }
+    }
+
+    /// Pairs one market's time-ordered rows with targets. Defaults to
+    /// forecasting the next row's first feature, which for pipeline datasets is
+    /// `log_return_1`: the forward return from this row to the next. The last
+    /// row has no label.
+    fn labelled_samples(&self, rows: &[Vec<f32>]) -> Vec<LabelledSample> {
+        rows.windows(2)
+            .filter(|pair| !pair[0].is_empty() && !pair[1].is_empty())
+            .map(|pair| LabelledSample {
+                input: pair[0].clone(),
+                target: pair[1][0],
+            })
+            .collect()
+    }
+
+    fn save(&self, path: &Path, metadata: CheckpointMetadata) -> Result<ModelCheckpoint> {
+        let checkpoint = self.checkpoint(metadata);
+        checkpoint.save(path)?;
+        Ok(checkpoint)
+    }
+
+    fn load(&mut self, path: &Path) -> Result<ModelCheckpoint> {
+        let checkpoint = ModelCheckpoint::load(path)?;
+        self.restore(&checkpoint)?;
+        Ok(checkpoint)
+    }
+
+    fn checkpoint(&self, metadata: CheckpointMetadata) -> ModelCheckpoint {
+        ModelCheckpoint::new(self.get_model_name(), self.export_weights(), self.hyperparameters(), metadata)
+    }
+
+    fn restore(&mut self, checkpoint: &ModelCheckpoint) -> Result<()> {
+        checkpoint.ensure_compatible(&self.get_model_name(), &self.hyperparameters())?;
+        self.import_weights(&checkpoint.weights)
+    }
+}
+
+/// Builds the model a checkpoint was saved from, sized by its hyperparameters,
+/// and loads its weights.
+pub fn model_from_checkpoint(checkpoint: &ModelCheckpoint) -> Result<Box<dyn TransformerModel + Send + Sync>> {
+    let param = |name: &str| -> Result<usize> {
+        checkpoint
+            .hyperparameters
+            .get(name)
+            .map(|value| *value as usize)
+            .ok_or_else(|| anyhow!("Checkpoint of {} has no '{}' hyperparameter", checkpoint.model_name, name))
+    };
+
+    let mut model: Box<dyn TransformerModel + Send + Sync> = match checkpoint.model_name.as_str() {
+        "hybrid_lstm_quantum" => Box::new(HybridLSTMQuantum::new(param("time_steps")?, param("qubits")?)),
+        "solana_flash_loan_transformer" => {
+            let hidden_layers = (0..param("hidden_layer_count")?)
+                .map(|i| param(&format!("hidden_layer_{}", i)))
+                .collect::<Result<Vec<usize>>>()?;
+            Box::new(FlashLoanNeuralNetwork::new(param("input_size")?, hidden_layers, param("output_size")?))
+        }
+        "perpetuals_trading_transformer" => Box::new(PerpetualsTradinTransformer::new(
+            param("attention_heads")?,
+            param("sequence_length")?,
+            param("embedding_dim")?,
+        )),
+        other => return Err(anyhow!("No model constructor for checkpoint '{}'", other)),
+    };
+
+    model.restore(checkpoint)?;
+    Ok(model)
 }
 
 impl TransformerOrchestrator {
@@ -39,13 +123,14 @@ impl TransformerOrchestrator {
         TransformerOrchestrator {
             models: HashMap::new(),
             metrics: HashMap::new(),
+            evaluations: HashMap::new(),
+            evaluation_config: EvaluationConfig::default(),
             active_training: false,
         }
     }
 
     pub fn register_model(&mut self, name: String, model: Box<dyn TransformerModel>) {
         let metrics = TransformerMetrics {
-            accuracy: 0.0,
             training_epochs: 0,
             last_update: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -59,15 +144,17 @@ impl TransformerOrchestrator {
         self.metrics.insert(name, metrics);
     }
 
-    pub async fn train_all_models(&mut self, dataset: &[Vec<f32>]) -> Result<()> {
+    pub async fn train_all_models(&mut self, dataset: &FeatureDataset) -> Result<()> {
         self.active_training = true;
         
         for (name, model) in &mut self.models {
             println!("Training transformer: {}", name);
-            model.train(dataset)?;
+            let evaluation = evaluation::train_and_evaluate(model.as_mut(), dataset, &self.evaluation_config)?;
+            println!("  Held-out test score: {:.3} over {} samples",
+                evaluation.test.headline(), evaluation.test.samples());
+            self.evaluations.insert(name.clone(), evaluation);
             
             if let Some(metrics) = self.metrics.get_mut(name) {
-                metrics.accuracy = model.get_accuracy();
                 metrics.training_epochs += 1;
                 metrics.last_update = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -80,19 +167,63 @@ impl TransformerOrchestrator {
         Ok(())
     }
 
+    /// Registers a checkpoint of every model trained on `dataset`, recording its
+    /// feature schema and row provenance. Versions are not promoted; that is
+    /// left to the deployment manager.
+    pub fn save_checkpoints(
+        &self,
+        registry: &mut ModelRegistry,
+        dataset: &FeatureDataset,
+    ) -> Result<HashMap<String, u32>> {
+        let hash = dataset_hash(&dataset.rows);
+        let provenance = dataset.source_counts();
+        let mut versions = HashMap::new();
+
+        for (name, model) in &self.models {
+            let mut training_metrics = self
+                .evaluations
+                .get(name)
+                .map(|evaluation| evaluation.to_metrics())
+                .unwrap_or_default();
+            if let Some(metrics) = self.metrics.get(name) {
+                training_metrics.insert("training_epochs".to_string(), metrics.training_epochs as f64);
+            }
+
+            let checkpoint = model.checkpoint(CheckpointMetadata {
+                feature_schema: dataset.schema.clone(),
+                training_metrics,
+                dataset_hash: hash.clone(),
+                provenance: provenance.clone(),
+            });
+            versions.insert(name.clone(), registry.register(&checkpoint)?);
+        }
+
+        Ok(versions)
+    }
+
+    /// Loads each model's promoted version so training resumes instead of
+    /// starting from random weights. Returns the names that were restored.
+    pub fn restore_production(&mut self, registry: &ModelRegistry) -> Result<Vec<String>> {
+        let mut restored = Vec::new();
+
+        for (name, model) in &mut self.models {
+            if let Some(checkpoint) = registry.load_production(&model.get_model_name())? {
+                model.restore(&checkpoint)?;
+                println!("♻️ Restored {} from checkpoint ({})", name, checkpoint.dataset_hash);
+                restored.push(name.clone());
+            }
+        }
+
+        Ok(restored)
+    }
+
     pub fn get_training_report(&self) -> TrainingReport {
         let total_models = self.models.len();
-        let avg_accuracy = if total_models > 0 {
-            self.metrics.values().map(|m| m.accuracy).sum::<f64>() / total_models as f64
-        } else {
-            0.0
-        };
-        
         let total_epochs: u32 = self.metrics.values().map(|m| m.training_epochs).sum();
         
         TrainingReport {
             total_models,
-            average_accuracy: avg_accuracy,
+            evaluation: EvaluationReport::new(self.evaluations.clone()),
             total_training_epochs: total_epochs,
             active_training: self.active_training,
             models: self.metrics.clone(),
@@ -103,7 +234,7 @@ impl TransformerOrchestrator {
 #[derive(Debug, Clone)]
 pub struct TrainingReport {
     pub total_models: usize,
-    pub average_accuracy: f64,
+    pub evaluation: EvaluationReport,
     pub total_training_epochs: u32,
     pub active_training: bool,
     pub models: HashMap<String, TransformerMetrics>,
@@ -1,6 +1,15 @@
 use ndarray::{Array2, Array1};
 use std::collections::HashMap;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use black_diamond_model_registry::take_weights;
+
+use crate::autodiff::{clip_gradients, gradient_check, Adam, GradientCheckReport, GradientClip, LearningRateSchedule, Optimizer, Tape, Var};
+use crate::datasets::perp_markets::PerpMarketAdapter;
+use super::{LabelledSample, TransformerModel};
+
+/// Per-row layout used by the generic `TransformerModel` interface
+const SNAPSHOT_FEATURES: usize = 6;
 
 pub struct PerpetualsTradinTransformer {
     pub attention_heads: usize,
@@ -15,6 +24,8 @@ pub struct PerpetualsTradinTransformer {
     pub funding_rate_predictor: FundingRatePredictor,
     pub liquidation_protector: LiquidationProtector,
     pub golden_ratio_factor: f32,
+    pub optimizer: Box<dyn Optimizer>,
+    pub gradient_clip: GradientClip,
 }
 
 pub struct LeverageOptimizer {
@@ -30,13 +41,30 @@ pub struct FundingRatePredictor {
     pub accuracy_threshold: f32,
 }
 
+#[derive(Debug, Clone)]
 pub struct LiquidationProtector {
     pub safety_margin: f32,
+    /// Equity over notional below which the venue liquidates
+    pub maintenance_margin: f32,
     pub dynamic_stop_loss: f32,
     pub position_sizing_rules: Vec<SizingRule>,
 }
 
-#[derive(Debug, Clone)]
+impl LiquidationProtector {
+    /// Fraction the price can move against a position at `leverage` before
+    /// its margin falls to maintenance, less the safety margin
+    pub fn liquidation_distance(&self, leverage: f32) -> f32 {
+        let liquidation_threshold = (1.0 / leverage - self.maintenance_margin).max(0.0);
+        liquidation_threshold * (1.0 - self.safety_margin)
+    }
+
+    /// Highest leverage whose liquidation distance is still `distance`
+    pub fn leverage_for_distance(&self, distance: f32) -> f32 {
+        1.0 / (distance / (1.0 - self.safety_margin) + self.maintenance_margin)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FundingRateData {
     pub timestamp: u64,
     pub rate: f32,
@@ -96,6 +124,7 @@ impl PerpetualsTradinTransformer {
         
         let liquidation_protector = LiquidationProtector {
             safety_margin: 0.15, // 15% safety margin
+            maintenance_margin: 0.05, // 5% of notional
             dynamic_stop_loss: 0.05, // 5% initial stop loss
             position_sizing_rules: vec![
                 SizingRule {
@@ -129,6 +158,8 @@ impl PerpetualsTradinTransformer {
             funding_rate_predictor,
             liquidation_protector,
             golden_ratio_factor: golden_ratio,
+            optimizer: Box::new(Adam::new(LearningRateSchedule::StepDecay { rate: 0.001, factor: 0.5, every: 500 })),
+            gradient_clip: GradientClip::GlobalNorm(1.0),
         }
     }
 
@@ -155,10 +186,7 @@ impl PerpetualsTradinTransformer {
             let input_sequence = self.encode_market_sequence(&data.market_sequence);
             let target_signal = self.encode_target_signal(&data.target_signal);
             
-            let attention_output = self.multi_head_attention(&input_sequence)?;
-            let prediction = self.generate_prediction(&attention_output);
-            
-            let loss = self.calculate_loss(&prediction, &target_signal);
+            let loss = self.update_weights(&input_sequence, &target_signal)?;
             
             if epoch % 100 == 0 {
                 let leverage = data.target_signal.optimal_leverage;
@@ -166,8 +194,6 @@ impl PerpetualsTradinTransformer {
                 println!("  Epoch {}: Loss={:.6}, Leverage={:.2}x, Profit={:.4} SOL", 
                     epoch, loss, leverage, profit);
             }
-            
-            self.update_weights(&input_sequence, &target_signal, &prediction)?;
         }
         
         self.update_funding_rate_predictor(training_data)?;
@@ -185,6 +211,10 @@ impl PerpetualsTradinTransformer {
             encoded[[i, 3]] = snapshot.funding_rate * 1000.0; // Scale funding rate
             encoded[[i, 4]] = snapshot.long_short_ratio;
             encoded[[i, 5]] = snapshot.volatility * 100.0; // Scale volatility
+            if self.embedding_dim > 6 && snapshot.oracle_price > 0.0 {
+                // Mark premium over the oracle, in percent
+                encoded[[i, 6]] = (snapshot.price - snapshot.oracle_price) / snapshot.oracle_price * 100.0;
+            }
             
             // Apply golden ratio enhancement
             for j in 0..self.embedding_dim {
@@ -301,8 +331,7 @@ impl PerpetualsTradinTransformer {
     }
 
     fn calculate_liquidation_distance(&self, leverage: f32) -> f32 {
-        let liquidation_threshold = 1.0 / leverage;
-        liquidation_threshold * (1.0 - self.liquidation_protector.safety_margin)
+        self.liquidation_protector.liquidation_distance(leverage)
     }
 
     fn encode_target_signal(&self, signal: &PerpetualsSignal) -> Array1<f32> {
@@ -321,28 +350,43 @@ impl PerpetualsTradinTransformer {
         encoded
     }
 
-    fn calculate_loss(&self, prediction: &PerpetualsSignal, target: &Array1<f32>) -> f32 {
-        let pred_encoded = self.encode_target_signal(prediction);
-        let diff = &pred_encoded - target;
-        diff.mapv(|x| x.powi(2)).sum() / pred_encoded.len() as f32
+    /// Query, key, value and output projections, in optimizer order
+    fn attention_parameters(&self) -> Vec<Array2<f32>> {
+        vec![
+            self.query_weights.clone(),
+            self.key_weights.clone(),
+            self.value_weights.clone(),
+            self.output_weights.clone(),
+        ]
     }
 
-    fn update_weights(&mut self, input: &Array2<f32>, target: &Array1<f32>, prediction: &PerpetualsSignal) -> Result<()> {
-        let learning_rate = 0.0001 * self.golden_ratio_factor;
-        let pred_encoded = self.encode_target_signal(prediction);
-        let error = &pred_encoded - target;
-        
-        // Simplified weight update for demonstration
-        for i in 0..self.query_weights.nrows() {
-            for j in 0..self.query_weights.ncols() {
-                if i < error.len() && j < input.ncols() {
-                    let gradient = error[i] * input.mean_axis(ndarray::Axis(0)).unwrap()[j % input.ncols()];
-                    self.query_weights[[i, j]] -= learning_rate * gradient;
-                }
-            }
-        }
-        
-        Ok(())
+    /// One gradient step on the squared error between the first four
+    /// aggregated attention outputs and the encoded target signal. Returns the
+    /// loss before the update.
+    fn update_weights(&mut self, input: &Array2<f32>, target: &Array1<f32>) -> Result<f32> {
+        let tape = Tape::new();
+        let params: Vec<Var> = self.attention_parameters().into_iter().map(|p| tape.var(p)).collect();
+        let loss = attention_loss_graph(&tape, &params, input, target, self.embedding_dim);
+        let gradients = tape.backward(loss);
+
+        let mut grads: Vec<Array2<f32>> = params.iter().map(|p| gradients.wrt(*p).clone()).collect();
+        clip_gradients(&mut grads, self.gradient_clip);
+        self.optimizer.step(
+            &mut [&mut self.query_weights, &mut self.key_weights, &mut self.value_weights, &mut self.output_weights],
+            &grads,
+        );
+
+        Ok(loss.scalar())
+    }
+
+    /// Compares backpropagated attention gradients for one window with finite differences
+    pub fn check_gradients(&self, sequence: &[MarketSnapshot], target: &PerpetualsSignal) -> GradientCheckReport {
+        let input = self.encode_market_sequence(sequence);
+        let target = self.encode_target_signal(target);
+
+        gradient_check(&self.attention_parameters(), 1e-2, |tape, params| {
+            attention_loss_graph(tape, params, &input, &target, self.embedding_dim)
+        })
     }
 
     fn update_funding_rate_predictor(&mut self, training_data: &[PerpetualsTrainingData]) -> Result<()> {
@@ -395,10 +439,212 @@ impl PerpetualsTradinTransformer {
     }
 }
 
-#[derive(Debug, Clone)]
+impl PerpetualsTradinTransformer {
+    /// Labels each window of `sequence_length` snapshots with the direction
+    /// and size of the mark price move to the following snapshot.
+    fn training_data_from_snapshots(&self, snapshots: &[MarketSnapshot]) -> Vec<PerpetualsTrainingData> {
+        snapshots
+            .windows(self.sequence_length + 1)
+            .map(|window| {
+                let (sequence, next) = window.split_at(self.sequence_length);
+                let last_price = sequence[sequence.len() - 1].price;
+                let change = if last_price > 0.0 { (next[0].price - last_price) / last_price } else { 0.0 };
+
+                let direction = if change > 0.0 {
+                    TradeDirection::Long
+                } else if change < 0.0 {
+                    TradeDirection::Short
+                } else {
+                    TradeDirection::Hold
+                };
+
+                PerpetualsTrainingData {
+                    market_sequence: sequence.to_vec(),
+                    target_signal: PerpetualsSignal {
+                        direction,
+                        confidence: (change.abs() * 100.0).min(1.0),
+                        optimal_leverage: 1.0,
+                        entry_price: last_price,
+                        stop_loss: self.liquidation_protector.dynamic_stop_loss,
+                        take_profit: change.abs(),
+                        funding_rate_impact: next[0].funding_rate,
+                        liquidation_distance: self.calculate_liquidation_distance(1.0),
+                    },
+                    outcome_profit: change as f64,
+                }
+            })
+            .collect()
+    }
+
+    /// Trains on a venue's snapshot history for `market` and replaces the
+    /// funding predictor's history with the venue's funding records.
+    pub async fn train_from_venue(&mut self, adapter: &dyn PerpMarketAdapter, market: &str, limit: usize) -> Result<()> {
+        let snapshots = adapter.snapshot_history(market, limit).await?;
+        if snapshots.len() <= self.sequence_length {
+            return Err(anyhow!(
+                "{} has {} snapshots of {}, need more than {}",
+                adapter.venue(),
+                snapshots.len(),
+                market,
+                self.sequence_length
+            ));
+        }
+
+        let training_data = self.training_data_from_snapshots(&snapshots);
+        self.train_perpetuals_patterns(&training_data)?;
+
+        self.funding_rate_predictor.historical_rates = adapter.funding_history(market, limit).await?;
+        println!("Loaded {} funding records for {} from {}",
+                self.funding_rate_predictor.historical_rates.len(), market, adapter.venue());
+        Ok(())
+    }
+
+    /// Runs `analyze_perpetuals_opportunity` on the venue's latest snapshots,
+    /// entering at the current mark price.
+    pub async fn analyze_venue(&self, adapter: &dyn PerpMarketAdapter, market: &str) -> Result<Option<PerpetualsSignal>> {
+        let snapshots = adapter.snapshot_history(market, self.sequence_length).await?;
+        let mark_price = adapter.market_state(market).await?.mark_price as f32;
+
+        Ok(self.analyze_perpetuals_opportunity(&snapshots).await.map(|mut signal| {
+            signal.entry_price = mark_price;
+            signal
+        }))
+    }
+
+    /// Rows are `[price, volume, open_interest, funding_rate, long_short_ratio, volatility]`.
+    fn snapshots_from_rows(rows: &[Vec<f32>]) -> Vec<MarketSnapshot> {
+        rows.iter()
+            .filter(|row| row.len() >= SNAPSHOT_FEATURES)
+            .enumerate()
+            .map(|(i, row)| MarketSnapshot {
+                timestamp: i as u64,
+                price: row[0],
+                oracle_price: row[0],
+                volume: row[1],
+                open_interest: row[2],
+                funding_rate: row[3],
+                long_short_ratio: row[4],
+                volatility: row[5],
+            })
+            .collect()
+    }
+}
+
+impl TransformerModel for PerpetualsTradinTransformer {
+    /// Trains on sliding windows of snapshots, labelling each window with the
+    /// direction of the price move to the following snapshot.
+    fn train(&mut self, dataset: &[Vec<f32>]) -> Result<()> {
+        let training_data = self.training_data_from_snapshots(&Self::snapshots_from_rows(dataset));
+        self.train_perpetuals_patterns(&training_data)
+    }
+
+    fn predict(&self, input: &[f32]) -> Vec<f32> {
+        let rows: Vec<Vec<f32>> = input.chunks(SNAPSHOT_FEATURES).map(|c| c.to_vec()).collect();
+        let sequence = self.encode_market_sequence(&Self::snapshots_from_rows(&rows));
+
+        match self.multi_head_attention(&sequence) {
+            Ok(attention_output) => self.encode_target_signal(&self.generate_prediction(&attention_output)).to_vec(),
+            Err(_) => vec![0.0; 4],
+        }
+    }
+
+    fn get_model_name(&self) -> String {
+        "perpetuals_trading_transformer".to_string()
+    }
+
+    fn export_weights(&self) -> HashMap<String, Vec<f32>> {
+        let mut weights_map = HashMap::new();
+        weights_map.insert("query_weights".to_string(), self.query_weights.iter().cloned().collect());
+        weights_map.insert("key_weights".to_string(), self.key_weights.iter().cloned().collect());
+        weights_map.insert("value_weights".to_string(), self.value_weights.iter().cloned().collect());
+        weights_map.insert("output_weights".to_string(), self.output_weights.iter().cloned().collect());
+        weights_map.insert(
+            "funding_rate_weights".to_string(),
+            self.funding_rate_predictor.prediction_weights.to_vec(),
+        );
+        weights_map
+    }
+
+    fn import_weights(&mut self, weights: &HashMap<String, Vec<f32>>) -> Result<()> {
+        let shape = (self.embedding_dim, self.embedding_dim);
+        let load = |name: &str| -> Result<Array2<f32>> {
+            Ok(Array2::from_shape_vec(shape, take_weights(weights, name, shape.0 * shape.1)?)?)
+        };
+
+        let query_weights = load("query_weights")?;
+        let key_weights = load("key_weights")?;
+        let value_weights = load("value_weights")?;
+        let output_weights = load("output_weights")?;
+        let funding_rate_weights = take_weights(
+            weights,
+            "funding_rate_weights",
+            self.funding_rate_predictor.prediction_weights.len(),
+        )?;
+
+        self.query_weights = query_weights;
+        self.key_weights = key_weights;
+        self.value_weights = value_weights;
+        self.output_weights = output_weights;
+        self.funding_rate_predictor.prediction_weights = Array1::from_vec(funding_rate_weights);
+        Ok(())
+    }
+
+    /// Each sample is a window of snapshots labelled with the relative price
+    /// move to the next snapshot, scored against the direction output.
+    fn labelled_samples(&self, rows: &[Vec<f32>]) -> Vec<LabelledSample> {
+        let rows: Vec<&Vec<f32>> = rows.iter().filter(|row| row.len() >= SNAPSHOT_FEATURES).collect();
+
+        rows.windows(self.sequence_length + 1)
+            .map(|window| {
+                let (sequence, next) = window.split_at(self.sequence_length);
+                let last_price = sequence[sequence.len() - 1][0];
+                LabelledSample {
+                    input: sequence.iter().flat_map(|row| row[..SNAPSHOT_FEATURES].iter().copied()).collect(),
+                    target: if last_price > 0.0 { (next[0][0] - last_price) / last_price } else { 0.0 },
+                }
+            })
+            .collect()
+    }
+
+    fn hyperparameters(&self) -> HashMap<String, f64> {
+        let mut params = HashMap::new();
+        params.insert("attention_heads".to_string(), self.attention_heads as f64);
+        params.insert("sequence_length".to_string(), self.sequence_length as f64);
+        params.insert("embedding_dim".to_string(), self.embedding_dim as f64);
+        params
+    }
+}
+
+/// `multi_head_attention` followed by the row mean of `generate_prediction`
+/// on the tape. Every head shares the same projections, so their average is
+/// a single head.
+fn attention_loss_graph<'t>(
+    tape: &'t Tape,
+    params: &[Var<'t>],
+    input: &Array2<f32>,
+    target: &Array1<f32>,
+    embedding_dim: usize,
+) -> Var<'t> {
+    let x = tape.var(input.clone());
+    let queries = x.matmul(params[0]);
+    let keys = x.matmul(params[1]);
+    let values = x.matmul(params[2]);
+
+    let scores = queries.matmul(keys.t()).scale(1.0 / (embedding_dim as f32).sqrt());
+    let attended = scores.softmax_rows().matmul(values).matmul(params[3]);
+
+    attended
+        .mean_rows()
+        .slice_cols(0, target.len())
+        .mse(&target.clone().insert_axis(ndarray::Axis(0)))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketSnapshot {
     pub timestamp: u64,
+    /// Mark price
     pub price: f32,
+    pub oracle_price: f32,
     pub volume: f32,
     pub open_interest: f32,
     pub funding_rate: f32,
@@ -411,4 +657,40 @@ pub struct PerpetualsTrainingData {
     pub market_sequence: Vec<MarketSnapshot>,
     pub target_signal: PerpetualsSignal,
     pub outcome_profit: f64,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backward_pass_matches_finite_differences() {
+        let model = PerpetualsTradinTransformer::new(2, 4, 8);
+        let sequence: Vec<MarketSnapshot> = (0..4)
+            .map(|i| MarketSnapshot {
+                timestamp: i as u64 * 60,
+                price: 100.0 + i as f32,
+                oracle_price: 100.0,
+                volume: 500_000.0,
+                open_interest: 5_000_000.0,
+                funding_rate: 0.0001 * i as f32,
+                long_short_ratio: 1.1,
+                volatility: 0.02,
+            })
+            .collect();
+        let target = PerpetualsSignal {
+            direction: TradeDirection::Long,
+            confidence: 0.8,
+            optimal_leverage: 5.0,
+            entry_price: 100.0,
+            stop_loss: 0.05,
+            take_profit: 0.15,
+            funding_rate_impact: 0.1,
+            liquidation_distance: 0.2,
+        };
+
+        let report = model.check_gradients(&sequence, &target);
+        assert_eq!(report.checked, 4 * 8 * 8);
+        assert!(report.passed(1e-2), "{:?}", report);
+    }
+}